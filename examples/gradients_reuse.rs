@@ -0,0 +1,60 @@
+//! Compares the number of allocations made when accumulating gradients over many steps,
+//! using a fresh [Gradients] every step vs reusing one with [Gradients::zero_out()].
+
+use dfdx::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+#[path = "common/counting_allocator.rs"]
+mod counting_allocator;
+use counting_allocator::num_allocs;
+
+const NUM_STEPS: usize = 1000;
+const NUM_MINIBATCHES: usize = 4;
+
+// accumulates the gradients of `model` from `gradients` into `acc`
+fn accumulate(acc: &mut Gradients, model: &Linear<64, 64>, gradients: &Gradients) {
+    Cpu::add(
+        acc.mut_gradient(&model.weight),
+        gradients.ref_gradient(&model.weight),
+    );
+    Cpu::add(
+        acc.mut_gradient(&model.bias),
+        gradients.ref_gradient(&model.bias),
+    );
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut model: Linear<64, 64> = Default::default();
+    model.reset_params(&mut rng);
+
+    let x: Tensor2D<8, 64> = Tensor2D::randn(&mut rng);
+    let gradients = model.forward(x.trace()).square().mean().backward();
+
+    let start_allocs = num_allocs();
+    for _ in 0..NUM_STEPS {
+        let mut acc: Gradients = Default::default();
+        for _ in 0..NUM_MINIBATCHES {
+            accumulate(&mut acc, &model, &gradients);
+        }
+        std::hint::black_box(acc);
+    }
+    println!(
+        "fresh gradients:  {:.1} allocations per step",
+        (num_allocs() - start_allocs) as f32 / NUM_STEPS as f32,
+    );
+
+    let mut acc: Gradients = Default::default();
+    let start_allocs = num_allocs();
+    for _ in 0..NUM_STEPS {
+        acc.zero_out();
+        for _ in 0..NUM_MINIBATCHES {
+            accumulate(&mut acc, &model, &gradients);
+        }
+        std::hint::black_box(&acc);
+    }
+    println!(
+        "reused gradients: {:.1} allocations per step",
+        (num_allocs() - start_allocs) as f32 / NUM_STEPS as f32,
+    );
+}
@@ -0,0 +1,63 @@
+//! Compares the number of allocations made by a chain of 5 elementwise ops,
+//! using the regular ops vs the in place versions, both without a tape (inference)
+//! and with a tape (training, including the backward pass).
+
+use dfdx::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::Instant;
+
+#[path = "common/counting_allocator.rs"]
+mod counting_allocator;
+use counting_allocator::num_allocs;
+
+const NUM_ITERS: usize = 1000;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let b: Tensor2D<64, 64> = Tensor2D::randn(&mut rng);
+    let c: Tensor2D<64, 64> = Tensor2D::randn(&mut rng);
+    let d: Tensor2D<64, 64> = Tensor2D::randn(&mut rng);
+
+    bench("regular", || {
+        let x: Tensor2D<64, 64> = Tensor2D::ones();
+        let x = ((x + &b) * &c - &d) * 0.5 + 1.0;
+        std::hint::black_box(x);
+    });
+    bench("in place", || {
+        let mut x: Tensor2D<64, 64> = Tensor2D::ones();
+        x += &b;
+        x *= &c;
+        x -= &d;
+        x *= 0.5;
+        x += 1.0;
+        std::hint::black_box(x);
+    });
+
+    let x: Tensor2D<64, 64> = Tensor2D::ones();
+    bench("traced regular", || {
+        let y = ((x.trace() + &b) * &c - &d) * 0.5 + 1.0;
+        std::hint::black_box(y.sum().backward());
+    });
+    bench("traced in place", || {
+        let mut y = x.trace();
+        y += &b;
+        y *= &c;
+        y -= &d;
+        y *= 0.5;
+        y += 1.0;
+        std::hint::black_box(y.sum().backward());
+    });
+}
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+    let start_allocs = num_allocs();
+    let start = Instant::now();
+    for _ in 0..NUM_ITERS {
+        f();
+    }
+    println!(
+        "{name:>15}: {:.1} allocations per iteration, {:?}",
+        (num_allocs() - start_allocs) as f32 / NUM_ITERS as f32,
+        start.elapsed()
+    );
+}
@@ -0,0 +1,32 @@
+//! Runs inference with weights that are compiled into the binary, using only the parts of
+//! dfdx that are available without the `std` feature:
+//!
+//! ```text
+//! cargo run --example no_std_inference --no-default-features
+//! ```
+
+use dfdx::prelude::*;
+
+type Mlp = (Linear<2, 3>, ReLU, Linear<3, 1>, Sigmoid);
+
+// e.g. exported from a model trained on the host with `std`
+const W0: [[f32; 2]; 3] = [[1.0, -1.0], [-1.0, 1.0], [0.5, 0.5]];
+const B0: [f32; 3] = [0.0, 0.0, -0.5];
+const W1: [[f32; 3]; 1] = [[4.0, 4.0, -8.0]];
+const B1: [f32; 1] = [-2.0];
+
+fn main() {
+    // load the weights by writing to the tensors directly
+    let mut mlp: Mlp = Default::default();
+    *mlp.0.weight.mut_data() = W0;
+    *mlp.0.bias.mut_data() = B0;
+    *mlp.2.weight.mut_data() = W1;
+    *mlp.2.bias.mut_data() = B1;
+
+    // forward without a tape, so no gradients are tracked
+    let x: Tensor2D<4, 2> = Tensor2D::new([[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+    let y = mlp.forward(x.clone());
+
+    // only print with std, the rest of `main` works the same without it
+    println!("xor({:?}) = {:?}", x.data(), y.data());
+}
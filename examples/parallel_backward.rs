@@ -0,0 +1,49 @@
+//! Compares how long backward takes on a wide chain of elementwise ops, using
+//! 1 thread vs all the threads of the rayon thread pool:
+//!
+//! ```text
+//! cargo run --release --example parallel_backward --features rayon
+//! ```
+
+use dfdx::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::{Duration, Instant};
+
+const NUM_ITERS: usize = 20;
+
+// runs forward & backward `NUM_ITERS` times, and returns how long backward took in total
+fn time_backward() -> Duration {
+    let mut rng = StdRng::seed_from_u64(0);
+    let x: Tensor2D<256, 4096> = Tensor2D::randn(&mut rng);
+    let w1: Tensor2D<256, 4096> = Tensor2D::randn(&mut rng);
+    let w2: Tensor2D<256, 4096> = Tensor2D::randn(&mut rng);
+
+    let mut total = Duration::ZERO;
+    for _ in 0..NUM_ITERS {
+        let y = (x.trace() * &w1).tanh();
+        let y = (y * &w2 + &x).sigmoid().square();
+        let loss = y.mean();
+        let start = Instant::now();
+        let gradients = loss.backward();
+        total += start.elapsed();
+        std::hint::black_box(gradients);
+    }
+    total
+}
+
+fn main() {
+    let single = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let single = single.install(time_backward);
+    println!("1 thread:  {:?} per backward", single / NUM_ITERS as u32);
+
+    let num_threads = rayon::current_num_threads();
+    let parallel = time_backward();
+    println!(
+        "{num_threads} threads: {:?} per backward ({:.1}x faster)",
+        parallel / NUM_ITERS as u32,
+        single.as_secs_f64() / parallel.as_secs_f64(),
+    );
+}
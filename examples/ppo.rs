@@ -29,6 +29,7 @@ fn main() {
     let mut sgd = Sgd::new(SgdConfig {
         lr: 1e-1,
         momentum: Some(Momentum::Nesterov(0.9)),
+        gradient_noise: None,
     });
 
     // run through training data
@@ -1,7 +1,8 @@
-//! A collection of data utility classes such as [one_hot_encode()] and [SubsetIterator].
+//! A collection of data utility classes such as [one_hot_encode()], [SubsetIterator], and [DataLoader].
 
 use crate::prelude::*;
 use rand::prelude::SliceRandom;
+use std::vec::Vec;
 
 /// Generates a tensor with ordered data from 0 to `N`.
 ///
@@ -52,14 +53,59 @@ pub fn arange<const N: usize>() -> Tensor1D<N> {
 ///     [0.0, 1.0, 0.0],
 /// ]);
 /// ```
+///
+/// **Panics** if any of the class labels are `>= N`.
 pub fn one_hot_encode<const B: usize, const N: usize>(class_labels: &[usize; B]) -> Tensor2D<B, N> {
+    one_hot_encode_smoothed(class_labels, 0.0)
+}
+
+/// One hot encodes an array of class labels into a [Tensor2D] of probability
+/// vectors, with label smoothing. The target class has a probability of `1 - epsilon`,
+/// and the remaining `epsilon` is spread evenly across the other `N - 1` classes.
+///
+/// See [one_hot_encode()] for the non smoothed version.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let probs = one_hot_encode_smoothed::<2, 3>(&[0, 2], 0.2);
+/// assert_eq!(probs.data(), &[[0.8, 0.1, 0.1], [0.1, 0.1, 0.8]]);
+/// ```
+///
+/// **Panics** if any of the class labels are `>= N`.
+pub fn one_hot_encode_smoothed<const B: usize, const N: usize>(
+    class_labels: &[usize; B],
+    epsilon: f32,
+) -> Tensor2D<B, N> {
     let mut result = Tensor2D::zeros();
-    for (i, row) in result.mut_data().iter_mut().enumerate() {
-        row[class_labels[i]] = 1.0;
+    for (row, &label) in result.mut_data().iter_mut().zip(class_labels.iter()) {
+        assert!(
+            label < N,
+            "class label {label} is out of range for {N} classes"
+        );
+        for (i, v) in row.iter_mut().enumerate() {
+            *v = if i == label {
+                1.0 - epsilon
+            } else {
+                epsilon / (N - 1) as f32
+            };
+        }
     }
     result
 }
 
+impl<const B: usize, const N: usize> Tensor2D<B, N, NoneTape> {
+    /// Calls [one_hot_encode()].
+    pub fn one_hot(class_labels: &[usize; B]) -> Self {
+        one_hot_encode(class_labels)
+    }
+
+    /// Calls [one_hot_encode_smoothed()].
+    pub fn one_hot_smoothed(class_labels: &[usize; B], epsilon: f32) -> Self {
+        one_hot_encode_smoothed(class_labels, epsilon)
+    }
+}
+
 /// A utility class to simplify sampling a fixed number of indices for
 /// data from a dataset.
 ///
@@ -116,9 +162,195 @@ impl<const B: usize> Iterator for SubsetIterator<B> {
     }
 }
 
+/// Something that can be stacked into a batch of `B` items. Implemented for `f32` and `f32` arrays,
+/// which are batched into tensors with a new leading dimension of size `B`, and for `usize`
+/// labels, which are batched into `[usize; B]`. Used by [DataLoader].
+pub trait Collate<const B: usize>: Sized + Clone {
+    /// The batched version of `Self`.
+    type Batched;
+
+    /// Used to fill the end of a partial batch.
+    const PADDING: Self;
+
+    /// Stacks `items` into a batch.
+    fn collate(items: [Self; B]) -> Self::Batched;
+}
+
+impl<const B: usize> Collate<B> for usize {
+    type Batched = [usize; B];
+    const PADDING: Self = 0;
+    fn collate(items: [Self; B]) -> Self::Batched {
+        items
+    }
+}
+
+impl<const B: usize> Collate<B> for f32 {
+    type Batched = Tensor1D<B>;
+    const PADDING: Self = 0.0;
+    fn collate(items: [Self; B]) -> Self::Batched {
+        Tensor1D::new(items)
+    }
+}
+
+impl<const B: usize, const M: usize> Collate<B> for [f32; M] {
+    type Batched = Tensor2D<B, M>;
+    const PADDING: Self = [0.0; M];
+    fn collate(items: [Self; B]) -> Self::Batched {
+        Tensor2D::new(items)
+    }
+}
+
+impl<const B: usize, const M: usize, const N: usize> Collate<B> for [[f32; N]; M] {
+    type Batched = Tensor3D<B, M, N>;
+    const PADDING: Self = [[0.0; N]; M];
+    fn collate(items: [Self; B]) -> Self::Batched {
+        Tensor3D::new(items)
+    }
+}
+
+impl<const B: usize, const M: usize, const N: usize, const O: usize> Collate<B>
+    for [[[f32; O]; N]; M]
+{
+    type Batched = Tensor4D<B, M, N, O>;
+    const PADDING: Self = [[[0.0; O]; N]; M];
+    fn collate(items: [Self; B]) -> Self::Batched {
+        Tensor4D::new(items)
+    }
+}
+
+/// A batch of `B` items from a [DataLoader].
+#[derive(Debug, Clone)]
+pub struct Batch<X, Y> {
+    /// The batched inputs.
+    pub x: X,
+
+    /// The batched labels.
+    pub y: Y,
+
+    /// The number of real items in the batch. This is `B` for every batch except possibly the last one
+    /// of an epoch when [DataLoader::drop_last()] is `false`, where the items after `len` are zeros.
+    pub len: usize,
+}
+
+/// Iterates over a slice of `(input, label)` pairs in batches of `B`, optionally in a random order.
+/// Each batch is stacked into tensors using [Collate].
+///
+/// Iterating a [DataLoader] is one epoch. To iterate another epoch, create a new one (with the same
+/// `rng` if shuffling, so each epoch gets a different order).
+///
+/// Generic Arguments:
+/// - `B` - The batch size.
+///
+/// By default the final partial batch is kept, and padded with zeros (see [Batch::len]), so every
+/// item is seen exactly once:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let data = [([1.0, 2.0], 0), ([3.0, 4.0], 1), ([5.0, 6.0], 2)];
+/// let mut loader = DataLoader::<_, _, 2>::new(&data);
+///
+/// let batch = loader.next().unwrap();
+/// assert_eq!(batch.x.data(), &[[1.0, 2.0], [3.0, 4.0]]);
+/// assert_eq!(batch.y, [0, 1]);
+/// assert_eq!(batch.len, 2);
+///
+/// let batch = loader.next().unwrap();
+/// assert_eq!(batch.x.data(), &[[5.0, 6.0], [0.0, 0.0]]);
+/// assert_eq!(batch.y, [2, 0]);
+/// assert_eq!(batch.len, 1);
+///
+/// assert!(loader.next().is_none());
+/// ```
+///
+/// Shuffling, and dropping the final partial batch:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # use rand::prelude::*;
+/// # let data = [([1.0, 2.0], 0), ([3.0, 4.0], 1), ([5.0, 6.0], 2)];
+/// let mut rng = StdRng::seed_from_u64(0);
+/// let loader = DataLoader::<_, _, 2>::new(&data)
+///     .shuffled(&mut rng)
+///     .drop_last(true);
+/// assert_eq!(loader.count(), 1);
+/// ```
+pub struct DataLoader<'a, X, Y, const B: usize> {
+    data: &'a [(X, Y)],
+    indices: Vec<usize>,
+    i: usize,
+    drop_last: bool,
+}
+
+impl<'a, X, Y, const B: usize> DataLoader<'a, X, Y, B> {
+    /// Iterates `data` in order, keeping the final partial batch.
+    pub fn new(data: &'a [(X, Y)]) -> Self {
+        assert!(B > 0, "batch size must be greater than 0");
+        Self {
+            data,
+            indices: (0..data.len()).collect(),
+            i: 0,
+            drop_last: false,
+        }
+    }
+
+    /// Shuffles the order `data` is iterated in using `rng`. Seed `rng` for a reproducible order.
+    pub fn shuffled<R: rand::Rng>(mut self, rng: &mut R) -> Self {
+        self.indices[self.i..].shuffle(rng);
+        self
+    }
+
+    /// Whether to skip the final batch if it has less than `B` items.
+    pub fn drop_last(mut self, drop_last: bool) -> Self {
+        self.drop_last = drop_last;
+        self
+    }
+}
+
+impl<'a, X: Collate<B>, Y: Collate<B>, const B: usize> Iterator for DataLoader<'a, X, Y, B> {
+    type Item = Batch<X::Batched, Y::Batched>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = B.min(self.indices.len() - self.i);
+        if len == 0 || (self.drop_last && len < B) {
+            return None;
+        }
+        let batch = &self.indices[self.i..self.i + len];
+        self.i += len;
+        let item = |k: usize| batch.get(k).map(|&j| &self.data[j]);
+        Some(Batch {
+            x: X::collate(std::array::from_fn(|k| {
+                item(k).map_or(X::PADDING, |(x, _)| x.clone())
+            })),
+            y: Y::collate(std::array::from_fn(|k| {
+                item(k).map_or(Y::PADDING, |(_, y)| y.clone())
+            })),
+            len,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_one_hot() {
+        let t: Tensor2D<3, 2> = Tensor2D::one_hot(&[1, 0, 1]);
+        assert_eq!(t.data(), &[[0.0, 1.0], [1.0, 0.0], [0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_one_hot_smoothed() {
+        let t: Tensor2D<2, 5> = Tensor2D::one_hot_smoothed(&[4, 1], 0.4);
+        assert_eq!(
+            t.data(),
+            &[[0.1, 0.1, 0.1, 0.1, 0.6], [0.1, 0.6, 0.1, 0.1, 0.1]]
+        );
+    }
+
+    #[test]
+    #[should_panic = "class label 3 is out of range for 3 classes"]
+    fn test_one_hot_out_of_range() {
+        let _: Tensor2D<2, 3> = Tensor2D::one_hot(&[0, 3]);
+    }
 
     #[test]
     fn sampler_uses_all() {
@@ -141,4 +373,68 @@ mod tests {
             assert!(seen.contains(&i));
         }
     }
+
+    #[test]
+    fn test_data_loader_visits_every_item_once() {
+        let data: Vec<([f32; 2], usize)> = (0..10).map(|i| ([i as f32, -(i as f32)], i)).collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut seen: Vec<usize> = Vec::new();
+        for batch in DataLoader::<_, _, 4>::new(&data).shuffled(&mut rng) {
+            for k in 0..batch.len {
+                let label = batch.y[k];
+                assert_eq!(batch.x.data()[k], [label as f32, -(label as f32)]);
+                seen.push(label);
+            }
+        }
+        assert_ne!(seen, (0..10).collect::<Vec<usize>>());
+        seen.sort_unstable();
+        assert_eq!(seen, (0..10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_data_loader_partial_batch() {
+        let data: Vec<(f32, usize)> = (0..10).map(|i| (i as f32, i)).collect();
+        let lens: Vec<usize> = DataLoader::<_, _, 4>::new(&data).map(|b| b.len).collect();
+        assert_eq!(lens, [4, 4, 2]);
+
+        let last = DataLoader::<_, _, 4>::new(&data).last().unwrap();
+        assert_eq!(last.x.data(), &[8.0, 9.0, 0.0, 0.0]);
+        assert_eq!(last.y, [8, 9, 0, 0]);
+
+        let lens: Vec<usize> = DataLoader::<_, _, 4>::new(&data)
+            .drop_last(true)
+            .map(|b| b.len)
+            .collect();
+        assert_eq!(lens, [4, 4]);
+
+        let lens: Vec<usize> = DataLoader::<_, _, 5>::new(&data)
+            .drop_last(true)
+            .map(|b| b.len)
+            .collect();
+        assert_eq!(lens, [5, 5]);
+    }
+
+    #[test]
+    fn test_data_loader_seeded_shuffle() {
+        let data: Vec<([[f32; 2]; 1], usize)> = (0..20).map(|i| ([[i as f32; 2]], i)).collect();
+        let order = |seed| -> Vec<usize> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            DataLoader::<_, _, 3>::new(&data)
+                .shuffled(&mut rng)
+                .flat_map(|b| b.y.into_iter().take(b.len))
+                .collect()
+        };
+        assert_eq!(order(0), order(0));
+        assert_ne!(order(0), order(1));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let batch = DataLoader::<_, _, 3>::new(&data)
+            .shuffled(&mut rng)
+            .next()
+            .unwrap();
+        let x: &[[[f32; 2]; 1]; 3] = batch.x.data();
+        for (x, &y) in x.iter().zip(batch.y.iter()) {
+            assert_eq!(x, &[[y as f32; 2]]);
+        }
+    }
 }
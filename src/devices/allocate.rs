@@ -1,22 +1,31 @@
 use super::Cpu;
 use crate::arrays::CountElements;
-use std::alloc::{alloc_zeroed, Layout};
+use std::alloc::{alloc_zeroed, handle_alloc_error, Layout};
+use std::boxed::Box;
 
 /// Allocate an Nd array on the heap.
 pub trait AllocateZeros {
-    /// Allocate T directly on the heap.
+    /// Allocate T directly on the heap. The array is never constructed on the stack, so this
+    /// works for arrays that are much larger than the stack.
     fn zeros<T: CountElements>() -> Box<T>;
 }
 
 impl AllocateZeros for Cpu {
     /// Allocates using [alloc_zeroed].
     fn zeros<T: CountElements>() -> Box<T> {
-        // TODO is this function safe for any T?
         // TODO move to using safe code once we can allocate an array directly on the heap.
         let layout = Layout::new::<T>();
         debug_assert_eq!(layout.size(), T::NUM_BYTES);
+        if layout.size() == 0 {
+            // SAFETY: a dangling pointer is a valid Box for zero sized types (e.g. `[f32; 0]`)
+            return unsafe { Box::from_raw(std::ptr::NonNull::<T>::dangling().as_ptr()) };
+        }
+        // SAFETY: `T` is nested arrays of `f32`/`usize`, for which all zeros is a valid value.
         unsafe {
             let ptr = alloc_zeroed(layout) as *mut T;
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
             Box::from_raw(ptr)
         }
     }
@@ -84,6 +93,14 @@ mod tests {
         assert_eq!(data.as_ref(), &[[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]]);
     }
 
+    #[test]
+    fn test_zero_sized_zeros() {
+        let t: Box<[f32; 0]> = Cpu::zeros();
+        assert_eq!(t.as_ref(), &[0.0; 0]);
+        let t: Box<[[f32; 0]; 3]> = Cpu::zeros();
+        assert_eq!(t.as_ref(), &[[0.0; 0]; 3]);
+    }
+
     #[test]
     fn test_alloc_large() {
         const N: usize = 1_000_000;
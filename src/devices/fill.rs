@@ -1,5 +1,6 @@
 use super::{AllocateZeros, Cpu};
 use crate::arrays::CountElements;
+use std::boxed::Box;
 
 /// Fills all elements with the specified function
 pub trait FillElements<T: CountElements>: Sized + AllocateZeros {
@@ -80,4 +81,20 @@ mod tests {
         Cpu::fill(&mut t, &mut |v| *v = 2.0);
         assert_eq!(t, [[[2.0; 2]; 3]; 5]);
     }
+
+    #[test]
+    fn test_4d_fill() {
+        let mut t: [[[[f32; 2]; 3]; 4]; 5] = ZeroElements::ZEROS;
+        let mut i = 0.0;
+        Cpu::fill(&mut t, &mut |v| {
+            *v = i;
+            i += 1.0;
+        });
+        // elements are visited in row major order
+        assert_eq!(t[0][0][0], [0.0, 1.0]);
+        assert_eq!(t[0][0][2], [4.0, 5.0]);
+        assert_eq!(t[0][1][0], [6.0, 7.0]);
+        assert_eq!(t[1][0][0], [24.0, 25.0]);
+        assert_eq!(t[4][3][2], [118.0, 119.0]);
+    }
 }
@@ -3,6 +3,7 @@
 mod allocate;
 mod fill;
 mod foreach;
+mod parallel;
 mod reduce;
 mod reduce_last_dim;
 
@@ -12,9 +13,11 @@ pub struct Cpu;
 pub use allocate::*;
 pub use fill::*;
 pub use foreach::*;
+pub(crate) use parallel::*;
 pub use reduce::*;
 pub use reduce_last_dim::*;
 
+use std::boxed::Box;
 use std::ops::*;
 
 /// Represents something that can act on `T`.
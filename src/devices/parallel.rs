@@ -0,0 +1,104 @@
+//! Elementwise backward operations that split their arrays across threads with [rayon]
+//! when the `rayon` feature is enabled.
+//!
+//! Only the work inside of a single operation is split up. The operations on a
+//! [crate::gradients::GradientTape] are still executed one after another, so they don't
+//! need to be [Send].
+
+use crate::arrays::CountElements;
+use crate::tensor_ops::{flat, flat_mut};
+
+/// The number of elements in each chunk that is given to a thread. Arrays with
+/// fewer elements than this are not split up.
+#[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+pub(crate) const CHUNK_LEN: usize = 1 << 14;
+
+/// Mutate elements of `a` by applying `f` to all elements of (a, b, c).
+/// This is the same as [super::ForEachElement::foreach_mrr()], but runs on the rayon
+/// thread pool with the `rayon` feature.
+///
+/// The elements are split into chunks of [CHUNK_LEN] regardless of the number of threads,
+/// and each element of `a` only depends on the same element of `b` and `c`, so the
+/// result is the same no matter how many threads there are.
+pub(crate) fn par_foreach_mrr<A, F>(a: &mut A, b: &A, c: &A, f: F)
+where
+    A: CountElements<Dtype = f32>,
+    F: Fn(&mut f32, &f32, &f32) + Sync,
+{
+    let (a, b, c) = (flat_mut(a), flat(b), flat(c));
+
+    #[cfg(feature = "rayon")]
+    if a.len() > CHUNK_LEN {
+        use rayon::prelude::*;
+        a.par_chunks_mut(CHUNK_LEN)
+            .zip(b.par_chunks(CHUNK_LEN))
+            .zip(c.par_chunks(CHUNK_LEN))
+            .for_each(|((a, b), c)| foreach_mrr(a, b, c, &f));
+        return;
+    }
+
+    foreach_mrr(a, b, c, &f);
+}
+
+fn foreach_mrr<F: Fn(&mut f32, &f32, &f32)>(a: &mut [f32], b: &[f32], c: &[f32], f: &F) {
+    for ((a, b), c) in a.iter_mut().zip(b.iter()).zip(c.iter()) {
+        f(a, b, c);
+    }
+}
+
+/// Computes `out += lhs * rhs` using [par_foreach_mrr()].
+pub(crate) fn par_addmul<A: CountElements<Dtype = f32>>(out: &mut A, lhs: &A, rhs: &A) {
+    par_foreach_mrr(out, lhs, rhs, |o, l, r| *o += l * r);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_par_addmul_matches_foreach() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a: Tensor2D<3, CHUNK_LEN> = Tensor2D::randn(&mut rng);
+        let b: Tensor2D<3, CHUNK_LEN> = Tensor2D::randn(&mut rng);
+        let mut expected: Tensor2D<3, CHUNK_LEN> = Tensor2D::randn(&mut rng);
+        let mut out = expected.clone();
+
+        Cpu::addmul(expected.mut_data(), a.data(), b.data());
+        par_addmul(out.mut_data(), a.data(), b.data());
+        assert_eq!(out.data(), expected.data());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_backward_same_for_any_number_of_threads() {
+        type Model = (Linear<64, 1024>, Tanh, Linear<1024, 1024>, Sigmoid);
+
+        // tensors aren't `Send`, so everything is created on the thread pool
+        let backward_with_threads = |num_threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let mut rng = StdRng::seed_from_u64(1);
+                let mut model: Model = Default::default();
+                model.reset_params(&mut rng);
+                let x: Tensor2D<64, 64> = Tensor2D::randn(&mut rng);
+                let scale: Tensor2D<64, 1024> = Tensor2D::randn(&mut rng);
+                let loss = (model.forward(x.trace()) * &scale).square().mean();
+                let gradients = loss.backward();
+                (
+                    *gradients.ref_gradient(&x),
+                    *gradients.ref_gradient(&model.2.bias),
+                )
+            })
+        };
+
+        let expected = backward_with_threads(1);
+        for num_threads in [2, 3, 8] {
+            assert_eq!(backward_with_threads(num_threads), expected);
+        }
+    }
+}
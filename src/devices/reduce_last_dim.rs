@@ -1,5 +1,6 @@
 use super::{AllocateZeros, Cpu};
 use crate::arrays::{CountElements, MultiDimensional};
+use std::boxed::Box;
 
 /// Something that can have its last dimension (inner most dimension) reduced to 1 number.
 ///
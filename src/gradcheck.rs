@@ -0,0 +1,190 @@
+//! Numerical gradient checking, to validate the backward pass of operations, e.g. custom ones
+//! written with [crate::gradients::Tape::add_backward_op()].
+//!
+//! [gradcheck()] compares the gradients computed by backprop against central differences:
+//! `(f(x + eps) - f(x - eps)) / (2 * eps)` for each element of `x`, and returns the maximum error.
+//! Use [gradcheck2()] for functions of two tensors.
+//!
+//! Since everything is computed with `f32`, `eps` shouldn't be too small. Something like `1e-3`
+//! with a tolerance of `1e-2` works for most operations.
+//!
+//! # Example
+//! ```rust
+//! # use dfdx::prelude::*;
+//! # use dfdx::gradcheck::gradcheck;
+//! let x: Tensor2D<2, 3> = Tensor2D::new([[0.1, -0.5, 1.0], [2.0, 0.3, -1.2]]);
+//! let error = gradcheck(|x: Tensor2D<2, 3, OwnedTape>| x.tanh().square().sum(), &x, 1e-3);
+//! assert!(error < 1e-2);
+//! ```
+
+use crate::prelude::*;
+use crate::tensor_ops::{flat, flat_mut};
+
+/// Checks the gradient of `f` with respect to `x`, and returns the maximum error between the
+/// gradient computed by backprop and the central differences with a step of `eps`.
+///
+/// The error of each element is `|analytic - numerical| / max(1, |analytic|, |numerical|)`, which is
+/// the relative error for large gradients and the absolute error for small ones.
+///
+/// `f` is called with `x` once to compute the gradient, and then twice for each element of `x`.
+/// If the result of `f` doesn't depend on `x`, the gradient is all zeros.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # use dfdx::gradcheck::gradcheck;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+///
+/// // the derivative of sin is cos
+/// let ok = |x: Tensor1D<3, OwnedTape>| x.map(|x| x.sin(), |x| x.cos()).sum();
+/// assert!(gradcheck(ok, &x, 1e-3) < 1e-2);
+///
+/// // a wrong derivative is detected
+/// let wrong = |x: Tensor1D<3, OwnedTape>| x.map(|x| x.sin(), |x| x.sin()).sum();
+/// assert!(gradcheck(wrong, &x, 1e-3) > 1e-2);
+/// ```
+pub fn gradcheck<T, F>(mut f: F, x: &T::NoTape, eps: f32) -> f32
+where
+    T: Tensor<Dtype = f32, Tape = OwnedTape>,
+    F: FnMut(T) -> Tensor0D<OwnedTape>,
+{
+    let gradients = f(trace(x)).backward();
+    max_error(&gradients, x, |i| {
+        central_difference::<T, _>(x, i, eps, |x| eval(f(traced(x))))
+    })
+}
+
+/// Checks the gradients of `f` with respect to both `a` and `b` like [gradcheck()], and returns
+/// the maximum error over the elements of both.
+///
+/// Both inputs are traced, so `f` needs to merge their tapes (e.g. with [div_merged()]).
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # use dfdx::gradcheck::gradcheck2;
+/// let a = Tensor1D::new([1.0, -2.0, 0.5]);
+/// let b = Tensor1D::new([0.7, 1.5, -2.0]);
+/// let f = |a: Tensor1D<3, OwnedTape>, b: Tensor1D<3, OwnedTape>| div_merged(a, b).square().sum();
+/// assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+/// ```
+pub fn gradcheck2<A, B, F>(mut f: F, a: &A::NoTape, b: &B::NoTape, eps: f32) -> f32
+where
+    A: Tensor<Dtype = f32, Tape = OwnedTape>,
+    B: Tensor<Dtype = f32, Tape = OwnedTape>,
+    F: FnMut(A, B) -> Tensor0D<OwnedTape>,
+{
+    let gradients = f(trace(a), trace(b)).backward();
+    let a_error = max_error(&gradients, a, |i| {
+        central_difference::<A, _>(a, i, eps, |a| eval(f(traced(a), trace(b))))
+    });
+    let b_error = max_error(&gradients, b, |i| {
+        central_difference::<B, _>(b, i, eps, |b| eval(f(trace(a), traced(b))))
+    });
+    a_error.max(b_error)
+}
+
+/// The value of `t`, dropping its tape.
+fn eval(t: Tensor0D<OwnedTape>) -> f32 {
+    *t.data()
+}
+
+/// `(f(x + eps) - f(x - eps)) / (2 * eps)`, where only the `i`th element of `x` is changed.
+fn central_difference<T: Tensor<Dtype = f32>, F: FnMut(T::NoTape) -> f32>(
+    x: &T::NoTape,
+    i: usize,
+    eps: f32,
+    mut f: F,
+) -> f32 {
+    let mut plus = x.duplicate();
+    flat_mut(plus.mut_data())[i] += eps;
+    let mut minus = x.duplicate();
+    flat_mut(minus.mut_data())[i] -= eps;
+    (f(plus) - f(minus)) / (2.0 * eps)
+}
+
+/// The maximum error between the gradient of `x` in `gradients`, and `numerical(i)` for
+/// each element `i` of `x`.
+fn max_error<T: Tensor<Dtype = f32>, F: FnMut(usize) -> f32>(
+    gradients: &Gradients,
+    x: &T,
+    mut numerical: F,
+) -> f32 {
+    let zeros = T::NoTape::zeros();
+    let analytic = match gradients.maybe_ref_gradient(x) {
+        Some(g) => flat(g),
+        None => flat(zeros.data()),
+    };
+    let mut error = 0.0f32;
+    for (i, a) in analytic.iter().enumerate() {
+        let n = numerical(i);
+        error = error.max((a - n).abs() / 1.0f32.max(a.abs()).max(n.abs()));
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_gradcheck_ops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let w: Tensor2D<4, 2> = Tensor2D::randn(&mut rng);
+
+        let f = |x: Tensor2D<3, 4, OwnedTape>| matmul(x, &w).log_softmax().square().mean();
+        assert!(gradcheck(f, &x, 1e-3) < 1e-2);
+
+        let f = |x: Tensor2D<3, 4, OwnedTape>| x.sigmoid().sum_last_dim().exp().sum();
+        assert!(gradcheck(f, &x, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    fn test_gradcheck_all_ranks() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x: Tensor0D = Tensor0D::new(0.5);
+        assert!(gradcheck(|x: Tensor0D<OwnedTape>| x.tanh(), &x, 1e-3) < 1e-2);
+
+        let x: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let f = |x: Tensor3D<2, 3, 4, OwnedTape>| x.softmax().square().sum();
+        assert!(gradcheck(f, &x, 1e-3) < 1e-2);
+
+        let x: Tensor4D<2, 1, 2, 3> = Tensor4D::randn(&mut rng);
+        let f = |x: Tensor4D<2, 1, 2, 3, OwnedTape>| x.sin().mean();
+        assert!(gradcheck(f, &x, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    fn test_gradcheck_detects_wrong_backward() {
+        let x = Tensor1D::new([0.5, 1.0, 2.0]);
+        let f = |x: Tensor1D<3, OwnedTape>| x.map(|x| x.powi(3), |x| 2.0 * x * x).sum();
+        // the error is `|2x^2 - 3x^2| / 3x^2` for x >= 1
+        let error = gradcheck(f, &x, 1e-3);
+        assert!((error - 1.0 / 3.0).abs() < 1e-2, "{error}");
+    }
+
+    #[test]
+    fn test_gradcheck_unused_input() {
+        let x = Tensor1D::new([1.0, 2.0]);
+        let f = |_: Tensor1D<2, OwnedTape>| Tensor0D::new(1.0).traced();
+        assert_eq!(gradcheck(f, &x, 1e-3), 0.0);
+    }
+
+    #[test]
+    fn test_gradcheck2() {
+        let a = Tensor1D::new([1.0, 2.0]);
+        let b = Tensor1D::new([-0.5, 3.0, 1.5]);
+        let f =
+            |a: Tensor1D<2, OwnedTape>, b: Tensor1D<3, OwnedTape>| outer_merged(a, b).exp().sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+
+        // only the gradient of `b` is wrong
+        let f = |a: Tensor1D<2, OwnedTape>, b: Tensor1D<3, OwnedTape>| {
+            let b = b.map(|x| 2.0 * x, |_| 1.0);
+            outer_merged(a, b).sum()
+        };
+        assert!(gradcheck2(f, &a, &b, 1e-3) > 0.1);
+    }
+}
@@ -2,11 +2,12 @@
 
 use crate::prelude::*;
 use std::collections::HashMap;
+use std::{boxed::Box, rc::Rc, vec::Vec};
 
 /// Records gradient computations to execute later.
 ///
 /// The only two things you can do with this are:
-/// 1. Adding an operation (an operation is a FnOnce that acts on &mut [Gradients])
+/// 1. Adding an operation (an operation is a Fn that acts on &mut [Gradients])
 /// 2. Executing all the operations to produce [Gradients]
 ///
 /// The reason for this design, which forces users to specify gradient computations, as opposed to having
@@ -29,19 +30,44 @@ use std::collections::HashMap;
 /// This is implementing the chain rule, which is normally defined as `gradient(t) += deriv * gradient(result)` with
 /// the following optimizations:
 /// 1. instead of allocating new data for the derivative (which is just -1 everywhere), we can reuse the `t` tensor since the negate
-///     function owns it.
-/// 2. We can combine computing the derivative and multiplying by the `gradient(result)` by just setting `t` to `-gradient(result)`
+///     operation captures it.
+/// 2. We can combine computing the derivative and multiplying by the `gradient(result)` in a single pass over the data.
 ///
 /// This would not be possible if these chain rule operations were inside of GradientTape!
-#[derive(Default)]
+///
+/// Operations are [Fn] instead of [FnOnce], so they must not consume or modify what they capture.
+/// This means a tape can be executed more than once, see [GradientTape::execute_ref()] and [OwnedTape::retain()].
+///
+/// # Higher order gradients
+///
+/// Since operations act directly on arrays, they are not themselves recorded on a tape, and the
+/// resulting [Gradients] can't be differentiated again. Some operations can also add a taped version
+/// of themselves (see [Tape::add_backward_op_taped()]), which computes the same gradients with tensor
+/// operations that are recorded on a new tape. [GradientTape::execute_with_tape()] runs those instead,
+/// which supports double backward (e.g. for gradient penalties like WGAN-GP) through these operations.
+/// For checking gradients numerically, see [crate::gradcheck].
+#[derive(Default, Clone)]
 pub struct GradientTape {
-    operations: Vec<Box<dyn FnOnce(&mut Gradients)>>,
+    operations: Vec<BackwardOp>,
+}
+
+/// An operation recorded on a [GradientTape].
+#[derive(Clone)]
+struct BackwardOp {
+    name: &'static str,
+    operation: Rc<dyn Fn(&mut Gradients)>,
+    taped_operation: Option<TapedOperation>,
 }
 
+/// The same operation as [BackwardOp::operation] written with taped tensor operations.
+/// See [GradientTape::execute_with_tape()].
+type TapedOperation = Rc<dyn Fn(&mut TapedGradients)>;
+
 impl std::fmt::Debug for GradientTape {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GradientTape")
             .field("num_operations", &self.operations.len())
+            .field("op_names", &self.op_names())
             .finish()
     }
 }
@@ -50,27 +76,200 @@ impl GradientTape {
     /// Add an operation to be executed later. Implementation is all left to the caller,
     /// but the operation should likely call [Gradients::ref_gradient] and [Gradients::mut_gradient].
     ///
-    /// NOTE: This adds the operation to the beginning of the list, so operations are executed
-    /// in reverse order that they are added.
+    /// NOTE: Operations are executed in reverse order that they are added. They are stored in
+    /// the order they are added, so adding an operation is O(1).
     ///
     /// # Arguments
-    /// * `operation` - A FnOnce that acts on [Gradients].
+    /// * `name` - A label for the operation, which shows up in [GradientTape::op_names()].
+    /// * `operation` - A Fn that acts on [Gradients].
     ///
     /// See src/tensor_ops for implementation examples.
-    pub(crate) fn add_backward_op<F: 'static + FnOnce(&mut Gradients)>(&mut self, operation: F) {
-        self.operations.insert(0, Box::new(operation));
+    pub(crate) fn add_backward_op_named<F: 'static + Fn(&mut Gradients)>(
+        &mut self,
+        name: &'static str,
+        operation: F,
+    ) {
+        self.operations.push(BackwardOp {
+            name,
+            operation: Rc::new(operation),
+            taped_operation: None,
+        });
+    }
+
+    /// Same as [GradientTape::add_backward_op_named()], but also adds `taped_operation`, which
+    /// computes the same gradients with tensor operations. See [GradientTape::execute_with_tape()].
+    pub(crate) fn add_backward_op_taped<F, G>(
+        &mut self,
+        name: &'static str,
+        operation: F,
+        taped_operation: G,
+    ) where
+        F: 'static + Fn(&mut Gradients),
+        G: 'static + Fn(&mut TapedGradients),
+    {
+        self.operations.push(BackwardOp {
+            name,
+            operation: Rc::new(operation),
+            taped_operation: Some(Rc::new(taped_operation)),
+        });
+    }
+
+    /// The number of operations that have been recorded.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns `true` if no operations have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// The names of all the recorded operations, in the order they were recorded.
+    /// Operations added without a name are called `"unnamed"`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let model: (Linear<3, 2>, ReLU) = Default::default();
+    /// let y = model.forward(Tensor1D::<3>::zeros().traced());
+    /// assert_eq!(y.tape().op_names(), ["vecmat_mul_transpose", "add", "relu"]);
+    /// ```
+    pub fn op_names(&self) -> Vec<&'static str> {
+        self.operations.iter().map(|op| op.name).collect()
+    }
+
+    /// An estimate of the memory used by the recorded operations, in bytes. This adds up the
+    /// size of each operation's closures (i.e. what they capture by value, like tensor ids and
+    /// [Rc] pointers) and its bookkeeping.
+    ///
+    /// NOTE: Heap data behind an [Rc] (e.g. the tensor arrays that most operations capture)
+    /// is not included, since it is usually shared with the tensors themselves. Operations
+    /// shared between tapes with [OwnedTape::retain()] are counted by each tape.
+    pub fn estimated_bytes(&self) -> usize {
+        self.operations
+            .iter()
+            .map(|op| {
+                let taped = op
+                    .taped_operation
+                    .as_ref()
+                    .map_or(0, |f| std::mem::size_of_val(&**f));
+                std::mem::size_of::<BackwardOp>() + std::mem::size_of_val(&*op.operation) + taped
+            })
+            .sum()
     }
 
     /// Compute the [Gradients]! This just runs all the operations on a new [Gradients] struct.
     ///
     /// Note that this method takes ownership of self, so it can't be called twice!
-    pub fn execute(mut self) -> Gradients {
+    /// Use [GradientTape::execute_ref()] to keep the tape around.
+    ///
+    /// The operations are executed one after another on the current thread, since they are
+    /// opaque closures that capture tensor data in an [std::rc::Rc] (so they are not [Send]).
+    /// With the `rayon` feature, elementwise operations (e.g. [tanh()], [mul()]) instead split
+    /// their arrays into chunks that run on the rayon thread pool. The resulting gradients are
+    /// the same no matter how many threads there are.
+    pub fn execute(self) -> Gradients {
+        self.execute_ref()
+    }
+
+    /// Same as [GradientTape::execute()], but doesn't consume the tape, so the operations
+    /// can be executed again (e.g. after more operations are added to a clone of the tape).
+    pub fn execute_ref(&self) -> Gradients {
         let mut gradients: Gradients = Default::default();
-        for operation in self.operations.drain(..) {
-            (operation)(&mut gradients);
+        self.execute_into(&mut gradients);
+        gradients
+    }
+
+    /// Same as [GradientTape::execute()], but also measures the wall-clock time each operation
+    /// takes. Returns the name (see [GradientTape::op_names()]) and duration of each operation,
+    /// in the order they were executed.
+    ///
+    /// This is a separate method so that [GradientTape::execute()] doesn't pay for the timing.
+    #[cfg(feature = "std")]
+    pub fn execute_timed(self) -> (Gradients, Vec<(&'static str, std::time::Duration)>) {
+        let mut gradients: Gradients = Default::default();
+        let mut timings = Vec::with_capacity(self.operations.len());
+        self.execute_each(&mut gradients, |op, gradients| {
+            let start = std::time::Instant::now();
+            (op.operation)(gradients);
+            timings.push((op.name, start.elapsed()));
+        });
+        (gradients, timings)
+    }
+
+    /// Runs all the operations on an existing [Gradients], so they add to any gradients
+    /// that are already there.
+    ///
+    /// With the `nan-checks` feature enabled, every gradient is checked for NaN/Inf after each
+    /// operation executes, which panics with the index of the first operation that produced one.
+    /// Operations are indexed in the order they execute, so index `0` is the last operation added.
+    pub(crate) fn execute_into(&self, gradients: &mut Gradients) {
+        self.execute_each(gradients, |op, gradients| (op.operation)(gradients));
+    }
+
+    /// Calls `execute` with each operation in reverse order, which should run the operation
+    /// on `gradients`. With the `nan-checks` feature, the gradients are checked after each one
+    /// like in [GradientTape::execute_into()].
+    fn execute_each<F: FnMut(&BackwardOp, &mut Gradients)>(
+        &self,
+        gradients: &mut Gradients,
+        mut execute: F,
+    ) {
+        #[cfg(not(feature = "nan-checks"))]
+        for op in self.operations.iter().rev() {
+            execute(op, gradients);
+        }
+        #[cfg(feature = "nan-checks")]
+        for (i, op) in self.operations.iter().rev().enumerate() {
+            execute(op, gradients);
+            if let Some(id) = gradients.any_non_finite() {
+                let name = op.name;
+                panic!("backward op {i} ({name}) produced a non-finite gradient for {id:?}");
+            }
+        }
+    }
+
+    /// Like [GradientTape::execute()], but runs the taped version of each operation, so that the
+    /// resulting gradients are tensors that can be differentiated again. See [TapedGradients].
+    ///
+    /// The taped versions are added with [Tape::add_backward_op_taped()]. Right now these are
+    /// [matmul()], [matmul_transpose()], [vecmat_mul_transpose()], [add()], [sub()], [mul()],
+    /// [relu()], [add_broadcast_rhs_first()], [sub_broadcast_rhs_first()], [sum()], [mean()],
+    /// the scalar ops (e.g. [mul_scalar()]) and the `_merged` versions of these. Of the nn
+    /// modules, that covers [Linear] on 1d and 2d inputs and [ReLU].
+    /// Use [backward_with_tape()] to run this from a tensor.
+    ///
+    /// **Panics** if an operation without a taped version was recorded.
+    pub fn execute_with_tape(self) -> TapedGradients {
+        let mut gradients = TapedGradients {
+            gradient_by_id: HashMap::new(),
+            tape: OwnedTape(Box::new(self.clone())),
+        };
+        for op in self.operations.iter().rev() {
+            match &op.taped_operation {
+                Some(taped_operation) => (taped_operation)(&mut gradients),
+                None => no_taped_operation(op.name),
+            }
         }
         gradients
     }
+
+    /// Moves all the operations from `other` into `self`, leaving `other` empty.
+    ///
+    /// The operations of `self` and `other` are assumed to be independent of each other,
+    /// so their relative order doesn't matter. Even so, the operations of `self` are kept
+    /// after the operations of `other`, so that they execute first.
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        other.operations.append(&mut self.operations);
+        std::mem::swap(&mut self.operations, &mut other.operations);
+    }
+}
+
+/// Panics because the operation `name` can't be differentiated twice.
+/// See [GradientTape::execute_with_tape()].
+#[cold]
+pub(crate) fn no_taped_operation(name: &str) -> ! {
+    panic!("backward op `{name}` has no taped version, so it can't be differentiated twice")
 }
 
 /// Contains a boxed [GradientTape]. When [Tape::add_backward_op] is called,
@@ -78,6 +277,60 @@ impl GradientTape {
 #[derive(Default, Debug)]
 pub struct OwnedTape(pub(crate) Box<GradientTape>);
 
+impl OwnedTape {
+    /// See [GradientTape::len()].
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// See [GradientTape::is_empty()].
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// See [GradientTape::op_names()].
+    pub fn op_names(&self) -> Vec<&'static str> {
+        self.0.op_names()
+    }
+
+    /// See [GradientTape::estimated_bytes()].
+    pub fn estimated_bytes(&self) -> usize {
+        self.0.estimated_bytes()
+    }
+
+    /// Returns a new tape with all the operations recorded so far. The operations are
+    /// shared instead of copied, so this is cheap. Each tape can then record separate
+    /// operations and be executed on its own.
+    ///
+    /// This lets multiple losses backprop through the same forward pass, e.g. an actor critic
+    /// with a shared trunk. Each loss gets separate [Gradients], and the trunk's operations
+    /// are executed once per loss:
+    ///
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let trunk: Linear<4, 8> = Default::default();
+    /// let policy: Linear<8, 2> = Default::default();
+    /// let value: Linear<8, 1> = Default::default();
+    /// let x: Tensor1D<4> = Tensor1D::ones();
+    ///
+    /// let (h, tape) = trunk.forward(x.trace()).split_tape();
+    /// let policy_loss = policy.forward(h.duplicate().put_tape(tape.retain())).square().mean();
+    /// let value_loss = value.forward(h.put_tape(tape)).square().mean();
+    ///
+    /// let policy_grads = policy_loss.backward();
+    /// let value_grads = value_loss.backward();
+    /// ```
+    ///
+    /// The two tapes shouldn't be merged back together (e.g. with [MergeTape]), since that would
+    /// run the shared operations twice.
+    ///
+    /// This is different from [Clone], which isn't implemented for [OwnedTape] because
+    /// cloning a tensor creates a new [UniqueId] that the recorded operations don't know about.
+    pub fn retain(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 /// Contains nothing. When [Tape::add_backward_op] is called, this struct does nothing.
 #[derive(Default, Debug, Clone, Copy)]
 pub struct NoneTape;
@@ -86,19 +339,95 @@ pub struct NoneTape;
 pub trait Tape {
     /// Whether this object currently owns the [GradientTape]. This is known at compile time.
     const OWNS_TAPE: bool;
-    fn add_backward_op<F: 'static + FnOnce(&mut Gradients)>(&mut self, operation: F);
+    /// Adds an operation that shows up as `"unnamed"` in [GradientTape::op_names()].
+    fn add_backward_op<F: 'static + Fn(&mut Gradients)>(&mut self, operation: F) {
+        self.add_backward_op_named("unnamed", operation)
+    }
+    /// Adds an operation labeled with `name`, which shows up in [GradientTape::op_names()].
+    fn add_backward_op_named<F: 'static + Fn(&mut Gradients)>(
+        &mut self,
+        name: &'static str,
+        operation: F,
+    );
+    /// Adds an operation like [Tape::add_backward_op_named()], along with `taped_operation`, which
+    /// computes the same gradients with tensor operations. See [GradientTape::execute_with_tape()].
+    ///
+    /// By default this drops `taped_operation` and just calls [Tape::add_backward_op_named()].
+    fn add_backward_op_taped<F, G>(&mut self, name: &'static str, operation: F, taped_operation: G)
+    where
+        F: 'static + Fn(&mut Gradients),
+        G: 'static + Fn(&mut TapedGradients),
+    {
+        let _ = taped_operation;
+        self.add_backward_op_named(name, operation)
+    }
 }
 
 impl Tape for OwnedTape {
     const OWNS_TAPE: bool = true;
-    fn add_backward_op<F: 'static + FnOnce(&mut Gradients)>(&mut self, operation: F) {
-        self.0.add_backward_op(operation)
+    fn add_backward_op_named<F: 'static + Fn(&mut Gradients)>(
+        &mut self,
+        name: &'static str,
+        operation: F,
+    ) {
+        self.0.add_backward_op_named(name, operation)
+    }
+    fn add_backward_op_taped<F, G>(&mut self, name: &'static str, operation: F, taped_operation: G)
+    where
+        F: 'static + Fn(&mut Gradients),
+        G: 'static + Fn(&mut TapedGradients),
+    {
+        self.0
+            .add_backward_op_taped(name, operation, taped_operation)
     }
 }
 
 impl Tape for NoneTape {
     const OWNS_TAPE: bool = false;
-    fn add_backward_op<F: 'static + FnOnce(&mut Gradients)>(&mut self, _operation: F) {}
+    fn add_backward_op_named<F: 'static + Fn(&mut Gradients)>(
+        &mut self,
+        _name: &'static str,
+        _operation: F,
+    ) {
+    }
+}
+
+/// Combines two tapes into a single tape. This is used by operations that take
+/// multiple tensors that may each own a tape (e.g. [choose()]).
+///
+/// The result owns a tape if either of the inputs does.
+pub trait MergeTape<Other: Tape>: Tape {
+    type Output: Tape;
+    fn merge_tape(self, other: Other) -> Self::Output;
+}
+
+impl MergeTape<NoneTape> for NoneTape {
+    type Output = NoneTape;
+    fn merge_tape(self, _other: NoneTape) -> Self::Output {
+        self
+    }
+}
+
+impl MergeTape<OwnedTape> for NoneTape {
+    type Output = OwnedTape;
+    fn merge_tape(self, other: OwnedTape) -> Self::Output {
+        other
+    }
+}
+
+impl MergeTape<NoneTape> for OwnedTape {
+    type Output = OwnedTape;
+    fn merge_tape(self, _other: NoneTape) -> Self::Output {
+        self
+    }
+}
+
+impl MergeTape<OwnedTape> for OwnedTape {
+    type Output = OwnedTape;
+    fn merge_tape(mut self, mut other: OwnedTape) -> Self::Output {
+        self.0.append(&mut other.0);
+        self
+    }
 }
 
 /// A generic container for keeping variable sized arrays associated with a [UniqueId].
@@ -117,14 +446,71 @@ impl Tape for NoneTape {
 /// of that trait is used to downcast the box to the expected value.
 #[derive(Debug, Default)]
 pub struct Gradients {
-    gradient_by_id: HashMap<UniqueId, Box<dyn std::any::Any>>,
+    gradient_by_id: HashMap<UniqueId, GradientEntry>,
+}
+
+/// A type erased array, along with functions that know how to zero it out,
+/// add another array of the same type to it, scale it, and view it as a flat slice.
+#[derive(Debug)]
+struct GradientEntry {
+    data: Box<dyn std::any::Any>,
+    zero_out: fn(&mut dyn std::any::Any),
+    add: fn(&mut dyn std::any::Any, &dyn std::any::Any),
+    scale: fn(&mut dyn std::any::Any, f32),
+    flat: fn(&dyn std::any::Any) -> &[f32],
+}
+
+/// Fills the `A` stored in `data` with zeros using `D`.
+fn zero_out_array<A: 'static + CountElements, D: FillElements<A>>(data: &mut dyn std::any::Any) {
+    D::fill(data.downcast_mut::<A>().unwrap(), &mut |v| {
+        *v = Default::default()
+    });
+}
+
+/// Adds the `A` stored in `other` to the `A` stored in `data` using `D`.
+fn add_arrays<A: 'static + CountElements<Dtype = f32>, D: ForEachElement<A>>(
+    data: &mut dyn std::any::Any,
+    other: &dyn std::any::Any,
+) {
+    D::foreach_mr(
+        data.downcast_mut::<A>().unwrap(),
+        other.downcast_ref::<A>().unwrap(),
+        &mut |d, o| *d += o,
+    );
+}
+
+/// Multiplies the `A` stored in `data` by `s` using `D`.
+fn scale_array<A: 'static + CountElements<Dtype = f32>, D: ForEachElement<A>>(
+    data: &mut dyn std::any::Any,
+    s: f32,
+) {
+    D::foreach_m(data.downcast_mut::<A>().unwrap(), &mut |d| *d *= s);
+}
+
+/// Panics because the gradient stored for `id` isn't an `A`. This only happens if two tensors
+/// with different shapes share the same [UniqueId].
+#[cold]
+fn wrong_gradient_type<A>(id: &UniqueId) -> ! {
+    panic!(
+        "the gradient stored for {id:?} is not a {}. Two tensors with different shapes have the same id.",
+        std::any::type_name::<A>()
+    )
+}
+
+/// Views the `A` stored in `data` as a flat slice.
+fn flat_array<A: 'static + CountElements<Dtype = f32>>(data: &dyn std::any::Any) -> &[f32] {
+    flat(data.downcast_ref::<A>().unwrap())
 }
 
 impl Gradients {
     /// Borrows a pair of a gradients `(&mut L, &R)`.
     /// `l` is the gradient to update, and `r` is the gradient to backprop.
     ///
-    /// **Panics** if `l` and `r` have the same id.
+    /// **Panics** if `l` and `r` have the same id. Use [Gradients::mut_and_ref_with()] if they might.
+    ///
+    /// Note that a tensor used twice in one operation (e.g. `mul(x.trace(), &x)`) is fine, since
+    /// backward ops only ever borrow the gradient of an input together with the gradient of
+    /// the (new) result; both uses of `x` are added into the same gradient.
     ///
     /// Examples:
     /// ```rust
@@ -140,10 +526,14 @@ impl Gradients {
     /// ```
     pub fn mut_and_ref<L, R>(&mut self, l: &L, r: &R) -> (&mut L::Array, &R::Array)
     where
-        L: HasUniqueId + HasArrayType + HasDevice,
+        L: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
         R: HasUniqueId + HasArrayType,
     {
-        assert_ne!(l.id(), r.id());
+        assert_ne!(
+            l.id(),
+            r.id(),
+            "use Gradients::mut_and_ref_with() if the ids may be equal"
+        );
         let l_ptr = self.mut_gradient(l) as *mut L::Array;
         let r_ptr = self.ref_gradient(r) as *const R::Array;
         let l_ref = unsafe { &mut *l_ptr };
@@ -151,9 +541,40 @@ impl Gradients {
         (l_ref, r_ref)
     }
 
-    /// Removes and returns the data associated with `t.id()`.
+    /// Calls `f` with the gradients `(&mut L, &R)`, like [Gradients::mut_and_ref()], except that
+    /// `l` and `r` may have the same id. In that case `f` receives a copy of the gradient from
+    /// before the update as `r`'s gradient, so a gradient can be updated based on itself.
     ///
-    /// **Panics** if data associated with `t` is not found. This indicates an unrecoverable bug.
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients: Gradients = Default::default();
+    /// *gradients.mut_gradient(&a) = [1.0, 2.0, 3.0];
+    /// gradients.mut_and_ref_with(&a, &a, |g_a, g| {
+    ///     for (l, r) in g_a.iter_mut().zip(g.iter()) {
+    ///         *l += r;
+    ///     }
+    /// });
+    /// assert_eq!(gradients.ref_gradient(&a), &[2.0, 4.0, 6.0]);
+    /// ```
+    pub fn mut_and_ref_with<L, R, F>(&mut self, l: &L, r: &R, f: F)
+    where
+        L: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
+        R: HasUniqueId + HasArrayType,
+        F: FnOnce(&mut L::Array, &R::Array),
+    {
+        if l.id() == r.id() {
+            let r_grad = self.ref_gradient(r).clone();
+            f(self.mut_gradient(l), &r_grad);
+        } else {
+            let (l_grad, r_grad) = self.mut_and_ref(l, r);
+            f(l_grad, r_grad);
+        }
+    }
+
+    /// Removes and returns the data associated with `t.id()`, or `None` if there isn't any
+    /// (e.g. because `t` wasn't used to compute the loss).
     ///
     /// Example usage:
     /// ```
@@ -161,15 +582,16 @@ impl Gradients {
     /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
     /// let mut gradients: Gradients = Default::default();
     /// *gradients.mut_gradient(&t) = [-4.0, 5.0, -6.0];
-    /// assert_eq!(gradients.remove(&t).as_ref(), &[-4.0, 5.0, -6.0]);
+    /// assert_eq!(gradients.remove(&t).unwrap().as_ref(), &[-4.0, 5.0, -6.0]);
+    /// assert!(gradients.remove(&t).is_none());
     /// ```
-    pub fn remove<T: HasUniqueId + HasArrayType>(&mut self, t: &T) -> Box<T::Array> {
-        self.gradient_by_id
-            .remove_entry(t.id())
-            .unwrap()
-            .1
-            .downcast()
-            .unwrap()
+    pub fn remove<T: HasUniqueId + HasArrayType>(&mut self, t: &T) -> Option<Box<T::Array>> {
+        self.gradient_by_id.remove(t.id()).map(|entry| {
+            entry
+                .data
+                .downcast()
+                .unwrap_or_else(|_| wrong_gradient_type::<T::Array>(t.id()))
+        })
     }
 
     /// Returns a mutable reference to the data associated with `t`.
@@ -187,16 +609,23 @@ impl Gradients {
     /// g[0] = 1.0;
     /// assert_eq!(gradients.ref_gradient(&t), &[1.0, 0.0, 0.0]);
     /// ```
-    pub fn mut_gradient<T: HasUniqueId + HasArrayType + HasDevice>(
+    pub fn mut_gradient<T: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice>(
         &mut self,
         t: &T,
     ) -> &mut T::Array {
         self.gradient_by_id
             .entry(*t.id())
-            .or_insert_with(|| T::Device::zeros::<T::Array>())
+            .or_insert_with(|| GradientEntry {
+                data: T::Device::zeros::<T::Array>(),
+                zero_out: zero_out_array::<T::Array, T::Device>,
+                add: add_arrays::<T::Array, T::Device>,
+                scale: scale_array::<T::Array, T::Device>,
+                flat: flat_array::<T::Array>,
+            })
+            .data
             .as_mut()
             .downcast_mut()
-            .unwrap()
+            .unwrap_or_else(|| wrong_gradient_type::<T::Array>(t.id()))
     }
 
     /// Returns a reference to the data associated with `t`.
@@ -218,9 +647,251 @@ impl Gradients {
         self.gradient_by_id
             .get(t.id())
             .unwrap()
+            .data
             .as_ref()
             .downcast_ref()
-            .unwrap()
+            .unwrap_or_else(|| wrong_gradient_type::<T::Array>(t.id()))
+    }
+
+    /// Returns a reference to the data associated with `t`, or `None` if
+    /// nothing is associated with `t` yet.
+    pub(crate) fn maybe_ref_gradient<T: HasUniqueId + HasArrayType>(
+        &self,
+        t: &T,
+    ) -> Option<&T::Array> {
+        self.gradient_by_id.get(t.id()).map(|entry| {
+            entry
+                .data
+                .as_ref()
+                .downcast_ref()
+                .unwrap_or_else(|| wrong_gradient_type::<T::Array>(t.id()))
+        })
+    }
+
+    /// Sets every stored array to `0.0`, without removing any entries or
+    /// deallocating any of the arrays. Subsequent calls to [Gradients::mut_gradient]
+    /// for ids that are already stored will not allocate.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients: Gradients = Default::default();
+    /// *gradients.mut_gradient(&t) = [-4.0, 5.0, -6.0];
+    /// gradients.zero_out();
+    /// assert_eq!(gradients.ref_gradient(&t), &[0.0, 0.0, 0.0]);
+    /// ```
+    pub fn zero_out(&mut self) {
+        for entry in self.gradient_by_id.values_mut() {
+            (entry.zero_out)(entry.data.as_mut());
+        }
+    }
+
+    /// Adds every array in `other` to the array with the same id in `self`. Arrays that are
+    /// only in `other` are moved into `self`.
+    ///
+    /// This is useful for gradient accumulation, where the gradients of several
+    /// micro-batches are summed before a single optimizer update. See [Gradients::scale()]
+    /// to turn the sum into an average.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients = t.trace().square().sum().backward();
+    /// gradients.accumulate(t.trace().sum().backward());
+    /// assert_eq!(gradients.ref_gradient(&t), &[3.0, 5.0, 7.0]);
+    /// ```
+    pub fn accumulate(&mut self, other: Gradients) {
+        for (id, entry) in other.gradient_by_id {
+            match self.gradient_by_id.get_mut(&id) {
+                Some(existing) => (existing.add)(existing.data.as_mut(), entry.data.as_ref()),
+                None => {
+                    self.gradient_by_id.insert(id, entry);
+                }
+            }
+        }
+    }
+
+    /// Multiplies every stored array by `s`. For example, after accumulating the
+    /// gradients of `n` micro-batches with [Gradients::accumulate()], `scale(1.0 / n as f32)`
+    /// averages them.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients: Gradients = Default::default();
+    /// *gradients.mut_gradient(&t) = [-4.0, 5.0, -6.0];
+    /// gradients.scale(0.5);
+    /// assert_eq!(gradients.ref_gradient(&t), &[-2.0, 2.5, -3.0]);
+    /// ```
+    pub fn scale(&mut self, s: f32) {
+        for entry in self.gradient_by_id.values_mut() {
+            (entry.scale)(entry.data.as_mut(), s);
+        }
+    }
+
+    /// Returns the smallest [UniqueId] whose array contains a NaN, or `None` if there are none.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients: Gradients = Default::default();
+    /// *gradients.mut_gradient(&t) = [-4.0, f32::NAN, -6.0];
+    /// assert_eq!(gradients.any_nan(), Some(*t.id()));
+    /// ```
+    pub fn any_nan(&self) -> Option<UniqueId> {
+        self.find(|v| v.is_nan())
+    }
+
+    /// Returns the smallest [UniqueId] whose array contains a NaN or an infinity, or `None`
+    /// if all arrays are finite.
+    pub fn any_non_finite(&self) -> Option<UniqueId> {
+        self.find(|v| !v.is_finite())
+    }
+
+    /// Returns the smallest [UniqueId] whose array contains an element matching `f`.
+    fn find<F: Fn(&f32) -> bool>(&self, f: F) -> Option<UniqueId> {
+        self.gradient_by_id
+            .iter()
+            .filter(|(_, entry)| (entry.flat)(entry.data.as_ref()).iter().any(&f))
+            .map(|(id, _)| *id)
+            .min()
+    }
+
+    /// Removes all entries, deallocating all of the stored arrays. The capacity of
+    /// the underlying map is kept.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let mut gradients: Gradients = Default::default();
+    /// gradients.mut_gradient(&t);
+    /// gradients.clear();
+    /// assert!(gradients.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.gradient_by_id.clear();
+    }
+
+    /// Returns the number of arrays stored.
+    pub fn len(&self) -> usize {
+        self.gradient_by_id.len()
+    }
+
+    /// Returns `true` if there are no arrays stored.
+    pub fn is_empty(&self) -> bool {
+        self.gradient_by_id.is_empty()
+    }
+}
+
+/// The gradients computed by [GradientTape::execute_with_tape()]. Each gradient is a tensor whose
+/// computation is recorded on a single [OwnedTape], so a function of the gradients (e.g. a gradient
+/// penalty) can be differentiated again.
+///
+/// The tape starts with all the operations of the [GradientTape] that computed the gradients, so
+/// differentiating a gradient also flows back through the operations of the forward pass.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let w = Tensor1D::new([0.5, -1.0, 2.0]);
+///
+/// // the gradient of `x * w` wrt. x is `w`
+/// let gradients = mul(x.trace(), &w).backward_with_tape();
+/// let x_grad = gradients.gradient(&x).unwrap();
+/// assert_eq!(x_grad.data(), &[0.5, -1.0, 2.0]);
+///
+/// // `sum(x_grad^2)` is `sum(w^2)`, so its gradient wrt. w is `2 * w`
+/// let penalty = x_grad.put_tape(gradients.into_tape()).square().sum();
+/// let penalty_gradients = penalty.backward();
+/// assert_eq!(penalty_gradients.ref_gradient(&w), &[1.0, -2.0, 4.0]);
+/// ```
+#[derive(Debug)]
+pub struct TapedGradients {
+    gradient_by_id: HashMap<UniqueId, Box<dyn std::any::Any>>,
+    tape: OwnedTape,
+}
+
+impl TapedGradients {
+    /// Returns the gradient of `t` without a tape, or `None` if `t` doesn't have a gradient.
+    ///
+    /// To differentiate it again, put [TapedGradients::into_tape()] into it (or into a tensor
+    /// computed from it).
+    pub fn gradient<T: Tensor>(&self, t: &T) -> Option<T::NoTape> {
+        self.gradient_by_id.get(t.id()).map(|gradient| {
+            gradient
+                .downcast_ref::<T::NoTape>()
+                .unwrap_or_else(|| wrong_gradient_type::<T::Array>(t.id()))
+                .duplicate()
+        })
+    }
+
+    /// Returns the tape that all the gradients were computed with.
+    pub fn into_tape(self) -> OwnedTape {
+        self.tape
+    }
+
+    /// Returns the gradient of `t` with a new tape, so that operations on it can be added
+    /// to `self` with [TapedGradients::accumulate()].
+    pub(crate) fn traced<T: Tensor<Tape = OwnedTape>>(&self, t: &impl HasUniqueId) -> Option<T> {
+        self.gradient_by_id.get(t.id()).map(|gradient| {
+            gradient
+                .downcast_ref::<T::NoTape>()
+                .unwrap_or_else(|| wrong_gradient_type::<T::Array>(t.id()))
+                .duplicate()
+                .put_tape(OwnedTape::default())
+        })
+    }
+
+    /// Adds `gradient` to the gradient of `t`, and moves the operations recorded on the tape
+    /// of `gradient` into `self`.
+    pub(crate) fn accumulate<T: Tensor<Dtype = f32, Tape = OwnedTape>>(
+        &mut self,
+        t: &impl HasUniqueId,
+        gradient: T,
+    ) {
+        let gradient = match self.gradient_by_id.remove(t.id()) {
+            None => gradient,
+            Some(existing) => {
+                let existing = existing
+                    .downcast::<T::NoTape>()
+                    .unwrap_or_else(|_| wrong_gradient_type::<T::Array>(t.id()));
+                add(gradient, existing.as_ref())
+            }
+        };
+        let (gradient, mut tape) = gradient.split_tape();
+        self.tape.0.operations.append(&mut tape.0.operations);
+
+        // whatever is differentiated next may not use this gradient, and the operations
+        // that computed it need it to have a gradient of its own.
+        let phantom_gradient = gradient.phantom();
+        self.tape.add_backward_op_named("gradient", move |grads| {
+            grads.mut_gradient(&phantom_gradient);
+        });
+        self.gradient_by_id.insert(*t.id(), Box::new(gradient));
+    }
+
+    /// Returns a new tensor with the values of `data`, where `data` is a copy of the values of `t`.
+    /// The gradient of the new tensor is added to the gradient of `t`, so using it in
+    /// operations is the same as using `t`.
+    pub(crate) fn alias<T, K>(&mut self, t: &K, data: &T::Array) -> T
+    where
+        T: 'static + Tensor<Dtype = f32, Tape = NoneTape> + TensorCreator,
+        K: 'static + HasUniqueId + HasArrayType<Array = T::Array, Dtype = f32> + HasDevice + Copy,
+    {
+        let mut alias = T::zeros();
+        alias.mut_data().clone_from(data);
+        let (t, phantom_alias) = (*t, alias.phantom());
+        self.tape.add_backward_op_named("alias", move |grads| {
+            let (t_grad, alias_grad) = grads.mut_and_ref(&t, &phantom_alias);
+            T::Device::add(t_grad, alias_grad);
+        });
+        alias
     }
 }
 
@@ -238,7 +909,9 @@ pub trait GradientProvider {
     /// Retrieves the data associated with `p` if there is any.
     /// This can modify `self`, for instance if velocities are calculated
     /// based on the associated data!
-    fn gradient<P>(&mut self, p: &P) -> Box<P::Array>
+    ///
+    /// Returns `None` if `p` shouldn't be updated, e.g. because it has no gradient.
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
     where
         P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice;
 }
@@ -264,6 +937,8 @@ pub trait CanUpdateWithGradients {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::AssertClose;
+    use std::cell::RefCell;
 
     struct Tensor {
         id: UniqueId,
@@ -284,6 +959,48 @@ mod tests {
         type Device = Cpu;
     }
 
+    #[test]
+    #[should_panic = "use Gradients::mut_and_ref_with()"]
+    fn test_mut_and_ref_same_id() {
+        let t = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        g.mut_gradient(&t);
+        g.mut_and_ref(&t, &t);
+    }
+
+    #[test]
+    #[should_panic = "is not a [f32; 3]. Two tensors with different shapes have the same id."]
+    fn test_gradient_type_mismatch() {
+        let t = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        g.mut_gradient(&t);
+        let t: crate::tensor::Tensor1D<3> = crate::tensor::Tensor1D {
+            id: t.id,
+            data: Default::default(),
+            tape: Default::default(),
+        };
+        g.mut_gradient(&t);
+    }
+
+    #[test]
+    fn test_mut_and_ref_with() {
+        let t1 = Tensor { id: unique_id() };
+        let t2 = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        *g.mut_gradient(&t1) = [1.0, 2.0, 3.0, 4.0, 5.0];
+        *g.mut_gradient(&t2) = [1.0; 5];
+
+        // different ids behave like mut_and_ref
+        g.mut_and_ref_with(&t2, &t1, Cpu::add);
+        assert_eq!(g.ref_gradient(&t2), &[2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        // the same id sees the gradient from before the update
+        g.mut_and_ref_with(&t1, &t1, |l, r| {
+            Cpu::foreach_mr(l, r, &mut |l, r| *l = *l * 2.0 + r);
+        });
+        assert_eq!(g.ref_gradient(&t1), &[3.0, 6.0, 9.0, 12.0, 15.0]);
+    }
+
     #[test]
     fn test_backward() {
         let id = unique_id();
@@ -291,7 +1008,7 @@ mod tests {
         let _t1: Tensor = Tensor { id };
 
         let mut tape = GradientTape::default();
-        tape.add_backward_op(move |g| {
+        tape.add_backward_op_named("test", move |g| {
             let t_grad = g.mut_gradient(&_t1);
             for x in t_grad.iter_mut() {
                 *x += 1.0;
@@ -300,4 +1017,298 @@ mod tests {
         let g = tape.execute();
         assert_eq!(g.ref_gradient(&t1), &[1.0; 5]);
     }
+
+    #[test]
+    fn test_op_names() {
+        use crate::tensor::Tensor as _;
+
+        let mut tape = OwnedTape::default();
+        assert!(tape.is_empty());
+        tape.add_backward_op(|_| {});
+        tape.add_backward_op_named("custom", |_| {});
+        assert_eq!(tape.len(), 2);
+        assert_eq!(tape.op_names(), ["unnamed", "custom"]);
+
+        let x: Tensor2D<2, 3> = Tensor2D::zeros();
+        let y = (x.trace().exp() * 2.0).sum();
+        assert_eq!(y.tape().len(), 3);
+        assert_eq!(y.tape().op_names(), ["exp", "mul_scalar", "sum"]);
+        assert!(format!("{:?}", y.tape()).contains(r#"op_names: ["exp", "mul_scalar", "sum"]"#));
+    }
+
+    #[test]
+    fn test_estimated_bytes() {
+        let mut tape = OwnedTape::default();
+        assert_eq!(tape.estimated_bytes(), 0);
+        tape.add_backward_op(|_| {});
+        let empty = tape.estimated_bytes();
+        assert!(empty > 0);
+
+        let captured = [0.0f32; 16];
+        tape.add_backward_op(move |_| {
+            assert_eq!(captured.iter().sum::<f32>(), 0.0);
+        });
+        assert_eq!(tape.estimated_bytes(), 2 * empty + 64);
+    }
+
+    /// Records the order that operations execute in, by pushing their index to a shared log.
+    fn add_logged_op(tape: &mut OwnedTape, log: &Rc<RefCell<Vec<usize>>>, i: usize) {
+        let log = log.clone();
+        tape.add_backward_op(move |_| log.borrow_mut().push(i));
+    }
+
+    #[test]
+    fn test_execution_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut tape = OwnedTape::default();
+        for i in 0..4 {
+            add_logged_op(&mut tape, &log, i);
+        }
+        tape.0.execute();
+        assert_eq!(log.borrow().as_slice(), &[3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_merged_execution_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut lhs = OwnedTape::default();
+        let mut rhs = OwnedTape::default();
+        add_logged_op(&mut lhs, &log, 0);
+        add_logged_op(&mut rhs, &log, 1);
+        add_logged_op(&mut lhs, &log, 2);
+        add_logged_op(&mut rhs, &log, 3);
+        let mut tape = lhs.merge_tape(rhs);
+        add_logged_op(&mut tape, &log, 4);
+        tape.0.execute();
+        assert_eq!(log.borrow().as_slice(), &[4, 2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn test_merge_with_none_tape_keeps_owned_tape() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut owned = OwnedTape::default();
+        add_logged_op(&mut owned, &log, 0);
+        add_logged_op(&mut owned, &log, 1);
+        let tape = owned.merge_tape(NoneTape);
+        assert_eq!(tape.len(), 2);
+        let mut tape = NoneTape.merge_tape(tape);
+        assert_eq!(tape.len(), 2);
+        add_logged_op(&mut tape, &log, 2);
+        tape.0.execute();
+        assert_eq!(log.borrow().as_slice(), &[2, 1, 0]);
+    }
+
+    #[test]
+    fn test_merged_tapes_backward() {
+        use crate::tensor::Tensor as _;
+
+        let a = Tensor1D::new([1.0, 2.0, 3.0]);
+        let b = Tensor1D::new([2.0, -1.0, 0.5]);
+        let r = div_merged(a.trace().square(), b.trace().exp());
+        let gradients = r.sum().backward();
+        let b_exp = b.duplicate().exp();
+        let mut a_grad = [0.0; 3];
+        let mut b_grad = [0.0; 3];
+        for i in 0..3 {
+            a_grad[i] = 2.0 * a.data()[i] / b_exp.data()[i];
+            b_grad[i] = -a.data()[i].powi(2) / b_exp.data()[i];
+        }
+        gradients.ref_gradient(&a).assert_close(&a_grad, 1e-6);
+        gradients.ref_gradient(&b).assert_close(&b_grad, 1e-6);
+    }
+
+    #[test]
+    fn test_execute_ref_twice() {
+        let t = Tensor { id: unique_id() };
+        let _t = Tensor { id: t.id };
+
+        let mut tape = GradientTape::default();
+        tape.add_backward_op_named("test", move |g| {
+            g.mut_gradient(&_t).fill(2.0);
+        });
+        let g1 = tape.execute_ref();
+        let g2 = tape.execute_ref();
+        assert_eq!(g1.ref_gradient(&t), &[2.0; 5]);
+        assert_eq!(g2.ref_gradient(&t), &[2.0; 5]);
+    }
+
+    #[test]
+    fn test_retain_shared_trunk() {
+        use crate::tensor::Tensor as _;
+        use rand::{prelude::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut trunk: Linear<4, 8> = Default::default();
+        let mut policy: Linear<8, 2> = Default::default();
+        let mut value: Linear<8, 1> = Default::default();
+        trunk.reset_params(&mut rng);
+        policy.reset_params(&mut rng);
+        value.reset_params(&mut rng);
+        let x: Tensor1D<4> = Tensor1D::randn(&mut rng);
+
+        let (h, tape) = trunk.forward(x.trace()).split_tape();
+        let policy_loss = policy
+            .forward(h.duplicate().put_tape(tape.retain()))
+            .square()
+            .mean();
+        let value_loss = value.forward(h.put_tape(tape)).square().mean();
+        let policy_grads = policy_loss.backward();
+        let value_grads = value_loss.backward();
+
+        let expected_policy = policy.forward(trunk.forward(x.trace())).square().mean();
+        let expected_policy = expected_policy.backward();
+        let expected_value = value.forward(trunk.forward(x.trace())).square().mean();
+        let expected_value = expected_value.backward();
+
+        assert_eq!(
+            policy_grads.ref_gradient(&trunk.weight),
+            expected_policy.ref_gradient(&trunk.weight)
+        );
+        assert_eq!(
+            policy_grads.ref_gradient(&policy.weight),
+            expected_policy.ref_gradient(&policy.weight)
+        );
+        assert!(policy_grads.maybe_ref_gradient(&value.weight).is_none());
+        assert_eq!(
+            value_grads.ref_gradient(&trunk.weight),
+            expected_value.ref_gradient(&trunk.weight)
+        );
+        assert_eq!(
+            value_grads.ref_gradient(&value.bias),
+            expected_value.ref_gradient(&value.bias)
+        );
+        assert!(value_grads.maybe_ref_gradient(&policy.weight).is_none());
+    }
+
+    #[test]
+    fn test_zero_out_keeps_entries() {
+        let t = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        g.mut_gradient(&t).fill(1.0);
+        let ptr = g.ref_gradient(&t).as_ptr();
+
+        g.zero_out();
+        assert_eq!(g.len(), 1);
+        assert_eq!(g.ref_gradient(&t), &[0.0; 5]);
+        assert_eq!(g.mut_gradient(&t).as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_clear_removes_entries() {
+        let t = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        g.mut_gradient(&t).fill(1.0);
+        assert!(!g.is_empty());
+
+        g.clear();
+        assert!(g.is_empty());
+        assert_eq!(g.mut_gradient(&t), &[0.0; 5]);
+    }
+
+    #[test]
+    fn test_accumulate_adds_and_moves_entries() {
+        let a = Tensor { id: unique_id() };
+        let b = Tensor { id: unique_id() };
+        let c = Tensor { id: unique_id() };
+
+        let mut g: Gradients = Default::default();
+        g.mut_gradient(&a).fill(1.0);
+        g.mut_gradient(&b).fill(2.0);
+
+        let mut other: Gradients = Default::default();
+        other.mut_gradient(&b).fill(0.5);
+        other.mut_gradient(&c).fill(-1.0);
+
+        g.accumulate(other);
+        g.scale(2.0);
+        assert_eq!(g.len(), 3);
+        assert_eq!(g.ref_gradient(&a), &[2.0; 5]);
+        assert_eq!(g.ref_gradient(&b), &[5.0; 5]);
+        assert_eq!(g.ref_gradient(&c), &[-2.0; 5]);
+    }
+
+    #[test]
+    fn test_accumulated_micro_batches_match_full_batch() {
+        use rand::{prelude::StdRng, SeedableRng};
+
+        type Model = (Linear<4, 8>, ReLU, Linear<8, 2>);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let mut micro_model = model.clone();
+        let x: Tensor2D<32, 4> = Tensor2D::randn(&mut rng);
+        let y: Tensor2D<32, 2> = Tensor2D::randn(&mut rng);
+
+        let mut opt: Sgd<Model> = Default::default();
+        let loss = mse_loss(model.forward(x.trace()), &y);
+        opt.update(&mut model, loss.backward());
+
+        let mut micro_opt: Sgd<Model> = Default::default();
+        let mut gradients: Gradients = Default::default();
+        for i in 0..4 {
+            let mut micro_x: Tensor2D<8, 4> = Tensor2D::zeros();
+            let mut micro_y: Tensor2D<8, 2> = Tensor2D::zeros();
+            micro_x
+                .mut_data()
+                .copy_from_slice(&x.data()[i * 8..(i + 1) * 8]);
+            micro_y
+                .mut_data()
+                .copy_from_slice(&y.data()[i * 8..(i + 1) * 8]);
+            let loss = mse_loss(micro_model.forward(micro_x.trace()), &micro_y);
+            gradients.accumulate(loss.backward());
+        }
+        gradients.scale(1.0 / 4.0);
+        micro_opt.update(&mut micro_model, gradients);
+
+        for (a, b) in model
+            .0
+            .weight
+            .data()
+            .iter()
+            .zip(micro_model.0.weight.data())
+        {
+            for (a, b) in a.iter().zip(b.iter()) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+        for (a, b) in model.2.bias.data().iter().zip(micro_model.2.bias.data()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_any_nan() {
+        let a = Tensor { id: unique_id() };
+        let b = Tensor { id: unique_id() };
+        let mut g: Gradients = Default::default();
+        assert_eq!(g.any_nan(), None);
+
+        g.mut_gradient(&a).fill(1.0);
+        g.mut_gradient(&b)[3] = f32::INFINITY;
+        assert_eq!(g.any_nan(), None);
+        assert_eq!(g.any_non_finite(), Some(b.id));
+
+        g.mut_gradient(&b)[1] = f32::NAN;
+        assert_eq!(g.any_nan(), Some(b.id));
+        g.mut_gradient(&a)[4] = f32::NAN;
+        assert_eq!(g.any_nan(), Some(a.id));
+        assert_eq!(g.any_non_finite(), Some(a.id));
+    }
+
+    #[cfg(feature = "nan-checks")]
+    #[test]
+    #[should_panic = "backward op 2 (ln) produced a non-finite gradient"]
+    fn test_nan_checks_panics_with_op_index() {
+        let x = Tensor1D::new([1.0, 0.0, 2.0]);
+        // ops execute as: seed with ones, `sum`, then `ln`, whose gradient is `inf` at `0`
+        let _ = x.trace().ln().sum().backward();
+    }
+
+    #[cfg(feature = "nan-checks")]
+    #[test]
+    #[should_panic = "backward op 2 (ln) produced a non-finite gradient"]
+    fn test_nan_checks_backward_timed() {
+        let x = Tensor1D::new([1.0, 0.0, 2.0]);
+        let _ = x.trace().ln().sum().backward_timed();
+    }
 }
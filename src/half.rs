@@ -0,0 +1,177 @@
+//! Mixed precision storage: [HalfTensor] stores the data of a tensor as 16 bit [f16](struct@f16)s
+//! from the `half` crate, which uses half the memory of `f32`, and all computation is done in `f32`.
+//!
+//! Values are rounded to the nearest [f16](struct@f16) when they are stored, and converted back to `f32` exactly
+//! before they are used. Results are accumulated in `f32` (e.g. [HalfTensor::sum()] and [matmul_half()]),
+//! so only the storage loses precision, not the accumulation.
+//!
+//! Training in [f16](struct@f16) is not supported: [HalfTensor] has no tape, so gradients and
+//! optimizer updates are computed with normal `f32` tensors.
+//!
+//! Enable with the `half` feature:
+//! ```toml
+//! dfdx = { version = "...", features = ["half"] }
+//! ```
+//!
+//! Examples:
+//! ```rust
+//! # use dfdx::prelude::*;
+//! # use dfdx::half::*;
+//! let x: HalfTensor<Tensor2D<2, 3>> = HalfTensor::from_tensor(&Tensor2D::ones());
+//! let w: HalfTensor<Tensor2D<3, 4>> = HalfTensor::from_tensor(&Tensor2D::ones());
+//! let y: HalfTensor<Tensor2D<2, 4>> = matmul_half(&x, &w);
+//! assert_eq!(y.to_tensor().data(), &[[3.0; 4]; 2]);
+//! ```
+
+use crate::prelude::*;
+use crate::tensor_ops::{flat, flat_mut};
+use std::{boxed::Box, marker::PhantomData, vec::Vec};
+
+pub use ::half::f16;
+
+/// The data of a tensor of type `T` stored as [f16](struct@f16)s, in row major order.
+///
+/// Convert from a tensor with [HalfTensor::from_tensor()], and back to a tensor with
+/// [HalfTensor::to_tensor()]. There is no tape, so gradients are computed with normal `f32` tensors.
+#[derive(Debug, Clone)]
+pub struct HalfTensor<T> {
+    data: Box<[f16]>,
+    marker: PhantomData<T>,
+}
+
+impl<T: Tensor<Dtype = f32>> HalfTensor<T> {
+    /// Rounds the data of `t` to [f16](struct@f16)s.
+    pub fn from_tensor(t: &T) -> Self {
+        Self {
+            data: flat(t.data()).iter().map(|&x| f16::from_f32(x)).collect(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Converts the data back to `f32` in a new tensor.
+    pub fn to_tensor(&self) -> T::NoTape {
+        let mut t = T::NoTape::zeros();
+        for (x, h) in flat_mut(t.mut_data()).iter_mut().zip(self.data.iter()) {
+            *x = h.to_f32();
+        }
+        t
+    }
+
+    /// The stored [f16](struct@f16)s, in row major order.
+    pub fn data(&self) -> &[f16] {
+        &self.data
+    }
+
+    /// Sums all the values, accumulating in `f32`.
+    pub fn sum(&self) -> f32 {
+        self.data.iter().map(|h| h.to_f32()).sum()
+    }
+
+    /// The mean of all the values, accumulating in `f32`.
+    pub fn mean(&self) -> f32 {
+        self.sum() / self.data.len() as f32
+    }
+}
+
+impl<T: Tensor<Dtype = f32>> From<&T> for HalfTensor<T> {
+    fn from(t: &T) -> Self {
+        Self::from_tensor(t)
+    }
+}
+
+/// Matrix multiplication of two [HalfTensor]s, accumulating in `f32`. Only the result is rounded
+/// to [f16](struct@f16).
+///
+/// The inputs are converted to `f32` one row of `a` at a time (and one element of `b` at a time),
+/// so besides the result this only needs `K + N` extra `f32`s, instead of converting both inputs
+/// to `f32` tensors.
+pub fn matmul_half<const M: usize, const K: usize, const N: usize>(
+    a: &HalfTensor<Tensor2D<M, K>>,
+    b: &HalfTensor<Tensor2D<K, N>>,
+) -> HalfTensor<Tensor2D<M, N>> {
+    if K == 0 || N == 0 {
+        // `chunks_exact()` can't split into empty rows
+        return HalfTensor::from_tensor(&Tensor2D::zeros());
+    }
+    let mut data = Vec::with_capacity(M * N);
+    let mut a_row = vec![0.0f32; K];
+    let mut acc = vec![0.0f32; N];
+    for a_half_row in a.data.chunks_exact(K) {
+        for (x, h) in a_row.iter_mut().zip(a_half_row.iter()) {
+            *x = h.to_f32();
+        }
+        acc.fill(0.0);
+        for (a_ik, b_row) in a_row.iter().zip(b.data.chunks_exact(N)) {
+            for (o, b_kn) in acc.iter_mut().zip(b_row.iter()) {
+                *o += a_ik * b_kn.to_f32();
+            }
+        }
+        data.extend(acc.iter().map(|&x| f16::from_f32(x)));
+    }
+    HalfTensor {
+        data: data.into_boxed_slice(),
+        marker: PhantomData,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_half_tensor_round_trip() {
+        let t = Tensor2D::new([[1.0, -0.5, 0.1], [1.2345, 1e-3, 60000.5]]);
+        let h = HalfTensor::from_tensor(&t);
+        assert_eq!(h.data().len(), 6);
+        assert_eq!(h.data()[0], f16::from_f32(1.0));
+        assert_close(
+            h.to_tensor().data(),
+            &[[1.0, -0.5, 0.099975586], [1.234375, 0.0010004044, 60000.0]],
+        );
+    }
+
+    #[test]
+    fn test_sum_accumulates_in_f32() {
+        // an f16 accumulator gets stuck at 2048, since 2048 + 1 rounds back to 2048
+        let h: HalfTensor<Tensor1D<10000>> = HalfTensor::from_tensor(&Tensor1D::ones());
+        let mut f16_sum = f16::from_f32(0.0);
+        for x in h.data() {
+            f16_sum = f16::from_f32(f16_sum.to_f32() + x.to_f32());
+        }
+        assert_eq!(f16_sum.to_f32(), 2048.0);
+        assert_eq!(h.sum(), 10000.0);
+        assert_eq!(h.mean(), 1.0);
+    }
+
+    #[test]
+    fn test_matmul_half_matches_f32() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a: Tensor2D<8, 64> = Tensor2D::randn(&mut rng);
+        let b: Tensor2D<64, 16> = Tensor2D::randn(&mut rng);
+        let expected = matmul(a.duplicate(), &b);
+
+        let c = matmul_half(&HalfTensor::from_tensor(&a), &HalfTensor::from_tensor(&b));
+        let c = c.to_tensor();
+        for (e, c) in flat(expected.data()).iter().zip(flat(c.data()).iter()) {
+            // f16 has 11 bits of precision, so relative errors are about 2^-11 per rounding
+            assert!((e - c).abs() <= 1e-2 * e.abs().max(1.0), "{e} vs {c}");
+        }
+    }
+
+    #[test]
+    fn test_matmul_half_accumulates_in_f32() {
+        // each output is 4096 * 1.0, which an f16 accumulator would get stuck at 2048 for
+        let a: HalfTensor<Tensor2D<1, 4096>> = HalfTensor::from_tensor(&Tensor2D::ones());
+        let b: HalfTensor<Tensor2D<4096, 2>> = HalfTensor::from_tensor(&Tensor2D::ones());
+        assert_eq!(matmul_half(&a, &b).to_tensor().data(), &[[4096.0; 2]]);
+    }
+
+    #[test]
+    fn test_matmul_half_empty() {
+        let a: HalfTensor<Tensor2D<2, 0>> = HalfTensor::from_tensor(&Tensor2D::zeros());
+        let b: HalfTensor<Tensor2D<0, 3>> = HalfTensor::from_tensor(&Tensor2D::zeros());
+        assert_eq!(matmul_half(&a, &b).to_tensor().data(), &[[0.0; 3]; 2]);
+    }
+}
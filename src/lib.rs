@@ -83,18 +83,31 @@
 //! // Use stochastic gradient descent (Sgd), with a learning rate of 1e-2, and 0.9 momentum.
 //! let mut opt = Sgd::new(SgdConfig {
 //!     lr: 1e-2,
-//!     momentum: Some(Momentum::Classic(0.9))
+//!     momentum: Some(Momentum::Classic(0.9)),
+//!     gradient_noise: None,
 //! });
 //!
 //! // pass the gradients & the model into the optimizer's update method
 //! opt.update(&mut model, gradients);
 //! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate no_std_compat as std;
+
 pub mod arrays;
 pub mod data;
 pub mod devices;
+pub mod gradcheck;
 pub mod gradients;
+#[cfg(feature = "half")]
+pub mod half;
 pub mod losses;
+pub mod metrics;
 pub mod nn;
 pub mod numpy;
 pub mod optim;
@@ -109,6 +122,7 @@ pub mod prelude {
     pub use crate::devices::*;
     pub use crate::gradients::*;
     pub use crate::losses::*;
+    pub use crate::metrics::*;
     pub use crate::nn::*;
     pub use crate::optim::*;
     pub use crate::tensor::*;
@@ -116,15 +130,22 @@ pub mod prelude {
     pub use crate::unique_id::*;
 }
 
-#[cfg(not(any(
-    feature = "mkl-static-seq",
-    feature = "mkl-static-iomp",
-    feature = "mkl-dynamic-seq",
-    feature = "mkl-dynamic-iomp"
-)))]
+#[cfg(not(feature = "cblas"))]
 /// The library used for BLAS. Configure with crate features.
 pub const BLAS_LIB: &str = "matrix-multiply";
 
+#[cfg(all(
+    feature = "cblas",
+    not(any(
+        feature = "mkl-static-seq",
+        feature = "mkl-static-iomp",
+        feature = "mkl-dynamic-seq",
+        feature = "mkl-dynamic-iomp"
+    ))
+))]
+/// The library used for BLAS. Configure with crate features.
+pub const BLAS_LIB: &str = "cblas";
+
 #[cfg(feature = "mkl-static-seq")]
 /// The library used for BLAS. Configure with crate features.
 pub const BLAS_LIB: &str = "mkl-static-seq";
@@ -208,4 +229,45 @@ pub(crate) mod tests {
     pub fn assert_close<T: AssertClose>(a: &T, b: &T) {
         a.assert_close(b, 1e-7);
     }
+
+    /// Keeps track of the bytes allocated by each thread, so that tests running
+    /// in parallel don't affect each other's measurements. See [peak_bytes()].
+    #[cfg(feature = "std")]
+    struct CountingAllocator;
+
+    #[cfg(feature = "std")]
+    std::thread_local! {
+        static LIVE_BYTES: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+        static PEAK_BYTES: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[cfg(feature = "std")]
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let _ = LIVE_BYTES.try_with(|live| {
+                live.set(live.get() + layout.size() as isize);
+                let _ = PEAK_BYTES.try_with(|peak| peak.set(peak.get().max(live.get())));
+            });
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            let _ = LIVE_BYTES.try_with(|live| live.set(live.get() - layout.size() as isize));
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    /// Runs `f` and returns the most bytes that were allocated at once by the current thread
+    /// while it ran, on top of what was already allocated when `f` was called.
+    #[cfg(feature = "std")]
+    pub fn peak_bytes<F: FnOnce()>(f: F) -> usize {
+        let start = LIVE_BYTES.with(|live| live.get());
+        PEAK_BYTES.with(|peak| peak.set(start));
+        f();
+        PEAK_BYTES.with(|peak| (peak.get() - start) as usize)
+    }
 }
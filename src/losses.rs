@@ -1,6 +1,8 @@
-//! Standard loss functions such as [mse_loss()], [cross_entropy_with_logits_loss()], and more.
+//! Standard loss functions such as [mse_loss()], [cross_entropy_with_logits_loss()], [focal_loss()], and more.
 
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 /// [Mean Squared Error](https://en.wikipedia.org/wiki/Mean_squared_error).
 /// This computes `(&targ - pred).square().mean()`.
@@ -51,6 +53,37 @@ pub fn cross_entropy_with_logits_loss<T: Tensor<Dtype = f32>>(
     -mean(sum_last_dim(mul(log_softmax(logits), target_probs)))
 }
 
+/// [cross_entropy_with_logits_loss()] with [label smoothing](https://arxiv.org/abs/1512.00567).
+/// The target class of each item has a probability of `1 - smoothing`, and the remaining `smoothing`
+/// is spread evenly across the other `C - 1` classes.
+///
+/// With `smoothing = 0.0` this is exactly the same as [cross_entropy_with_logits_loss()] with
+/// one hot targets. See [one_hot_encode_smoothed()] for how the targets are computed.
+///
+/// # Inputs
+/// - `logits` - the un-normalized output from a model. [log_softmax()] is called **in** this function
+/// - `targets` - the index of the target class of each item in the batch.
+/// - `smoothing` - how much probability to move off of the target class, must be in `[0.0, 1.0]`.
+///
+/// # Example
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let logits = Tensor2D::new([[-1.0, 0.5, 2.0], [0.0, 0.0, 0.0]]);
+/// let loss = cross_entropy_with_label_smoothing(logits.traced(), [2, 0], 0.1);
+/// ```
+pub fn cross_entropy_with_label_smoothing<const B: usize, const C: usize, H: Tape>(
+    logits: Tensor2D<B, C, H>,
+    targets: [usize; B],
+    smoothing: f32,
+) -> Tensor0D<H> {
+    assert!(
+        (0.0..=1.0).contains(&smoothing),
+        "smoothing must be in [0.0, 1.0], found {smoothing}"
+    );
+    let target_probs = one_hot_encode_smoothed(&targets, smoothing);
+    cross_entropy_with_logits_loss(logits, &target_probs)
+}
+
 /// [KL Divergence loss](https://en.wikipedia.org/wiki/Kullback%E2%80%93Leibler_divergence).
 /// This computes `(target_probs * (target_probs.log() - logits.log_softmax())).sum(-1).mean()`
 ///
@@ -81,7 +114,7 @@ pub fn kl_div_with_logits_loss<T: Tensor<Dtype = f32>>(
 
 /// [Binary Cross Entropy](https://en.wikipedia.org/wiki/Cross_entropy#Cross-entropy_loss_function_and_logistic_regression) With Logits in numerically stable way.
 ///
-/// Computes `(1 - target_probs) * logits + log(1 + exp(-logits))`.
+/// Computes `mean((1 - target_probs) * logits + log(1 + exp(-logits)))`.
 ///
 /// # Inputs
 /// - `logits` - unnormalized inputs. **NOT** output of sigmoid
@@ -95,6 +128,35 @@ pub fn kl_div_with_logits_loss<T: Tensor<Dtype = f32>>(
 /// let loss = binary_cross_entropy_with_logits_loss(logits.traced(), &target_probs);
 /// ```
 ///
+/// See [sigmoid_cross_entropy_no_reduction()] for the loss of each element, and how it
+/// is computed in a numerically stable way.
+pub fn binary_cross_entropy_with_logits_loss<T: Tensor<Dtype = f32>>(
+    logits: T,
+    target_probs: &T::NoTape,
+) -> Tensor0D<T::Tape> {
+    mean(sigmoid_cross_entropy_no_reduction(logits, target_probs))
+}
+
+/// The [Binary Cross Entropy](https://en.wikipedia.org/wiki/Cross_entropy#Cross-entropy_loss_function_and_logistic_regression)
+/// With Logits of each element, without reducing the result. Useful for multi-label classification
+/// where the losses need to be masked or weighted before they are reduced.
+///
+/// Computes `(1 - target_probs) * logits + log(1 + exp(-logits))` in a numerically stable way,
+/// so large positive or negative logits do not produce `inf` or `NaN` in any element.
+///
+/// # Inputs
+/// - `logits` - unnormalized inputs. **NOT** output of sigmoid
+/// - `target_probs` - target values between 0 and 1.
+///
+/// # Example
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let logits = Tensor2D::new([[-1.0, 100.0], [0.5, 2.0]]);
+/// let target_probs = Tensor2D::new([[1.0, 1.0], [0.0, 1.0]]);
+/// let losses = sigmoid_cross_entropy_no_reduction(logits.traced(), &target_probs);
+/// let loss = losses.masked_fill(&[[false, false], [true, false]], 0.0).mean();
+/// ```
+///
 /// # Numerically Stable Derivation
 ///
 /// The numerical stable version involves subtracting the maximum value
@@ -106,10 +168,10 @@ pub fn kl_div_with_logits_loss<T: Tensor<Dtype = f32>>(
 /// 5. 1 / exp(Q) = exp(-Q): `Q + log(exp(-Q) + exp(-logits) / exp(Q))`
 /// 6. exp(A) / exp(B) = exp(A-B): `Q + log(exp(-Q) + exp(-logits - Q))`
 /// 7. Now set `Q = max(logits)`!
-pub fn binary_cross_entropy_with_logits_loss<T: Tensor<Dtype = f32>>(
+pub fn sigmoid_cross_entropy_no_reduction<T: Tensor<Dtype = f32>>(
     logits: T,
     target_probs: &T::NoTape,
-) -> Tensor0D<T::Tape> {
+) -> T {
     let (logits, tape) = logits.split_tape();
 
     // max_value = (-logits).clamp(min=0)
@@ -131,12 +193,113 @@ pub fn binary_cross_entropy_with_logits_loss<T: Tensor<Dtype = f32>>(
     // e = logits * d
     let e = mul(logits.put_tape(tape), &d);
 
-    mean(add(e, &c))
+    add(e, &c)
+}
+
+/// [Cosine embedding loss](https://pytorch.org/docs/stable/generated/torch.nn.CosineEmbeddingLoss.html).
+/// Pulls pairs of vectors together when their target is `1.0`, and pushes them apart when their
+/// target is `-1.0`.
+///
+/// Computes `mean(1 - cos)` for pairs with a target of `1.0`, and `mean(max(0, cos - margin))`
+/// for pairs with a target of `-1.0`, where `cos = cosine_similarity(a, b)` along the last dimension.
+///
+/// # Inputs
+/// - `a` & `b` - the pairs of vectors, where each pair is along the last dimension.
+/// - `target` - `1.0` or `-1.0` for each pair.
+/// - `margin` - how far below 0 the similarity of dissimilar pairs has to be pushed.
+///
+/// See [cosine_similarity()].
+///
+/// # Example
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[1.0, 0.0], [0.0, 1.0]]);
+/// let b = Tensor2D::new([[1.0, 0.0], [0.0, 1.0]]);
+/// let target = Tensor1D::new([1.0, -1.0]);
+/// let loss = cosine_embedding_loss(a.traced(), &b, &target, 0.0);
+/// assert_eq!(loss.data(), &0.5);
+/// ```
+pub fn cosine_embedding_loss<T: Tensor<Dtype = f32>>(
+    a: T,
+    b: &T::NoTape,
+    target: &<T::LastDimReduced as Tensor>::NoTape,
+    margin: f32,
+) -> Tensor0D<T::Tape> {
+    type Reduced<T> = <T as Tensor>::LastDimReduced;
+    type ReducedDevice<T> = <Reduced<T> as HasDevice>::Device;
+
+    let mut similar = <Reduced<T> as Tensor>::NoTape::zeros();
+    ReducedDevice::<T>::foreach_mr(similar.mut_data(), target.data(), &mut |s, y| {
+        *s = if *y > 0.0 { 1.0 } else { 0.0 }
+    });
+    let dissimilar = negate(sub_scalar(similar.duplicate(), 1.0));
+
+    let (cos, tape) = cosine_similarity(a, b, 1e-8).split_tape();
+
+    // max(0, cos - margin) for dissimilar pairs
+    let push = mul(
+        relu(sub_scalar(cos.duplicate().put_tape(tape), margin)),
+        &dissimilar,
+    );
+    let (push, tape) = push.split_tape();
+
+    // 1 - cos for similar pairs
+    let pull = mul(negate(sub_scalar(cos.put_tape(tape), 1.0)), &similar);
+
+    mean(add(pull, &push))
+}
+
+/// [Focal loss](https://arxiv.org/abs/1708.02002) for classification with imbalanced classes.
+///
+/// Computes `-alpha * (1 - p_t)^gamma * log(p_t)` averaged over the batch, where `p_t` is
+/// the [softmax()] probability of the target class. The focusing term `(1 - p_t)^gamma`
+/// down-weights examples that are already well classified.
+///
+/// With `gamma = 0.0` and `alpha = None` this is exactly the same as
+/// [cross_entropy_with_logits_loss()] with one hot targets.
+///
+/// # Inputs
+/// - `logits` - the un-normalized output from a model. [log_softmax()] is called **in** this function
+/// - `targets` - the index of the target class of each item in the batch.
+/// - `gamma` - the focusing parameter, must be `>= 0.0`.
+/// - `alpha` - an optional weight for the whole loss, `1.0` if `None`.
+///
+/// # Example
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let logits = Tensor2D::new([[-1.0, 0.5, 2.0], [0.0, 0.0, 0.0]]);
+/// let loss = focal_loss(logits.traced(), [2, 0], 2.0, Some(0.25));
+/// ```
+pub fn focal_loss<const B: usize, const C: usize, H: Tape>(
+    logits: Tensor2D<B, C, H>,
+    targets: [usize; B],
+    gamma: f32,
+    alpha: Option<f32>,
+) -> Tensor0D<H> {
+    assert!(gamma >= 0.0, "gamma must be >= 0.0, found {gamma}");
+    let alpha = alpha.unwrap_or(1.0);
+    let log_pt = gather_last_dim(log_softmax(logits), &targets);
+
+    // with `l = log(p_t)` and `q = 1 - p_t`, the loss of each item is `-alpha * q^gamma * l`,
+    // and its derivative is `-alpha * (q^gamma - gamma * q^(gamma - 1) * p_t * l)`.
+    let f = move |l: &f32| -alpha * (-l.exp_m1()).powf(gamma) * l;
+    let df = move |l: &f32| {
+        let q = -l.exp_m1();
+        // `q^(gamma - 1) * l` goes to 0 as `l` goes to 0, but is `inf * 0` when computed directly
+        let focus = if gamma == 0.0 || *l == 0.0 {
+            0.0
+        } else {
+            gamma * q.powf(gamma - 1.0) * l.exp() * l
+        };
+        -alpha * (q.powf(gamma) - focus)
+    };
+    mean(map_named("focal_loss", log_pt, f, df))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::{assert_close, AssertClose};
 
     #[test]
     fn test_mse() {
@@ -166,24 +329,24 @@ mod tests {
         let x = Tensor1D::new([-0.5722721, 0.8469643, 1.2063414, -1.0964301, 1.1945194]);
         let y = Tensor1D::new([0.10473672, 0.24449949, 0.3266706, 0.22253996, 0.10155323]);
         let loss = cross_entropy_with_logits_loss(x.trace(), &y);
-        assert_eq!(loss.data(), &1.8713832);
+        assert_eq!(loss.data(), &1.8713834);
         let g = loss.backward();
-        assert_eq!(
+        assert_close(
             g.ref_gradient(&x),
             &[
                 -0.047592897,
                 -0.008269042,
                 0.0117146075,
                 -0.18870775,
-                0.23285514
-            ]
+                0.23285514,
+            ],
         );
     }
 
     #[test]
     fn test_crossentropy() {
         let x = Tensor1D::new([0.87248087, -0.24252531, -1.0060949, 1.155084, 1.5545048]);
-        let losses = [1.5655229, 2.680529, 3.4440987, 1.2829198, 0.883499];
+        let losses = [1.5655229, 2.680529, 3.4440987, 1.2829196, 0.88349897];
         for i in 0..5 {
             let mut targ = [0.0; 5];
             targ[i] = 1.0;
@@ -193,6 +356,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_label_smoothing_zero_is_cross_entropy() {
+        let x = Tensor2D::new([
+            [0.87248087, -0.24252531, -1.0060949, 1.155084],
+            [1.5545048, -0.90954804, -1.0193185, -0.39221755],
+        ]);
+        let targets = [3, 1];
+
+        let smoothed = cross_entropy_with_label_smoothing(x.trace(), targets, 0.0);
+        let ce = cross_entropy_with_logits_loss(x.trace(), &one_hot_encode(&targets));
+        assert_eq!(smoothed.data(), ce.data());
+        assert_eq!(
+            smoothed.backward().ref_gradient(&x),
+            ce.backward().ref_gradient(&x)
+        );
+    }
+
+    #[test]
+    fn test_label_smoothing_soft_targets() {
+        let x = Tensor2D::new([[0.5, -1.2, 2.0, 0.1], [-0.3, 0.8, -2.0, 1.5]]);
+        let loss = cross_entropy_with_label_smoothing(x.trace(), [2, 0], 0.3);
+
+        // targets are 0.7 for the true class and 0.3 / 3 = 0.1 everywhere else
+        let q = [[0.1, 0.1, 0.7, 0.1], [0.7, 0.1, 0.1, 0.1]];
+        let p = x.duplicate().softmax();
+        let log_p = x.duplicate().log_softmax();
+        let mut expected_loss = 0.0;
+        let mut expected_grad = [[0.0; 4]; 2];
+        for i in 0..2 {
+            for j in 0..4 {
+                expected_loss -= q[i][j] * log_p.data()[i][j] / 2.0;
+                expected_grad[i][j] = (p.data()[i][j] - q[i][j]) / 2.0;
+            }
+        }
+        assert!((loss.data() - expected_loss).abs() < 1e-6);
+        loss.backward()
+            .ref_gradient(&x)
+            .assert_close(&expected_grad, 1e-6);
+    }
+
     #[test]
     fn test_kl_div() {
         let logits = Tensor2D::new([
@@ -210,17 +413,17 @@ mod tests {
             [0.0166, 0.8512, 0.1322],
         ]);
         let loss = kl_div_with_logits_loss(logits.trace(), &targ);
-        assert_eq!(loss.data(), &0.40656146);
+        assert_eq!(loss.data(), &0.40656143);
         let gradients = loss.backward();
-        assert_eq!(
+        assert_close(
             gradients.ref_gradient(&logits),
             &[
                 [-0.031813223, -0.044453412, 0.07626665],
                 [0.05489187, -0.04143352, -0.013458336],
                 [-0.037454266, 0.02207594, 0.015378334],
                 [-0.09656205, 0.013436668, 0.083125375],
-                [0.02881821, -0.10633193, 0.0775137]
-            ]
+                [0.02881821, -0.10633193, 0.0775137],
+            ],
         );
     }
 
@@ -243,4 +446,157 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_cosine_embedding_loss() {
+        let a = Tensor2D::new([[3.0, 4.0], [1.0, 0.0]]);
+        let b = Tensor2D::new([[4.0, 3.0], [1.0, 1.0]]);
+        let target = Tensor1D::new([1.0, -1.0]);
+        let loss = cosine_embedding_loss(a.trace(), &b, &target, 0.5);
+        assert!((loss.data() - 0.12355339).abs() < 1e-6);
+        let gradients = loss.backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[[-0.0224, 0.0168], [0.0, 0.35355338]],
+        );
+        assert_close(
+            gradients.ref_gradient(&b),
+            &[[0.0168, -0.0224], [0.17677669, -0.17677669]],
+        );
+    }
+
+    #[test]
+    fn test_cosine_embedding_loss_below_margin() {
+        let a = Tensor1D::new([1.0, 0.0]);
+        let b = Tensor1D::new([-1.0, 1.0]);
+        let loss = cosine_embedding_loss(a.trace(), &b, &Tensor0D::new(-1.0), 0.0);
+        assert_eq!(loss.data(), &0.0);
+        let gradients = loss.backward();
+        assert_eq!(gradients.ref_gradient(&a), &[0.0; 2]);
+        assert_eq!(gradients.ref_gradient(&b), &[0.0; 2]);
+    }
+
+    #[test]
+    fn test_focal_loss_gamma_zero_is_cross_entropy() {
+        let x = Tensor2D::new([
+            [0.87248087, -0.24252531, -1.0060949, 1.155084],
+            [1.5545048, -0.90954804, -1.0193185, -0.39221755],
+            [2.2524886, 1.3035554, -0.5722721, 0.8469643],
+        ]);
+        let targets = [3, 0, 2];
+
+        let focal = focal_loss(x.trace(), targets, 0.0, None);
+        let ce = cross_entropy_with_logits_loss(x.trace(), &Tensor2D::one_hot(&targets));
+        assert_eq!(focal.data(), ce.data());
+
+        let focal_grads = focal.backward();
+        let ce_grads = ce.backward();
+        focal_grads
+            .ref_gradient(&x)
+            .assert_close(ce_grads.ref_gradient(&x), 1e-7);
+    }
+
+    #[test]
+    fn test_focal_loss_down_weights_easy_examples() {
+        let x = Tensor2D::new([[4.0, 0.0, 0.0], [0.0, 0.0, 0.5]]);
+        let ce = focal_loss(x.clone(), [0, 2], 0.0, None);
+        let focal = focal_loss(x.clone(), [0, 2], 2.0, None);
+        assert!(focal.data() < ce.data());
+
+        let alpha = focal_loss(x, [0, 2], 2.0, Some(0.25));
+        assert!((alpha.data() - 0.25 * focal.data()).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_focal_loss_finite_differences() {
+        const X: [[f32; 4]; 3] = [
+            [0.5, -1.2, 2.0, 0.1],
+            [-0.3, 0.8, -2.0, 1.5],
+            [1.0, 1.0, -0.5, 3.0],
+        ];
+        let targets = [2, 1, 3];
+
+        for (gamma, alpha) in [(2.0, None), (0.5, Some(0.25)), (1.0, Some(2.0))] {
+            let x = Tensor2D::new(X);
+            let gradients = focal_loss(x.trace(), targets, gamma, alpha).backward();
+
+            let loss =
+                |x: [[f32; 4]; 3]| *focal_loss(Tensor2D::new(x), targets, gamma, alpha).data();
+            const EPS: f32 = 1e-2;
+            let mut x_grad = [[0.0; 4]; 3];
+            for i in 0..3 {
+                for j in 0..4 {
+                    let (mut xp, mut xm) = (X, X);
+                    xp[i][j] += EPS;
+                    xm[i][j] -= EPS;
+                    x_grad[i][j] = (loss(xp) - loss(xm)) / (2.0 * EPS);
+                }
+            }
+            gradients.ref_gradient(&x).assert_close(&x_grad, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_focal_loss_confident_grads_are_finite() {
+        let x = Tensor2D::new([[200.0, -200.0], [-200.0, 200.0]]);
+        let gradients = focal_loss(x.trace(), [0, 1], 0.5, None).backward();
+        assert!(gradients
+            .ref_gradient(&x)
+            .iter()
+            .flatten()
+            .all(|g| g.is_finite()));
+    }
+
+    #[test]
+    fn test_sigmoid_cross_entropy_no_reduction() {
+        let p = Tensor2D::new([[100.0; 3], [-100.0; 3], [-1.0, 0.0, 1.0]]);
+        let t = Tensor2D::new([[0.0, 0.5, 1.0]; 3]);
+
+        let losses = sigmoid_cross_entropy_no_reduction(p.trace(), &t);
+        assert_close(
+            losses.data(),
+            &[
+                [100.0, 50.0, 0.0],
+                [0.0, 50.0, 100.0],
+                [0.31326166, std::f32::consts::LN_2, 0.31326166],
+            ],
+        );
+        assert_eq!(
+            mean(losses).data(),
+            binary_cross_entropy_with_logits_loss(p.clone(), &t).data()
+        );
+
+        let gradients = sigmoid_cross_entropy_no_reduction(p.trace(), &t)
+            .sum()
+            .backward();
+        // the gradient of each element is `sigmoid(logits) - target_probs`
+        gradients.ref_gradient(&p).assert_close(
+            &[
+                [1.0, 0.5, 0.0],
+                [0.0, -0.5, -1.0],
+                [0.26894143, 0.0, -0.26894143],
+            ],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_sigmoid_cross_entropy_masked() {
+        let p = Tensor2D::new([[1e4, -2.0], [0.5, -1e4]]);
+        let t = Tensor2D::new([[0.0, 1.0], [1.0, 1.0]]);
+        let mask = [[true, false], [false, true]];
+
+        let losses = sigmoid_cross_entropy_no_reduction(p.trace(), &t);
+        let loss = losses.masked_fill(&mask, 0.0).mean();
+        assert!((loss.data() - (2.126928 + 0.474077) / 4.0).abs() < 1e-6);
+
+        let gradients = loss.backward();
+        gradients.ref_gradient(&p).assert_close(
+            &[
+                [0.0, (0.11920292 - 1.0) / 4.0],
+                [(0.62245935 - 1.0) / 4.0, 0.0],
+            ],
+            1e-6,
+        );
+    }
 }
@@ -0,0 +1,256 @@
+//! Metrics for evaluating models, such as [accuracy()], [top_k_accuracy()], and [ConfusionMatrix].
+//!
+//! These are not differentiable, and only read the data of the tensors passed in, so they
+//! accept tensors with any tape.
+
+use crate::prelude::*;
+
+/// Returns the number of classes ranked above `label` in `logits`. Ties are broken by the lower
+/// class index, so the class with rank `0` is the same class picked by [argmax()].
+fn rank_of<const C: usize>(logits: &[f32; C], label: usize) -> usize {
+    assert!(label < C, "label {label} is out of range for {C} classes");
+    let target = logits[label];
+    logits
+        .iter()
+        .enumerate()
+        .filter(|&(i, l)| *l > target || (*l == target && i < label))
+        .count()
+}
+
+/// Returns the index of the largest value in `logits`. If there are multiple largest
+/// values, the first one is returned. `NaN` values are never picked (unless every value is `NaN`).
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// assert_eq!(argmax(&[0.1, 2.0, -1.0, 2.0]), 1);
+/// ```
+pub fn argmax<const C: usize>(logits: &[f32; C]) -> usize {
+    let mut best = 0;
+    for (i, l) in logits.iter().enumerate() {
+        if *l > logits[best] || logits[best].is_nan() {
+            best = i;
+        }
+    }
+    best
+}
+
+/// The fraction of rows in `logits` whose largest value (see [argmax()]) is at the index in `labels`.
+///
+/// Returns `0.0` for an empty batch (`B == 0`).
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let logits = Tensor2D::new([[0.1, 0.9], [0.8, 0.2], [0.3, 0.7], [0.6, 0.4]]);
+/// assert_eq!(accuracy(&logits, &[1, 0, 0, 1]), 0.5);
+/// ```
+///
+/// **Panics** if any of the labels are `>= C`.
+pub fn accuracy<const B: usize, const C: usize, H>(
+    logits: &Tensor2D<B, C, H>,
+    labels: &[usize; B],
+) -> f32 {
+    top_k_accuracy(logits, labels, 1)
+}
+
+/// The fraction of rows in `logits` where the class in `labels` is one of the `k` largest values.
+/// Ties are broken the same way as [argmax()], so `top_k_accuracy(logits, labels, 1)` is
+/// the same as [accuracy()].
+///
+/// If `k >= C` every prediction is correct, and if `k == 0` none are.
+///
+/// Returns `0.0` for an empty batch (`B == 0`).
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let logits = Tensor2D::new([[0.1, 0.5, 0.4], [0.6, 0.3, 0.1]]);
+/// assert_eq!(top_k_accuracy(&logits, &[2, 2], 2), 0.5);
+/// assert_eq!(top_k_accuracy(&logits, &[2, 2], 3), 1.0);
+/// ```
+///
+/// **Panics** if any of the labels are `>= C`.
+pub fn top_k_accuracy<const B: usize, const C: usize, H>(
+    logits: &Tensor2D<B, C, H>,
+    labels: &[usize; B],
+    k: usize,
+) -> f32 {
+    if B == 0 {
+        return 0.0;
+    }
+    let num_correct = logits
+        .data()
+        .iter()
+        .zip(labels.iter())
+        .filter(|(l, y)| rank_of(l, **y) < k)
+        .count();
+    num_correct as f32 / B as f32
+}
+
+/// Counts of (true label, predicted label) pairs, accumulated over any number of batches
+/// with [ConfusionMatrix::add()]. The prediction for each row of logits is its [argmax()].
+///
+/// # Generics
+/// - `C`: the number of classes.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let mut confusion: ConfusionMatrix<2> = Default::default();
+/// confusion.add(&Tensor2D::new([[0.1, 0.9], [0.8, 0.2]]), &[1, 1]);
+/// confusion.add(&Tensor2D::new([[0.7, 0.3]]), &[0]);
+/// assert_eq!(confusion.counts(), &[[1, 0], [1, 1]]);
+/// assert_eq!(confusion.total(), 3);
+/// assert_eq!(confusion.recall(1), 0.5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionMatrix<const C: usize> {
+    counts: [[usize; C]; C],
+}
+
+impl<const C: usize> Default for ConfusionMatrix<C> {
+    fn default() -> Self {
+        Self {
+            counts: [[0; C]; C],
+        }
+    }
+}
+
+impl<const C: usize> ConfusionMatrix<C> {
+    /// Adds the predictions of a batch of `logits` against their true `labels`.
+    ///
+    /// **Panics** if any of the labels are `>= C`.
+    pub fn add<const B: usize, H>(&mut self, logits: &Tensor2D<B, C, H>, labels: &[usize; B]) {
+        for (l, y) in logits.data().iter().zip(labels.iter()) {
+            assert!(*y < C, "label {y} is out of range for {C} classes");
+            self.counts[*y][argmax(l)] += 1;
+        }
+    }
+
+    /// The counts, where `counts()[label][prediction]` is the number of rows of class `label`
+    /// that were predicted as class `prediction`.
+    pub fn counts(&self) -> &[[usize; C]; C] {
+        &self.counts
+    }
+
+    /// The total number of rows added.
+    pub fn total(&self) -> usize {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// The fraction of all rows that were predicted correctly. Returns `0.0` if nothing has been added.
+    pub fn accuracy(&self) -> f32 {
+        let correct: usize = (0..C).map(|i| self.counts[i][i]).sum();
+        ratio(correct, self.total())
+    }
+
+    /// The fraction of rows predicted as `class` that are actually `class`.
+    /// Returns `0.0` if nothing was predicted as `class`.
+    pub fn precision(&self, class: usize) -> f32 {
+        let predicted: usize = self.counts.iter().map(|row| row[class]).sum();
+        ratio(self.counts[class][class], predicted)
+    }
+
+    /// The fraction of rows of `class` that were predicted as `class`.
+    /// Returns `0.0` if no rows of `class` were added.
+    pub fn recall(&self, class: usize) -> f32 {
+        ratio(self.counts[class][class], self.counts[class].iter().sum())
+    }
+}
+
+fn ratio(num: usize, den: usize) -> f32 {
+    if den == 0 {
+        0.0
+    } else {
+        num as f32 / den as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argmax() {
+        assert_eq!(argmax(&[1.0]), 0);
+        assert_eq!(argmax(&[1.0, 3.0, 2.0]), 1);
+        assert_eq!(argmax(&[1.0, 3.0, 3.0]), 1);
+        assert_eq!(argmax(&[f32::NAN, -1.0, 2.0]), 2);
+        assert_eq!(argmax(&[-1.0, f32::NAN, -2.0]), 0);
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let logits = Tensor2D::new([
+            [0.1, 0.2, 0.7],
+            [0.5, 0.3, 0.2],
+            [0.3, 0.3, 0.4],
+            [0.2, 0.6, 0.2],
+            [0.4, 0.4, 0.2],
+        ]);
+        assert_eq!(accuracy(&logits, &[2, 0, 2, 1, 0]), 1.0);
+        assert_eq!(accuracy(&logits, &[2, 1, 0, 1, 1]), 0.4);
+        assert_eq!(accuracy(&logits.trace(), &[0, 1, 0, 0, 2]), 0.0);
+    }
+
+    #[test]
+    fn test_accuracy_empty_batch() {
+        let logits: Tensor2D<0, 3> = Tensor2D::zeros();
+        assert_eq!(accuracy(&logits, &[]), 0.0);
+        assert_eq!(top_k_accuracy(&logits, &[], 2), 0.0);
+    }
+
+    #[test]
+    #[should_panic = "label 3 is out of range for 3 classes"]
+    fn test_accuracy_label_out_of_range() {
+        let logits: Tensor2D<1, 3> = Tensor2D::zeros();
+        accuracy(&logits, &[3]);
+    }
+
+    #[test]
+    fn test_top_k_accuracy() {
+        let logits = Tensor2D::new([
+            [0.1, 0.5, 0.3, 0.1],
+            [0.4, 0.3, 0.2, 0.1],
+            [0.25, 0.25, 0.25, 0.25],
+        ]);
+        let labels = [2, 3, 1];
+        assert_eq!(top_k_accuracy(&logits, &labels, 0), 0.0);
+        assert_eq!(top_k_accuracy(&logits, &labels, 1), 0.0);
+        assert_eq!(top_k_accuracy(&logits, &labels, 2), 2.0 / 3.0);
+        assert_eq!(top_k_accuracy(&logits, &labels, 3), 2.0 / 3.0);
+        assert_eq!(top_k_accuracy(&logits, &labels, 4), 1.0);
+        assert_eq!(top_k_accuracy(&logits, &labels, 100), 1.0);
+        assert_eq!(
+            top_k_accuracy(&logits, &labels, 1),
+            accuracy(&logits, &labels)
+        );
+    }
+
+    #[test]
+    fn test_confusion_matrix() {
+        let mut confusion: ConfusionMatrix<3> = Default::default();
+        assert_eq!(confusion.total(), 0);
+        assert_eq!(confusion.accuracy(), 0.0);
+        assert_eq!(confusion.precision(0), 0.0);
+
+        let logits = Tensor2D::new([
+            [0.9, 0.1, 0.0],
+            [0.2, 0.7, 0.1],
+            [0.6, 0.3, 0.1],
+            [0.1, 0.1, 0.8],
+        ]);
+        confusion.add(&logits, &[0, 1, 1, 2]);
+        confusion.add(&Tensor2D::new([[0.0, 1.0, 0.0]]), &[2]);
+        assert_eq!(confusion.counts(), &[[1, 0, 0], [1, 1, 0], [0, 1, 1]]);
+        assert_eq!(confusion.total(), 5);
+        assert_eq!(confusion.accuracy(), 0.6);
+        assert_eq!(confusion.precision(0), 0.5);
+        assert_eq!(confusion.precision(1), 0.5);
+        assert_eq!(confusion.precision(2), 1.0);
+        assert_eq!(confusion.recall(0), 1.0);
+        assert_eq!(confusion.recall(1), 0.5);
+        assert_eq!(confusion.recall(2), 0.5);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::prelude::*;
 use rand::Rng;
+use std::string::String;
 
 macro_rules! activation_impls {
     ($struct_name:ident, $func_name:ident, #[$docstring:meta]) => {
         #[$docstring]
         #[derive(Default, Debug, Clone, Copy)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $struct_name;
 
         impl CanUpdateWithGradients for $struct_name {
@@ -17,8 +19,16 @@ macro_rules! activation_impls {
             fn reset_params<R: Rng>(&mut self, _: &mut R) {}
         }
 
+        impl CountParams for $struct_name {}
+        #[cfg(feature = "std")]
         impl SaveToNpz for $struct_name {}
+        #[cfg(feature = "std")]
         impl LoadFromNpz for $struct_name {}
+        #[cfg(feature = "std")]
+        impl SaveToSafetensors for $struct_name {}
+        impl VisitParams for $struct_name {}
+        #[cfg(feature = "std")]
+        impl LoadFromSafetensors for $struct_name {}
 
         impl<T: Tensor<Dtype = f32>> Module<T> for $struct_name {
             type Output = T;
@@ -40,6 +50,34 @@ activation_impls!(Square, square, #[doc="Unit struct that impls [Module] as call
 activation_impls!(Sqrt, sqrt, #[doc="Unit struct that impls [Module] as calling [sqrt()] on `input`."]);
 activation_impls!(Abs, abs, #[doc="Unit struct that impls [Module] as calling [abs()] on `input`."]);
 
+macro_rules! onnx_impls {
+    ($struct_name:ident, $op_type:literal) => {
+        impl ToOnnx for $struct_name {
+            #[doc = concat!("Adds a `", $op_type, "` node.")]
+            fn to_onnx(&self, _: &str, input: String, g: &mut OnnxGraph) -> String {
+                g.add_node($op_type, &[&input], &[])
+            }
+        }
+    };
+}
+
+onnx_impls!(ReLU, "Relu");
+onnx_impls!(Sin, "Sin");
+onnx_impls!(Cos, "Cos");
+onnx_impls!(Ln, "Log");
+onnx_impls!(Exp, "Exp");
+onnx_impls!(Sigmoid, "Sigmoid");
+onnx_impls!(Tanh, "Tanh");
+onnx_impls!(Sqrt, "Sqrt");
+onnx_impls!(Abs, "Abs");
+
+impl ToOnnx for Square {
+    /// Adds a `Mul` node of `input` with itself.
+    fn to_onnx(&self, _: &str, input: String, g: &mut OnnxGraph) -> String {
+        g.add_node("Mul", &[&input, &input], &[])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,251 @@
+use crate::prelude::*;
+use crate::tensor_ops::AssertDivisibleByHeads;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// Multi-head self attention, as introduced in [Attention Is All You Need](https://arxiv.org/abs/1706.03762).
+///
+/// The input is projected into queries, keys and values with [Self::w_q], [Self::w_k] and
+/// [Self::w_v], which are split into `HEADS` heads of `E / HEADS` elements each. Each head
+/// attends separately using [multi_head_attention()], and the concatenated heads are
+/// projected with [Self::w_o].
+///
+/// If [Self::causal] is true, each position can only attend to itself and the positions before it.
+///
+/// # Generics
+/// - `E`: the embedding size, which must be divisible by `HEADS`. This is checked at compile time.
+/// - `HEADS`: the number of heads.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: MultiHeadAttention<8, 2> = Default::default();
+/// let x: Tensor2D<5, 8> = Tensor2D::zeros();
+/// let y: Tensor2D<5, 8> = model.forward(x);
+///
+/// let model: MultiHeadAttention<8, 2> = MultiHeadAttention { causal: true, ..Default::default() };
+/// let x: Tensor3D<3, 5, 8> = Tensor3D::zeros();
+/// let y: Tensor3D<3, 5, 8> = model.forward(x);
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiHeadAttention<const E: usize, const HEADS: usize> {
+    /// The query projection.
+    pub w_q: Linear<E, E>,
+
+    /// The key projection.
+    pub w_k: Linear<E, E>,
+
+    /// The value projection.
+    pub w_v: Linear<E, E>,
+
+    /// The output projection, applied to the concatenated heads.
+    pub w_o: Linear<E, E>,
+
+    /// Whether to mask out future positions.
+    pub causal: bool,
+}
+
+impl<const E: usize, const HEADS: usize> CanUpdateWithGradients for MultiHeadAttention<E, HEADS> {
+    /// Updates all four projections.
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.w_q.update(grads);
+        self.w_k.update(grads);
+        self.w_v.update(grads);
+        self.w_o.update(grads);
+    }
+}
+
+impl<const E: usize, const HEADS: usize> ResetParams for MultiHeadAttention<E, HEADS> {
+    /// Calls [ResetParams::reset_params()] on all four projections.
+    fn reset_params<R: rand::Rng>(&mut self, rng: &mut R) {
+        self.w_q.reset_params(rng);
+        self.w_k.reset_params(rng);
+        self.w_v.reset_params(rng);
+        self.w_o.reset_params(rng);
+    }
+}
+
+impl<const E: usize, const HEADS: usize> CountParams for MultiHeadAttention<E, HEADS> {
+    /// `4 * (E * E + E)` for the four projections.
+    fn num_params(&self) -> usize {
+        self.w_q.num_params()
+            + self.w_k.num_params()
+            + self.w_v.num_params()
+            + self.w_o.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const E: usize, const HEADS: usize> SaveToNpz for MultiHeadAttention<E, HEADS> {
+    /// Saves the projections with the prefixes `{pre}w_q.`, `{pre}w_k.`, `{pre}w_v.` and `{pre}w_o.`.
+    fn write<W>(&self, pre: &str, w: &mut ZipWriter<W>) -> ZipResult<()>
+    where
+        W: Write + Seek,
+    {
+        self.w_q.write(&format!("{pre}w_q."), w)?;
+        self.w_k.write(&format!("{pre}w_k."), w)?;
+        self.w_v.write(&format!("{pre}w_v."), w)?;
+        self.w_o.write(&format!("{pre}w_o."), w)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const E: usize, const HEADS: usize> LoadFromNpz for MultiHeadAttention<E, HEADS> {
+    /// Reads the projections with the same names as [SaveToNpz].
+    fn read<R>(&mut self, pre: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: Read + Seek,
+    {
+        self.w_q.read(&format!("{pre}w_q."), r)?;
+        self.w_k.read(&format!("{pre}w_k."), r)?;
+        self.w_v.read(&format!("{pre}w_v."), r)?;
+        self.w_o.read(&format!("{pre}w_o."), r)?;
+        Ok(())
+    }
+}
+
+impl<const E: usize, const HEADS: usize> VisitParams for MultiHeadAttention<E, HEADS> {
+    /// Visits the projections with the same names as [SaveToNpz].
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.w_q.visit_params(&format!("{pre}w_q."), f);
+        self.w_k.visit_params(&format!("{pre}w_k."), f);
+        self.w_v.visit_params(&format!("{pre}w_v."), f);
+        self.w_o.visit_params(&format!("{pre}w_o."), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const E: usize, const HEADS: usize> SaveToSafetensors for MultiHeadAttention<E, HEADS> {
+    /// Saves the projections with the same names as [SaveToNpz].
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        self.w_q.write_safetensors(&format!("{pre}w_q."), w);
+        self.w_k.write_safetensors(&format!("{pre}w_k."), w);
+        self.w_v.write_safetensors(&format!("{pre}w_v."), w);
+        self.w_o.write_safetensors(&format!("{pre}w_o."), w);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const E: usize, const HEADS: usize> LoadFromSafetensors for MultiHeadAttention<E, HEADS> {
+    /// Reads the projections with the same names as [SaveToNpz].
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        self.w_q.read_safetensors(&format!("{pre}w_q."), r)?;
+        self.w_k.read_safetensors(&format!("{pre}w_k."), r)?;
+        self.w_v.read_safetensors(&format!("{pre}w_v."), r)?;
+        self.w_o.read_safetensors(&format!("{pre}w_o."), r)?;
+        Ok(())
+    }
+}
+
+impl<const E: usize, const HEADS: usize, const S: usize, H: Tape> Module<Tensor2D<S, E, H>>
+    for MultiHeadAttention<E, HEADS>
+{
+    type Output = Tensor2D<S, E, H>;
+
+    /// Self attention over the `S` positions of `x`.
+    fn forward(&self, x: Tensor2D<S, E, H>) -> Self::Output {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertDivisibleByHeads::<E, HEADS>::OK;
+
+        let (x, tape) = x.split_tape();
+        let (k, tape) = self.w_k.forward(x.duplicate().put_tape(tape)).split_tape();
+        let (v, tape) = self.w_v.forward(x.duplicate().put_tape(tape)).split_tape();
+        let q = self.w_q.forward(x.put_tape(tape));
+        let y = multi_head_attention::<HEADS, S, S, E, H>(q, &k, &v, self.causal);
+        self.w_o.forward(y)
+    }
+}
+
+impl<const E: usize, const HEADS: usize, const B: usize, const S: usize, H: Tape>
+    Module<Tensor3D<B, S, E, H>> for MultiHeadAttention<E, HEADS>
+{
+    type Output = Tensor3D<B, S, E, H>;
+
+    /// Batched self attention, where each of the `B` sequences only attends to itself.
+    fn forward(&self, x: Tensor3D<B, S, E, H>) -> Self::Output {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertDivisibleByHeads::<E, HEADS>::OK;
+
+        let (x, tape) = x.split_tape();
+        let (k, tape) = self.w_k.forward(x.duplicate().put_tape(tape)).split_tape();
+        let (v, tape) = self.w_v.forward(x.duplicate().put_tape(tape)).split_tape();
+        let q = self.w_q.forward(x.put_tape(tape));
+        let y = batch_multi_head_attention::<HEADS, B, S, S, E, H>(q, &k, &v, self.causal);
+        self.w_o.forward(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_mha_gradients_flow_to_all_projections() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: MultiHeadAttention<4, 2> = Default::default();
+        model.reset_params(&mut rng);
+
+        let x: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let y = model.forward(x.trace());
+        let gradients = y.square().mean().backward();
+        for w in [&model.w_q, &model.w_k, &model.w_v, &model.w_o] {
+            assert_ne!(gradients.ref_gradient(&w.weight), &[[0.0; 4]; 4]);
+            assert_ne!(gradients.ref_gradient(&w.bias), &[0.0; 4]);
+        }
+        assert_ne!(gradients.ref_gradient(&x), &[[0.0; 4]; 3]);
+
+        let m0 = model.clone();
+        let mut opt: Sgd<MultiHeadAttention<4, 2>> = Default::default();
+        opt.update(&mut model, gradients);
+        assert_ne!(model.w_q.weight.data(), m0.w_q.weight.data());
+        assert_ne!(model.w_k.weight.data(), m0.w_k.weight.data());
+        assert_ne!(model.w_v.weight.data(), m0.w_v.weight.data());
+        assert_ne!(model.w_o.weight.data(), m0.w_o.weight.data());
+    }
+
+    #[test]
+    fn test_mha_causal_ignores_future() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut model: MultiHeadAttention<6, 3> = MultiHeadAttention {
+            causal: true,
+            ..Default::default()
+        };
+        model.reset_params(&mut rng);
+
+        let x: Tensor2D<4, 6> = Tensor2D::randn(&mut rng);
+        let mut x2 = x.clone();
+        x2.mut_data()[3] = [5.0; 6];
+        let y = model.forward(x);
+        let y2 = model.forward(x2);
+        for i in 0..3 {
+            y.data()[i].assert_close(&y2.data()[i], 1e-6);
+        }
+        assert_ne!(y.data()[3], y2.data()[3]);
+    }
+
+    #[test]
+    fn test_mha_batched_matches_2d() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut model: MultiHeadAttention<4, 2> = Default::default();
+        model.reset_params(&mut rng);
+        assert_eq!(model.num_params(), 4 * (4 * 4 + 4));
+        assert_eq!(model.param_count(), model.num_params());
+
+        let x: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let y = model.forward(x.duplicate());
+        for b in 0..2 {
+            let y_b = model.forward(Tensor2D::new(x.data()[b]));
+            y.data()[b].assert_close(y_b.data(), 1e-6);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+use std::rc::Rc;
+use std::string::String;
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// Calls `module.forward(input)` **without** recording any of the intermediate operations on the tape.
+/// Instead, only the input is saved, and `module.forward()` is run again during backward to
+/// recompute the intermediate values. This is known as gradient checkpointing, and trades
+/// extra compute for not keeping all the intermediate values of `module` in memory.
+///
+/// The gradients are the same as calling `module.forward(input)` directly, as long as
+/// `module.forward()` is deterministic (e.g. this is **not** the case for [DropoutOneIn]).
+///
+/// `module` is stored in an [Rc] so the backward operation can call it again. See [Checkpoint] for
+/// a [Module] that does this for you.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # use std::rc::Rc;
+/// let model: Rc<(Linear<3, 5>, ReLU, Linear<5, 2>)> = Default::default();
+/// let x: Tensor1D<3> = Tensor1D::ones();
+/// let y: Tensor1D<2, OwnedTape> = checkpoint(&model, x.trace());
+/// let gradients = y.sum().backward();
+/// ```
+pub fn checkpoint<M, T>(module: &Rc<M>, input: T) -> CheckpointOutput<M, T>
+where
+    T: Tensor<Dtype = f32>,
+    T::NoTape: PutTape<OwnedTape, Output = T::OwnedTape>,
+    M: 'static
+        + Module<T::NoTape>
+        + Module<T::OwnedTape, Output = <NoTapeOutput<M, T> as Tensor>::OwnedTape>,
+    NoTapeOutput<M, T>: Tensor<Dtype = f32, Tape = NoneTape> + PutTape<T::Tape>,
+{
+    let (x, mut tape) = input.split_tape();
+    let y = Module::<T::NoTape>::forward(module.as_ref(), x.duplicate());
+    let phantom_y = y.phantom();
+    let module = module.clone();
+    tape.add_backward_op_named("checkpoint", move |grads| {
+        let y_grad = match grads.maybe_ref_gradient(&phantom_y) {
+            Some(y_grad) => y_grad.clone(),
+            None => return,
+        };
+        let x = x.duplicate().put_tape(OwnedTape::default());
+        let (y, inner_tape) = Module::<T::OwnedTape>::forward(module.as_ref(), x).split_tape();
+        *grads.mut_gradient(&y) = y_grad;
+        inner_tape.0.execute_into(grads);
+    });
+    y.put_tape(tape)
+}
+
+/// The output of `M` when called on the [NoneTape] version of `T`.
+pub type NoTapeOutput<M, T> = <M as Module<<T as Tensor>::NoTape>>::Output;
+
+/// The output of [checkpoint()], which is [NoTapeOutput] with the tape of `T`.
+pub type CheckpointOutput<M, T> = <NoTapeOutput<M, T> as PutTape<<T as Tensor>::Tape>>::Output;
+
+/// A [Module] that calls [checkpoint()] on `M`, so that the intermediate values of `M`
+/// are recomputed during backward instead of being stored on the tape.
+///
+/// The gradients are the same as using `M` directly, as long as `M` is deterministic.
+///
+/// # Generics
+/// - `M` the module to checkpoint.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Model = (Checkpoint<(Linear<5, 10>, ReLU, Linear<10, 10>, ReLU)>, Linear<10, 2>);
+/// let model: Model = Default::default();
+/// let y: Tensor1D<2, OwnedTape> = model.forward(Tensor1D::<5>::zeros().traced());
+/// ```
+///
+/// **Panics** if it is updated or loaded while the result of a forward pass with [OwnedTape]
+/// is still alive, since the backward operation holds on to `M`.
+#[derive(Debug)]
+pub struct Checkpoint<M>(Rc<M>);
+
+impl<M> Checkpoint<M> {
+    fn module_mut(&mut self) -> &mut M {
+        Rc::get_mut(&mut self.0).expect("Checkpoint is still in use by a backward operation")
+    }
+}
+
+impl<M: Default> Default for Checkpoint<M> {
+    fn default() -> Self {
+        Self(Rc::new(M::default()))
+    }
+}
+
+impl<M: Clone> Clone for Checkpoint<M> {
+    /// Clones `M` into a new [Rc], so the clone can be updated independently.
+    fn clone(&self) -> Self {
+        Self(Rc::new(self.0.as_ref().clone()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<M: serde::Serialize> serde::Serialize for Checkpoint<M> {
+    /// Pass through to `M`'s [serde::Serialize].
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M: serde::Deserialize<'de>> serde::Deserialize<'de> for Checkpoint<M> {
+    /// Pass through to `M`'s [serde::Deserialize].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        M::deserialize(deserializer).map(|m| Self(Rc::new(m)))
+    }
+}
+
+impl<M: CanUpdateWithGradients> CanUpdateWithGradients for Checkpoint<M> {
+    /// Pass through to `M`'s [CanUpdateWithGradients].
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.module_mut().update(grads);
+    }
+}
+
+impl<M: ResetParams> ResetParams for Checkpoint<M> {
+    /// Pass through to `M`'s [ResetParams].
+    fn reset_params<R: rand::Rng>(&mut self, rng: &mut R) {
+        self.module_mut().reset_params(rng);
+    }
+}
+
+impl<M: CountParams> CountParams for Checkpoint<M> {
+    /// Pass through to `M`'s [CountParams].
+    fn num_params(&self) -> usize {
+        self.0.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: SaveToNpz> SaveToNpz for Checkpoint<M> {
+    /// Pass through to `M`'s [SaveToNpz].
+    fn write<W: Write + Seek>(&self, p: &str, w: &mut ZipWriter<W>) -> ZipResult<()> {
+        self.0.write(p, w)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: LoadFromNpz> LoadFromNpz for Checkpoint<M> {
+    /// Pass through to `M`'s [LoadFromNpz].
+    fn read<R: Read + Seek>(&mut self, p: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError> {
+        self.module_mut().read(p, r)
+    }
+}
+
+impl<M: VisitParams> VisitParams for Checkpoint<M> {
+    /// Pass through to `M`'s [VisitParams].
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, p: &str, f: &mut F) {
+        self.0.visit_params(p, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: SaveToSafetensors> SaveToSafetensors for Checkpoint<M> {
+    /// Pass through to `M`'s [SaveToSafetensors].
+    fn write_safetensors(&self, p: &str, w: &mut SafetensorsWriter) {
+        self.0.write_safetensors(p, w)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: LoadFromSafetensors> LoadFromSafetensors for Checkpoint<M> {
+    /// Pass through to `M`'s [LoadFromSafetensors].
+    fn read_safetensors(&mut self, p: &str, r: &SafetensorsReader) -> Result<(), SafetensorsError> {
+        self.module_mut().read_safetensors(p, r)
+    }
+}
+
+impl<M: ToOnnx> ToOnnx for Checkpoint<M> {
+    /// Pass through to `M`'s [ToOnnx].
+    fn to_onnx(&self, p: &str, input: String, g: &mut OnnxGraph) -> String {
+        self.0.to_onnx(p, input, g)
+    }
+}
+
+impl<T, M> Module<T> for Checkpoint<M>
+where
+    T: Tensor<Dtype = f32>,
+    T::NoTape: PutTape<OwnedTape, Output = T::OwnedTape>,
+    M: 'static
+        + Module<T::NoTape>
+        + Module<T::OwnedTape, Output = <NoTapeOutput<M, T> as Tensor>::OwnedTape>,
+    NoTapeOutput<M, T>: Tensor<Dtype = f32, Tape = NoneTape> + PutTape<T::Tape>,
+{
+    type Output = CheckpointOutput<M, T>;
+
+    /// Calls [checkpoint()] on `M`.
+    fn forward(&self, input: T) -> Self::Output {
+        checkpoint(&self.0, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    type Block = (Linear<4, 8>, Tanh, Linear<8, 8>, Sigmoid, Linear<8, 3>);
+
+    #[test]
+    fn test_checkpoint_same_gradients() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: Rc<Block> = Default::default();
+        Rc::get_mut(&mut model).unwrap().reset_params(&mut rng);
+        let x: Tensor2D<5, 4> = Tensor2D::randn(&mut rng);
+
+        let y: Tensor2D<5, 3, OwnedTape> = model.forward(x.trace());
+        let expected_y = *y.data();
+        let expected = y.square().mean().backward();
+
+        let y: Tensor2D<5, 3, OwnedTape> = checkpoint(&model, x.trace());
+        assert_eq!(y.data(), &expected_y);
+        let gradients = y.square().mean().backward();
+
+        assert_close(gradients.ref_gradient(&x), expected.ref_gradient(&x));
+        assert_close(
+            gradients.ref_gradient(&model.0.weight),
+            expected.ref_gradient(&model.0.weight),
+        );
+        assert_close(
+            gradients.ref_gradient(&model.2.bias),
+            expected.ref_gradient(&model.2.bias),
+        );
+        assert_close(
+            gradients.ref_gradient(&model.4.weight),
+            expected.ref_gradient(&model.4.weight),
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_records_one_operation() {
+        let model: Rc<Block> = Default::default();
+        let x: Tensor1D<4> = Tensor1D::zeros();
+        let y: Tensor1D<3, OwnedTape> = model.forward(x.trace());
+        assert_eq!(y.tape().len(), 8);
+        let y: Tensor1D<3, OwnedTape> = checkpoint(&model, x.trace());
+        assert_eq!(y.tape().op_names(), ["checkpoint"]);
+    }
+
+    #[test]
+    fn test_checkpoint_deep_sequential() {
+        type Layers = Repeated<(Linear<8, 8>, Tanh), 25>;
+        type Deep = (Layers, Layers);
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut model: Rc<Deep> = Default::default();
+        Rc::get_mut(&mut model).unwrap().reset_params(&mut rng);
+        let x: Tensor2D<4, 8> = Tensor2D::randn(&mut rng);
+
+        // every operation of every layer keeps its intermediate values alive until backward
+        let y: Tensor2D<4, 8, OwnedTape> = model.forward(x.trace());
+        assert_eq!(y.tape().len(), 150);
+        let expected = y.square().mean().backward();
+
+        // only the checkpoint operation is recorded, see the peak bytes test below
+        let y: Tensor2D<4, 8, OwnedTape> = checkpoint(&model, x.trace());
+        assert_eq!(y.tape().op_names(), ["checkpoint"]);
+        let gradients = y.square().mean().backward();
+
+        assert_close(gradients.ref_gradient(&x), expected.ref_gradient(&x));
+        for layer in [&model.0.modules[0].0, &model.1.modules[24].0] {
+            assert_close(
+                gradients.ref_gradient(&layer.weight),
+                expected.ref_gradient(&layer.weight),
+            );
+            assert_close(
+                gradients.ref_gradient(&layer.bias),
+                expected.ref_gradient(&layer.bias),
+            );
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_deep_sequential_peak_bytes() {
+        type Layers = Repeated<(Linear<8, 8>, Tanh), 5>;
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut model: Repeated<Checkpoint<Layers>, 10> = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor2D<4, 8> = Tensor2D::randn(&mut rng);
+
+        // the same 50 layers, without checkpointing
+        let mut expected = Default::default();
+        let plain = crate::tests::peak_bytes(|| {
+            let mut y: Tensor2D<4, 8, OwnedTape> = x.trace();
+            for layers in model.modules.iter() {
+                y = layers.0.forward(y);
+            }
+            expected = y.square().mean().backward();
+        });
+
+        // only the inputs of the 10 checkpoints are kept alive until backward, and the
+        // intermediate values of one checkpoint at a time are recomputed during backward
+        let mut gradients = Default::default();
+        let checkpointed = crate::tests::peak_bytes(|| {
+            let y: Tensor2D<4, 8, OwnedTape> = model.forward(x.trace());
+            gradients = y.square().mean().backward();
+        });
+
+        assert!(
+            checkpointed * 4 < plain * 3,
+            "checkpointed: {checkpointed} bytes, plain: {plain} bytes"
+        );
+        let layer = &model.modules[9].0.modules[4].0;
+        assert_close(
+            gradients.ref_gradient(&layer.weight),
+            expected.ref_gradient(&layer.weight),
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_module_in_sequential() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut model: (Linear<4, 4>, Checkpoint<Block>) = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor1D<4> = Tensor1D::randn(&mut rng);
+
+        let y = model.1 .0.forward(model.0.forward(x.trace()));
+        let expected = y.square().sum().backward();
+
+        let y = model.forward(x.trace());
+        let gradients = y.square().sum().backward();
+        assert_close(gradients.ref_gradient(&x), expected.ref_gradient(&x));
+        assert_close(
+            gradients.ref_gradient(&model.0.weight),
+            expected.ref_gradient(&model.0.weight),
+        );
+        assert_close(
+            gradients.ref_gradient(&model.1 .0 .2.weight),
+            expected.ref_gradient(&model.1 .0 .2.weight),
+        );
+
+        let mut opt: Sgd<(Linear<4, 4>, Checkpoint<Block>)> = Default::default();
+        opt.update(&mut model, gradients);
+    }
+
+    #[test]
+    #[should_panic = "Checkpoint is still in use by a backward operation"]
+    fn test_checkpoint_update_before_backward() {
+        let mut model: Checkpoint<Linear<2, 2>> = Default::default();
+        let _y = model.forward(Tensor1D::<2>::zeros().traced());
+        let mut opt: Sgd<Checkpoint<Linear<2, 2>>> = Default::default();
+        opt.update(&mut model, Default::default());
+    }
+
+    #[test]
+    fn test_checkpoint_no_tape() {
+        let model: Checkpoint<Block> = Default::default();
+        let _: Tensor1D<3> = model.forward(Tensor1D::zeros());
+        assert_eq!(model.num_params(), (4 * 8 + 8) + (8 * 8 + 8) + (8 * 3 + 3));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+use rand_distr::Uniform;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// A transposed 2d convolution (sometimes called a deconvolution) of a batch of images (4d tensors),
+/// using [Tensor4D::conv_transpose2d()] and then adding [Self::bias] to every channel. This increases
+/// the spatial dimensions, and is used in decoders of autoencoders and generators of GANs.
+///
+/// The output size is `(H - 1) * S + K - 2 * P`, which is the input size of a convolution with
+/// kernel size `K`, stride `S` and padding `P` that outputs size `H`.
+///
+/// # Generics
+/// - `I`: the number of input channels.
+/// - `O`: the number of output channels.
+/// - `K`: the size of the kernel.
+/// - `S`: the stride.
+/// - `P`: the padding, which is removed from each side of the output.
+/// - `H2` and `W2`: the output height and width. Stable rust can't compute these in a type, so they
+///   are specified up front, and checked at compile time.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: ConvTranspose2D<3, 6, 4, 2, 1, 8, 10> = Default::default();
+/// let x: Tensor4D<5, 3, 4, 5> = Tensor4D::zeros();
+/// let y: Tensor4D<5, 6, 8, 10> = model.forward(x);
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvTranspose2D<
+    const I: usize,
+    const O: usize,
+    const K: usize,
+    const S: usize,
+    const P: usize,
+    const H2: usize,
+    const W2: usize,
+> {
+    /// The kernels, shape (I, O, K, K). This is the same shape as pytorch's `ConvTranspose2d.weight`.
+    pub weight: Tensor4D<I, O, K, K, NoneTape>,
+
+    /// Bias vector, shape (O, )
+    pub bias: Tensor1D<O, NoneTape>,
+}
+
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > CanUpdateWithGradients for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.weight.update(grads);
+        self.bias.update(grads);
+    }
+}
+
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > ResetParams for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Initializes [Self::weight] and [Self::bias] from a [Uniform] distribution
+    /// between [-1 / sqrt(O * K * K), 1 / sqrt(O * K * K)], the same as pytorch.
+    ///
+    /// This uses [Randomize::randomize()] to set the values of the tensor.
+    fn reset_params<R: Rng>(&mut self, rng: &mut R) {
+        let bound: f32 = 1.0 / ((O * K * K) as f32).sqrt();
+        let dist = Uniform::new(-bound, bound);
+        self.weight.randomize(rng, &dist);
+        self.bias.randomize(rng, &dist);
+    }
+}
+
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > CountParams for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// `I * O * K * K` for [Self::weight] plus `O` for [Self::bias].
+    fn num_params(&self) -> usize {
+        self.weight.num_params() + self.bias.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > SaveToNpz for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Saves [Self::weight] to `{pre}weight.npy` and [Self::bias] to `{pre}bias.npy`
+    /// using [npz_fwrite()].
+    fn write<Wr>(&self, pre: &str, w: &mut ZipWriter<Wr>) -> ZipResult<()>
+    where
+        Wr: Write + Seek,
+    {
+        npz_fwrite(w, format!("{pre}weight.npy"), self.weight.data())?;
+        npz_fwrite(w, format!("{pre}bias.npy"), self.bias.data())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > LoadFromNpz for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Reads [Self::weight] from `{pre}weight.npy` and [Self::bias] from `{pre}bias.npy`
+    /// using [npz_fread()].
+    fn read<R>(&mut self, pre: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: Read + Seek,
+    {
+        npz_fread(r, format!("{pre}weight.npy"), self.weight.mut_data())?;
+        npz_fread(r, format!("{pre}bias.npy"), self.bias.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > VisitParams for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Visits [Self::weight] as `{pre}weight` and [Self::bias] as `{pre}bias`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.weight.visit_params(&format!("{pre}weight"), f);
+        self.bias.visit_params(&format!("{pre}bias"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > SaveToSafetensors for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Saves [Self::weight] to `{pre}weight` and [Self::bias] to `{pre}bias`.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.weight.data());
+        w.add(format!("{pre}bias"), self.bias.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H2: usize,
+        const W2: usize,
+    > LoadFromSafetensors for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    /// Reads [Self::weight] from `{pre}weight` and [Self::bias] from `{pre}bias`.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.weight.mut_data())?;
+        r.read_into(&format!("{pre}bias"), self.bias.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<
+        const B: usize,
+        const I: usize,
+        const O: usize,
+        const K: usize,
+        const S: usize,
+        const P: usize,
+        const H: usize,
+        const W: usize,
+        const H2: usize,
+        const W2: usize,
+        TAPE: Tape,
+    > Module<Tensor4D<B, I, H, W, TAPE>> for ConvTranspose2D<I, O, K, S, P, H2, W2>
+{
+    type Output = Tensor4D<B, O, H2, W2, TAPE>;
+
+    /// Batched forward using [Tensor4D::conv_transpose2d()] and [add_broadcast_rhs_channel()].
+    fn forward(&self, x: Tensor4D<B, I, H, W, TAPE>) -> Self::Output {
+        let y = x.conv_transpose2d::<S, P, O, K, H2, W2>(&self.weight);
+        add_broadcast_rhs_channel(y, &self.bias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    /// A regular convolution with the same `K`, `S` and `P`, to check shapes against. Output
+    /// size is `(H + 2 * P - K) / S + 1`.
+    fn conv2d<const H: usize, const W: usize, const K: usize, const H2: usize, const W2: usize>(
+        x: &[[f32; W]; H],
+        f: &[[f32; K]; K],
+        s: usize,
+        p: usize,
+    ) -> [[f32; W2]; H2] {
+        let mut out = [[0.0; W2]; H2];
+        for (oi, out_i) in out.iter_mut().enumerate() {
+            for (oj, o) in out_i.iter_mut().enumerate() {
+                for (ki, f_ki) in f.iter().enumerate() {
+                    for (kj, f) in f_ki.iter().enumerate() {
+                        let i = (oi * s + ki).checked_sub(p).filter(|&i| i < H);
+                        let j = (oj * s + kj).checked_sub(p).filter(|&j| j < W);
+                        if let (Some(i), Some(j)) = (i, j) {
+                            *o += x[i][j] * f;
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_conv_transpose2d_forward_adds_bias() {
+        let model: ConvTranspose2D<1, 2, 2, 1, 0, 3, 3> = ConvTranspose2D {
+            weight: Tensor4D::new([[[[1.0; 2]; 2], [[1.0, 0.0], [0.0, 0.0]]]]),
+            bias: Tensor1D::new([0.5, -1.0]),
+        };
+        let x: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let y = model.forward(x.trace());
+        assert_eq!(
+            y.data(),
+            &[[
+                [[1.5, 3.5, 2.5], [4.5, 10.5, 6.5], [3.5, 7.5, 4.5]],
+                [[0.0, 1.0, -1.0], [2.0, 3.0, -1.0], [-1.0; 3]],
+            ]]
+        );
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&model.bias), &[9.0; 2]);
+        assert_eq!(
+            gradients.ref_gradient(&model.weight),
+            &[[[[10.0; 2]; 2]; 2]]
+        );
+        assert_eq!(gradients.ref_gradient(&x), &[[[[5.0; 2]; 2]]]);
+    }
+
+    #[test]
+    fn test_conv_then_conv_transpose_shapes() {
+        // a conv with K=3, S=2, P=1 maps 7x5 to 4x3, and the matching transposed conv maps it back
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<7, 5> = Tensor2D::randn(&mut rng);
+        let f: Tensor2D<3, 3> = Tensor2D::randn(&mut rng);
+        let y: [[f32; 3]; 4] = conv2d(x.data(), f.data(), 2, 1);
+
+        let model: ConvTranspose2D<1, 1, 3, 2, 1, 7, 5> = ConvTranspose2D {
+            weight: Tensor4D::new([[*f.data()]]),
+            bias: Tensor1D::zeros(),
+        };
+        let z: Tensor4D<1, 1, 4, 3> = Tensor4D::randn(&mut rng);
+        let r: Tensor4D<1, 1, 7, 5> = model.forward(z.duplicate());
+
+        // the transposed conv is the adjoint of the conv: <conv(x), z> == <x, conv_transpose(z)>
+        let lhs: f32 = y
+            .iter()
+            .flatten()
+            .zip(z.data()[0][0].iter().flatten())
+            .map(|(a, b)| a * b)
+            .sum();
+        let rhs: f32 = x
+            .data()
+            .iter()
+            .flatten()
+            .zip(r.data()[0][0].iter().flatten())
+            .map(|(a, b)| a * b)
+            .sum();
+        assert!((lhs - rhs).abs() < 1e-4, "{lhs} {rhs}");
+    }
+
+    #[test]
+    fn test_conv_transpose2d_in_sequential() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: (
+            ConvTranspose2D<4, 2, 4, 2, 1, 4, 4>,
+            ReLU,
+            ConvTranspose2D<2, 1, 4, 2, 1, 8, 8>,
+        ) = Default::default();
+        model.reset_params(&mut rng);
+        assert_eq!(model.num_params(), (4 * 2 * 16 + 2) + (2 * 16 + 1));
+        assert_eq!(model.param_count(), model.num_params());
+
+        let x: Tensor4D<3, 4, 2, 2> = Tensor4D::randn(&mut rng);
+        let y: Tensor4D<3, 1, 8, 8, OwnedTape> = model.forward(x.trace());
+        let gradients = y.square().mean().backward();
+        assert!(gradients.ref_gradient(&model.0.weight) != &[[[[0.0; 4]; 4]; 2]; 4]);
+        assert!(gradients.ref_gradient(&model.2.bias) != &[0.0]);
+    }
+}
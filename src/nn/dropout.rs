@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::string::String;
 use std::{cell::RefCell, ops::DerefMut};
 
 /// A [Module<Tensor>] that calls [dropout()] in [Module::forward()] with probability `1.0 / N`.
@@ -41,8 +42,40 @@ impl<const N: usize> ResetParams for DropoutOneIn<N> {
     fn reset_params<R: Rng>(&mut self, _: &mut R) {}
 }
 
+impl<const N: usize> CountParams for DropoutOneIn<N> {}
+#[cfg(feature = "std")]
 impl<const N: usize> SaveToNpz for DropoutOneIn<N> {}
+#[cfg(feature = "std")]
 impl<const N: usize> LoadFromNpz for DropoutOneIn<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> SaveToSafetensors for DropoutOneIn<N> {}
+impl<const N: usize> VisitParams for DropoutOneIn<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> LoadFromSafetensors for DropoutOneIn<N> {}
+
+impl<const N: usize> ToOnnx for DropoutOneIn<N> {
+    /// Does nothing, since dropout is not applied at inference time.
+    fn to_onnx(&self, _: &str, input: String, _: &mut OnnxGraph) -> String {
+        input
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for DropoutOneIn<N> {
+    /// Serializes nothing, since the [StdRng] can't be serialized.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for DropoutOneIn<N> {
+    /// Seeds [StdRng] with a new seed, the same as [Default].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(Default::default())
+    }
+}
 
 impl<const N: usize, T: Tensor<Dtype = f32>> Module<T> for DropoutOneIn<N> {
     type Output = T;
@@ -126,8 +159,39 @@ impl ResetParams for Dropout {
     fn reset_params<R: rand::Rng>(&mut self, _: &mut R) {}
 }
 
+impl CountParams for Dropout {}
+#[cfg(feature = "std")]
 impl SaveToNpz for Dropout {}
+#[cfg(feature = "std")]
 impl LoadFromNpz for Dropout {}
+#[cfg(feature = "std")]
+impl SaveToSafetensors for Dropout {}
+impl VisitParams for Dropout {}
+#[cfg(feature = "std")]
+impl LoadFromSafetensors for Dropout {}
+
+impl ToOnnx for Dropout {
+    /// Does nothing, since dropout is not applied at inference time.
+    fn to_onnx(&self, _: &str, input: String, _: &mut OnnxGraph) -> String {
+        input
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Dropout {
+    /// Serializes [Self::p], since the [StdRng] can't be serialized.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.p)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Dropout {
+    /// Deserializes [Self::p], and seeds [StdRng] with a new seed, the same as [Dropout::p()].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f32::deserialize(deserializer).map(Self::p)
+    }
+}
 
 impl<T: Tensor<Dtype = f32>> Module<T> for Dropout {
     type Output = T;
@@ -0,0 +1,108 @@
+use crate::prelude::*;
+use rand::Rng;
+
+/// Flattens 3d tensors to 1d tensors, and batches of 3d tensors (4d tensors) to 2d tensors
+/// by calling [reshape()] in [Module::forward()].
+///
+/// # Generics
+/// - `N`: the number of elements in each flattened item, which must be `C * H * W`. Stable rust can't
+///   compute `C * H * W` in a type, so this is specified up front, and checked at compile time.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Model = (Flatten<12>, Linear<12, 2>);
+/// let model: Model = Default::default();
+/// let x: Tensor3D<3, 2, 2> = Tensor3D::zeros();
+/// let y: Tensor1D<2> = model.forward(x);
+/// let x: Tensor4D<5, 3, 2, 2> = Tensor4D::zeros();
+/// let y: Tensor2D<5, 2> = model.forward(x);
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flatten<const N: usize>;
+
+impl<const N: usize> CanUpdateWithGradients for Flatten<N> {
+    /// Does nothing.
+    fn update<G: GradientProvider>(&mut self, _: &mut G) {}
+}
+
+impl<const N: usize> ResetParams for Flatten<N> {
+    /// Does nothing.
+    fn reset_params<R: Rng>(&mut self, _: &mut R) {}
+}
+
+impl<const N: usize> CountParams for Flatten<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> SaveToNpz for Flatten<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> LoadFromNpz for Flatten<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> SaveToSafetensors for Flatten<N> {}
+impl<const N: usize> VisitParams for Flatten<N> {}
+#[cfg(feature = "std")]
+impl<const N: usize> LoadFromSafetensors for Flatten<N> {}
+
+impl<const C: usize, const H: usize, const W: usize, const N: usize, TAPE: Tape>
+    Module<Tensor3D<C, H, W, TAPE>> for Flatten<N>
+{
+    type Output = Tensor1D<N, TAPE>;
+
+    /// Reshapes a `Tensor3D<C, H, W>` into a `Tensor1D<N>`.
+    fn forward(&self, input: Tensor3D<C, H, W, TAPE>) -> Self::Output {
+        reshape(input)
+    }
+}
+
+impl<
+        const B: usize,
+        const C: usize,
+        const H: usize,
+        const W: usize,
+        const N: usize,
+        TAPE: Tape,
+    > Module<Tensor4D<B, C, H, W, TAPE>> for Flatten<N>
+{
+    type Output = Tensor2D<B, N, TAPE>;
+
+    /// Reshapes a `Tensor4D<B, C, H, W>` into a `Tensor2D<B, N>`.
+    fn forward(&self, input: Tensor4D<B, C, H, W, TAPE>) -> Self::Output {
+        reshape(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_flatten_3d() {
+        let x = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let y: Tensor1D<8, OwnedTape> = Flatten.forward(x.trace());
+        assert_eq!(y.data(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[1.0; 2]; 2]; 2]);
+    }
+
+    #[test]
+    fn test_flatten_4d() {
+        let x: Tensor4D<3, 2, 1, 2> = Tensor4D::ones();
+        let y: Tensor2D<3, 4, OwnedTape> = Flatten.forward(x.trace());
+        assert_eq!(y.data(), &[[1.0; 4]; 3]);
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[[1.0; 2]; 1]; 2]; 3]);
+    }
+
+    #[test]
+    fn test_flatten_in_sequential() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: (Flatten<8>, Linear<8, 2>) = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor4D<3, 2, 2, 2> = Tensor4D::randn(&mut rng);
+        let y: Tensor2D<3, 2, OwnedTape> = model.forward(x.trace());
+        let gradients = y.square().mean().backward();
+        assert_ne!(gradients.ref_gradient(&model.1.weight), &[[0.0; 8]; 2]);
+        assert_ne!(gradients.ref_gradient(&x), &[[[[0.0; 2]; 2]; 2]; 3]);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::prelude::*;
+use std::string::String;
+
+/// Freezes the parameters of `M`, so that optimizers don't update them. This is useful for
+/// transfer learning, where the first layers of a pretrained model are kept fixed.
+///
+/// [CanUpdateWithGradients::update()] does nothing, and everything else is passed through to `M`,
+/// including [SaveToNpz] & [LoadFromNpz] with the same names. So a model can be saved while frozen
+/// and loaded back without [Frozen], or unwrapped with [Frozen::into_inner()] to fine-tune it.
+///
+/// Note that the gradients of `M`'s parameters are still computed if the input has a tape,
+/// since the gradients of the input need to flow through `M`. They are just never applied.
+///
+/// # Generics
+/// - `M`: the module to freeze.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Model = (Frozen<(Linear<5, 10>, ReLU)>, Linear<10, 2>);
+/// let mut model: Model = Default::default();
+/// let y: Tensor1D<2, OwnedTape> = model.forward(Tensor1D::<5>::zeros().traced());
+/// let mut opt: Sgd<Model> = Default::default();
+/// opt.update(&mut model, y.sum().backward());
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frozen<M>(pub M);
+
+impl<M> Frozen<M> {
+    /// Unfreezes the module.
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+}
+
+impl<M> CanUpdateWithGradients for Frozen<M> {
+    /// Does nothing.
+    fn update<G: GradientProvider>(&mut self, _: &mut G) {}
+}
+
+impl<M: ResetParams> ResetParams for Frozen<M> {
+    /// Pass through to `M`'s [ResetParams].
+    fn reset_params<R: rand::Rng>(&mut self, rng: &mut R) {
+        self.0.reset_params(rng);
+    }
+}
+
+impl<M: CountParams> CountParams for Frozen<M> {
+    /// Pass through to `M`'s [CountParams].
+    fn num_params(&self) -> usize {
+        self.0.num_params()
+    }
+}
+
+impl<T, M: Module<T>> Module<T> for Frozen<M> {
+    type Output = M::Output;
+
+    /// Pass through to `M`'s [Module].
+    fn forward(&self, x: T) -> Self::Output {
+        self.0.forward(x)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: SaveToNpz> SaveToNpz for Frozen<M> {
+    /// Pass through to `M`'s [SaveToNpz].
+    fn write<W>(
+        &self,
+        filename_prefix: &str,
+        w: &mut zip::ZipWriter<W>,
+    ) -> zip::result::ZipResult<()>
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        self.0.write(filename_prefix, w)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: LoadFromNpz> LoadFromNpz for Frozen<M> {
+    /// Pass through to `M`'s [LoadFromNpz].
+    fn read<R>(&mut self, filename_prefix: &str, r: &mut zip::ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        self.0.read(filename_prefix, r)
+    }
+}
+
+impl<M: VisitParams> VisitParams for Frozen<M> {
+    /// Pass through to `M`'s [VisitParams].
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, prefix: &str, f: &mut F) {
+        self.0.visit_params(prefix, f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: SaveToSafetensors> SaveToSafetensors for Frozen<M> {
+    /// Pass through to `M`'s [SaveToSafetensors].
+    fn write_safetensors(&self, prefix: &str, w: &mut SafetensorsWriter) {
+        self.0.write_safetensors(prefix, w);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: LoadFromSafetensors> LoadFromSafetensors for Frozen<M> {
+    /// Pass through to `M`'s [LoadFromSafetensors].
+    fn read_safetensors(
+        &mut self,
+        prefix: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        self.0.read_safetensors(prefix, r)
+    }
+}
+
+impl<M: ToOnnx> ToOnnx for Frozen<M> {
+    /// Pass through to `M`'s [ToOnnx].
+    fn to_onnx(&self, prefix: &str, input: String, g: &mut OnnxGraph) -> String {
+        self.0.to_onnx(prefix, input, g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    type Model = (Frozen<(Linear<3, 4>, ReLU)>, Linear<4, 2>);
+
+    #[test]
+    fn test_frozen_not_updated() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        assert_ne!(model.0 .0 .0.weight.data(), &[[0.0; 3]; 4]);
+        let m0 = model.clone();
+
+        let mut opt: Sgd<Model> = Default::default();
+        let x: Tensor2D<5, 3> = Tensor2D::randn(&mut rng);
+        let gradients = model.forward(x.trace()).square().mean().backward();
+        assert_ne!(
+            gradients.ref_gradient(&model.0 .0 .0.weight),
+            &[[0.0; 3]; 4]
+        );
+        opt.update(&mut model, gradients);
+
+        assert_eq!(model.0 .0 .0.weight.data(), m0.0 .0 .0.weight.data());
+        assert_eq!(model.0 .0 .0.bias.data(), m0.0 .0 .0.bias.data());
+        assert_ne!(model.1.weight.data(), m0.1.weight.data());
+        assert_ne!(model.1.bias.data(), m0.1.bias.data());
+    }
+
+    #[test]
+    fn test_frozen_save_load_unfrozen() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut frozen: Model = Default::default();
+        frozen.reset_params(&mut rng);
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        frozen.save(file.path().to_str().unwrap()).unwrap();
+
+        let mut model: (Linear<3, 4>, ReLU, Linear<4, 2>) = Default::default();
+        assert!(model.load(file.path().to_str().unwrap()).is_err());
+
+        let mut model: ((Linear<3, 4>, ReLU), Linear<4, 2>) = Default::default();
+        model.load(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(model.0 .0.weight.data(), frozen.0 .0 .0.weight.data());
+        assert_eq!(model.1.weight.data(), frozen.1.weight.data());
+
+        let x: Tensor1D<3> = Tensor1D::randn(&mut rng);
+        let inner = frozen.0.clone().into_inner();
+        assert_eq!(
+            inner.forward(x.duplicate()).data(),
+            frozen.0.forward(x).data()
+        );
+    }
+}
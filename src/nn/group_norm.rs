@@ -0,0 +1,380 @@
+use crate::prelude::*;
+use crate::tensor_ops::{flat, flat_mut};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+use std::vec::Vec;
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// Causes a compile time error if `C` is not divisible by `G`.
+struct AssertGroups<const G: usize, const C: usize>;
+
+impl<const G: usize, const C: usize> AssertGroups<G, C> {
+    const OK: () = assert!(
+        G > 0 && C.is_multiple_of(G),
+        "CHANNELS must be divisible by GROUPS"
+    );
+}
+
+/// Implements group normalization as described in [Group Normalization](https://arxiv.org/abs/1803.08494).
+///
+/// The `C` channels of each sample are split into `G` groups of `C / G` consecutive channels, and
+/// each group is normalized to 0 mean and unit std dev, over both its channels and spatial dimensions.
+/// Then an affine transform is done with the learnable per channel parameters [Self::gamma] and [Self::beta].
+///
+/// Unlike batch normalization, this doesn't depend on the batch size. With `G = 1` this is the same as
+/// layer normalization over the channels (see [LayerNorm1D]), and with `G = C` it is instance normalization.
+///
+/// [Self::epsilon] is added to the variance to ensure big enough numbers. It defaults to `1e-5`.
+///
+/// # Generics
+/// - `G` The number of groups. `C` must be divisible by `G`, which is checked at compile time.
+/// - `C` The number of channels.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: GroupNorm<2, 4> = Default::default();
+/// let x: Tensor4D<3, 4, 5, 5> = Default::default();
+/// let _: Tensor4D<3, 4, 5, 5> = model.forward(x);
+/// ```
+///
+/// The number of channels must be divisible by the number of groups:
+/// ```compile_fail
+/// # use dfdx::prelude::*;
+/// let model: GroupNorm<3, 4> = Default::default();
+/// let _: Tensor2D<2, 4> = model.forward(Tensor2D::zeros());
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupNorm<const G: usize, const C: usize> {
+    pub gamma: Tensor1D<C, NoneTape>,
+    pub beta: Tensor1D<C, NoneTape>,
+    pub epsilon: f32,
+}
+
+impl<const G: usize, const C: usize> Default for GroupNorm<G, C> {
+    /// Fills [Self::gamma] with 1s and [Self::beta] with 0s and sets [Self::epsilon] to `1e-5`.
+    fn default() -> Self {
+        Self {
+            gamma: Tensor1D::ones(),
+            beta: Tensor1D::zeros(),
+            epsilon: 1e-5,
+        }
+    }
+}
+
+impl<const G: usize, const C: usize> ResetParams for GroupNorm<G, C> {
+    /// Fills [Self::gamma] with 1s and [Self::beta] with 0s.
+    fn reset_params<R: rand::Rng>(&mut self, _: &mut R) {
+        Cpu::fill(self.gamma.mut_data(), &mut |v| *v = 1.0);
+        Cpu::fill(self.beta.mut_data(), &mut |v| *v = 0.0);
+    }
+}
+
+impl<const G: usize, const C: usize> CanUpdateWithGradients for GroupNorm<G, C> {
+    /// Updates [Self::gamma] and [Self::beta].
+    fn update<T: GradientProvider>(&mut self, grads: &mut T) {
+        self.gamma.update(grads);
+        self.beta.update(grads);
+    }
+}
+
+impl<const G: usize, const C: usize> CountParams for GroupNorm<G, C> {
+    /// Counts [Self::gamma] and [Self::beta]. [Self::epsilon] is not a parameter.
+    fn num_params(&self) -> usize {
+        self.gamma.num_params() + self.beta.num_params()
+    }
+}
+
+/// Splits `t` into `num_groups` chunks of consecutive elements, and normalizes each chunk to
+/// 0 mean and unit std dev.
+///
+/// This is a single fused operation, since the size of the chunks can't be expressed in a type.
+/// The gradient of each chunk is `(g - mean(g) - y * mean(g * y)) / std`, where `g` is the gradient
+/// of the result and `y` is the result.
+fn normalize_groups<T: Tensor<Dtype = f32>>(t: T, num_groups: usize, epsilon: f32) -> T {
+    let n = <T::Array as CountElements>::NUM_ELEMENTS / num_groups;
+    let mut result = T::NoTape::zeros();
+    let mut inv_stds = Vec::with_capacity(num_groups);
+    for (r, x) in flat_mut(result.mut_data())
+        .chunks_mut(n)
+        .zip(flat(t.data()).chunks(n))
+    {
+        let mean = x.iter().sum::<f32>() / n as f32;
+        let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+        let inv_std = (var + epsilon).sqrt().recip();
+        for (r, x) in r.iter_mut().zip(x.iter()) {
+            *r = (x - mean) * inv_std;
+        }
+        inv_stds.push(inv_std);
+    }
+
+    let (t, mut tape) = t.split_tape();
+    let phantom_t = t.phantom();
+    let y = result.duplicate();
+    tape.add_backward_op_named("normalize_groups", move |grads| {
+        let (t_grad, y_grad) = grads.mut_and_ref(&phantom_t, &y);
+        let chunks = flat_mut(t_grad)
+            .chunks_mut(n)
+            .zip(flat(y_grad).chunks(n))
+            .zip(flat(y.data()).chunks(n));
+        for (((t_grad, y_grad), y), inv_std) in chunks.zip(inv_stds.iter()) {
+            let g_mean = y_grad.iter().sum::<f32>() / n as f32;
+            let gy_mean = y_grad.iter().zip(y.iter()).map(|(g, y)| g * y).sum::<f32>() / n as f32;
+            for ((t, g), y) in t_grad.iter_mut().zip(y_grad.iter()).zip(y.iter()) {
+                *t += (g - g_mean - y * gy_mean) * inv_std;
+            }
+        }
+    });
+    result.put_tape(tape)
+}
+
+impl<H: Tape, const G: usize, const C: usize> Module<Tensor1D<C, H>> for GroupNorm<G, C> {
+    type Output = Tensor1D<C, H>;
+
+    /// Normalizes each group, then calls [mul()] with [Self::gamma] and [add()] with [Self::beta].
+    fn forward(&self, x: Tensor1D<C, H>) -> Self::Output {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertGroups::<G, C>::OK;
+        let x = normalize_groups(x, G, self.epsilon);
+        add(mul(x, &self.gamma), &self.beta)
+    }
+}
+
+impl<H: Tape, const B: usize, const G: usize, const C: usize> Module<Tensor2D<B, C, H>>
+    for GroupNorm<G, C>
+{
+    type Output = Tensor2D<B, C, H>;
+
+    /// Normalizes each group of each sample, then calls [mul_broadcast_rhs_first()] with
+    /// [Self::gamma] and [add_broadcast_rhs_first()] with [Self::beta].
+    fn forward(&self, x: Tensor2D<B, C, H>) -> Self::Output {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertGroups::<G, C>::OK;
+        let x = normalize_groups(x, B * G, self.epsilon);
+        add_broadcast_rhs_first(mul_broadcast_rhs_first(x, &self.gamma), &self.beta)
+    }
+}
+
+impl<T: Tape, const G: usize, const C: usize, const H: usize, const W: usize>
+    Module<Tensor3D<C, H, W, T>> for GroupNorm<G, C>
+{
+    type Output = Tensor3D<C, H, W, T>;
+
+    /// Reshapes into a batch of 1 image, and calls forward on the [Tensor4D].
+    fn forward(&self, x: Tensor3D<C, H, W, T>) -> Self::Output {
+        let x: Tensor4D<1, C, H, W, T> = x.reshape();
+        self.forward(x).reshape()
+    }
+}
+
+impl<T: Tape, const B: usize, const G: usize, const C: usize, const H: usize, const W: usize>
+    Module<Tensor4D<B, C, H, W, T>> for GroupNorm<G, C>
+{
+    type Output = Tensor4D<B, C, H, W, T>;
+
+    /// Normalizes each group of each image, then calls [mul_broadcast_rhs_channel()] with
+    /// [Self::gamma] and [add_broadcast_rhs_channel()] with [Self::beta].
+    fn forward(&self, x: Tensor4D<B, C, H, W, T>) -> Self::Output {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertGroups::<G, C>::OK;
+        let x = normalize_groups(x, B * G, self.epsilon);
+        add_broadcast_rhs_channel(mul_broadcast_rhs_channel(x, &self.gamma), &self.beta)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const G: usize, const C: usize> SaveToNpz for GroupNorm<G, C> {
+    /// Saves [Self::gamma] to `{pre}gamma.npy` and [Self::beta] to `{pre}beta.npy`
+    /// using [npz_fwrite()].
+    fn write<W: Write + Seek>(&self, pre: &str, w: &mut ZipWriter<W>) -> ZipResult<()> {
+        npz_fwrite(w, format!("{pre}gamma.npy"), self.gamma.data())?;
+        npz_fwrite(w, format!("{pre}beta.npy"), self.beta.data())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const G: usize, const C: usize> LoadFromNpz for GroupNorm<G, C> {
+    /// Reads [Self::gamma] from `{p}gamma.npy` and [Self::beta] from `{p}beta.npy`
+    /// using [npz_fread()].
+    fn read<R: Read + Seek>(&mut self, p: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError> {
+        npz_fread(r, format!("{p}gamma.npy"), self.gamma.mut_data())?;
+        npz_fread(r, format!("{p}beta.npy"), self.beta.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<const G: usize, const C: usize> VisitParams for GroupNorm<G, C> {
+    /// Visits [Self::gamma] as `{pre}gamma` and [Self::beta] as `{pre}beta`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.gamma.visit_params(&format!("{pre}gamma"), f);
+        self.beta.visit_params(&format!("{pre}beta"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const G: usize, const C: usize> SaveToSafetensors for GroupNorm<G, C> {
+    /// Saves [Self::gamma] to `{pre}weight` and [Self::beta] to `{pre}bias`, which are the names
+    /// pytorch's `nn.GroupNorm` uses.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.gamma.data());
+        w.add(format!("{pre}bias"), self.beta.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const G: usize, const C: usize> LoadFromSafetensors for GroupNorm<G, C> {
+    /// Reads [Self::gamma] from `{pre}weight` and [Self::beta] from `{pre}bias`, which are the names
+    /// pytorch's `nn.GroupNorm` uses.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.gamma.mut_data())?;
+        r.read_into(&format!("{pre}bias"), self.beta.mut_data())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradcheck::gradcheck;
+    use crate::tests::AssertClose;
+    use rand::{prelude::StdRng, SeedableRng};
+    use rand_distr::Standard;
+    use tempfile::NamedTempFile;
+
+    fn random_model<const G: usize, const C: usize>(rng: &mut StdRng) -> GroupNorm<G, C> {
+        let mut model: GroupNorm<G, C> = Default::default();
+        model.gamma.randomize(rng, &Standard);
+        model.beta.randomize(rng, &Standard);
+        model
+    }
+
+    #[test]
+    fn test_group_norm_forward() {
+        let model: GroupNorm<2, 4> = Default::default();
+        let x: Tensor2D<1, 4> = Tensor2D::new([[1.0, 3.0, -2.0, 6.0]]);
+        let y = model.forward(x);
+        // [1, 3] has mean 2 & std 1, and [-2, 6] has mean 2 & std 4
+        y.data().assert_close(&[[-1.0, 1.0, -1.0, 1.0]], 1e-4);
+    }
+
+    #[test]
+    fn test_group_norm_1_group_is_layer_norm() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let model: GroupNorm<1, 6> = random_model(&mut rng);
+        let layer_norm = LayerNorm1D {
+            gamma: model.gamma.clone(),
+            beta: model.beta.clone(),
+            epsilon: model.epsilon,
+        };
+
+        let x: Tensor2D<3, 6> = Tensor2D::randn(&mut rng);
+        let y = model.forward(x.trace());
+        let e = layer_norm.forward(x.trace());
+        y.data().assert_close(e.data(), 1e-5);
+
+        let v: Tensor2D<3, 6> = Tensor2D::randn(&mut rng);
+        let gradients = mul(y, &v).sum().backward();
+        let expected = mul(e, &v).sum().backward();
+        gradients
+            .ref_gradient(&x)
+            .assert_close(expected.ref_gradient(&x), 1e-5);
+        gradients
+            .ref_gradient(&model.gamma)
+            .assert_close(expected.ref_gradient(&layer_norm.gamma), 1e-5);
+        gradients
+            .ref_gradient(&model.beta)
+            .assert_close(expected.ref_gradient(&layer_norm.beta), 1e-5);
+
+        let x: Tensor1D<6> = Tensor1D::randn(&mut rng);
+        model
+            .forward(x.clone())
+            .data()
+            .assert_close(layer_norm.forward(x).data(), 1e-5);
+    }
+
+    #[test]
+    fn test_group_norm_channels_groups_is_instance_norm() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let model: GroupNorm<3, 3> = Default::default();
+        let x: Tensor4D<2, 3, 2, 2> = Tensor4D::randn(&mut rng);
+        let y = model.forward(x.trace());
+
+        // instance norm normalizes each channel of each image over its spatial dimensions
+        let e: Tensor2D<6, 4, OwnedTape> = x.trace().reshape();
+        let e: Tensor4D<2, 3, 2, 2, OwnedTape> = e.normalize(model.epsilon).reshape();
+        y.data().assert_close(e.data(), 1e-5);
+
+        let v: Tensor4D<2, 3, 2, 2> = Tensor4D::randn(&mut rng);
+        let gradients = mul(y, &v).sum().backward();
+        let expected = mul(e, &v).sum().backward();
+        gradients
+            .ref_gradient(&x)
+            .assert_close(expected.ref_gradient(&x), 1e-5);
+    }
+
+    #[test]
+    fn test_group_norm_3d_same_as_4d() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let model: GroupNorm<2, 4> = random_model(&mut rng);
+        let x: Tensor3D<4, 2, 3> = Tensor3D::randn(&mut rng);
+        let y = model.forward(x.clone());
+        let e = model.forward(Tensor4D::<1, 4, 2, 3>::new([*x.data()]));
+        y.data().assert_close(&e.data()[0], 1e-6);
+    }
+
+    #[test]
+    fn test_group_norm_gradcheck() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let model: GroupNorm<2, 4> = random_model(&mut rng);
+        let v: Tensor4D<2, 4, 2, 3> = Tensor4D::randn(&mut rng);
+        let x: Tensor4D<2, 4, 2, 3> = Tensor4D::randn(&mut rng);
+        let f = |x: Tensor4D<2, 4, 2, 3, OwnedTape>| mul(model.forward(x), &v).sum();
+        assert!(gradcheck(f, &x, 1e-3) < 1e-2);
+
+        let f = |gamma: Tensor1D<4, OwnedTape>| {
+            let (gamma, tape) = gamma.split_tape();
+            let model = GroupNorm::<2, 4> {
+                gamma,
+                beta: model.beta.clone(),
+                epsilon: model.epsilon,
+            };
+            mul(model.forward(x.duplicate().put_tape(tape)), &v).sum()
+        };
+        assert!(gradcheck(f, &model.gamma, 1e-3) < 1e-2);
+
+        // the gradient of beta is the sum over the batch & spatial dimensions
+        let gradients = mul(model.forward(x.trace()), &v).sum().backward();
+        let mut beta_grad = [0.0; 4];
+        for img in v.data().iter() {
+            for (g, channel) in beta_grad.iter_mut().zip(img.iter()) {
+                *g += channel.iter().flatten().sum::<f32>();
+            }
+        }
+        gradients
+            .ref_gradient(&model.beta)
+            .assert_close(&beta_grad, 1e-5);
+    }
+
+    #[test]
+    fn test_group_norm_save_load() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let saved: GroupNorm<2, 4> = random_model(&mut rng);
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        saved.save(file.path().to_str().unwrap()).unwrap();
+
+        let mut loaded: GroupNorm<2, 4> = Default::default();
+        loaded.load(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(loaded.gamma.data(), saved.gamma.data());
+        assert_eq!(loaded.beta.data(), saved.beta.data());
+        assert_eq!(loaded.num_params(), 8);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::prelude::*;
 use rand::prelude::Rng;
+#[cfg(feature = "std")]
 use std::io::{Read, Seek, Write};
+use std::string::String;
+#[cfg(feature = "std")]
 use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 macro_rules! tuple_impls {
@@ -17,6 +20,14 @@ macro_rules! tuple_impls {
             }
         }
 
+        impl<$($name: CountParams),+> CountParams for ($($name,)+) {
+            /// Sums the number of parameters of each part of the tuple.
+            fn num_params(&self) -> usize {
+                0 $(+ self.$idx.num_params())+
+            }
+        }
+
+        #[cfg(feature = "std")]
         impl<$($name: SaveToNpz),+> SaveToNpz for ($($name,)+) {
             /// Calls `SaveToNpz::write(self.<idx>, ...)` on each part of the tuple. See [SaveToNpz].
             ///
@@ -29,6 +40,7 @@ macro_rules! tuple_impls {
             }
         }
 
+        #[cfg(feature = "std")]
         impl<$($name: LoadFromNpz),+> LoadFromNpz for ($($name,)+) {
             /// Calls `LoadFromNpz::read(self.<idx>, ...)` on each part of the tuple. See [LoadFromNpz].
             ///
@@ -41,6 +53,41 @@ macro_rules! tuple_impls {
             }
         }
 
+        impl<$($name: VisitParams),+> VisitParams for ($($name,)+) {
+            /// Calls `VisitParams::visit_params(self.<idx>, ...)` on each part of the tuple,
+            /// with the same names as [SaveToNpz].
+            fn visit_params<V: FnMut(&str, &dyn Param)>(&self, base: &str, f: &mut V) {
+                $(self.$idx.visit_params(&format!("{}{}.", base, $idx), f);)+
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl<$($name: SaveToSafetensors),+> SaveToSafetensors for ($($name,)+) {
+            /// Calls `SaveToSafetensors::write_safetensors(self.<idx>, ...)` on each part of the tuple,
+            /// with the same names as [SaveToNpz].
+            fn write_safetensors(&self, base: &str, w: &mut SafetensorsWriter) {
+                $(self.$idx.write_safetensors(&format!("{}{}.", base, $idx), w);)+
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl<$($name: LoadFromSafetensors),+> LoadFromSafetensors for ($($name,)+) {
+            /// Calls `LoadFromSafetensors::read_safetensors(self.<idx>, ...)` on each part of the tuple,
+            /// with the same names as [LoadFromNpz].
+            fn read_safetensors(&mut self, base: &str, r: &SafetensorsReader) -> Result<(), SafetensorsError> {
+                $(self.$idx.read_safetensors(&format!("{}{}.", base, $idx), r)?;)+
+                Ok(())
+            }
+        }
+
+        impl<$($name: ToOnnx),+> ToOnnx for ($($name,)+) {
+            /// Adds the nodes of each part of the tuple in order, with the same names as [SaveToNpz].
+            fn to_onnx(&self, base: &str, x: String, g: &mut OnnxGraph) -> String {
+                $(let x = self.$idx.to_onnx(&format!("{}{}.", base, $idx), x, g);)+
+                x
+            }
+        }
+
         /*This macro expands like this for a 4-tuple:
 
         impl<
@@ -105,15 +152,47 @@ mod tests {
         assert_eq!(y.data(), &[0.0, 0.0, 0.0, 1.0f32.tanh(), 2.0f32.tanh()]);
     }
 
+    #[test]
+    fn test_visit_params_names_and_counts() {
+        let model: (Linear<3, 4>, ReLU, (Linear<4, 2>, LayerNorm1D<2>)) = Default::default();
+        let mut visited = Vec::new();
+        model.visit_params("", &mut |name, p| {
+            visited.push((name.to_string(), p.param_shape(), p.param_data().len()))
+        });
+        assert_eq!(
+            visited,
+            [
+                ("0.weight".to_string(), vec![4, 3], 12),
+                ("0.bias".to_string(), vec![4], 4),
+                ("2.0.weight".to_string(), vec![2, 4], 8),
+                ("2.0.bias".to_string(), vec![2], 2),
+                ("2.1.gamma".to_string(), vec![2], 2),
+                ("2.1.beta".to_string(), vec![2], 2),
+            ]
+        );
+        assert_eq!(model.param_count(), model.num_params());
+
+        let mut ids = Vec::new();
+        model.visit_params("model.", &mut |name, p| {
+            if name == "model.0.weight" {
+                let w = p.as_any().downcast_ref::<Tensor2D<4, 3>>().unwrap();
+                assert_eq!(w.data(), model.0.weight.data());
+            }
+            ids.push(*p.param_id());
+        });
+        assert_eq!(ids[0], *model.0.weight.id());
+        assert_eq!(ids[5], *model.2 .1.beta.id());
+    }
+
     #[test]
     fn test_2_tuple_update() {
         let mut rng = StdRng::seed_from_u64(0);
         let mut model: (Linear<2, 3>, Linear<3, 4>) = Default::default();
         model.reset_params(&mut rng);
         assert!(model.0.weight.data() != &[[0.0; 2]; 3]);
-        assert!(model.0.bias.data() != &[0.0; 3]);
+        assert_eq!(model.0.bias.data(), &[0.0; 3]);
         assert!(model.1.weight.data() != &[[0.0; 3]; 4]);
-        assert!(model.1.bias.data() != &[0.0; 4]);
+        assert_eq!(model.1.bias.data(), &[0.0; 4]);
 
         let m0 = model.clone();
 
@@ -131,6 +210,7 @@ mod tests {
         let mut sgd = Sgd::new(SgdConfig {
             lr: 1.0,
             momentum: None,
+            gradient_noise: None,
         });
         sgd.update(&mut model, gradients);
 
@@ -299,4 +379,24 @@ mod tests {
         let y = model.forward(Tensor1D::zeros());
         assert_eq!(y.data(), &[1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
     }
+
+    #[test]
+    fn test_tuple_num_params() {
+        type Model = (
+            Linear<10, 5>,
+            ReLU,
+            LayerNorm1D<5>,
+            DropoutOneIn<2>,
+            Residual<(Linear<5, 5>, Tanh)>,
+            (
+                Repeated<Linear<5, 5>, 3>,
+                SplitInto<(Linear<5, 2>, Linear<5, 1>)>,
+            ),
+        );
+        let model: Model = Default::default();
+        assert_eq!(
+            model.num_params(),
+            (10 * 5 + 5) + (5 + 5) + (5 * 5 + 5) + 3 * (5 * 5 + 5) + (5 * 2 + 2) + (5 + 1)
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+use rand_distr::{Normal, Uniform};
+
+/// Fills `t` from a [Uniform] distribution between `[-bound, bound]`, where `bound = sqrt(6 / fan_in)`.
+///
+/// This is [Kaiming/He initialization](https://arxiv.org/abs/1502.01852) with the gain for [ReLU],
+/// which keeps the variance of activations constant through deep [ReLU] networks.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # let mut rng = rand::thread_rng();
+/// let mut t: Tensor2D<3, 6> = Tensor2D::zeros();
+/// kaiming_uniform(&mut t, &mut rng, 6);
+/// assert!(t.data().iter().flatten().all(|v| v.abs() <= 1.0));
+/// ```
+pub fn kaiming_uniform<T: Randomize<f32>, R: Rng>(t: &mut T, rng: &mut R, fan_in: usize) {
+    let bound = (6.0 / fan_in as f32).sqrt();
+    t.randomize(rng, &Uniform::new_inclusive(-bound, bound));
+}
+
+/// Fills `t` from a [Normal] distribution with mean `0.0` and standard deviation `sqrt(2 / fan_in)`.
+///
+/// See [kaiming_uniform()] for more details.
+pub fn kaiming_normal<T: Randomize<f32>, R: Rng>(t: &mut T, rng: &mut R, fan_in: usize) {
+    let std = (2.0 / fan_in as f32).sqrt();
+    t.randomize(rng, &Normal::new(0.0, std).unwrap());
+}
+
+/// Fills `t` from a [Uniform] distribution between `[-bound, bound]`, where
+/// `bound = sqrt(6 / (fan_in + fan_out))`.
+///
+/// This is [Xavier/Glorot initialization](http://proceedings.mlr.press/v9/glorot10a.html),
+/// which is a good fit for [Tanh] and [Sigmoid] networks.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # let mut rng = rand::thread_rng();
+/// let mut t: Tensor2D<2, 4> = Tensor2D::zeros();
+/// xavier_uniform(&mut t, &mut rng, 4, 2);
+/// assert!(t.data().iter().flatten().all(|v| v.abs() <= 1.0));
+/// ```
+pub fn xavier_uniform<T: Randomize<f32>, R: Rng>(
+    t: &mut T,
+    rng: &mut R,
+    fan_in: usize,
+    fan_out: usize,
+) {
+    let bound = (6.0 / (fan_in + fan_out) as f32).sqrt();
+    t.randomize(rng, &Uniform::new_inclusive(-bound, bound));
+}
+
+/// Fills `t` from a [Normal] distribution with mean `0.0` and standard deviation
+/// `sqrt(2 / (fan_in + fan_out))`.
+///
+/// See [xavier_uniform()] for more details.
+pub fn xavier_normal<T: Randomize<f32>, R: Rng>(
+    t: &mut T,
+    rng: &mut R,
+    fan_in: usize,
+    fan_out: usize,
+) {
+    let std = (2.0 / (fan_in + fan_out) as f32).sqrt();
+    t.randomize(rng, &Normal::new(0.0, std).unwrap());
+}
+
+/// The initialization scheme used by [ResetParamsWith::reset_params_with()].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Init {
+    /// The scheme used by [ResetParams::reset_params()]: a [Uniform] distribution between
+    /// `[-1 / sqrt(fan_in), 1 / sqrt(fan_in)]`.
+    #[default]
+    Default,
+
+    /// See [kaiming_uniform()].
+    KaimingUniform,
+
+    /// See [kaiming_normal()].
+    KaimingNormal,
+
+    /// See [xavier_uniform()].
+    XavierUniform,
+
+    /// See [xavier_normal()].
+    XavierNormal,
+}
+
+impl Init {
+    /// Fills `t` according to `self`, given the fan-in and fan-out of the layer `t` belongs to.
+    pub fn init<T: Randomize<f32>, R: Rng>(
+        &self,
+        t: &mut T,
+        rng: &mut R,
+        fan_in: usize,
+        fan_out: usize,
+    ) {
+        match self {
+            Self::Default => {
+                let bound = 1.0 / (fan_in as f32).sqrt();
+                t.randomize(rng, &Uniform::new(-bound, bound));
+            }
+            Self::KaimingUniform => kaiming_uniform(t, rng, fan_in),
+            Self::KaimingNormal => kaiming_normal(t, rng, fan_in),
+            Self::XavierUniform => xavier_uniform(t, rng, fan_in, fan_out),
+            Self::XavierNormal => xavier_normal(t, rng, fan_in, fan_out),
+        }
+    }
+}
+
+/// How [Linear::reset_params_with_bias()] initializes the bias.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiasInit {
+    /// Fills the bias with `0.0`. This is deterministic, and is what [ResetParams::reset_params()]
+    /// and [ResetParamsWith::reset_params_with()] use.
+    #[default]
+    Zeros,
+
+    /// A [Uniform] distribution between `[-1 / sqrt(fan_in), 1 / sqrt(fan_in)]`, the same as pytorch.
+    Uniform,
+}
+
+impl BiasInit {
+    /// Fills `t` according to `self`, given the fan-in of the layer `t` belongs to.
+    pub fn init<const N: usize, R: Rng>(&self, t: &mut Tensor1D<N>, rng: &mut R, fan_in: usize) {
+        match self {
+            Self::Zeros => Cpu::fill(t.mut_data(), &mut |v| *v = 0.0),
+            Self::Uniform => {
+                let bound = 1.0 / (fan_in as f32).sqrt();
+                t.randomize(rng, &Uniform::new(-bound, bound));
+            }
+        }
+    }
+}
+
+/// Something that can reset its parameters with a chosen [Init] scheme.
+///
+/// [ResetParams::reset_params()] is the same as `reset_params_with(rng, Init::Default)`.
+///
+/// # Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// # let mut rng = rand::thread_rng();
+/// let mut model: Linear<5, 2> = Default::default();
+/// model.reset_params_with(&mut rng, Init::KaimingNormal);
+/// ```
+pub trait ResetParamsWith: ResetParams {
+    /// Mutate the unit's weights using [rand::Rng] and the `init` scheme.
+    fn reset_params_with<R: Rng>(&mut self, rng: &mut R, init: Init);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    fn mean_and_std<const N: usize>(t: &Tensor1D<N>) -> (f32, f32) {
+        let mean = t.data().iter().sum::<f32>() / N as f32;
+        let var = t.data().iter().map(|v| (v - mean).powi(2)).sum::<f32>() / N as f32;
+        (mean, var.sqrt())
+    }
+
+    #[test]
+    fn test_kaiming() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut t: Tensor1D<10000> = Tensor1D::zeros();
+
+        kaiming_uniform(&mut t, &mut rng, 24);
+        assert!(t.data().iter().all(|v| v.abs() <= 0.5));
+        let (mean, std) = mean_and_std(&t);
+        assert!(mean.abs() < 0.01);
+        // the std of a uniform distribution is bound / sqrt(3)
+        assert!((std - 0.5 / 3f32.sqrt()).abs() < 0.01);
+
+        kaiming_normal(&mut t, &mut rng, 32);
+        let (mean, std) = mean_and_std(&t);
+        assert!(mean.abs() < 0.01);
+        assert!((std - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_xavier() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut t: Tensor1D<10000> = Tensor1D::zeros();
+
+        xavier_uniform(&mut t, &mut rng, 16, 8);
+        assert!(t.data().iter().all(|v| v.abs() <= 0.5));
+        let (mean, std) = mean_and_std(&t);
+        assert!(mean.abs() < 0.01);
+        assert!((std - 0.5 / 3f32.sqrt()).abs() < 0.01);
+
+        xavier_normal(&mut t, &mut rng, 20, 30);
+        let (mean, std) = mean_and_std(&t);
+        assert!(mean.abs() < 0.01);
+        assert!((std - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_linear_reset_params_with() {
+        let mut model: Linear<64, 32> = Default::default();
+
+        // the default is the same as reset_params() with the same seed
+        model.reset_params(&mut StdRng::seed_from_u64(0));
+        let expected = model.clone();
+        model.reset_params_with(&mut StdRng::seed_from_u64(0), Init::Default);
+        assert_eq!(model.weight.data(), expected.weight.data());
+        assert_eq!(model.bias.data(), expected.bias.data());
+
+        let mut rng = StdRng::seed_from_u64(1);
+        model.reset_params_with(&mut rng, Init::KaimingUniform);
+        let bound = (6.0f32 / 64.0).sqrt();
+        assert!(model
+            .weight
+            .data()
+            .iter()
+            .flatten()
+            .all(|w| w.abs() <= bound));
+        assert!(model
+            .weight
+            .data()
+            .iter()
+            .flatten()
+            .any(|w| w.abs() > 1.0 / 8.0));
+
+        model.reset_params_with(&mut rng, Init::XavierUniform);
+        let bound = (6.0f32 / 96.0).sqrt();
+        assert!(model
+            .weight
+            .data()
+            .iter()
+            .flatten()
+            .all(|w| w.abs() <= bound));
+    }
+}
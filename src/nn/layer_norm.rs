@@ -1,5 +1,8 @@
 use crate::prelude::*;
+#[cfg(feature = "std")]
 use std::io::{Read, Seek, Write};
+use std::string::String;
+#[cfg(feature = "std")]
 use zip::{result::ZipResult, ZipArchive};
 
 /// Implements layer normalization as described in [Layer Normalization](https://arxiv.org/abs/1607.06450).
@@ -19,6 +22,7 @@ use zip::{result::ZipResult, ZipArchive};
 /// let x: Tensor1D<5> = Default::default();
 /// let _: Tensor1D<5> = model.forward(x);
 /// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayerNorm1D<const M: usize> {
     pub gamma: Tensor1D<M, NoneTape>,
     pub beta: Tensor1D<M, NoneTape>,
@@ -52,6 +56,13 @@ impl<const M: usize> CanUpdateWithGradients for LayerNorm1D<M> {
     }
 }
 
+impl<const M: usize> CountParams for LayerNorm1D<M> {
+    /// Counts [Self::gamma] and [Self::beta]. [Self::epsilon] is not a parameter.
+    fn num_params(&self) -> usize {
+        self.gamma.num_params() + self.beta.num_params()
+    }
+}
+
 impl<H: Tape, const M: usize> Module<Tensor1D<M, H>> for LayerNorm1D<M> {
     type Output = Tensor1D<M, H>;
 
@@ -80,6 +91,7 @@ impl<H: Tape, const B: usize, const M: usize> Module<Tensor2D<B, M, H>> for Laye
     }
 }
 
+#[cfg(feature = "std")]
 impl<const M: usize> SaveToNpz for LayerNorm1D<M> {
     /// Saves [Self::gamma] to `{pre}gamma.npy` and [Self::beta] to `{pre}beta.npy`
     /// using [npz_fwrite()].
@@ -90,6 +102,7 @@ impl<const M: usize> SaveToNpz for LayerNorm1D<M> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const M: usize> LoadFromNpz for LayerNorm1D<M> {
     /// Reads [Self::gamma] from `{p}gamma.npy` and [Self::beta] from `{p}beta.npy`
     /// using [npz_fread()].
@@ -100,11 +113,62 @@ impl<const M: usize> LoadFromNpz for LayerNorm1D<M> {
     }
 }
 
+impl<const M: usize> VisitParams for LayerNorm1D<M> {
+    /// Visits [Self::gamma] as `{pre}gamma` and [Self::beta] as `{pre}beta`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.gamma.visit_params(&format!("{pre}gamma"), f);
+        self.beta.visit_params(&format!("{pre}beta"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const M: usize> SaveToSafetensors for LayerNorm1D<M> {
+    /// Saves [Self::gamma] to `{pre}weight` and [Self::beta] to `{pre}bias`, which are the names
+    /// pytorch's `nn.LayerNorm` uses.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.gamma.data());
+        w.add(format!("{pre}bias"), self.beta.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const M: usize> LoadFromSafetensors for LayerNorm1D<M> {
+    /// Reads [Self::gamma] from `{pre}weight` and [Self::beta] from `{pre}bias`, which are the names
+    /// pytorch's `nn.LayerNorm` uses.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.gamma.mut_data())?;
+        r.read_into(&format!("{pre}bias"), self.beta.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<const M: usize> ToOnnx for LayerNorm1D<M> {
+    /// Adds a `LayerNormalization` over the last axis, with [Self::gamma] as `{pre}gamma`,
+    /// [Self::beta] as `{pre}beta`, and [Self::epsilon].
+    fn to_onnx(&self, pre: &str, input: String, g: &mut OnnxGraph) -> String {
+        let gamma = g.add_initializer(format!("{pre}gamma"), &[M], self.gamma.data());
+        let beta = g.add_initializer(format!("{pre}beta"), &[M], self.beta.data());
+        g.add_node(
+            "LayerNormalization",
+            &[&input, &gamma, &beta],
+            &[
+                ("axis", OnnxAttribute::Int(-1)),
+                ("epsilon", OnnxAttribute::Float(self.epsilon)),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
 
     use super::*;
+    use crate::tests::AssertClose;
     use rand::{prelude::StdRng, SeedableRng};
     use rand_distr::Standard;
     use tempfile::NamedTempFile;
@@ -202,6 +266,56 @@ mod tests {
         assert_eq!(gradients.ref_gradient(&m.beta), &[0.099999994; 10]);
     }
 
+    #[test]
+    fn test_layer_norm_2d_finite_differences() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut m: LayerNorm1D<4> = Default::default();
+        m.gamma.randomize(&mut rng, &Standard);
+        m.beta.randomize(&mut rng, &Standard);
+        let x: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let w: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let loss = |x: [[f32; 4]; 3]| -> f32 { *mul(m.forward(Tensor2D::new(x)), &w).sum().data() };
+
+        let gradients = mul(m.forward(x.trace()), &w).sum().backward();
+
+        let h = 1e-2;
+        let mut expected = [[0.0; 4]; 3];
+        for (i, expected_i) in expected.iter_mut().enumerate() {
+            for (j, e) in expected_i.iter_mut().enumerate() {
+                let mut plus = *x.data();
+                plus[i][j] += h;
+                let mut minus = *x.data();
+                minus[i][j] -= h;
+                *e = (loss(plus) - loss(minus)) / (2.0 * h);
+            }
+        }
+        gradients.ref_gradient(&x).assert_close(&expected, 1e-2);
+    }
+
+    #[test]
+    fn test_layer_norm_same_with_and_without_tape() {
+        // there are no running statistics, so training & inference behave the same,
+        // and each sample is normalized independently of the rest of the batch
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut m: LayerNorm1D<5> = Default::default();
+        m.gamma.randomize(&mut rng, &Standard);
+        m.beta.randomize(&mut rng, &Standard);
+        let x: Tensor2D<2, 5> = Tensor2D::randn(&mut rng);
+
+        let y_train = m.forward(x.trace());
+        let y_eval = m.forward(x.clone());
+        assert_eq!(y_train.data(), y_eval.data());
+
+        let y_single = m.forward(Tensor1D::new(x.data()[1]));
+        assert_eq!(y_single.data(), &y_eval.data()[1]);
+    }
+
+    #[test]
+    fn test_layer_norm_num_params() {
+        let model: LayerNorm1D<7> = Default::default();
+        assert_eq!(model.num_params(), 14);
+    }
+
     #[test]
     fn test_save_layer_norm() {
         let model: LayerNorm1D<13> = Default::default();
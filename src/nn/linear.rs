@@ -1,7 +1,9 @@
 use crate::prelude::*;
 use rand::Rng;
-use rand_distr::Uniform;
+#[cfg(feature = "std")]
 use std::io::{Read, Seek, Write};
+use std::{string::String, vec::Vec};
+#[cfg(feature = "std")]
 use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 /// A linear transformation of the form `weight * x + bias`, where `weight` is a matrix, `x` is a vector or matrix,
@@ -23,6 +25,7 @@ use zip::{result::ZipResult, ZipArchive, ZipWriter};
 /// assert_eq!(y.data(), &[0.0; 2]);
 /// ```
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Linear<const I: usize, const O: usize> {
     /// Transposed weight matrix, shape (O, I)
     pub weight: Tensor2D<O, I, NoneTape>,
@@ -38,19 +41,51 @@ impl<const I: usize, const O: usize> CanUpdateWithGradients for Linear<I, O> {
     }
 }
 
+impl<const I: usize, const O: usize> Linear<I, O> {
+    /// Initializes [Self::weight] with `init` and [Self::bias] with `bias`, using `I` as the
+    /// fan-in and `O` as the fan-out.
+    ///
+    /// Use [BiasInit::Uniform] to initialize the bias the same as pytorch:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// # let mut rng = rand::thread_rng();
+    /// let mut model: Linear<5, 2> = Default::default();
+    /// model.reset_params_with_bias(&mut rng, Init::Default, BiasInit::Uniform);
+    /// assert!(model.bias.data().iter().all(|b| b.abs() <= 1.0 / 5f32.sqrt()));
+    /// ```
+    pub fn reset_params_with_bias<R: Rng>(&mut self, rng: &mut R, init: Init, bias: BiasInit) {
+        init.init(&mut self.weight, rng, I, O);
+        bias.init(&mut self.bias, rng, I);
+    }
+}
+
 impl<const I: usize, const O: usize> ResetParams for Linear<I, O> {
-    /// Initializes [Self::weight] and [Self::bias] from a [Uniform] distribution
-    /// between [-1 / sqrt(I), 1 / sqrt(I)].
+    /// Initializes [Self::weight] from a [rand_distr::Uniform] distribution
+    /// between [-1 / sqrt(I), 1 / sqrt(I)], and fills [Self::bias] with zeros.
     ///
-    /// This uses [Randomize::randomize()] to set the values of the tensor.
+    /// This is the same as `reset_params_with_bias(rng, Init::Default, BiasInit::Zeros)`,
+    /// see [Linear::reset_params_with_bias()] to initialize the bias differently.
     fn reset_params<R: Rng>(&mut self, rng: &mut R) {
-        let bound: f32 = 1.0 / (I as f32).sqrt();
-        let dist = Uniform::new(-bound, bound);
-        self.weight.randomize(rng, &dist);
-        self.bias.randomize(rng, &dist);
+        self.reset_params_with_bias(rng, Init::Default, BiasInit::Zeros);
+    }
+}
+
+impl<const I: usize, const O: usize> ResetParamsWith for Linear<I, O> {
+    /// Initializes [Self::weight] with `init`, using `I` as the fan-in and `O` as the fan-out.
+    /// [Self::bias] is filled with zeros, the same as [ResetParams::reset_params()].
+    fn reset_params_with<R: Rng>(&mut self, rng: &mut R, init: Init) {
+        self.reset_params_with_bias(rng, init, BiasInit::Zeros);
     }
 }
 
+impl<const I: usize, const O: usize> CountParams for Linear<I, O> {
+    /// `I * O` for [Self::weight] plus `O` for [Self::bias].
+    fn num_params(&self) -> usize {
+        self.weight.num_params() + self.bias.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
 impl<const I: usize, const O: usize> SaveToNpz for Linear<I, O> {
     /// Saves [Self::weight] to `{pre}weight.npy` and [Self::bias] to `{pre}bias.npy`
     /// using [npz_fwrite()].
@@ -64,6 +99,7 @@ impl<const I: usize, const O: usize> SaveToNpz for Linear<I, O> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const I: usize, const O: usize> LoadFromNpz for Linear<I, O> {
     /// Reads [Self::weight] from `{pre}weight.npy` and [Self::bias] from `{pre}bias.npy`
     /// using [npz_fread()].
@@ -77,6 +113,52 @@ impl<const I: usize, const O: usize> LoadFromNpz for Linear<I, O> {
     }
 }
 
+impl<const I: usize, const O: usize> VisitParams for Linear<I, O> {
+    /// Visits [Self::weight] as `{pre}weight` and [Self::bias] as `{pre}bias`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.weight.visit_params(&format!("{pre}weight"), f);
+        self.bias.visit_params(&format!("{pre}bias"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> SaveToSafetensors for Linear<I, O> {
+    /// Saves [Self::weight] to `{pre}weight` and [Self::bias] to `{pre}bias`.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.weight.data());
+        w.add(format!("{pre}bias"), self.bias.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> LoadFromSafetensors for Linear<I, O> {
+    /// Reads [Self::weight] from `{pre}weight` and [Self::bias] from `{pre}bias`.
+    ///
+    /// [Self::weight] has shape `[O, I]`, which is the same as pytorch's `nn.Linear.weight`.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.weight.mut_data())?;
+        r.read_into(&format!("{pre}bias"), self.bias.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<const I: usize, const O: usize> ToOnnx for Linear<I, O> {
+    /// Adds a `MatMul` with [Self::weight] transposed as `{pre}weight`, and an `Add` with
+    /// [Self::bias] as `{pre}bias`.
+    fn to_onnx(&self, pre: &str, input: String, g: &mut OnnxGraph) -> String {
+        let w = self.weight.data();
+        let w_t: Vec<f32> = (0..I).flat_map(|i| (0..O).map(move |o| w[o][i])).collect();
+        let weight = g.add_initializer(format!("{pre}weight"), &[I, O], &w_t);
+        let bias = g.add_initializer(format!("{pre}bias"), &[O], self.bias.data());
+        let x = g.add_node("MatMul", &[&input, &weight], &[]);
+        g.add_node("Add", &[&x, &bias], &[])
+    }
+}
+
 impl<const I: usize, const O: usize, H: Tape> Module<Tensor1D<I, H>> for Linear<I, O> {
     type Output = Tensor1D<O, H>;
 
@@ -97,6 +179,26 @@ impl<const B: usize, const I: usize, const O: usize, H: Tape> Module<Tensor2D<B,
     }
 }
 
+impl<const B: usize, const S: usize, const I: usize, const O: usize, H: Tape>
+    Module<Tensor3D<B, S, I, H>> for Linear<I, O>
+{
+    type Output = Tensor3D<B, S, O, H>;
+
+    /// Batched 3d forward using [batch_matmul_transpose()] and [add_broadcast_rhs_first()].
+    /// [Self::bias] is repeated `S` times with [Tensor1D::repeat()], so its gradient is
+    /// summed over both the batch and sequence dimensions.
+    fn forward(&self, x: Tensor3D<B, S, I, H>) -> Self::Output {
+        let (y, tape) = batch_matmul_transpose(x, &self.weight).split_tape();
+        let (bias, tape) = self
+            .bias
+            .duplicate()
+            .put_tape(tape)
+            .repeat::<S>()
+            .split_tape();
+        add_broadcast_rhs_first(y.put_tape(tape), &bias)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{prelude::StdRng, SeedableRng};
@@ -104,7 +206,7 @@ mod tests {
     use tempfile::NamedTempFile;
 
     use super::*;
-    use crate::tests::assert_close;
+    use crate::tests::{assert_close, AssertClose};
 
     const W: [[f32; 5]; 2] = [
         [-0.3458893, -0.30371523, -0.3712057, 0.14303583, -0.0268966],
@@ -175,6 +277,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_forward_3d() {
+        let model: Linear<5, 2> = Linear {
+            weight: Tensor2D::new(W),
+            bias: Tensor1D::new(B),
+        };
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor3D<3, 4, 5> = Tensor3D::randn(&mut rng);
+        let y = model.forward(x.trace());
+        let y_data = *y.data();
+        let gradients = y.square().mean().backward();
+
+        // the same as the 2d forward on each batch item, with weight and bias gradients summed
+        let mut weight_grad = [[0.0; 5]; 2];
+        let mut bias_grad = [0.0; 2];
+        for (b, (y_b, x_grad_b)) in y_data
+            .iter()
+            .zip(gradients.ref_gradient(&x).iter())
+            .enumerate()
+        {
+            let x_b = Tensor2D::new(x.data()[b]);
+            let y2 = model.forward(x_b.trace());
+            assert_close(y2.data(), y_b);
+            let expected = (y2.square().mean() / 3.0).backward();
+            assert_close(expected.ref_gradient(&x_b), x_grad_b);
+            for (g, e) in weight_grad
+                .iter_mut()
+                .flatten()
+                .zip(expected.ref_gradient(&model.weight).iter().flatten())
+            {
+                *g += e;
+            }
+            for (g, e) in bias_grad
+                .iter_mut()
+                .zip(expected.ref_gradient(&model.bias).iter())
+            {
+                *g += e;
+            }
+        }
+        gradients
+            .ref_gradient(&model.weight)
+            .assert_close(&weight_grad, 1e-6);
+        gradients
+            .ref_gradient(&model.bias)
+            .assert_close(&bias_grad, 1e-6);
+    }
+
+    #[test]
+    fn test_forward_3d_in_sequential() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut model: (Linear<4, 8>, ReLU, Linear<8, 3>) = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor3D<2, 5, 4> = Tensor3D::randn(&mut rng);
+        let y: Tensor3D<2, 5, 3, OwnedTape> = model.forward(x.trace());
+        let gradients = y.sum().backward();
+        // every one of the 2 * 5 timesteps contributes 1 to each output's bias gradient
+        assert_eq!(gradients.ref_gradient(&model.2.bias), &[10.0; 3]);
+    }
+
     #[test]
     fn test_save_linear() {
         let model: Linear<5, 3> = Default::default();
@@ -202,7 +364,7 @@ mod tests {
     fn test_load_linear() {
         let mut rng = StdRng::seed_from_u64(0);
         let mut saved_model: Linear<5, 3> = Default::default();
-        saved_model.reset_params(&mut rng);
+        saved_model.reset_params_with_bias(&mut rng, Init::Default, BiasInit::Uniform);
 
         let file = NamedTempFile::new().expect("failed to create tempfile");
         assert!(saved_model.save(file.path().to_str().unwrap()).is_ok());
@@ -215,4 +377,33 @@ mod tests {
         assert_eq!(loaded_model.weight.data(), saved_model.weight.data());
         assert_eq!(loaded_model.bias.data(), saved_model.bias.data());
     }
+
+    #[test]
+    fn test_linear_num_params() {
+        let model: Linear<5, 3> = Default::default();
+        assert_eq!(model.num_params(), 5 * 3 + 3);
+    }
+
+    #[test]
+    fn test_linear_reset_bias() {
+        let mut model: Linear<16, 8> = Default::default();
+        Cpu::fill(model.bias.mut_data(), &mut |b| *b = 1.0);
+        model.reset_params(&mut StdRng::seed_from_u64(0));
+        assert_eq!(model.bias.data(), &[0.0; 8]);
+        assert_ne!(model.weight.data(), &[[0.0; 16]; 8]);
+
+        // the weight doesn't depend on how the bias is initialized
+        let expected = model.clone();
+        model.reset_params_with_bias(
+            &mut StdRng::seed_from_u64(0),
+            Init::Default,
+            BiasInit::Uniform,
+        );
+        assert_eq!(model.weight.data(), expected.weight.data());
+        assert!(model.bias.data().iter().all(|b| b.abs() <= 0.25));
+        assert_ne!(model.bias.data(), &[0.0; 8]);
+
+        model.reset_params_with(&mut StdRng::seed_from_u64(1), Init::XavierNormal);
+        assert_eq!(model.bias.data(), &[0.0; 8]);
+    }
 }
@@ -53,25 +53,74 @@
 //! state_dict = {k: torch.from_numpy(v) for k, v in np.load("dfdx-model.npz").items()}
 //! mlp.load_state_dict(state_dict)
 //! ```
+//!
+//! Going the other way, [LoadFromNpz::load_from_pytorch()] loads a `.npz` saved from a pytorch
+//! `state_dict()`, mapping pytorch names like `fc1.weight` to dfdx names like `0.weight`.
+//!
+//! Modules can also be loaded from [.safetensors](https://github.com/huggingface/safetensors) files with
+//! [LoadFromSafetensors::load_safetensors()], and saved with [SaveToSafetensors::save_safetensors()].
+//! These use the same names as pytorch's `state_dict()`, so you can load pretrained pytorch weights:
+//!
+//! ```python
+//! from safetensors.torch import save_file
+//! save_file(mlp.state_dict(), "pytorch-model.safetensors")
+//! ```
+//!
+//! # Exporting to ONNX
+//!
+//! Models made of modules that implement [ToOnnx] can be exported for inference with [to_onnx()],
+//! and run with onnxruntime.
 
 mod activations;
+mod attention;
+mod checkpoint;
+mod conv_transpose;
 mod dropout;
+mod flatten;
+mod frozen;
+mod group_norm;
 mod impl_module_for_tuples;
+mod init;
 mod layer_norm;
 mod linear;
 mod module;
+#[cfg(feature = "std")]
 mod npz;
+mod onnx;
+mod positional_encoding;
+mod prelu;
 mod repeated;
 mod residual;
+#[cfg(feature = "std")]
+mod safetensors;
 mod split_into;
+mod tied_linear;
+mod unbiased_linear;
+mod upsample;
 
 pub use activations::*;
+pub use attention::*;
+pub use checkpoint::*;
+pub use conv_transpose::*;
 pub use dropout::*;
+pub use flatten::*;
+pub use frozen::*;
+pub use group_norm::*;
 pub use impl_module_for_tuples::*;
+pub use init::*;
 pub use layer_norm::*;
 pub use linear::*;
 pub use module::*;
+#[cfg(feature = "std")]
 pub use npz::*;
+pub use onnx::*;
+pub use positional_encoding::*;
+pub use prelu::*;
 pub use repeated::*;
 pub use residual::*;
+#[cfg(feature = "std")]
+pub use safetensors::*;
 pub use split_into::*;
+pub use tied_linear::*;
+pub use unbiased_linear::*;
+pub use upsample::*;
@@ -1,4 +1,8 @@
-use crate::prelude::CanUpdateWithGradients;
+use crate::numpy::NumpyShape;
+use crate::prelude::{CanUpdateWithGradients, CountElements, Tensor, UniqueId};
+use crate::tensor_ops::flat;
+use std::any::Any;
+use std::vec::Vec;
 
 /// A unit of a neural network. Acts on the generic `Input`
 /// and produces `Module::Output`.
@@ -66,3 +70,118 @@ pub trait ResetParams {
     /// ```
     fn reset_params<R: rand::Rng>(&mut self, rng: &mut R);
 }
+
+/// Something that can count the number of learnable parameters it has.
+///
+/// Only tensors that are updated by [CanUpdateWithGradients] count as parameters, so
+/// constants and non-learnable buffers (like [super::LayerNorm1D::epsilon]) are excluded.
+///
+/// The default implementation returns `0`, which is what modules without parameters use.
+///
+/// # Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Mlp = (Linear<5, 3>, ReLU, Linear<3, 2>);
+/// let model: Mlp = Default::default();
+/// assert_eq!(model.num_params(), (5 * 3 + 3) + (3 * 2 + 2));
+/// ```
+pub trait CountParams {
+    /// The total number of elements in all of the learnable tensors of `self`.
+    fn num_params(&self) -> usize {
+        0
+    }
+}
+
+/// Every element of a tensor is a learnable parameter.
+impl<T: Tensor> CountParams for T {
+    fn num_params(&self) -> usize {
+        <T::Array as CountElements>::NUM_ELEMENTS
+    }
+}
+
+/// A learnable tensor passed to the callback of [VisitParams::visit_params()].
+///
+/// This is object safe, so parameters of every shape can be passed to the same closure. Use
+/// [Param::as_any()] to downcast to the concrete tensor type.
+pub trait Param {
+    /// The [UniqueId] of the tensor, which its gradients are stored under.
+    fn param_id(&self) -> &UniqueId;
+
+    /// The shape of the tensor, e.g. `[O, I]` for [super::Linear::weight].
+    fn param_shape(&self) -> Vec<usize>;
+
+    /// All the elements of the tensor, in row major order.
+    fn param_data(&self) -> &[f32];
+
+    /// The tensor as [Any], to downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static + Tensor<Dtype = f32>> Param for T
+where
+    T::Array: NumpyShape,
+{
+    fn param_id(&self) -> &UniqueId {
+        self.id()
+    }
+
+    fn param_shape(&self) -> Vec<usize> {
+        T::Array::shape()
+    }
+
+    fn param_data(&self) -> &[f32] {
+        flat(self.data())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Something that can visit all of its learnable parameters by name, for things like logging
+/// per-layer weight norms or computing a regularizer over all weights.
+///
+/// Parameters are visited in a deterministic order, with the same names used by [super::SaveToNpz]
+/// (without the `.npy`), e.g. `"0.weight"`, `"0.bias"`, `"2.weight"` for a tuple. The default
+/// implementation visits nothing, which is what modules without parameters use.
+///
+/// # Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Mlp = (Linear<5, 3>, ReLU, Linear<3, 2>);
+/// let model: Mlp = Default::default();
+/// let mut names = Vec::new();
+/// model.visit_params("", &mut |name, p| names.push((name.to_string(), p.param_shape())));
+/// assert_eq!(
+///     names,
+///     [
+///         ("0.weight".to_string(), vec![3, 5]),
+///         ("0.bias".to_string(), vec![3]),
+///         ("2.weight".to_string(), vec![2, 3]),
+///         ("2.bias".to_string(), vec![2]),
+///     ]
+/// );
+/// assert_eq!(model.param_count(), (5 * 3 + 3) + (3 * 2 + 2));
+/// ```
+pub trait VisitParams {
+    /// Calls `f` with the name and value of each learnable parameter. `name` is the prefix
+    /// for all the parameters of `self` (or the full name, for a tensor).
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, _name: &str, _f: &mut F) {}
+
+    /// The total number of elements in all the parameters visited by [VisitParams::visit_params()].
+    fn param_count(&self) -> usize {
+        let mut count = 0;
+        self.visit_params("", &mut |_, p| count += p.param_data().len());
+        count
+    }
+}
+
+/// A tensor visits itself, with `name` as its name.
+impl<T: 'static + Tensor<Dtype = f32>> VisitParams for T
+where
+    T::Array: NumpyShape,
+{
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, name: &str, f: &mut F) {
+        f(name, self);
+    }
+}
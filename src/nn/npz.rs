@@ -1,7 +1,7 @@
 use crate::numpy::{self, NpyError, NumpyDtype, NumpyShape, ReadNumbers, WriteNumbers};
 use std::{
     fs::File,
-    io::{BufReader, BufWriter, Read, Seek, Write},
+    io::{BufReader, BufWriter, Cursor, Read, Seek, Write},
     path::Path,
 };
 use zip::{
@@ -73,6 +73,65 @@ pub trait LoadFromNpz {
         Ok(())
     }
 
+    /// Loads data from a `.npz` saved from a pytorch `state_dict()`, e.g. with:
+    ///
+    /// ```python
+    /// np.savez("model.npz", **{k: v.numpy() for k, v in model.state_dict().items()})
+    /// ```
+    ///
+    /// `names` maps the prefixes of the pytorch names to the prefixes used by dfdx, so
+    /// `&[("fc1", "0"), ("fc2", "2")]` loads `fc1.weight` into `0.weight`. The longest matching
+    /// prefix is used, and names without a matching prefix are loaded as is. Parameters have
+    /// the same layout as pytorch (e.g. [super::Linear::weight] is `(out_features, in_features)`),
+    /// so nothing is transposed.
+    ///
+    /// Errors use the pytorch name of the file, e.g. [NpzError::ShapeMismatch] with `fc2.weight.npy`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let mut model: (Linear<5, 10>, ReLU, Linear<10, 5>) = Default::default();
+    /// model.load_from_pytorch("model.npz", &[("fc1", "0"), ("fc2", "2")])?;
+    /// ```
+    fn load_from_pytorch<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        names: &[(&str, &str)],
+    ) -> Result<(), NpzError> {
+        let f = File::open(path).map_err(|e| NpzError::Npy(NpyError::IoError(e)))?;
+        let mut src = ZipArchive::new(BufReader::new(f)).map_err(NpzError::Zip)?;
+
+        // copy every file into an in memory zip with the dfdx names
+        let mut renamed = Vec::with_capacity(src.len());
+        let mut dst = ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..src.len() {
+            let mut file = src.by_index(i).map_err(NpzError::Zip)?;
+            let name = rename(file.name(), names);
+            dst.start_file(&name, Default::default())
+                .map_err(NpzError::Zip)?;
+            std::io::copy(&mut file, &mut dst).map_err(|e| NpzError::Npy(NpyError::IoError(e)))?;
+            renamed.push((name, file.name().to_string()));
+        }
+        let mut zip =
+            ZipArchive::new(dst.finish().map_err(NpzError::Zip)?).map_err(NpzError::Zip)?;
+
+        self.read("", &mut zip).map_err(|e| match e {
+            NpzError::ShapeMismatch {
+                name,
+                expected,
+                found,
+            } => NpzError::ShapeMismatch {
+                name: renamed
+                    .iter()
+                    .find(|(dfdx, _)| dfdx == &name)
+                    .map_or(name, |(_, torch)| torch.clone()),
+                expected,
+                found,
+            },
+            e => e,
+        })
+    }
+
     /// Reads this object from a [ZipArchive]. `r` with a base filename of `filename_prefix`.
     ///
     /// Example:
@@ -93,15 +152,73 @@ pub trait LoadFromNpz {
     }
 }
 
+/// Replaces the longest prefix of `name` in `names` (which must be followed by a `.`).
+fn rename(name: &str, names: &[(&str, &str)]) -> String {
+    names
+        .iter()
+        .filter(|(from, _)| {
+            name.strip_prefix(from)
+                .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|(from, _)| from.len())
+        .map_or_else(
+            || name.to_string(),
+            |(from, to)| format!("{to}{}", &name[from.len()..]),
+        )
+}
+
 /// Error that can happen while loading data from a `.npz` zip archive.
+#[derive(Debug)]
 pub enum NpzError {
     /// Something went wrong with reading from the `.zip` archive.
     Zip(ZipError),
 
     /// Something went wrong with loading data from a `.npy` file
     Npy(NpyError),
+
+    /// The file `name` has shape `found`, but the parameter has shape `expected`.
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+
+    /// The archive has a file `name` that does not belong to any parameter,
+    /// e.g. because it was written for a module with more parameters.
+    UnexpectedFile(String),
+}
+
+impl NpzError {
+    /// Adds the name of the file to a [NpyError::ShapeMismatch].
+    pub(crate) fn from_npy(name: &str, e: NpyError) -> Self {
+        match e {
+            NpyError::ShapeMismatch { expected, found } => Self::ShapeMismatch {
+                name: name.to_string(),
+                expected,
+                found,
+            },
+            e => Self::Npy(e),
+        }
+    }
+}
+
+impl std::fmt::Display for NpzError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Zip(e) => write!(f, "{e}"),
+            Self::Npy(e) => write!(f, "{e}"),
+            Self::ShapeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(f, "`{name}` has shape {found:?}, expected {expected:?}"),
+            Self::UnexpectedFile(name) => write!(f, "`{name}` does not belong to any parameter"),
+        }
+    }
 }
 
+impl std::error::Error for NpzError {}
+
 /// Writes `data` to a new file in a zip archive named `filename`.
 ///
 /// Example:
@@ -134,6 +251,124 @@ pub fn npz_fread<R: Read + Seek, T: NumpyDtype + NumpyShape + ReadNumbers>(
     data: &mut T,
 ) -> Result<(), NpzError> {
     let mut f = r.by_name(&filename).map_err(NpzError::Zip)?;
-    numpy::read(&mut f, data).map_err(NpzError::Npy)?;
+    numpy::read(&mut f, data).map_err(|e| NpzError::from_npy(&filename, e))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    type Model = (Linear<3, 4>, ReLU, Linear<4, 2>);
+
+    #[test]
+    fn test_save_load_nested_names() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut saved: Model = Default::default();
+        saved.reset_params(&mut rng);
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        saved.save(file.path()).expect("");
+
+        let zip = ZipArchive::new(File::open(file.path()).unwrap()).unwrap();
+        let mut names = zip.file_names().collect::<Vec<&str>>();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["0.bias.npy", "0.weight.npy", "2.bias.npy", "2.weight.npy"]
+        );
+
+        let mut loaded: Model = Default::default();
+        loaded.load(file.path()).expect("");
+        assert_eq!(loaded.0.weight.data(), saved.0.weight.data());
+        assert_eq!(loaded.0.bias.data(), saved.0.bias.data());
+        assert_eq!(loaded.2.weight.data(), saved.2.weight.data());
+        assert_eq!(loaded.2.bias.data(), saved.2.bias.data());
+    }
+
+    /// Writes a `.npz` with the names pytorch uses for `Linear` layers named `fc1` & `fc2`.
+    fn save_pytorch_mlp(path: &std::path::Path, w2: &[[f32; 4]; 2]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        npz_fwrite(&mut zip, "fc1.weight.npy".into(), &[[0.5f32; 3]; 4]).unwrap();
+        npz_fwrite(&mut zip, "fc1.bias.npy".into(), &[1.0f32, 2.0, 3.0, 4.0]).unwrap();
+        npz_fwrite(&mut zip, "fc2.weight.npy".into(), w2).unwrap();
+        npz_fwrite(&mut zip, "fc2.bias.npy".into(), &[-1.0f32, 1.0]).unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_rename() {
+        let names = [("fc1", "0"), ("encoder", "1"), ("encoder.fc", "1.0")];
+        assert_eq!(rename("fc1.weight.npy", &names), "0.weight.npy");
+        assert_eq!(rename("fc10.weight.npy", &names), "fc10.weight.npy");
+        assert_eq!(rename("encoder.fc.bias.npy", &names), "1.0.bias.npy");
+        assert_eq!(rename("encoder.2.bias.npy", &names), "1.2.bias.npy");
+        assert_eq!(rename("other.npy", &names), "other.npy");
+    }
+
+    #[test]
+    fn test_load_from_pytorch() {
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let w2 = [[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]];
+        save_pytorch_mlp(file.path(), &w2);
+
+        let mut model: Model = Default::default();
+        assert!(model.load(file.path()).is_err());
+        model
+            .load_from_pytorch(file.path(), &[("fc1", "0"), ("fc2", "2")])
+            .expect("");
+        assert_eq!(model.0.weight.data(), &[[0.5; 3]; 4]);
+        assert_eq!(model.0.bias.data(), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(model.2.weight.data(), &w2);
+        assert_eq!(model.2.bias.data(), &[-1.0, 1.0]);
+
+        // same layout as pytorch, so x @ W.T + b
+        let y = model.2.forward(Tensor1D::new([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(y.data(), &[4.0, 14.0]);
+    }
+
+    #[test]
+    fn test_load_from_pytorch_shape_mismatch() {
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        save_pytorch_mlp(file.path(), &[[0.0; 4]; 2]);
+
+        let mut model: (Linear<3, 4>, ReLU, Linear<4, 3>) = Default::default();
+        let err = model
+            .load_from_pytorch(file.path(), &[("fc1", "0"), ("fc2", "2")])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`fc2.weight.npy` has shape [2, 4], expected [3, 4]"
+        );
+    }
+
+    #[test]
+    fn test_load_shape_mismatch() {
+        let saved: Model = Default::default();
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        saved.save(file.path()).expect("");
+
+        let mut loaded: (Linear<3, 4>, ReLU, Linear<4, 3>) = Default::default();
+        match loaded.load(file.path()) {
+            Err(NpzError::ShapeMismatch {
+                name,
+                expected,
+                found,
+            }) => {
+                assert_eq!(name, "2.weight.npy");
+                assert_eq!(expected, [3, 4]);
+                assert_eq!(found, [2, 4]);
+            }
+            r => panic!("{:?}", r),
+        }
+
+        let mut loaded: (Linear<3, 5>, ReLU, Linear<5, 2>) = Default::default();
+        let err = loaded.load(file.path()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`0.weight.npy` has shape [4, 3], expected [5, 3]"
+        );
+    }
+}
@@ -0,0 +1,484 @@
+#[cfg(feature = "std")]
+use std::path::Path;
+use std::{string::String, vec::Vec};
+
+/// Something that can be exported to an [ONNX](https://onnx.ai/) graph for inference, e.g. with onnxruntime.
+///
+/// Most [super::Module]s in nn implement ToOnnx, including tuples, so a model can be exported
+/// with [to_onnx()]. Using a module that doesn't implement it is a compile time error.
+///
+/// Parameters are added as initializers with the same names as [super::SaveToNpz], except
+/// for [super::Linear] & [super::UnbiasedLinear], whose `weight` is stored transposed
+/// (shape `(I, O)`) so that it can be used with a `MatMul` node for inputs of any rank.
+///
+/// Modules that don't do anything at inference time (e.g. [super::Dropout]) return `input`.
+pub trait ToOnnx {
+    /// Adds the nodes that compute this module from the value named `input` to `g`, using
+    /// `prefix` for the names of initializers. Returns the name of the output value.
+    fn to_onnx(&self, prefix: &str, input: String, g: &mut OnnxGraph) -> String;
+}
+
+/// Exports `model` to the `.onnx` file at `path`, with a single float input named `input`
+/// of shape `input_shape`, and a single float output named `output`.
+///
+/// Example:
+/// ```ignore
+/// # use dfdx::prelude::*;
+/// let model: (Linear<5, 10>, ReLU, Linear<10, 2>) = Default::default();
+/// to_onnx(&model, &[16, 5], "model.onnx")?;
+/// ```
+#[cfg(feature = "std")]
+pub fn to_onnx<M: ToOnnx, P: AsRef<Path>>(
+    model: &M,
+    input_shape: &[usize],
+    path: P,
+) -> std::io::Result<()> {
+    std::fs::write(path, to_onnx_bytes(model, input_shape))
+}
+
+/// Returns the serialized `ModelProto` that [to_onnx()] writes.
+pub fn to_onnx_bytes<M: ToOnnx>(model: &M, input_shape: &[usize]) -> Vec<u8> {
+    let mut g = OnnxGraph::default();
+    let output = model.to_onnx("", "input".into(), &mut g);
+    g.add_node_with_output("Identity", &[&output], &[], "output".into());
+
+    let mut graph = Proto::default();
+    for node in g.nodes.iter() {
+        graph.message(1, node);
+    }
+    graph.string(2, "dfdx");
+    for init in g.initializers.iter() {
+        graph.message(5, init);
+    }
+    graph.message(11, &value_info("input", Some(input_shape)));
+    graph.message(12, &value_info("output", None));
+
+    let mut opset = Proto::default();
+    opset.string(1, "");
+    opset.varint(2, OPSET_VERSION);
+
+    let mut model = Proto::default();
+    model.varint(1, IR_VERSION);
+    model.string(2, "dfdx");
+    model.string(3, env!("CARGO_PKG_VERSION"));
+    model.message(7, &graph);
+    model.message(8, &opset);
+    model.0
+}
+
+/// The version of the ONNX file format that is written.
+const IR_VERSION: u64 = 8;
+
+/// The version of the default operator set, which is the first one with `LayerNormalization`.
+const OPSET_VERSION: u64 = 17;
+
+/// `TensorProto.DataType.FLOAT`
+const FLOAT: u64 = 1;
+
+/// An attribute of an [OnnxGraph] node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnnxAttribute {
+    Float(f32),
+    Int(i64),
+}
+
+/// The nodes & initializers of an ONNX graph, which [ToOnnx] adds to.
+#[derive(Debug, Default)]
+pub struct OnnxGraph {
+    nodes: Vec<Proto>,
+    initializers: Vec<Proto>,
+}
+
+impl OnnxGraph {
+    /// Adds a float initializer named `name` with `data` in row major order, and returns `name`.
+    pub fn add_initializer(&mut self, name: String, shape: &[usize], data: &[f32]) -> String {
+        let mut init = Proto::default();
+        for &d in shape {
+            init.varint(1, d as u64);
+        }
+        init.varint(2, FLOAT);
+        init.string(8, &name);
+        let raw: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        init.bytes(9, &raw);
+        self.initializers.push(init);
+        name
+    }
+
+    /// Adds a node that applies `op_type` to `inputs`, and returns the name of its single output.
+    pub fn add_node(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[(&str, OnnxAttribute)],
+    ) -> String {
+        let output = format!("{}_{}", op_type, self.nodes.len());
+        self.add_node_with_output(op_type, inputs, attributes, output)
+    }
+
+    fn add_node_with_output(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[(&str, OnnxAttribute)],
+        output: String,
+    ) -> String {
+        let mut node = Proto::default();
+        for input in inputs {
+            node.string(1, input);
+        }
+        node.string(2, &output);
+        node.string(3, &format!("{}_{}", op_type, self.nodes.len()));
+        node.string(4, op_type);
+        for (name, value) in attributes {
+            let mut attr = Proto::default();
+            attr.string(1, name);
+            match value {
+                OnnxAttribute::Float(f) => {
+                    attr.fixed32(2, f.to_bits());
+                    attr.varint(20, 1);
+                }
+                OnnxAttribute::Int(i) => {
+                    attr.varint(3, *i as u64);
+                    attr.varint(20, 2);
+                }
+            }
+            node.message(5, &attr);
+        }
+        self.nodes.push(node);
+        output
+    }
+}
+
+/// A `ValueInfoProto` for a float tensor, with an unknown shape if `shape` is `None`.
+fn value_info(name: &str, shape: Option<&[usize]>) -> Proto {
+    let mut tensor_type = Proto::default();
+    tensor_type.varint(1, FLOAT);
+    if let Some(shape) = shape {
+        let mut shape_proto = Proto::default();
+        for &d in shape {
+            let mut dim = Proto::default();
+            dim.varint(1, d as u64);
+            shape_proto.message(1, &dim);
+        }
+        tensor_type.message(2, &shape_proto);
+    }
+    let mut type_proto = Proto::default();
+    type_proto.message(1, &tensor_type);
+
+    let mut info = Proto::default();
+    info.string(1, name);
+    info.message(2, &type_proto);
+    info
+}
+
+/// Just enough of the protobuf wire format to write an ONNX `ModelProto`.
+#[derive(Debug, Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    fn raw_varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.raw_varint((field << 3) | wire_type);
+    }
+
+    fn varint(&mut self, field: u64, v: u64) {
+        self.key(field, 0);
+        self.raw_varint(v);
+    }
+
+    fn fixed32(&mut self, field: u64, v: u32) {
+        self.key(field, 5);
+        self.0.extend(v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, field: u64, v: &[u8]) {
+        self.key(field, 2);
+        self.raw_varint(v.len() as u64);
+        self.0.extend_from_slice(v);
+    }
+
+    fn string(&mut self, field: u64, v: &str) {
+        self.bytes(field, v.as_bytes());
+    }
+
+    fn message(&mut self, field: u64, v: &Proto) {
+        self.bytes(field, &v.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use rand::{prelude::StdRng, SeedableRng};
+    use std::collections::HashMap;
+    use tempfile::NamedTempFile;
+
+    #[derive(Debug, Clone)]
+    enum Field {
+        Varint(u64),
+        Fixed32(u32),
+        Bytes(Vec<u8>),
+    }
+
+    /// Decodes the fields of a protobuf message.
+    fn decode(mut bytes: &[u8]) -> Vec<(u64, Field)> {
+        fn varint(bytes: &mut &[u8]) -> u64 {
+            let mut v = 0;
+            for shift in (0..).step_by(7) {
+                let b = bytes[0];
+                *bytes = &bytes[1..];
+                v |= ((b & 0x7f) as u64) << shift;
+                if b < 0x80 {
+                    break;
+                }
+            }
+            v
+        }
+        let mut fields = Vec::new();
+        while !bytes.is_empty() {
+            let key = varint(&mut bytes);
+            let field = match key & 7 {
+                0 => Field::Varint(varint(&mut bytes)),
+                5 => {
+                    let v = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+                    bytes = &bytes[4..];
+                    Field::Fixed32(v)
+                }
+                2 => {
+                    let len = varint(&mut bytes) as usize;
+                    let v = bytes[..len].to_vec();
+                    bytes = &bytes[len..];
+                    Field::Bytes(v)
+                }
+                w => panic!("unexpected wire type {w}"),
+            };
+            fields.push((key >> 3, field));
+        }
+        fields
+    }
+
+    fn get(fields: &[(u64, Field)], n: u64) -> Vec<Field> {
+        fields
+            .iter()
+            .filter(|(k, _)| *k == n)
+            .map(|(_, f)| f.clone())
+            .collect()
+    }
+
+    fn bytes(f: &Field) -> &[u8] {
+        match f {
+            Field::Bytes(b) => b,
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    fn string(f: &Field) -> String {
+        String::from_utf8(bytes(f).to_vec()).unwrap()
+    }
+
+    fn varint(f: &Field) -> u64 {
+        match f {
+            Field::Varint(v) => *v,
+            _ => panic!("expected varint"),
+        }
+    }
+
+    /// A row major tensor for [run()].
+    #[derive(Debug, Clone)]
+    struct Value {
+        shape: Vec<usize>,
+        data: Vec<f32>,
+    }
+
+    /// Evaluates the ONNX `model` on `input`, for the ops that dfdx exports.
+    fn run(model: &[u8], input: Value) -> Value {
+        let model = decode(model);
+        assert_eq!(varint(&get(&model, 1)[0]), IR_VERSION);
+        let opset = decode(bytes(&get(&model, 8)[0]));
+        assert_eq!(varint(&get(&opset, 2)[0]), OPSET_VERSION);
+        let graph = decode(bytes(&get(&model, 7)[0]));
+
+        let mut values: HashMap<String, Value> = HashMap::new();
+        values.insert("input".into(), input);
+        for init in get(&graph, 5) {
+            let init = decode(bytes(&init));
+            assert_eq!(varint(&get(&init, 2)[0]), FLOAT);
+            let shape = get(&init, 1).iter().map(|d| varint(d) as usize).collect();
+            let data = bytes(&get(&init, 9)[0])
+                .chunks(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            values.insert(string(&get(&init, 8)[0]), Value { shape, data });
+        }
+
+        for node in get(&graph, 1) {
+            let node = decode(bytes(&node));
+            let inputs: Vec<Value> = get(&node, 1)
+                .iter()
+                .map(|i| values[&string(i)].clone())
+                .collect();
+            let mut attrs = HashMap::new();
+            for attr in get(&node, 5) {
+                let attr = decode(bytes(&attr));
+                if let Some(Field::Fixed32(f)) = get(&attr, 2).first() {
+                    attrs.insert(string(&get(&attr, 1)[0]), f32::from_bits(*f));
+                }
+            }
+            let x = inputs[0].clone();
+            let unary = |f: fn(f32) -> f32| Value {
+                shape: x.shape.clone(),
+                data: x.data.iter().map(|&v| f(v)).collect(),
+            };
+            let op_type = string(&get(&node, 4)[0]);
+            let y = match op_type.as_str() {
+                "Identity" => x.clone(),
+                "Relu" => unary(|v| v.max(0.0)),
+                "Sigmoid" => unary(|v| 1.0 / (1.0 + (-v).exp())),
+                "Tanh" => unary(f32::tanh),
+                "Exp" => unary(f32::exp),
+                "Add" | "Mul" => {
+                    let (a, b) = (&inputs[0], &inputs[1]);
+                    let op = |(i, v): (usize, &f32)| {
+                        let w = b.data[i % b.data.len()];
+                        if op_type == "Add" {
+                            v + w
+                        } else {
+                            v * w
+                        }
+                    };
+                    Value {
+                        shape: a.shape.clone(),
+                        data: a.data.iter().enumerate().map(op).collect(),
+                    }
+                }
+                "MatMul" => {
+                    let (a, b) = (&inputs[0], &inputs[1]);
+                    let (k, n) = (b.shape[0], b.shape[1]);
+                    let mut data = Vec::new();
+                    for row in a.data.chunks(k) {
+                        for j in 0..n {
+                            data.push((0..k).map(|i| row[i] * b.data[i * n + j]).sum());
+                        }
+                    }
+                    let mut shape = a.shape.clone();
+                    *shape.last_mut().unwrap() = n;
+                    Value { shape, data }
+                }
+                "LayerNormalization" => {
+                    let (gamma, beta) = (&inputs[1], &inputs[2]);
+                    let eps = attrs["epsilon"];
+                    let n = gamma.data.len();
+                    let mut data = Vec::new();
+                    for row in x.data.chunks(n) {
+                        let mean = row.iter().sum::<f32>() / n as f32;
+                        let var = row.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+                        for (i, v) in row.iter().enumerate() {
+                            data.push(
+                                (v - mean) / (var + eps).sqrt() * gamma.data[i] + beta.data[i],
+                            );
+                        }
+                    }
+                    Value {
+                        shape: x.shape.clone(),
+                        data,
+                    }
+                }
+                op => panic!("unsupported op {op}"),
+            };
+            values.insert(string(&get(&node, 2)[0]), y);
+        }
+        values.remove("output").unwrap()
+    }
+
+    type Model = (
+        Linear<4, 8>,
+        ReLU,
+        LayerNorm1D<8>,
+        Residual<(Linear<8, 8>, Tanh)>,
+        (
+            Dropout,
+            Repeated<(UnbiasedLinear<8, 8>, Sigmoid), 2>,
+            Linear<8, 3>,
+        ),
+    );
+
+    #[test]
+    fn test_onnx_matches_forward() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        model
+            .0
+            .reset_params_with_bias(&mut rng, Init::Default, BiasInit::Uniform);
+        model
+            .2
+            .gamma
+            .randomize(&mut rng, &rand_distr::StandardNormal);
+
+        let x: Tensor2D<5, 4> = Tensor2D::randn(&mut rng);
+        let bytes = to_onnx_bytes(&model, &[5, 4]);
+        let y = run(
+            &bytes,
+            Value {
+                shape: vec![5, 4],
+                data: x.data().iter().flatten().copied().collect(),
+            },
+        );
+        assert_eq!(y.shape, [5, 3]);
+        let expected = model.forward(x);
+        let expected: Vec<f32> = expected.data().iter().flatten().copied().collect();
+        for (a, b) in y.data.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-5, "{:?} {:?}", y.data, expected);
+        }
+    }
+
+    #[test]
+    fn test_onnx_graph_structure() {
+        let model: (Linear<2, 3>, Dropout, Sigmoid) = Default::default();
+        let model = to_onnx_bytes(&model, &[1, 2]);
+        let graph = decode(bytes(&get(&decode(&model), 7)[0]));
+
+        let ops: Vec<String> = get(&graph, 1)
+            .iter()
+            .map(|n| string(&get(&decode(bytes(n)), 4)[0]))
+            .collect();
+        assert_eq!(ops, ["MatMul", "Add", "Sigmoid", "Identity"]);
+
+        let inits: Vec<(String, Vec<u64>)> = get(&graph, 5)
+            .iter()
+            .map(|i| {
+                let i = decode(bytes(i));
+                let dims = get(&i, 1).iter().map(varint).collect();
+                (string(&get(&i, 8)[0]), dims)
+            })
+            .collect();
+        assert_eq!(
+            inits,
+            [("0.weight".into(), vec![2, 3]), ("0.bias".into(), vec![3])]
+        );
+
+        let input = decode(bytes(&get(&graph, 11)[0]));
+        assert_eq!(string(&get(&input, 1)[0]), "input");
+        let output = decode(bytes(&get(&graph, 12)[0]));
+        assert_eq!(string(&get(&output, 1)[0]), "output");
+    }
+
+    #[test]
+    fn test_to_onnx_file() {
+        let model: (Linear<2, 3>, ReLU) = Default::default();
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        to_onnx(&model, &[4, 2], file.path()).unwrap();
+        assert_eq!(
+            std::fs::read(file.path()).unwrap(),
+            to_onnx_bytes(&model, &[4, 2])
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Creates the sinusoidal positional encoding from [Attention Is All You Need](https://arxiv.org/abs/1706.03762),
+/// which is a constant, so it has no tape.
+///
+/// Row `pos` is the encoding of position `pos`, where:
+/// - `PE[pos, 2i] = sin(pos / 10000^(2i / DIM))`
+/// - `PE[pos, 2i + 1] = cos(pos / 10000^(2i / DIM))`
+///
+/// # Generics
+/// - `SEQ`: the number of positions.
+/// - `DIM`: the embedding size.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let pe: Tensor2D<3, 4> = sinusoidal_positional_encoding();
+/// assert_eq!(pe.data()[0], [0.0, 1.0, 0.0, 1.0]);
+///
+/// let x: Tensor2D<3, 4> = Tensor2D::zeros();
+/// let x = x + pe;
+/// ```
+pub fn sinusoidal_positional_encoding<const SEQ: usize, const DIM: usize>() -> Tensor2D<SEQ, DIM> {
+    let mut pe: Tensor2D<SEQ, DIM> = Tensor2D::zeros();
+    for (pos, row) in pe.mut_data().iter_mut().enumerate() {
+        for (d, v) in row.iter_mut().enumerate() {
+            let i = (d / 2) as f64;
+            let angle = pos as f64 / 10000f64.powf(2.0 * i / DIM as f64);
+            *v = if d % 2 == 0 { angle.sin() } else { angle.cos() } as f32;
+        }
+    }
+    pe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+
+    #[test]
+    fn test_sinusoidal_positional_encoding() {
+        let pe: Tensor2D<3, 4> = sinusoidal_positional_encoding();
+        assert_close(
+            pe.data(),
+            &[
+                [0.0, 1.0, 0.0, 1.0],
+                [0.84147098, 0.5403023, 0.00999983, 0.99995],
+                [0.9092974, -0.41614684, 0.01999867, 0.9998],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinusoidal_positional_encoding_odd_dim() {
+        let pe: Tensor2D<2, 3> = sinusoidal_positional_encoding();
+        let w = 1.0 / 10000f32.powf(2.0 / 3.0);
+        assert_close(
+            pe.data(),
+            &[[0.0, 1.0, 0.0], [1f32.sin(), 1f32.cos(), w.sin()]],
+        );
+    }
+
+    #[test]
+    fn test_sinusoidal_positional_encoding_has_no_tape() {
+        let pe: Tensor2D<4, 6> = sinusoidal_positional_encoding();
+        let _: &NoneTape = pe.tape();
+    }
+}
@@ -0,0 +1,221 @@
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// Parametric ReLU: `max(0, x) + slope * min(0, x)`, where [Self::slope] is learned,
+/// as introduced in [Delving Deep into Rectifiers](https://arxiv.org/abs/1502.01852).
+///
+/// There is one slope per feature (the last dimension of 1d & 2d inputs) or
+/// channel (the second dimension of 4d inputs). Use `PReLU<1>` on inputs with a single feature
+/// or channel for a single shared slope.
+///
+/// # Generics
+/// - `N`: the number of features or channels.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: PReLU<3> = Default::default();
+/// assert_eq!(model.slope.data(), &[0.25; 3]);
+/// let x = Tensor2D::new([[-4.0, 0.0, 4.0], [1.0, -2.0, -8.0]]);
+/// let y = model.forward(x);
+/// assert_eq!(y.data(), &[[-1.0, 0.0, 4.0], [1.0, -0.5, -2.0]]);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PReLU<const N: usize> {
+    /// The slope for negative inputs, shape (N, )
+    pub slope: Tensor1D<N, NoneTape>,
+}
+
+impl<const N: usize> Default for PReLU<N> {
+    /// Fills [Self::slope] with `0.25`, the same as pytorch.
+    fn default() -> Self {
+        Self {
+            slope: Tensor1D::new([0.25; N]),
+        }
+    }
+}
+
+impl<const N: usize> CanUpdateWithGradients for PReLU<N> {
+    /// Updates [Self::slope].
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.slope.update(grads);
+    }
+}
+
+impl<const N: usize> ResetParams for PReLU<N> {
+    /// Fills [Self::slope] with `0.25`.
+    fn reset_params<R: rand::Rng>(&mut self, _: &mut R) {
+        Cpu::fill(self.slope.mut_data(), &mut |v| *v = 0.25);
+    }
+}
+
+impl<const N: usize> CountParams for PReLU<N> {
+    /// `N` for [Self::slope].
+    fn num_params(&self) -> usize {
+        self.slope.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> SaveToNpz for PReLU<N> {
+    /// Saves [Self::slope] to `{pre}weight.npy`, the same name as pytorch, using [npz_fwrite()].
+    fn write<W>(&self, pre: &str, w: &mut ZipWriter<W>) -> ZipResult<()>
+    where
+        W: Write + Seek,
+    {
+        npz_fwrite(w, format!("{pre}weight.npy"), self.slope.data())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> LoadFromNpz for PReLU<N> {
+    /// Reads [Self::slope] from `{pre}weight.npy` using [npz_fread()].
+    fn read<R>(&mut self, pre: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: Read + Seek,
+    {
+        npz_fread(r, format!("{pre}weight.npy"), self.slope.mut_data())
+    }
+}
+
+impl<const N: usize> VisitParams for PReLU<N> {
+    /// Visits [Self::slope] as `{pre}weight`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.slope.visit_params(&format!("{pre}weight"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> SaveToSafetensors for PReLU<N> {
+    /// Saves [Self::slope] to `{pre}weight`.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.slope.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> LoadFromSafetensors for PReLU<N> {
+    /// Reads [Self::slope] from `{pre}weight`.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.slope.mut_data())
+    }
+}
+
+impl<const N: usize, H: Tape> Module<Tensor1D<N, H>> for PReLU<N> {
+    type Output = Tensor1D<N, H>;
+
+    /// Calls [prelu()].
+    fn forward(&self, x: Tensor1D<N, H>) -> Self::Output {
+        prelu(x, &self.slope)
+    }
+}
+
+impl<const B: usize, const N: usize, H: Tape> Module<Tensor2D<B, N, H>> for PReLU<N> {
+    type Output = Tensor2D<B, N, H>;
+
+    /// Calls [prelu_broadcast_rhs_first()].
+    fn forward(&self, x: Tensor2D<B, N, H>) -> Self::Output {
+        prelu_broadcast_rhs_first(x, &self.slope)
+    }
+}
+
+impl<const B: usize, const N: usize, const H: usize, const W: usize, T: Tape>
+    Module<Tensor4D<B, N, H, W, T>> for PReLU<N>
+{
+    type Output = Tensor4D<B, N, H, W, T>;
+
+    /// Calls [prelu_broadcast_rhs_channel()].
+    fn forward(&self, x: Tensor4D<B, N, H, W, T>) -> Self::Output {
+        prelu_broadcast_rhs_channel(x, &self.slope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    const X: [[f32; 3]; 2] = [[-1.5, 0.3, -0.2], [0.7, -2.0, 1.1]];
+    const V: [[f32; 3]; 2] = [[0.5, -1.0, 2.0], [1.5, 0.25, -0.75]];
+
+    /// `sum(prelu(x) * v)`, without a tape.
+    fn loss(x: [[f32; 3]; 2], slope: [f32; 3]) -> f32 {
+        let model = PReLU {
+            slope: Tensor1D::new(slope),
+        };
+        *mul(model.forward(Tensor2D::new(x)), &Tensor2D::new(V))
+            .sum()
+            .data()
+    }
+
+    #[test]
+    fn test_prelu_grads_match_finite_differences() {
+        let slope = [0.1, -0.3, 0.6];
+        let model = PReLU {
+            slope: Tensor1D::new(slope),
+        };
+        let x = Tensor2D::new(X);
+        let y = model.forward(x.trace());
+        let gradients = mul(y, &Tensor2D::new(V)).sum().backward();
+
+        const EPS: f32 = 1e-2;
+        let mut x_grad = [[0.0; 3]; 2];
+        for i in 0..2 {
+            for j in 0..3 {
+                let (mut xp, mut xm) = (X, X);
+                xp[i][j] += EPS;
+                xm[i][j] -= EPS;
+                x_grad[i][j] = (loss(xp, slope) - loss(xm, slope)) / (2.0 * EPS);
+            }
+        }
+        let mut slope_grad = [0.0; 3];
+        for (j, g) in slope_grad.iter_mut().enumerate() {
+            let (mut sp, mut sm) = (slope, slope);
+            sp[j] += EPS;
+            sm[j] -= EPS;
+            *g = (loss(X, sp) - loss(X, sm)) / (2.0 * EPS);
+        }
+
+        gradients.ref_gradient(&x).assert_close(&x_grad, 1e-3);
+        gradients
+            .ref_gradient(&model.slope)
+            .assert_close(&slope_grad, 1e-3);
+        // only the negative inputs contribute to the slope
+        gradients
+            .ref_gradient(&model.slope)
+            .assert_close(&[-1.5 * 0.5, -2.0 * 0.25, -0.2 * 2.0], 1e-6);
+    }
+
+    #[test]
+    fn test_prelu_update_changes_slope() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: (Linear<2, 3>, PReLU<3>) = Default::default();
+        model.reset_params(&mut rng);
+        assert_eq!(model.1.slope.data(), &[0.25; 3]);
+
+        let x: Tensor2D<4, 2> = Tensor2D::randn(&mut rng);
+        let gradients = model.forward(x.trace()).sum().backward();
+        let mut opt: Sgd<(Linear<2, 3>, PReLU<3>)> = Default::default();
+        opt.update(&mut model, gradients);
+        assert_ne!(model.1.slope.data(), &[0.25; 3]);
+    }
+
+    #[test]
+    fn test_prelu_4d_per_channel() {
+        let model = PReLU {
+            slope: Tensor1D::new([0.5, 0.0]),
+        };
+        let x: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[-1.0, 1.0]], [[-1.0, 1.0]]]]);
+        let y = model.forward(x);
+        assert_eq!(y.data(), &[[[[-0.5, 1.0]], [[0.0, 1.0]]]]);
+    }
+}
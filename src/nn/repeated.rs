@@ -1,6 +1,9 @@
 use super::*;
 use crate::prelude::CanUpdateWithGradients;
+#[cfg(feature = "std")]
 use std::io::{Read, Seek, Write};
+use std::string::String;
+#[cfg(feature = "std")]
 use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 /// Repeats `T` `N` times. This requires that `T`'s input is the same as it's output.
@@ -32,6 +35,27 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for Repeated<T, N> {
+    /// Serializes [Self::modules] as a sequence.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.modules.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for Repeated<T, N> {
+    /// Deserializes a sequence of exactly `N` modules.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let modules: Vec<T> = serde::Deserialize::deserialize(deserializer)?;
+        let len = modules.len();
+        let modules = modules
+            .try_into()
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("{N} modules").as_str()))?;
+        Ok(Self { modules })
+    }
+}
+
 impl<T: ResetParams, const N: usize> ResetParams for Repeated<T, N> {
     fn reset_params<R: rand::Rng>(&mut self, rng: &mut R) {
         for i in 0..N {
@@ -48,6 +72,13 @@ impl<T: CanUpdateWithGradients, const N: usize> CanUpdateWithGradients for Repea
     }
 }
 
+impl<T: CountParams, const N: usize> CountParams for Repeated<T, N> {
+    fn num_params(&self) -> usize {
+        self.modules.iter().map(|m| m.num_params()).sum()
+    }
+}
+
+#[cfg(feature = "std")]
 impl<T: SaveToNpz, const N: usize> SaveToNpz for Repeated<T, N> {
     /// Calls `SaveToNpz::write(self.modules[i], ...)` on each sub module. See [SaveToNpz].
     ///
@@ -62,6 +93,7 @@ impl<T: SaveToNpz, const N: usize> SaveToNpz for Repeated<T, N> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: LoadFromNpz, const N: usize> LoadFromNpz for Repeated<T, N> {
     /// Calls `LoadFromNpz::read(self.modules[i], ...)` on each sub module. See [LoadFromNpz].
     ///
@@ -79,6 +111,54 @@ impl<T: LoadFromNpz, const N: usize> LoadFromNpz for Repeated<T, N> {
     }
 }
 
+impl<T: VisitParams, const N: usize> VisitParams for Repeated<T, N> {
+    /// Calls `VisitParams::visit_params(self.modules[i], ...)` on each sub module,
+    /// with the same names as [SaveToNpz].
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, base: &str, f: &mut F) {
+        for (i, module) in self.modules.iter().enumerate() {
+            module.visit_params(&format!("{}{}.", base, i), f);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: SaveToSafetensors, const N: usize> SaveToSafetensors for Repeated<T, N> {
+    /// Calls `SaveToSafetensors::write_safetensors(self.modules[i], ...)` on each sub module,
+    /// with the same names as [SaveToNpz].
+    fn write_safetensors(&self, base: &str, w: &mut SafetensorsWriter) {
+        for (i, module) in self.modules.iter().enumerate() {
+            module.write_safetensors(&format!("{}{}.", base, i), w);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: LoadFromSafetensors, const N: usize> LoadFromSafetensors for Repeated<T, N> {
+    /// Calls `LoadFromSafetensors::read_safetensors(self.modules[i], ...)` on each sub module,
+    /// with the same names as [LoadFromNpz].
+    fn read_safetensors(
+        &mut self,
+        base: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        for (i, module) in self.modules.iter_mut().enumerate() {
+            module.read_safetensors(&format!("{}{}.", base, i), r)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ToOnnx, const N: usize> ToOnnx for Repeated<T, N> {
+    /// Adds the nodes of each sub module in order, with the same names as [SaveToNpz].
+    fn to_onnx(&self, base: &str, input: String, g: &mut OnnxGraph) -> String {
+        let mut x = input;
+        for (i, module) in self.modules.iter().enumerate() {
+            x = module.to_onnx(&format!("{}{}.", base, i), x, g);
+        }
+        x
+    }
+}
+
 impl<Input, T: Module<Input, Output = Input>, const N: usize> Module<Input> for Repeated<T, N> {
     type Output = T::Output;
     fn forward(&self, mut x: Input) -> Self::Output {
@@ -123,7 +203,7 @@ mod tests {
 
         for i in 0..5 {
             assert_ne!(m.modules[i].0.weight.data(), &[[0.0; 3]; 3]);
-            assert_ne!(m.modules[i].0.bias.data(), &[0.0; 3]);
+            assert_eq!(m.modules[i].0.bias.data(), &[0.0; 3]);
         }
     }
 
@@ -195,4 +275,27 @@ mod tests {
             );
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_repeated() {
+        type Model = Repeated<(Linear<3, 3>, Dropout, Checkpoint<(Linear<3, 3>, ReLU)>), 2>;
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut saved: Model = Default::default();
+        saved.reset_params(&mut rng);
+        saved.modules[1].1.p = 0.25;
+
+        let bytes = bincode::serialize(&saved).unwrap();
+        let loaded: Model = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.modules[1].1.p, 0.25);
+        let x: Tensor1D<3> = Tensor1D::randn(&mut rng);
+        assert_eq!(
+            loaded.forward(x.duplicate()).data(),
+            saved.forward(x).data()
+        );
+
+        assert!(bincode::deserialize::<Repeated<Linear<3, 3>, 3>>(&bytes).is_err());
+        let bytes = bincode::serialize(&Repeated::<Linear<3, 3>, 3>::default()).unwrap();
+        assert!(bincode::deserialize::<Repeated<Linear<3, 3>, 2>>(&bytes).is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use std::string::String;
 
 /// A residual connection around `F`: `F(x) + x`,
 /// as introduced in [Deep Residual Learning for Image Recognition](https://arxiv.org/abs/1512.03385).
@@ -15,6 +16,7 @@ use crate::prelude::*;
 /// assert_eq!(y.data(), &[-2.0, -1.0, 0.0, 2.0, 4.0]);
 /// ```
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Residual<F>(F);
 
 impl<F: CanUpdateWithGradients> CanUpdateWithGradients for Residual<F> {
@@ -31,6 +33,13 @@ impl<F: ResetParams> ResetParams for Residual<F> {
     }
 }
 
+impl<F: CountParams> CountParams for Residual<F> {
+    /// Pass through to `F`'s [CountParams].
+    fn num_params(&self) -> usize {
+        self.0.num_params()
+    }
+}
+
 impl<T, F> Module<T> for Residual<F>
 where
     T: Tensor<Dtype = f32>,
@@ -45,6 +54,7 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<F: SaveToNpz> SaveToNpz for Residual<F> {
     /// Pass through to `F`'s [SaveToNpz].
     fn write<W>(
@@ -60,6 +70,7 @@ impl<F: SaveToNpz> SaveToNpz for Residual<F> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<F: LoadFromNpz> LoadFromNpz for Residual<F> {
     /// Pass through to `F`'s [LoadFromNpz].
     fn read<R>(&mut self, filename_prefix: &str, r: &mut zip::ZipArchive<R>) -> Result<(), NpzError>
@@ -71,6 +82,41 @@ impl<F: LoadFromNpz> LoadFromNpz for Residual<F> {
     }
 }
 
+impl<F: VisitParams> VisitParams for Residual<F> {
+    /// Pass through to `F`'s [VisitParams].
+    fn visit_params<V: FnMut(&str, &dyn Param)>(&self, prefix: &str, f: &mut V) {
+        self.0.visit_params(prefix, f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: SaveToSafetensors> SaveToSafetensors for Residual<F> {
+    /// Pass through to `F`'s [SaveToSafetensors].
+    fn write_safetensors(&self, prefix: &str, w: &mut SafetensorsWriter) {
+        self.0.write_safetensors(prefix, w);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: LoadFromSafetensors> LoadFromSafetensors for Residual<F> {
+    /// Pass through to `F`'s [LoadFromSafetensors].
+    fn read_safetensors(
+        &mut self,
+        prefix: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        self.0.read_safetensors(prefix, r)
+    }
+}
+
+impl<F: ToOnnx> ToOnnx for Residual<F> {
+    /// Adds `F`'s nodes, and then an `Add` of `input` to the result.
+    fn to_onnx(&self, prefix: &str, input: String, g: &mut OnnxGraph) -> String {
+        let x = self.0.to_onnx(prefix, input.clone(), g);
+        g.add_node("Add", &[&x, &input], &[])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,7 +135,7 @@ mod tests {
 
         model.reset_params(&mut rng);
         assert_ne!(model.0.weight.data(), &[[0.0; 2]; 5]);
-        assert_ne!(model.0.bias.data(), &[0.0; 5]);
+        assert_eq!(model.0.bias.data(), &[0.0; 5]);
     }
 
     const W0: [[f32; 2]; 5] = [
@@ -192,7 +238,9 @@ mod tests {
     fn test_load_residual() {
         let mut rng = StdRng::seed_from_u64(0);
         let mut saved_model: Residual<Linear<5, 3>> = Default::default();
-        saved_model.reset_params(&mut rng);
+        saved_model
+            .0
+            .reset_params_with_bias(&mut rng, Init::Default, BiasInit::Uniform);
 
         let file = NamedTempFile::new().expect("failed to create tempfile");
         assert!(saved_model.save(file.path().to_str().unwrap()).is_ok());
@@ -0,0 +1,853 @@
+use crate::numpy::{Endian, NumpyShape, ReadNumbers, WriteNumbers};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
+/// Something that can be saved to a [.safetensors](https://github.com/huggingface/safetensors) file.
+///
+/// All [super::Module]s in nn implement SaveToSafetensors, using the same names as the equivalent
+/// pytorch `state_dict()`.
+pub trait SaveToSafetensors {
+    /// Save this object into the `.safetensors` file located at `path`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+    /// model.save_safetensors("model.safetensors")?;
+    /// ```
+    fn save_safetensors<P: AsRef<Path>>(&self, path: P) -> Result<(), SafetensorsError> {
+        let mut w = SafetensorsWriter::default();
+        self.write_safetensors("", &mut w);
+        w.save(path)
+    }
+
+    /// Adds all the tensors of this object to `w`, with names prefixed by `prefix`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let model: Linear<5, 10> = Default::default();
+    /// let mut w = SafetensorsWriter::default();
+    /// model.write_safetensors("0.", &mut w);
+    /// model.write_safetensors("1.", &mut w);
+    /// ```
+    /// Will add the tensors `0.weight`, `0.bias`, `1.weight`, and `1.bias`.
+    fn write_safetensors(&self, _prefix: &str, _w: &mut SafetensorsWriter) {}
+}
+
+/// Something that can be loaded from a [.safetensors](https://github.com/huggingface/safetensors) file,
+/// for example one saved from a pytorch `state_dict()`.
+///
+/// All [super::Module]s in nn implement LoadFromSafetensors, using the same names as the equivalent
+/// pytorch `state_dict()`. Every tensor is checked to exist, and have the same dtype and shape
+/// as the parameter it is loaded into.
+pub trait LoadFromSafetensors {
+    /// Loads data from the `.safetensors` file at `path`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let mut model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+    /// model.load_safetensors("model.safetensors")?;
+    /// ```
+    fn load_safetensors<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SafetensorsError> {
+        let r = SafetensorsReader::open(path)?;
+        self.read_safetensors("", &r)
+    }
+
+    /// Reads all the tensors of this object from `r`, with names prefixed by `prefix`.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let mut model: Linear<5, 10> = Default::default();
+    /// let r = SafetensorsReader::open("model.safetensors")?;
+    /// model.read_safetensors("0.", &r)?;
+    /// ```
+    /// Will read the tensors `0.weight` and `0.bias`.
+    fn read_safetensors(
+        &mut self,
+        _prefix: &str,
+        _r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        Ok(())
+    }
+}
+
+/// Error that can happen while loading or saving a `.safetensors` file.
+#[derive(Debug)]
+pub enum SafetensorsError {
+    /// Error from opening, reading, or writing a file.
+    Io(std::io::Error),
+
+    /// The file is not a valid `.safetensors` file.
+    InvalidHeader(String),
+
+    /// The file has no tensor with this name.
+    MissingTensor(String),
+
+    /// The tensor `name` is stored as `found`, but only `expected` is supported.
+    DtypeMismatch {
+        name: String,
+        expected: &'static str,
+        found: String,
+    },
+
+    /// The tensor `name` is stored with shape `found`, but the parameter has shape `expected`.
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+}
+
+impl std::fmt::Display for SafetensorsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::InvalidHeader(msg) => write!(f, "invalid safetensors header: {msg}"),
+            Self::MissingTensor(name) => write!(f, "missing tensor `{name}`"),
+            Self::DtypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(f, "tensor `{name}` has dtype {found}, expected {expected}"),
+            Self::ShapeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "tensor `{name}` has shape {found:?}, expected {expected:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SafetensorsError {}
+
+impl From<std::io::Error> for SafetensorsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The only dtype that dfdx reads and writes.
+const DTYPE: &str = "F32";
+
+/// Collects named tensors and writes them in the `.safetensors` format. See [SaveToSafetensors].
+#[derive(Debug, Default)]
+pub struct SafetensorsWriter {
+    tensors: Vec<(String, Vec<usize>, Vec<u8>)>,
+}
+
+impl SafetensorsWriter {
+    /// Adds `data` as a tensor named `name`, with the shape of `T`.
+    pub fn add<T: NumpyShape + WriteNumbers>(&mut self, name: String, data: &T) {
+        let mut bytes = Vec::new();
+        data.write_numbers(&mut bytes, Endian::Little)
+            .expect("writing to a Vec can't fail");
+        self.tensors.push((name, T::shape(), bytes));
+    }
+
+    /// Writes all the tensors added so far to `w`.
+    pub fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let mut header = String::from("{");
+        let mut offset = 0;
+        for (i, (name, shape, bytes)) in self.tensors.iter().enumerate() {
+            if i > 0 {
+                header.push(',');
+            }
+            let shape: Vec<String> = shape.iter().map(|s| s.to_string()).collect();
+            header.push_str(&format!(
+                "{}:{{\"dtype\":\"{DTYPE}\",\"shape\":[{}],\"data_offsets\":[{},{}]}}",
+                json_string(name),
+                shape.join(","),
+                offset,
+                offset + bytes.len(),
+            ));
+            offset += bytes.len();
+        }
+        header.push('}');
+
+        // the data is expected to be 8 byte aligned
+        while header.len() % 8 != 0 {
+            header.push(' ');
+        }
+
+        w.write_all(&(header.len() as u64).to_le_bytes())?;
+        w.write_all(header.as_bytes())?;
+        for (_, _, bytes) in self.tensors.iter() {
+            w.write_all(bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Writes all the tensors added so far to a new file at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SafetensorsError> {
+        let mut f = BufWriter::new(File::create(path)?);
+        self.write(&mut f)?;
+        f.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct TensorInfo {
+    dtype: String,
+    shape: Vec<usize>,
+    start: usize,
+    end: usize,
+}
+
+/// The contents of a `.safetensors` file. See [LoadFromSafetensors].
+#[derive(Debug)]
+pub struct SafetensorsReader {
+    tensors: HashMap<String, TensorInfo>,
+    data: Vec<u8>,
+}
+
+impl SafetensorsReader {
+    /// Reads the whole `.safetensors` file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SafetensorsError> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Parses the bytes of a `.safetensors` file.
+    pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Self, SafetensorsError> {
+        let invalid = |msg: &str| SafetensorsError::InvalidHeader(msg.into());
+
+        let header_len: [u8; 8] = bytes
+            .get(..8)
+            .ok_or_else(|| invalid("file is too small"))?
+            .try_into()
+            .unwrap();
+        let header_end = u64::from_le_bytes(header_len)
+            .checked_add(8)
+            .filter(|&end| end <= bytes.len() as u64)
+            .ok_or_else(|| invalid("header length is larger than the file"))?
+            as usize;
+        let header = std::str::from_utf8(&bytes[8..header_end])
+            .map_err(|_| invalid("header is not utf8"))?;
+
+        let mut tensors = HashMap::new();
+        let data_len = bytes.len() - header_end;
+        for (name, info) in Json::parse(header)?.into_object()? {
+            if name == "__metadata__" {
+                continue;
+            }
+            let mut info = info.into_object()?;
+            let mut field = |key: &str| match info.iter().position(|(k, _)| k == key) {
+                Some(i) => Ok(info.swap_remove(i).1),
+                None => Err(invalid(&format!("tensor `{name}` has no `{key}`"))),
+            };
+            let dtype = field("dtype")?.into_string()?;
+            let shape = field("shape")?.into_usizes()?;
+            let offsets = field("data_offsets")?.into_usizes()?;
+            let (start, end) = match offsets[..] {
+                [start, end] if start <= end && end <= data_len => (start, end),
+                _ => {
+                    return Err(invalid(&format!(
+                        "tensor `{name}` has invalid data_offsets"
+                    )))
+                }
+            };
+            tensors.insert(
+                name,
+                TensorInfo {
+                    dtype,
+                    shape,
+                    start,
+                    end,
+                },
+            );
+        }
+
+        bytes.drain(..header_end);
+        Ok(Self {
+            tensors,
+            data: bytes,
+        })
+    }
+
+    /// Returns the names of all the tensors in the file, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tensors.keys().map(|k| k.as_str())
+    }
+
+    /// Reads the tensor named `name` into `data`.
+    ///
+    /// Returns an error if there is no tensor named `name`, or if it is not an `F32` tensor with
+    /// the same shape as `T`.
+    pub fn read_into<T: NumpyShape + ReadNumbers>(
+        &self,
+        name: &str,
+        data: &mut T,
+    ) -> Result<(), SafetensorsError> {
+        let info = self
+            .tensors
+            .get(name)
+            .ok_or_else(|| SafetensorsError::MissingTensor(name.into()))?;
+        if info.dtype != DTYPE {
+            return Err(SafetensorsError::DtypeMismatch {
+                name: name.into(),
+                expected: DTYPE,
+                found: info.dtype.clone(),
+            });
+        }
+        if info.shape != T::shape() {
+            return Err(SafetensorsError::ShapeMismatch {
+                name: name.into(),
+                expected: T::shape(),
+                found: info.shape.clone(),
+            });
+        }
+        let numel: usize = info.shape.iter().product();
+        if info.end - info.start != numel * std::mem::size_of::<f32>() {
+            return Err(SafetensorsError::InvalidHeader(format!(
+                "tensor `{name}` has the wrong number of bytes for its shape"
+            )));
+        }
+        let mut bytes = &self.data[info.start..info.end];
+        data.read_numbers(&mut bytes, Endian::Little)?;
+        Ok(())
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Just enough JSON to read a `.safetensors` header.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(s: &str) -> Result<Self, SafetensorsError> {
+        let mut p = JsonParser {
+            bytes: s.as_bytes(),
+            i: 0,
+            depth: 0,
+        };
+        let value = p.value()?;
+        p.skip_whitespace();
+        match p.i == p.bytes.len() {
+            true => Ok(value),
+            false => Err(p.error("trailing characters")),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool => "a bool",
+            Self::Number(_) => "a number",
+            Self::String(_) => "a string",
+            Self::Array(_) => "an array",
+            Self::Object(_) => "an object",
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> SafetensorsError {
+        SafetensorsError::InvalidHeader(format!("expected {expected}, found {}", self.kind()))
+    }
+
+    fn into_object(self) -> Result<Vec<(String, Json)>, SafetensorsError> {
+        match self {
+            Self::Object(o) => Ok(o),
+            v => Err(v.mismatch("an object")),
+        }
+    }
+
+    fn into_string(self) -> Result<String, SafetensorsError> {
+        match self {
+            Self::String(s) => Ok(s),
+            v => Err(v.mismatch("a string")),
+        }
+    }
+
+    fn into_usizes(self) -> Result<Vec<usize>, SafetensorsError> {
+        match self {
+            Self::Array(a) => a
+                .into_iter()
+                .map(|v| match v {
+                    Self::Number(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+                    v => Err(v.mismatch("an unsigned integer")),
+                })
+                .collect(),
+            v => Err(v.mismatch("an array")),
+        }
+    }
+}
+
+/// How deeply arrays and objects can be nested. A `.safetensors` header is only 3 levels deep,
+/// so this is plenty, and keeps a malicious header from overflowing the stack.
+const MAX_JSON_DEPTH: usize = 64;
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    i: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, msg: &str) -> SafetensorsError {
+        SafetensorsError::InvalidHeader(format!("{msg} at byte {}", self.i))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.i), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.i += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.i).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.i += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), SafetensorsError> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected `{}`", c as char))),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, SafetensorsError> {
+        match self.bytes[self.i..].starts_with(word.as_bytes()) {
+            true => {
+                self.i += word.len();
+                Ok(value)
+            }
+            false => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, SafetensorsError> {
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b't') => self.literal("true", Json::Bool),
+            Some(b'f') => self.literal("false", Json::Bool),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, SafetensorsError>,
+    ) -> Result<Json, SafetensorsError> {
+        if self.depth == MAX_JSON_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, SafetensorsError> {
+        self.expect(b'{')?;
+        let mut items = Vec::new();
+        if !self.eat(b'}') {
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Err(self.error("expected a key"));
+                }
+                let key = self.string()?;
+                self.expect(b':')?;
+                items.push((key, self.value()?));
+                if !self.eat(b',') {
+                    break;
+                }
+            }
+            self.expect(b'}')?;
+        }
+        Ok(Json::Object(items))
+    }
+
+    fn array(&mut self) -> Result<Json, SafetensorsError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if !self.eat(b']') {
+            loop {
+                items.push(self.value()?);
+                if !self.eat(b',') {
+                    break;
+                }
+            }
+            self.expect(b']')?;
+        }
+        Ok(Json::Array(items))
+    }
+
+    fn number(&mut self) -> Result<Json, SafetensorsError> {
+        let start = self.i;
+        while matches!(
+            self.bytes.get(self.i),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.i += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.i])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, SafetensorsError> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            let c = *self
+                .bytes
+                .get(self.i)
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.i += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self
+                        .bytes
+                        .get(self.i)
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.i += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c => out.push(c),
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.error("string is not utf8"))
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, SafetensorsError> {
+        let mut code = self.hex4();
+        if let Some(high @ 0xD800..=0xDBFF) = code {
+            // the second half of a utf16 surrogate pair must follow
+            code = match self.bytes.get(self.i..self.i + 2) {
+                Some(b"\\u") => {
+                    self.i += 2;
+                    match self.hex4() {
+                        Some(low @ 0xDC00..=0xDFFF) => {
+                            Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+        }
+        code.and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let code = self
+            .bytes
+            .get(self.i..self.i + 4)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok());
+        self.i += 4;
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_safetensors_roundtrip_arrays() {
+        let mut w = SafetensorsWriter::default();
+        w.add("a".into(), &[[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        w.add("b.c".into(), &-1.5f32);
+        let mut bytes = Vec::new();
+        w.write(&mut bytes).unwrap();
+
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+        assert_eq!(bytes.len(), 8 + header_len + 7 * 4);
+
+        let r = SafetensorsReader::from_bytes(bytes).unwrap();
+        let mut names: Vec<&str> = r.names().collect();
+        names.sort_unstable();
+        assert_eq!(names, ["a", "b.c"]);
+
+        let mut a = [[0.0f32; 3]; 2];
+        r.read_into("a", &mut a).unwrap();
+        assert_eq!(a, [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let mut c = 0.0f32;
+        r.read_into("b.c", &mut c).unwrap();
+        assert_eq!(c, -1.5);
+    }
+
+    #[test]
+    fn test_safetensors_errors() {
+        let mut w = SafetensorsWriter::default();
+        w.add("a".into(), &[1.0f32, 2.0, 3.0]);
+        let mut bytes = Vec::new();
+        w.write(&mut bytes).unwrap();
+        let r = SafetensorsReader::from_bytes(bytes).unwrap();
+
+        let mut wrong_shape = [[0.0f32; 3]; 1];
+        let err = r.read_into("a", &mut wrong_shape).unwrap_err();
+        assert_eq!(err.to_string(), "tensor `a` has shape [3], expected [1, 3]");
+        assert!(matches!(err, SafetensorsError::ShapeMismatch { .. }));
+
+        let mut data = [0.0f32; 3];
+        let err = r.read_into("b", &mut data).unwrap_err();
+        assert_eq!(err.to_string(), "missing tensor `b`");
+        assert_eq!(data, [0.0; 3]);
+    }
+
+    #[test]
+    fn test_safetensors_parse_pytorch_header() {
+        // a header like the one written by the python `safetensors` library
+        let header = br#"{"__metadata__":{"format":"pt"},"bias":{"dtype":"F32","shape":[2],"data_offsets":[0,8]},"step":{"dtype":"I64","shape":[],"data_offsets":[8,16]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend(1.0f32.to_le_bytes());
+        bytes.extend(2.0f32.to_le_bytes());
+        bytes.extend(7i64.to_le_bytes());
+        let r = SafetensorsReader::from_bytes(bytes).unwrap();
+
+        let mut bias = [0.0f32; 2];
+        r.read_into("bias", &mut bias).unwrap();
+        assert_eq!(bias, [1.0, 2.0]);
+
+        let mut step = 0.0f32;
+        let err = r.read_into("step", &mut step).unwrap_err();
+        assert_eq!(err.to_string(), "tensor `step` has dtype I64, expected F32");
+    }
+
+    #[test]
+    fn test_safetensors_invalid_header() {
+        let header = br#"{"a":{"dtype":"F32","shape":[2],"data_offsets":[0,80]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend([0; 8]);
+        assert!(matches!(
+            SafetensorsReader::from_bytes(bytes),
+            Err(SafetensorsError::InvalidHeader(_))
+        ));
+
+        assert!(matches!(
+            SafetensorsReader::from_bytes(vec![255; 16]),
+            Err(SafetensorsError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_safetensors_deeply_nested_header() {
+        let mut header = br#"{"a":"#.to_vec();
+        header.extend([b'['; 100_000]);
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&header);
+        let err = SafetensorsReader::from_bytes(bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid safetensors header: too deeply nested at byte 68"
+        );
+
+        let nested = "[".repeat(MAX_JSON_DEPTH) + &"]".repeat(MAX_JSON_DEPTH);
+        assert!(Json::parse(&nested).is_ok());
+        let nested = "[".repeat(MAX_JSON_DEPTH + 1) + &"]".repeat(MAX_JSON_DEPTH + 1);
+        assert!(Json::parse(&nested).is_err());
+    }
+
+    #[test]
+    fn test_safetensors_json_strings() {
+        let name = "a \"quoted\" \\ name\n";
+        let parsed = Json::parse(&json_string(name))
+            .unwrap()
+            .into_string()
+            .unwrap();
+        assert_eq!(parsed, name);
+        let parsed = Json::parse(r#""\u00e9\ud83d\ude00""#)
+            .unwrap()
+            .into_string()
+            .unwrap();
+        assert_eq!(parsed, "\u{e9}\u{1f600}");
+    }
+
+    #[test]
+    fn test_safetensors_file() {
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let mut w = SafetensorsWriter::default();
+        w.add("x".into(), &[[0.5f32; 2]; 2]);
+        w.save(file.path()).unwrap();
+        let mut x = [[0.0f32; 2]; 2];
+        SafetensorsReader::open(file.path())
+            .unwrap()
+            .read_into("x", &mut x)
+            .unwrap();
+        assert_eq!(x, [[0.5; 2]; 2]);
+    }
+
+    type Model = (
+        Linear<3, 4>,
+        ReLU,
+        LayerNorm1D<4>,
+        Residual<Linear<4, 4>>,
+        Repeated<(Linear<4, 4>, Tanh), 2>,
+        Linear<4, 2>,
+    );
+
+    #[test]
+    fn test_safetensors_model_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut saved: Model = Default::default();
+        saved.reset_params(&mut rng);
+        Cpu::fill(saved.2.beta.mut_data(), &mut |v| *v = 0.5);
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        saved.save_safetensors(file.path()).unwrap();
+
+        let r = SafetensorsReader::open(file.path()).unwrap();
+        let mut names: Vec<&str> = r.names().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            [
+                "0.bias",
+                "0.weight",
+                "2.bias",
+                "2.weight",
+                "3.bias",
+                "3.weight",
+                "4.0.0.bias",
+                "4.0.0.weight",
+                "4.1.0.bias",
+                "4.1.0.weight",
+                "5.bias",
+                "5.weight",
+            ]
+        );
+
+        let mut loaded: Model = Default::default();
+        loaded.load_safetensors(file.path()).unwrap();
+        assert_eq!(loaded.0.weight.data(), saved.0.weight.data());
+        assert_eq!(loaded.2.beta.data(), &[0.5; 4]);
+        assert_eq!(
+            loaded.4.modules[1].0.weight.data(),
+            saved.4.modules[1].0.weight.data()
+        );
+        assert_eq!(loaded.5.weight.data(), saved.5.weight.data());
+
+        let x: Tensor1D<3> = Tensor1D::randn(&mut rng);
+        assert_eq!(
+            loaded.forward(x.duplicate()).data(),
+            saved.forward(x).data()
+        );
+    }
+
+    #[test]
+    fn test_safetensors_model_errors() {
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let saved: (Linear<3, 4>, Linear<4, 2>) = Default::default();
+        saved.save_safetensors(file.path()).unwrap();
+
+        let mut wrong_shape: (Linear<3, 4>, Linear<4, 3>) = Default::default();
+        let err = wrong_shape.load_safetensors(file.path()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "tensor `1.weight` has shape [2, 4], expected [3, 4]"
+        );
+
+        let mut missing: (Linear<3, 4>, Linear<4, 2>, Linear<2, 2>) = Default::default();
+        let err = missing.load_safetensors(file.path()).unwrap_err();
+        assert_eq!(err.to_string(), "missing tensor `2.weight`");
+    }
+
+    #[test]
+    fn test_safetensors_matches_visit_params() {
+        type Model = (
+            Frozen<Linear<2, 4>>,
+            PReLU<4>,
+            MultiHeadAttention<4, 2>,
+            UnbiasedLinear<4, 3>,
+        );
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut saved: Model = Default::default();
+        saved.reset_params(&mut rng);
+        let mut w = SafetensorsWriter::default();
+        saved.write_safetensors("", &mut w);
+        let mut bytes = Vec::new();
+        w.write(&mut bytes).unwrap();
+        let r = SafetensorsReader::from_bytes(bytes).unwrap();
+
+        let mut visited = Vec::new();
+        saved.visit_params("", &mut |name, p| {
+            let info = &r.tensors[name];
+            assert_eq!(info.dtype, "F32");
+            assert_eq!(info.shape, p.param_shape());
+            let data: Vec<f32> = r.data[info.start..info.end]
+                .chunks(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            assert_eq!(data, p.param_data());
+            visited.push(name.to_string());
+        });
+        let mut names: Vec<&str> = r.names().collect();
+        names.sort_unstable();
+        visited.sort_unstable();
+        assert_eq!(names, visited);
+        assert_eq!(visited.len(), 2 + 1 + 8 + 1);
+
+        let mut loaded: Model = Default::default();
+        loaded.read_safetensors("", &r).unwrap();
+        assert_eq!(loaded.2.w_v.weight.data(), saved.2.w_v.weight.data());
+        assert_eq!(loaded.3.weight.data(), saved.3.weight.data());
+    }
+}
@@ -17,6 +17,7 @@ use crate::prelude::*;
 /// let _: (Tensor1D<3>, Tensor1D<7>) = model.forward(Tensor1D::<5>::zeros());
 /// ```
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SplitInto<T>(T);
 
 impl<T: CanUpdateWithGradients> CanUpdateWithGradients for SplitInto<T> {
@@ -31,6 +32,13 @@ impl<T: ResetParams> ResetParams for SplitInto<T> {
     }
 }
 
+impl<T: CountParams> CountParams for SplitInto<T> {
+    fn num_params(&self) -> usize {
+        self.0.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
 impl<T: SaveToNpz> SaveToNpz for SplitInto<T> {
     fn write<W>(&self, p: &str, w: &mut zip::ZipWriter<W>) -> zip::result::ZipResult<()>
     where
@@ -40,6 +48,7 @@ impl<T: SaveToNpz> SaveToNpz for SplitInto<T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: LoadFromNpz> LoadFromNpz for SplitInto<T> {
     fn read<R>(&mut self, p: &str, r: &mut zip::ZipArchive<R>) -> Result<(), NpzError>
     where
@@ -49,6 +58,26 @@ impl<T: LoadFromNpz> LoadFromNpz for SplitInto<T> {
     }
 }
 
+impl<T: VisitParams> VisitParams for SplitInto<T> {
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, p: &str, f: &mut F) {
+        self.0.visit_params(p, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: SaveToSafetensors> SaveToSafetensors for SplitInto<T> {
+    fn write_safetensors(&self, p: &str, w: &mut SafetensorsWriter) {
+        self.0.write_safetensors(p, w)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: LoadFromSafetensors> LoadFromSafetensors for SplitInto<T> {
+    fn read_safetensors(&mut self, p: &str, r: &SafetensorsReader) -> Result<(), SafetensorsError> {
+        self.0.read_safetensors(p, r)
+    }
+}
+
 macro_rules! tuple_impls {
     ([$($heads:ident),+] $tail:ident) => {
 impl<
@@ -0,0 +1,338 @@
+use crate::prelude::*;
+use rand::Rng;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+use std::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// An encoder [Linear] and a decoder that share one weight matrix, with `M` in between:
+/// `decoder(M(encoder(x)))`. The decoder multiplies by the transpose of the encoder's weight,
+/// and has its own bias [Self::decoder_bias].
+///
+/// Since there is only one weight tensor, the gradients from both use-sites accumulate into
+/// a single gradient, an optimizer updates it once, and the encoder & decoder can never drift apart.
+/// This is the weight tying used by autoencoders and language models.
+///
+/// # Generics
+/// - `I` The "input" & "output" size of vectors & matrices.
+/// - `O` The size of the encoded vectors & matrices that `M` acts on.
+/// - `M` The module applied between the encoder and decoder, e.g. an activation.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: TiedLinear<5, 2, ReLU> = Default::default();
+/// assert_eq!(model.encoder.weight.data(), &[[0.0; 5]; 2]);
+/// assert_eq!(model.decoder_bias.data(), &[0.0; 5]);
+/// let x: Tensor1D<5> = Default::default();
+/// let y: Tensor1D<5> = model.forward(x);
+/// assert_eq!(y.data(), &[0.0; 5]);
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TiedLinear<const I: usize, const O: usize, M> {
+    /// The encoder, whose weight (shape (O, I)) is also used transposed by the decoder.
+    pub encoder: Linear<I, O>,
+
+    /// The module applied to the output of the encoder.
+    pub inner: M,
+
+    /// Bias vector of the decoder, shape (I, )
+    pub decoder_bias: Tensor1D<I, NoneTape>,
+}
+
+impl<const I: usize, const O: usize, M: CanUpdateWithGradients> CanUpdateWithGradients
+    for TiedLinear<I, O, M>
+{
+    /// Updates the shared weight once, with the gradient of both the encoder & decoder.
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.encoder.update(grads);
+        self.inner.update(grads);
+        self.decoder_bias.update(grads);
+    }
+}
+
+impl<const I: usize, const O: usize, M: ResetParams> ResetParams for TiedLinear<I, O, M> {
+    /// Resets [Self::encoder] & [Self::inner], and fills [Self::decoder_bias] with zeros.
+    fn reset_params<R: Rng>(&mut self, rng: &mut R) {
+        self.encoder.reset_params(rng);
+        self.inner.reset_params(rng);
+        Cpu::fill(self.decoder_bias.mut_data(), &mut |v| *v = 0.0);
+    }
+}
+
+impl<const I: usize, const O: usize, M: CountParams> CountParams for TiedLinear<I, O, M> {
+    /// `I * O + O` for [Self::encoder], plus `I` for [Self::decoder_bias]. The shared weight
+    /// is only counted once.
+    fn num_params(&self) -> usize {
+        self.encoder.num_params() + self.inner.num_params() + self.decoder_bias.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize, M: SaveToNpz> SaveToNpz for TiedLinear<I, O, M> {
+    /// Saves [Self::encoder] to `{pre}encoder.`, [Self::inner] to `{pre}inner.`, and
+    /// [Self::decoder_bias] to `{pre}decoder_bias.npy`. The shared weight is only saved once.
+    fn write<W>(&self, pre: &str, w: &mut ZipWriter<W>) -> ZipResult<()>
+    where
+        W: Write + Seek,
+    {
+        self.encoder.write(&format!("{pre}encoder."), w)?;
+        self.inner.write(&format!("{pre}inner."), w)?;
+        npz_fwrite(
+            w,
+            format!("{pre}decoder_bias.npy"),
+            self.decoder_bias.data(),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize, M: LoadFromNpz> LoadFromNpz for TiedLinear<I, O, M> {
+    /// Reads [Self::encoder] from `{pre}encoder.`, [Self::inner] from `{pre}inner.`, and
+    /// [Self::decoder_bias] from `{pre}decoder_bias.npy`.
+    fn read<R>(&mut self, pre: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: Read + Seek,
+    {
+        self.encoder.read(&format!("{pre}encoder."), r)?;
+        self.inner.read(&format!("{pre}inner."), r)?;
+        npz_fread(
+            r,
+            format!("{pre}decoder_bias.npy"),
+            self.decoder_bias.mut_data(),
+        )?;
+        Ok(())
+    }
+}
+
+impl<const I: usize, const O: usize, M: VisitParams> VisitParams for TiedLinear<I, O, M> {
+    /// Visits [Self::encoder] as `{pre}encoder.`, [Self::inner] as `{pre}inner.`, and
+    /// [Self::decoder_bias] as `{pre}decoder_bias`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.encoder.visit_params(&format!("{pre}encoder."), f);
+        self.inner.visit_params(&format!("{pre}inner."), f);
+        self.decoder_bias
+            .visit_params(&format!("{pre}decoder_bias"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize, M: SaveToSafetensors> SaveToSafetensors
+    for TiedLinear<I, O, M>
+{
+    /// Saves [Self::encoder] to `{pre}encoder.`, [Self::inner] to `{pre}inner.`, and
+    /// [Self::decoder_bias] to `{pre}decoder_bias`.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        self.encoder.write_safetensors(&format!("{pre}encoder."), w);
+        self.inner.write_safetensors(&format!("{pre}inner."), w);
+        w.add(format!("{pre}decoder_bias"), self.decoder_bias.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize, M: LoadFromSafetensors> LoadFromSafetensors
+    for TiedLinear<I, O, M>
+{
+    /// Reads [Self::encoder] from `{pre}encoder.`, [Self::inner] from `{pre}inner.`, and
+    /// [Self::decoder_bias] from `{pre}decoder_bias`.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        self.encoder
+            .read_safetensors(&format!("{pre}encoder."), r)?;
+        self.inner.read_safetensors(&format!("{pre}inner."), r)?;
+        r.read_into(&format!("{pre}decoder_bias"), self.decoder_bias.mut_data())?;
+        Ok(())
+    }
+}
+
+impl<const I: usize, const O: usize, M: ToOnnx> ToOnnx for TiedLinear<I, O, M> {
+    /// Adds [Self::encoder]'s & [Self::inner]'s nodes, then a `MatMul` with the shared weight
+    /// as `{pre}decoder_weight`, and an `Add` with [Self::decoder_bias] as `{pre}decoder_bias`.
+    fn to_onnx(&self, pre: &str, input: String, g: &mut OnnxGraph) -> String {
+        let x = self.encoder.to_onnx(&format!("{pre}encoder."), input, g);
+        let x = self.inner.to_onnx(&format!("{pre}inner."), x, g);
+        let w: Vec<f32> = self
+            .encoder
+            .weight
+            .data()
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        let weight = g.add_initializer(format!("{pre}decoder_weight"), &[O, I], &w);
+        let bias = g.add_initializer(format!("{pre}decoder_bias"), &[I], self.decoder_bias.data());
+        let x = g.add_node("MatMul", &[&x, &weight], &[]);
+        g.add_node("Add", &[&x, &bias], &[])
+    }
+}
+
+impl<const I: usize, const O: usize, M, H: Tape> Module<Tensor1D<I, H>> for TiedLinear<I, O, M>
+where
+    M: Module<Tensor1D<O, H>, Output = Tensor1D<O, H>>,
+{
+    type Output = Tensor1D<I, H>;
+
+    /// 1d forward using [Linear], then `M`, then [vecmat_mul()] with the shared weight and [add()].
+    fn forward(&self, x: Tensor1D<I, H>) -> Self::Output {
+        let x = self.inner.forward(self.encoder.forward(x));
+        add(vecmat_mul(x, &self.encoder.weight), &self.decoder_bias)
+    }
+}
+
+impl<const B: usize, const I: usize, const O: usize, M, H: Tape> Module<Tensor2D<B, I, H>>
+    for TiedLinear<I, O, M>
+where
+    M: Module<Tensor2D<B, O, H>, Output = Tensor2D<B, O, H>>,
+{
+    type Output = Tensor2D<B, I, H>;
+
+    /// Batched 2d forward using [Linear], then `M`, then [matmul()] with the shared weight and
+    /// [add_broadcast_rhs_first()].
+    fn forward(&self, x: Tensor2D<B, I, H>) -> Self::Output {
+        let x = self.inner.forward(self.encoder.forward(x));
+        add_broadcast_rhs_first(matmul(x, &self.encoder.weight), &self.decoder_bias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    fn transpose<const M: usize, const N: usize>(a: &[[f32; N]; M]) -> [[f32; M]; N] {
+        let mut b = [[0.0; M]; N];
+        for (i, row) in a.iter().enumerate() {
+            for (j, v) in row.iter().enumerate() {
+                b[j][i] = *v;
+            }
+        }
+        b
+    }
+
+    /// Builds an untied encoder & decoder with the same values as `model`.
+    fn untie(model: &TiedLinear<4, 3, Tanh>) -> (Linear<4, 3>, Tanh, Linear<3, 4>) {
+        let decoder = Linear {
+            weight: Tensor2D::new(transpose(model.encoder.weight.data())),
+            bias: model.decoder_bias.clone(),
+        };
+        (model.encoder.clone(), Tanh, decoder)
+    }
+
+    #[test]
+    fn test_tied_forward_matches_untied() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: TiedLinear<4, 3, Tanh> = Default::default();
+        model.reset_params(&mut rng);
+        model
+            .decoder_bias
+            .randomize(&mut rng, &rand_distr::StandardNormal);
+        let untied = untie(&model);
+
+        let x: Tensor1D<4> = Tensor1D::randn(&mut rng);
+        assert_close(model.forward(x.clone()).data(), untied.forward(x).data());
+
+        let x: Tensor2D<2, 4> = Tensor2D::randn(&mut rng);
+        assert_close(model.forward(x.clone()).data(), untied.forward(x).data());
+    }
+
+    #[test]
+    fn test_tied_gradients_accumulate() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut model: TiedLinear<4, 3, Tanh> = Default::default();
+        model.reset_params(&mut rng);
+        let untied = untie(&model);
+
+        let x: Tensor2D<2, 4> = Tensor2D::randn(&mut rng);
+        let g = model.forward(x.trace()).square().mean().backward();
+        let e = untied.forward(x.trace()).square().mean().backward();
+
+        let enc = e.ref_gradient(&untied.0.weight);
+        let dec = transpose(e.ref_gradient(&untied.2.weight));
+        let mut expected = [[0.0; 4]; 3];
+        for i in 0..3 {
+            for j in 0..4 {
+                expected[i][j] = enc[i][j] + dec[i][j];
+            }
+        }
+        assert_close(g.ref_gradient(&model.encoder.weight), &expected);
+        assert_close(
+            g.ref_gradient(&model.decoder_bias),
+            e.ref_gradient(&untied.2.bias),
+        );
+    }
+
+    #[test]
+    fn test_tied_weights_stay_tied_while_training() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut model: TiedLinear<4, 3, Tanh> = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor2D<8, 4> = Tensor2D::randn(&mut rng);
+
+        let mut opt = Sgd::new(SgdConfig {
+            lr: 1e-1,
+            momentum: None,
+            gradient_noise: None,
+        });
+        let mut prev_loss = f32::INFINITY;
+        for _ in 0..5 {
+            let weight = *model.encoder.weight.data();
+            let y = model.forward(x.trace());
+            let loss = mse_loss(y, &x);
+            let loss_value = *loss.data();
+            let gradients = loss.backward();
+            let grad = *gradients.ref_gradient(&model.encoder.weight);
+            opt.update(&mut model, gradients);
+
+            // a single update of `lr * (encoder grad + decoder grad)`
+            let mut expected = weight;
+            for (e, g) in expected.iter_mut().flatten().zip(grad.iter().flatten()) {
+                *e -= 1e-1 * g;
+            }
+            assert_close(model.encoder.weight.data(), &expected);
+
+            // the decoder always uses the current encoder weight
+            let x0: Tensor1D<4> = Tensor1D::new(x.data()[0]);
+            assert_close(
+                model.forward(x0.clone()).data(),
+                untie(&model).forward(x0).data(),
+            );
+
+            assert!(loss_value < prev_loss);
+            prev_loss = loss_value;
+        }
+    }
+
+    #[test]
+    fn test_tied_num_params() {
+        let model: TiedLinear<4, 3, Linear<3, 3>> = Default::default();
+        assert_eq!(model.num_params(), (4 * 3 + 3) + (3 * 3 + 3) + 4);
+    }
+
+    #[test]
+    fn test_tied_save_load() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut saved: TiedLinear<4, 3, Tanh> = Default::default();
+        saved.reset_params(&mut rng);
+        saved
+            .decoder_bias
+            .randomize(&mut rng, &rand_distr::StandardNormal);
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let path = file.path().to_str().unwrap();
+        saved.save(path).expect("failed to save model");
+
+        let mut loaded: TiedLinear<4, 3, Tanh> = Default::default();
+        loaded.load(path).expect("failed to load model");
+        assert_eq!(loaded.encoder.weight.data(), saved.encoder.weight.data());
+        assert_eq!(loaded.encoder.bias.data(), saved.encoder.bias.data());
+        assert_eq!(loaded.decoder_bias.data(), saved.decoder_bias.data());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+use rand_distr::Uniform;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+use std::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// A linear transformation of the form `weight * x`, where `weight` is a matrix, and `x` is a vector or matrix.
+/// This is the same as [Linear] without a bias.
+///
+/// # Generics
+/// - `I` The "input" size of vectors & matrices.
+/// - `O` The "output" size of vectors & matrices.
+///
+/// # Examples
+/// `UnbiasedLinear<5, 2>` can act on vectors with 5 elements, and results in vectors with 2 elements.
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: UnbiasedLinear<5, 2> = Default::default();
+/// assert_eq!(model.weight.data(), &[[0.0; 5]; 2]);
+/// let x: Tensor1D<5> = Default::default();
+/// let y: Tensor1D<2> = model.forward(x);
+/// assert_eq!(y.data(), &[0.0; 2]);
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnbiasedLinear<const I: usize, const O: usize> {
+    /// Transposed weight matrix, shape (O, I)
+    pub weight: Tensor2D<O, I, NoneTape>,
+}
+
+impl<const I: usize, const O: usize> CanUpdateWithGradients for UnbiasedLinear<I, O> {
+    fn update<G: GradientProvider>(&mut self, grads: &mut G) {
+        self.weight.update(grads);
+    }
+}
+
+impl<const I: usize, const O: usize> ResetParams for UnbiasedLinear<I, O> {
+    /// Initializes [Self::weight] from a [Uniform] distribution
+    /// between [-1 / sqrt(I), 1 / sqrt(I)], the same as [Linear].
+    ///
+    /// This uses [Randomize::randomize()] to set the values of the tensor.
+    fn reset_params<R: Rng>(&mut self, rng: &mut R) {
+        let bound: f32 = 1.0 / (I as f32).sqrt();
+        let dist = Uniform::new(-bound, bound);
+        self.weight.randomize(rng, &dist);
+    }
+}
+
+impl<const I: usize, const O: usize> ResetParamsWith for UnbiasedLinear<I, O> {
+    /// Initializes [Self::weight] with `init`, using `I` as the fan-in and `O` as the fan-out.
+    fn reset_params_with<R: Rng>(&mut self, rng: &mut R, init: Init) {
+        init.init(&mut self.weight, rng, I, O);
+    }
+}
+
+impl<const I: usize, const O: usize> CountParams for UnbiasedLinear<I, O> {
+    /// `I * O` for [Self::weight].
+    fn num_params(&self) -> usize {
+        self.weight.num_params()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> SaveToNpz for UnbiasedLinear<I, O> {
+    /// Saves [Self::weight] to `{pre}weight.npy` using [npz_fwrite()].
+    ///
+    /// No `{pre}bias.npy` is written, so loading this into a [Linear] fails, instead of
+    /// silently using a bias of zero.
+    fn write<W>(&self, pre: &str, w: &mut ZipWriter<W>) -> ZipResult<()>
+    where
+        W: Write + Seek,
+    {
+        npz_fwrite(w, format!("{pre}weight.npy"), self.weight.data())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> LoadFromNpz for UnbiasedLinear<I, O> {
+    /// Reads [Self::weight] from `{pre}weight.npy` using [npz_fread()]. Returns
+    /// an error if the shape of the saved weight is not `(O, I)`.
+    ///
+    /// Any `{pre}bias.npy` is ignored, so the weight of a [Linear] can be loaded too.
+    fn read<R>(&mut self, pre: &str, r: &mut ZipArchive<R>) -> Result<(), NpzError>
+    where
+        R: Read + Seek,
+    {
+        npz_fread(r, format!("{pre}weight.npy"), self.weight.mut_data())
+    }
+}
+
+impl<const I: usize, const O: usize> VisitParams for UnbiasedLinear<I, O> {
+    /// Visits [Self::weight] as `{pre}weight`.
+    fn visit_params<F: FnMut(&str, &dyn Param)>(&self, pre: &str, f: &mut F) {
+        self.weight.visit_params(&format!("{pre}weight"), f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> SaveToSafetensors for UnbiasedLinear<I, O> {
+    /// Saves [Self::weight] to `{pre}weight`.
+    fn write_safetensors(&self, pre: &str, w: &mut SafetensorsWriter) {
+        w.add(format!("{pre}weight"), self.weight.data());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const O: usize> LoadFromSafetensors for UnbiasedLinear<I, O> {
+    /// Reads [Self::weight] from `{pre}weight`, which is the same as pytorch's
+    /// `nn.Linear(bias=False).weight`.
+    fn read_safetensors(
+        &mut self,
+        pre: &str,
+        r: &SafetensorsReader,
+    ) -> Result<(), SafetensorsError> {
+        r.read_into(&format!("{pre}weight"), self.weight.mut_data())
+    }
+}
+
+impl<const I: usize, const O: usize> ToOnnx for UnbiasedLinear<I, O> {
+    /// Adds a `MatMul` with [Self::weight] transposed as `{pre}weight`.
+    fn to_onnx(&self, pre: &str, input: String, g: &mut OnnxGraph) -> String {
+        let w = self.weight.data();
+        let w_t: Vec<f32> = (0..I).flat_map(|i| (0..O).map(move |o| w[o][i])).collect();
+        let weight = g.add_initializer(format!("{pre}weight"), &[I, O], &w_t);
+        g.add_node("MatMul", &[&input, &weight], &[])
+    }
+}
+
+impl<const I: usize, const O: usize, H: Tape> Module<Tensor1D<I, H>> for UnbiasedLinear<I, O> {
+    type Output = Tensor1D<O, H>;
+
+    /// 1d forward using [vecmat_mul_transpose()].
+    fn forward(&self, x: Tensor1D<I, H>) -> Self::Output {
+        vecmat_mul_transpose(x, &self.weight)
+    }
+}
+
+impl<const B: usize, const I: usize, const O: usize, H: Tape> Module<Tensor2D<B, I, H>>
+    for UnbiasedLinear<I, O>
+{
+    type Output = Tensor2D<B, O, H>;
+
+    /// Batched 2d forward using [matmul_transpose()].
+    fn forward(&self, x: Tensor2D<B, I, H>) -> Self::Output {
+        matmul_transpose(x, &self.weight)
+    }
+}
+
+impl<const B: usize, const S: usize, const I: usize, const O: usize, H: Tape>
+    Module<Tensor3D<B, S, I, H>> for UnbiasedLinear<I, O>
+{
+    type Output = Tensor3D<B, S, O, H>;
+
+    /// Batched 3d forward using [batch_matmul_transpose()].
+    fn forward(&self, x: Tensor3D<B, S, I, H>) -> Self::Output {
+        batch_matmul_transpose(x, &self.weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    const W: [[f32; 5]; 2] = [
+        [-0.3458893, -0.30371523, -0.3712057, 0.14303583, -0.0268966],
+        [0.11733949, 0.14059687, -0.10670426, -0.09373143, 0.18974298],
+    ];
+
+    #[test]
+    fn test_unbiased_forward_matches_linear() {
+        let model: UnbiasedLinear<5, 2> = UnbiasedLinear {
+            weight: Tensor2D::new(W),
+        };
+        let linear: Linear<5, 2> = Linear {
+            weight: Tensor2D::new(W),
+            bias: Tensor1D::zeros(),
+        };
+
+        let x = Tensor1D::new([-0.8808001, 2.4185333, 2.2478335, 0.0565211, 2.031299]);
+        let y = model.forward(x.trace());
+        assert_close(y.data(), &[-0.93430865 - 0.3765365, 0.08624211 + 0.290717]);
+        assert_close(y.data(), linear.forward(x.duplicate()).data());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<3, 5> = Tensor2D::randn(&mut rng);
+        let y = model.forward(x.trace());
+        assert_close(y.data(), linear.forward(x.duplicate()).data());
+        let gradients = y.square().mean().backward();
+        let expected = linear.forward(x.trace()).square().mean().backward();
+        assert_close(
+            gradients.ref_gradient(&model.weight),
+            expected.ref_gradient(&linear.weight),
+        );
+        assert_close(gradients.ref_gradient(&x), expected.ref_gradient(&x));
+
+        let x: Tensor3D<2, 3, 5> = Tensor3D::randn(&mut rng);
+        let y = model.forward(x.trace());
+        assert_close(y.data(), linear.forward(x.duplicate()).data());
+    }
+
+    #[test]
+    fn test_unbiased_reset_and_update() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: UnbiasedLinear<4, 3> = Default::default();
+        model.reset_params(&mut rng);
+        let bound = 1.0 / 2.0;
+        assert!(model
+            .weight
+            .data()
+            .iter()
+            .flatten()
+            .all(|w| w.abs() <= bound));
+        assert_eq!(model.num_params(), 4 * 3);
+
+        let before = *model.weight.data();
+        let x: Tensor1D<4> = Tensor1D::ones();
+        let gradients = model.forward(x.trace()).sum().backward();
+        let mut opt: Sgd<UnbiasedLinear<4, 3>> = Default::default();
+        opt.update(&mut model, gradients);
+        assert!(model.weight.data() != &before);
+    }
+
+    #[test]
+    fn test_unbiased_save_load() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut saved: UnbiasedLinear<5, 3> = Default::default();
+        saved.reset_params(&mut rng);
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let path = file.path().to_str().unwrap();
+        saved.save(path).expect("failed to save model");
+
+        let mut loaded: UnbiasedLinear<5, 3> = Default::default();
+        loaded.load(path).expect("failed to load model");
+        assert_eq!(loaded.weight.data(), saved.weight.data());
+
+        // a biased layer can't be loaded from it, since there is no bias
+        let mut linear: Linear<5, 3> = Default::default();
+        assert!(linear.load(path).is_err());
+
+        // the wrong shape is an error
+        let mut wrong: UnbiasedLinear<3, 5> = Default::default();
+        assert!(wrong.load(path).is_err());
+    }
+
+    #[test]
+    fn test_unbiased_load_from_linear() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut linear: Linear<5, 3> = Default::default();
+        linear.reset_params(&mut rng);
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        let path = file.path().to_str().unwrap();
+        linear.save(path).expect("failed to save model");
+
+        let mut loaded: UnbiasedLinear<5, 3> = Default::default();
+        loaded.load(path).expect("failed to load model");
+        assert_eq!(loaded.weight.data(), linear.weight.data());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use rand::Rng;
+
+/// How [Upsample2D] computes the upsampled values.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UpsampleMode {
+    /// Calls [Tensor4D::upsample_nearest()] or [Tensor3D::upsample_nearest()].
+    #[default]
+    Nearest,
+
+    /// Calls [Tensor4D::upsample_bilinear()] or [Tensor3D::upsample_bilinear()].
+    Bilinear,
+}
+
+/// Upsamples the spatial dimensions of an image (3d tensor) or a batch of images (4d tensors)
+/// by a factor of `S`, using [Self::mode].
+///
+/// # Generics
+/// - `S`: the scale factor.
+/// - `H2` and `W2`: the output height and width, which must be `H * S` and `W * S`. Stable rust
+///   can't compute these in a type, so they are specified up front, and checked at compile time.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let model: Upsample2D<2, 4, 6> = Default::default();
+/// let x: Tensor4D<5, 3, 2, 3> = Tensor4D::zeros();
+/// let y: Tensor4D<5, 3, 4, 6> = model.forward(x.duplicate());
+///
+/// let model: Upsample2D<2, 4, 6> = Upsample2D { mode: UpsampleMode::Bilinear };
+/// let y: Tensor4D<5, 3, 4, 6> = model.forward(x);
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Upsample2D<const S: usize, const H2: usize, const W2: usize> {
+    pub mode: UpsampleMode,
+}
+
+impl<const S: usize, const H2: usize, const W2: usize> CanUpdateWithGradients
+    for Upsample2D<S, H2, W2>
+{
+    /// Does nothing.
+    fn update<G: GradientProvider>(&mut self, _: &mut G) {}
+}
+
+impl<const S: usize, const H2: usize, const W2: usize> ResetParams for Upsample2D<S, H2, W2> {
+    /// Does nothing.
+    fn reset_params<R: Rng>(&mut self, _: &mut R) {}
+}
+
+impl<const S: usize, const H2: usize, const W2: usize> CountParams for Upsample2D<S, H2, W2> {}
+#[cfg(feature = "std")]
+impl<const S: usize, const H2: usize, const W2: usize> SaveToNpz for Upsample2D<S, H2, W2> {}
+#[cfg(feature = "std")]
+impl<const S: usize, const H2: usize, const W2: usize> LoadFromNpz for Upsample2D<S, H2, W2> {}
+#[cfg(feature = "std")]
+impl<const S: usize, const H2: usize, const W2: usize> SaveToSafetensors for Upsample2D<S, H2, W2> {}
+impl<const S: usize, const H2: usize, const W2: usize> VisitParams for Upsample2D<S, H2, W2> {}
+#[cfg(feature = "std")]
+impl<const S: usize, const H2: usize, const W2: usize> LoadFromSafetensors
+    for Upsample2D<S, H2, W2>
+{
+}
+
+impl<
+        const S: usize,
+        const B: usize,
+        const C: usize,
+        const H: usize,
+        const W: usize,
+        const H2: usize,
+        const W2: usize,
+        TAPE: Tape,
+    > Module<Tensor4D<B, C, H, W, TAPE>> for Upsample2D<S, H2, W2>
+{
+    type Output = Tensor4D<B, C, H2, W2, TAPE>;
+
+    fn forward(&self, input: Tensor4D<B, C, H, W, TAPE>) -> Self::Output {
+        match self.mode {
+            UpsampleMode::Nearest => input.upsample_nearest::<S, H2, W2>(),
+            UpsampleMode::Bilinear => input.upsample_bilinear::<S, H2, W2>(),
+        }
+    }
+}
+
+impl<
+        const S: usize,
+        const C: usize,
+        const H: usize,
+        const W: usize,
+        const H2: usize,
+        const W2: usize,
+        TAPE: Tape,
+    > Module<Tensor3D<C, H, W, TAPE>> for Upsample2D<S, H2, W2>
+{
+    type Output = Tensor3D<C, H2, W2, TAPE>;
+
+    fn forward(&self, input: Tensor3D<C, H, W, TAPE>) -> Self::Output {
+        match self.mode {
+            UpsampleMode::Nearest => input.upsample_nearest::<S, H2, W2>(),
+            UpsampleMode::Bilinear => input.upsample_bilinear::<S, H2, W2>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsample2d_nearest() {
+        let x: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let model: Upsample2D<2, 4, 4> = Default::default();
+        let y = model.forward(x.trace());
+        assert_eq!(y.data(), x.duplicate().upsample_nearest::<2, 4, 4>().data());
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[[4.0; 2]; 2]]]);
+    }
+
+    #[test]
+    fn test_upsample2d_bilinear() {
+        let x: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let model: Upsample2D<2, 4, 4> = Upsample2D {
+            mode: UpsampleMode::Bilinear,
+        };
+        let y = model.forward(x.trace());
+        assert_eq!(
+            y.data(),
+            x.duplicate().upsample_bilinear::<2, 4, 4>().data()
+        );
+    }
+
+    #[test]
+    fn test_upsample2d_in_sequential() {
+        let model: (Upsample2D<3, 3, 6>, Flatten<18>) = Default::default();
+        let x: Tensor4D<2, 1, 1, 2> = Tensor4D::ones();
+        let y: Tensor2D<2, 18, OwnedTape> = model.forward(x.trace());
+        assert_eq!(y.data(), &[[1.0; 18]; 2]);
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[[9.0; 2]]]; 2]);
+    }
+
+    #[test]
+    fn test_upsample2d_3d() {
+        let x: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+        let model: Upsample2D<2, 4, 4> = Upsample2D {
+            mode: UpsampleMode::Bilinear,
+        };
+        let y = model.forward(x.trace());
+        assert_eq!(
+            y.data(),
+            x.duplicate().upsample_bilinear::<2, 4, 4>().data()
+        );
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[4.0; 2]; 2]]);
+    }
+}
@@ -49,11 +49,29 @@ where
     T: NumpyDtype + NumpyShape + ReadNumbers,
     R: Read,
 {
-    let endian = read_header::<T, R>(r)?;
+    let endian = read_header::<T, R>(r, T::shape())?;
     t.read_numbers(r, endian).map_err(NpyError::IoError)?;
     Ok(())
 }
 
+/// Reads an array with a leading dimension of `data.len()` from a [Read] into `data`.
+///
+/// Unlike [read()], the length only needs to be known at runtime. The
+/// header's shape must still match exactly.
+pub fn read_slice<T, R>(r: &mut R, data: &mut [T]) -> Result<(), NpyError>
+where
+    T: NumpyDtype + NumpyShape + ReadNumbers,
+    R: Read,
+{
+    let mut shape = T::shape();
+    shape.insert(0, data.len());
+    let endian = read_header::<T, R>(r, shape)?;
+    for data_i in data.iter_mut() {
+        data_i.read_numbers(r, endian).map_err(NpyError::IoError)?;
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum NpyError {
     /// Magic number did not match the expected value.
@@ -75,13 +93,44 @@ pub enum NpyError {
         found_str: String,
     },
 
+    /// The header's shape is `found`, but the array being read into has shape `expected`.
+    ShapeMismatch {
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+
     /// Unexpected alignment for [Endian].
     InvalidAlignment,
 }
 
-fn read_header<T, R>(r: &mut R) -> Result<Endian, NpyError>
+impl std::fmt::Display for NpyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMagicNumber(magic) => write!(f, "invalid magic number {magic:?}"),
+            Self::InvalidVersion(version) => write!(f, "invalid version {version:?}"),
+            Self::IoError(e) => write!(f, "{e}"),
+            Self::Utf8Error(e) => write!(f, "{e}"),
+            Self::ParsingMismatch {
+                expected_str,
+                found_str,
+                ..
+            } => write!(
+                f,
+                "expected `{expected_str}` in header, found `{found_str}`"
+            ),
+            Self::ShapeMismatch { expected, found } => {
+                write!(f, "array has shape {found:?}, expected {expected:?}")
+            }
+            Self::InvalidAlignment => write!(f, "invalid alignment"),
+        }
+    }
+}
+
+impl std::error::Error for NpyError {}
+
+fn read_header<T, R>(r: &mut R, shape: Vec<usize>) -> Result<Endian, NpyError>
 where
-    T: NumpyDtype + NumpyShape,
+    T: NumpyDtype,
     R: Read,
 {
     let mut magic = [0; 6];
@@ -123,18 +172,44 @@ where
 
     // shape
     i = expect(&header, i, b"'shape': (")?;
-    let shape_str = to_shape_str(T::shape());
-    i = expect(&header, i, shape_str.as_bytes())?;
-    expect(&header, i, b"), }")?;
+    let len = header[i..].iter().position(|&c| c == b')').unwrap_or(0);
+    let found = parse_shape(&header[i..i + len]).ok_or_else(|| {
+        let expected = to_shape_str(shape.clone()).into_bytes();
+        let found = header[i..i + len].to_vec();
+        NpyError::ParsingMismatch {
+            expected_str: String::from_utf8_lossy(&expected).into_owned(),
+            found_str: String::from_utf8_lossy(&found).into_owned(),
+            expected,
+            found,
+        }
+    })?;
+    if found != shape {
+        return Err(NpyError::ShapeMismatch {
+            expected: shape,
+            found,
+        });
+    }
+    expect(&header, i + len, b"), }")?;
 
     Ok(endian)
 }
 
+/// Parses the inside of a shape tuple, e.g. `2, 3` or `5,`.
+fn parse_shape(buf: &[u8]) -> Option<Vec<usize>> {
+    std::str::from_utf8(buf)
+        .ok()?
+        .split(',')
+        .map(|dim| dim.trim())
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse().ok())
+        .collect()
+}
+
 fn expect(buf: &[u8], i: usize, chars: &[u8]) -> Result<usize, NpyError> {
     for (offset, &c) in chars.iter().enumerate() {
-        if buf[i + offset] != c {
+        if buf.get(i + offset) != Some(&c) {
             let expected = chars.to_vec();
-            let found = buf[i..i + offset + 1].to_vec();
+            let found = buf[i.min(buf.len())..(i + offset + 1).min(buf.len())].to_vec();
             let expected_str = String::from_utf8(expected.clone()).map_err(NpyError::Utf8Error)?;
             let found_str = String::from_utf8(found.clone()).map_err(NpyError::Utf8Error)?;
             return Err(NpyError::ParsingMismatch {
@@ -214,6 +289,26 @@ mod tests {
         load(file.path(), &mut v).expect_err("");
     }
 
+    #[test]
+    fn test_shape_mismatch() {
+        let data: [[f32; 3]; 2] = [[0.0; 3]; 2];
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        save(file.path(), &data).expect("Saving failed");
+
+        let mut value = [[0.0f32; 2]; 3];
+        match load(file.path(), &mut value) {
+            Err(NpyError::ShapeMismatch { expected, found }) => {
+                assert_eq!(expected, [3, 2]);
+                assert_eq!(found, [2, 3]);
+            }
+            r => panic!("{:?}", r),
+        }
+
+        let mut value = [0.0f32; 6];
+        let err = load(file.path(), &mut value).unwrap_err();
+        assert_eq!(err.to_string(), "array has shape [2, 3], expected [6]");
+    }
+
     #[test]
     fn test_1d_f32_save() {
         let data: [f32; 5] = [0.0, 1.0, 2.0, 3.0, -4.0];
@@ -257,4 +352,23 @@ mod tests {
         let mut value = [[0.0f32; 2]; 3];
         assert!(load(file.path(), &mut value).is_err());
     }
+
+    #[test]
+    fn test_slice_roundtrip() {
+        let data = vec![0.0f32, 1.0, 2.0, 3.0, -4.0];
+
+        let mut buf = Vec::new();
+        write_slice(&mut buf, &data).expect("Saving failed");
+
+        let mut value = vec![0.0f32; 5];
+        read_slice(&mut buf.as_slice(), &mut value).expect("");
+        assert_eq!(value, data);
+
+        let mut value = [0.0f32; 5];
+        read(&mut buf.as_slice(), &mut value).expect("");
+        assert_eq!(value, [0.0, 1.0, 2.0, 3.0, -4.0]);
+
+        let mut value = vec![0.0f32; 4];
+        read_slice(&mut buf.as_slice(), &mut value).expect_err("");
+    }
 }
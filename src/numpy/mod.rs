@@ -1,13 +1,21 @@
 //! Provides some generic functions to load & save Nd arrays in the [.npy](https://numpy.org/devdocs/reference/generated/numpy.lib.format.html)
 //! format. See [load()] and [save()]
 
+use std::vec::Vec;
+
+#[cfg(feature = "std")]
 mod load;
+#[cfg(feature = "std")]
 mod save;
 
+#[cfg(feature = "std")]
 pub use load::*;
+#[cfg(feature = "std")]
 pub use save::*;
 
+#[cfg(feature = "std")]
 const MAGIC_NUMBER: &[u8] = b"\x93NUMPY";
+#[cfg(feature = "std")]
 const VERSION: &[u8] = &[1, 0];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -17,6 +25,7 @@ pub enum Endian {
     Native,
 }
 
+#[cfg(feature = "std")]
 fn to_shape_str(shape: Vec<usize>) -> String {
     shape
         .iter()
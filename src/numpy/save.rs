@@ -41,17 +41,41 @@ where
     T: NumpyDtype + NumpyShape + WriteNumbers,
     W: Write,
 {
-    write_header::<T, W>(w, Endian::Little)?;
+    write_header::<T, W>(w, Endian::Little, T::shape())?;
     t.write_numbers(w, Endian::Little)?;
     Ok(())
 }
 
-fn write_header<T, W>(w: &mut W, endian: Endian) -> Result<()>
+/// Writes `data` to a [Write] as an array with a leading dimension of `data.len()`.
+///
+/// Unlike [write()], the length only needs to be known at runtime.
+///
+/// Example Usage:
+/// ```ignore
+/// use dfdx::numpy;
+/// let data = vec![1.0f32, 2.0, 3.0];
+/// numpy::write_slice(&mut w, &data);
+/// ```
+pub fn write_slice<T, W>(w: &mut W, data: &[T]) -> Result<()>
+where
+    T: NumpyDtype + NumpyShape + WriteNumbers,
+    W: Write,
+{
+    let mut shape = T::shape();
+    shape.insert(0, data.len());
+    write_header::<T, W>(w, Endian::Little, shape)?;
+    for data_i in data.iter() {
+        data_i.write_numbers(w, Endian::Little)?;
+    }
+    Ok(())
+}
+
+fn write_header<T, W>(w: &mut W, endian: Endian, shape: Vec<usize>) -> Result<()>
 where
-    T: NumpyDtype + NumpyShape,
+    T: NumpyDtype,
     W: Write,
 {
-    let shape_str = to_shape_str(T::shape());
+    let shape_str = to_shape_str(shape);
 
     let mut header: Vec<u8> = Vec::new();
     write!(
@@ -1,5 +1,15 @@
+#[cfg(feature = "std")]
+use super::checkpoint::{read_by_position, write_by_position};
+use super::gradient_noise::{check_eta, GradientNoise};
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use std::boxed::Box;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
 use std::marker::PhantomData;
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 /// An implementation of the Adam optimizer from
 /// [Adam: A Method for Stochastic Optimization](https://arxiv.org/abs/1412.6980)
@@ -21,6 +31,7 @@ use std::marker::PhantomData;
 ///     lr: 1e-2,
 ///     betas: [0.5, 0.25],
 ///     eps: 1e-6,
+///     gradient_noise: None,
 /// });
 /// ```
 ///
@@ -34,6 +45,7 @@ pub struct Adam<M> {
     gradients: Gradients,
     moment1: Gradients,
     moment2: Gradients,
+    noise: GradientNoise,
 
     marker: PhantomData<*const M>,
 }
@@ -47,6 +59,7 @@ pub struct Adam<M> {
 ///     lr: 1e-2,
 ///     betas: [0.1, 0.2],
 ///     eps: 1e-6,
+///     gradient_noise: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Copy)]
@@ -59,6 +72,15 @@ pub struct AdamConfig {
 
     /// Epsilon for numerical stability. Defaults to `1e-8`.
     pub eps: f32,
+
+    /// Optional variance `eta` of gaussian noise added to each gradient before the update.
+    /// The variance decays as `eta / (1 + t)^0.55`, where `t` is the number of updates so far.
+    /// Defaults to `None`.
+    ///
+    /// The noise is seeded by [Adam::new_with_seed()] (or with `0` by [Adam::new()]),
+    /// so it's reproducible across runs. Its random state is saved along with the rest of
+    /// the optimizer's state, see [OptimizerState] (and `OptimizerStateDict` with the `serde` feature).
+    pub gradient_noise: Option<f32>,
 }
 
 impl Default for AdamConfig {
@@ -67,6 +89,7 @@ impl Default for AdamConfig {
             lr: 1e-3,
             betas: [0.9, 0.999],
             eps: 1e-8,
+            gradient_noise: None,
         }
     }
 }
@@ -79,25 +102,37 @@ impl<M> Default for Adam<M> {
 }
 
 impl<M> Adam<M> {
-    /// Constructs using hyperparameters from `cfg`.
+    /// Constructs using hyperparameters from `cfg`, seeding [AdamConfig::gradient_noise] with `0`.
     pub fn new(cfg: AdamConfig) -> Self {
+        Self::new_with_seed(cfg, 0)
+    }
+
+    /// Constructs using hyperparameters from `cfg`, seeding [AdamConfig::gradient_noise] with `seed`.
+    ///
+    /// **Panics** if [AdamConfig::gradient_noise] is negative, infinite or NaN.
+    pub fn new_with_seed(cfg: AdamConfig, seed: u64) -> Self {
+        check_eta(cfg.gradient_noise);
         Self {
             cfg,
             t: 0,
             gradients: Default::default(),
             moment1: Default::default(),
             moment2: Default::default(),
+            noise: GradientNoise::new(seed),
             marker: PhantomData,
         }
     }
 }
 
 impl<M> GradientProvider for Adam<M> {
-    fn gradient<P>(&mut self, p: &P) -> Box<P::Array>
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
     where
         P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
     {
-        let mut g_t = self.gradients.remove(p);
+        let mut g_t = self.gradients.remove(p)?;
+        if let Some(eta) = self.cfg.gradient_noise {
+            self.noise.add(eta, g_t.as_mut());
+        }
         let m_t = self.moment1.mut_gradient(p);
         let v_t = self.moment2.mut_gradient(p);
         P::Device::foreach_mmm(g_t.as_mut(), m_t, v_t, &mut |g, m, v| {
@@ -107,7 +142,7 @@ impl<M> GradientProvider for Adam<M> {
             let v_hat = *v * (1.0 - self.cfg.betas[1].powi(self.t)).recip();
             *g = self.cfg.lr * m_hat / (v_hat.sqrt() + self.cfg.eps)
         });
-        g_t
+        Some(g_t)
     }
 }
 
@@ -116,6 +151,89 @@ impl<M: CanUpdateWithGradients> Optimizer<M> for Adam<M> {
         self.t = self.t.checked_add(1).unwrap();
         self.gradients = gradients;
         module.update(self);
+        self.noise.step();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: CanUpdateWithGradients> OptimizerState<M> for Adam<M> {
+    /// Writes the timestep to `t.npy`, the random state of the gradient noise to
+    /// `gradient_noise.npy`, and the moments of the `i`th parameter of `module` to
+    /// `moment1.{i}.npy` and `moment2.{i}.npy`.
+    fn write_state<W: Write + Seek>(
+        &self,
+        module: &mut M,
+        filename_prefix: &str,
+        w: &mut ZipWriter<W>,
+    ) -> ZipResult<()> {
+        npz_fwrite(w, format!("{}t.npy", filename_prefix), &(self.t as f64))?;
+        self.noise.write(filename_prefix, w)?;
+        write_by_position(
+            &self.moment1,
+            module,
+            &format!("{}moment1.", filename_prefix),
+            w,
+        )?;
+        write_by_position(
+            &self.moment2,
+            module,
+            &format!("{}moment2.", filename_prefix),
+            w,
+        )
+    }
+
+    fn read_state<R: Read + Seek>(
+        &mut self,
+        module: &mut M,
+        filename_prefix: &str,
+        r: &mut ZipArchive<R>,
+    ) -> Result<(), NpzError> {
+        let mut t = 0.0f64;
+        npz_fread(r, format!("{}t.npy", filename_prefix), &mut t)?;
+        self.t = t as i32;
+        self.noise.read(filename_prefix, r)?;
+        read_by_position(
+            &mut self.moment1,
+            module,
+            &format!("{}moment1.", filename_prefix),
+            r,
+        )?;
+        read_by_position(
+            &mut self.moment2,
+            module,
+            &format!("{}moment2.", filename_prefix),
+            r,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<M: CanUpdateWithGradients> OptimizerStateDict<M> for Adam<M> {
+    /// Stores the timestep as [StateDict::step], the random state of the gradient noise as
+    /// [StateDict::gradient_noise], and the moments in the buffers `"moment1"` and `"moment2"`.
+    fn state_dict(&self, module: &mut M) -> StateDict {
+        use super::state_dict::gather_by_position;
+        StateDict {
+            step: self.t as u64,
+            buffers: vec![
+                ("moment1".into(), gather_by_position(&self.moment1, module)),
+                ("moment2".into(), gather_by_position(&self.moment2, module)),
+            ],
+            gradient_noise: (&self.noise).into(),
+        }
+    }
+
+    fn load_state_dict(&mut self, module: &mut M, state: &StateDict) -> Result<(), StateDictError> {
+        use super::state_dict::scatter_by_position;
+        let mut moment1 = Default::default();
+        scatter_by_position(&mut moment1, module, state, "moment1")?;
+        let mut moment2 = Default::default();
+        scatter_by_position(&mut moment2, module, state, "moment2")?;
+        self.t = state.step as i32;
+        self.moment1 = moment1;
+        self.moment2 = moment2;
+        self.noise = state.gradient_noise.into();
+        Ok(())
     }
 }
 
@@ -156,6 +274,7 @@ mod tests {
             lr: 1e-3,
             betas: [0.5, 0.25],
             eps: 1e-8,
+            gradient_noise: None,
         });
         let mut t: Tensor1D<5> = Tensor1D::ones();
         let rate = Tensor1D::new([1e-4, 1e-3, 1e-2, 1e-1, 1e-0]);
@@ -179,6 +298,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_adam_skips_params_without_gradients() {
+        let mut opt = Adam::default();
+        let mut model: (Tensor1D<3>, Tensor1D<3>) = (Tensor1D::ones(), Tensor1D::ones());
+        let gradients = model.0.trace().square().mean().backward();
+        opt.update(&mut model, gradients);
+        assert_ne!(model.0.data(), &[1.0; 3]);
+        assert_eq!(model.1.data(), &[1.0; 3]);
+        assert!(opt.moment1.maybe_ref_gradient(&model.1).is_none());
+        assert!(opt.moment2.maybe_ref_gradient(&model.1).is_none());
+    }
+
     #[test]
     fn test_adam_changes_all_params() {
         type Model = (Linear<5, 16>, ReLU, Linear<16, 16>, ReLU, Linear<16, 10>);
@@ -193,6 +324,7 @@ mod tests {
             lr: 1e-3,
             betas: [0.9, 0.999],
             eps: 1e-8,
+            gradient_noise: None,
         });
 
         let py = model.forward(x.trace());
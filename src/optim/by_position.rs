@@ -0,0 +1,120 @@
+//! Walks over the per parameter state of an optimizer in the order [CanUpdateWithGradients::update]
+//! visits the parameters of a module, which is how [super::OptimizerState] and
+//! [super::OptimizerStateDict] store it.
+
+use crate::arrays::{CountElements, HasArrayType};
+use crate::devices::{ForEachElement, HasDevice};
+use crate::gradients::{CanUpdateWithGradients, GradientProvider, Gradients};
+use crate::unique_id::HasUniqueId;
+use std::{boxed::Box, vec::Vec};
+
+/// Calls `f` with the position and the elements (in row major order) of the entry in `gradients`
+/// of each parameter of `module`. Parameters without an entry in `gradients` are all zeros.
+///
+/// Stops calling `f` after the first error, which is returned. The parameters of `module` are
+/// left unchanged.
+pub(super) fn visit_by_position<M, E, F>(
+    gradients: &Gradients,
+    module: &mut M,
+    f: F,
+) -> Result<(), E>
+where
+    M: CanUpdateWithGradients,
+    F: FnMut(usize, &[f32]) -> Result<(), E>,
+{
+    let mut visit = VisitByPosition {
+        gradients,
+        f,
+        position: 0,
+        result: Ok(()),
+    };
+    module.update(&mut visit);
+    visit.result
+}
+
+/// Sets the entry in `gradients` of each parameter of `module`, where `f` is called with the
+/// position of the parameter and fills in its elements. This is the inverse of [visit_by_position()].
+///
+/// Stops calling `f` after the first error, which is returned. Otherwise returns the number of
+/// parameters of `module`. The parameters of `module` are left unchanged.
+pub(super) fn fill_by_position<M, E, F>(
+    gradients: &mut Gradients,
+    module: &mut M,
+    f: F,
+) -> Result<usize, E>
+where
+    M: CanUpdateWithGradients,
+    F: FnMut(usize, &mut [f32]) -> Result<(), E>,
+{
+    let mut fill = FillByPosition {
+        gradients,
+        f,
+        position: 0,
+        result: Ok(()),
+    };
+    module.update(&mut fill);
+    fill.result.map(|_| fill.position)
+}
+
+/// A [GradientProvider] that calls `f` with the entry of each parameter it is asked about,
+/// and returns `None` so the parameters are left unchanged.
+struct VisitByPosition<'a, F, E> {
+    gradients: &'a Gradients,
+    f: F,
+    position: usize,
+    result: Result<(), E>,
+}
+
+impl<'a, F, E> GradientProvider for VisitByPosition<'a, F, E>
+where
+    F: FnMut(usize, &[f32]) -> Result<(), E>,
+{
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
+    where
+        P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
+    {
+        if self.result.is_ok() {
+            self.result = match self.gradients.maybe_ref_gradient(p) {
+                Some(g) => (self.f)(self.position, crate::tensor_ops::flat(g)),
+                None => (self.f)(
+                    self.position,
+                    &vec![0.0; <P::Array as CountElements>::NUM_ELEMENTS],
+                ),
+            };
+        }
+        self.position += 1;
+        None
+    }
+}
+
+/// A [GradientProvider] that sets the entry of each parameter it is asked about with `f`,
+/// and returns `None` so the parameters are left unchanged.
+struct FillByPosition<'a, F, E> {
+    gradients: &'a mut Gradients,
+    f: F,
+    position: usize,
+    result: Result<(), E>,
+}
+
+impl<'a, F, E> GradientProvider for FillByPosition<'a, F, E>
+where
+    F: FnMut(usize, &mut [f32]) -> Result<(), E>,
+{
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
+    where
+        P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
+    {
+        if self.result.is_ok() {
+            let mut data: Vec<f32> = vec![0.0; <P::Array as CountElements>::NUM_ELEMENTS];
+            self.result = (self.f)(self.position, &mut data);
+            if self.result.is_ok() {
+                let mut data = data.drain(..);
+                P::Device::foreach_m(self.gradients.mut_gradient(p), &mut |v| {
+                    *v = data.next().unwrap()
+                });
+            }
+        }
+        self.position += 1;
+        None
+    }
+}
@@ -0,0 +1,381 @@
+use super::by_position::{fill_by_position, visit_by_position};
+use crate::gradients::{CanUpdateWithGradients, Gradients};
+use crate::nn::{LoadFromNpz, NpzError, SaveToNpz};
+use crate::numpy::{self, NpyError};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, Write},
+    path::Path,
+};
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// An optimizer whose internal state (e.g. moment buffers & step counters) can be
+/// saved to and loaded from a `.npz` file, so that training can be resumed exactly.
+///
+/// Optimizers store their per parameter state keyed by [crate::unique_id::UniqueId], which
+/// is different every time a program is run. Instead, the state is written
+/// under the position of the parameter in `module`, which is the order [CanUpdateWithGradients::update]
+/// visits the parameters in. For example the first moment of the 2nd parameter of
+/// `module` that [super::Adam] writes with a prefix of `"optimizer."` is stored in `optimizer.moment1.1.npy`.
+///
+/// See [save_checkpoint()] and [load_checkpoint()].
+pub trait OptimizerState<M> {
+    /// Writes the internal state of the optimizer for all of `module`'s parameters
+    /// into [ZipWriter] `w` with a base filename of `filename_prefix`.
+    ///
+    /// `module` is only mutably borrowed to visit its parameters with
+    /// [CanUpdateWithGradients::update()], its parameters are left unchanged.
+    fn write_state<W: Write + Seek>(
+        &self,
+        module: &mut M,
+        filename_prefix: &str,
+        w: &mut ZipWriter<W>,
+    ) -> ZipResult<()>;
+
+    /// Reads the internal state of the optimizer for all of `module`'s parameters
+    /// from [ZipArchive] `r` with a base filename of `filename_prefix`.
+    ///
+    /// `module` does not have to be the same object that was passed to [OptimizerState::write_state()],
+    /// it only needs to have the same structure.
+    fn read_state<R: Read + Seek>(
+        &mut self,
+        module: &mut M,
+        filename_prefix: &str,
+        r: &mut ZipArchive<R>,
+    ) -> Result<(), NpzError>;
+
+    /// Saves only the internal state of the optimizer into the `.npz` file located at `path`.
+    /// Use [save_checkpoint()] to save the parameters of `module` too.
+    ///
+    /// See [OptimizerState::write_state()] for why `module` is mutably borrowed.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let mut model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+    /// let opt: Adam<_> = Default::default();
+    /// opt.save("adam.npz", &mut model)?;
+    /// ```
+    fn save<P: AsRef<Path>>(&self, path: P, module: &mut M) -> ZipResult<()> {
+        let f = File::create(path)?;
+        let f = BufWriter::new(f);
+        let mut zip = ZipWriter::new(f);
+        self.write_state(module, "", &mut zip)?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Loads the internal state of the optimizer from a `.npz` file created by
+    /// [OptimizerState::save()], re-associating it with the parameters of `module`.
+    ///
+    /// Returns an error if `module` has a different number of parameters, or parameters
+    /// with a different number of elements, than the module the state was saved with.
+    ///
+    /// Example:
+    /// ```ignore
+    /// # use dfdx::prelude::*;
+    /// let mut model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+    /// let mut opt: Adam<_> = Default::default();
+    /// opt.load("adam.npz", &mut model)?;
+    /// ```
+    fn load<P: AsRef<Path>>(&mut self, path: P, module: &mut M) -> Result<(), NpzError> {
+        let f = File::open(path).map_err(|e| NpzError::Npy(NpyError::IoError(e)))?;
+        let f = BufReader::new(f);
+        let mut zip = ZipArchive::new(f).map_err(NpzError::Zip)?;
+        self.read_state(module, "", &mut zip)
+    }
+}
+
+/// Saves both `module` and the internal state of `opt` into the `.npz` file located at `path`.
+///
+/// The parameters of `module` are stored with a prefix of `"model."`, and the
+/// state of `opt` with a prefix of `"optimizer."`. See [OptimizerState::write_state()]
+/// for why `module` is mutably borrowed.
+///
+/// Example:
+/// ```ignore
+/// # use dfdx::prelude::*;
+/// let mut model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+/// let opt: Adam<_> = Default::default();
+/// save_checkpoint("checkpoint.npz", &mut model, &opt)?;
+/// ```
+pub fn save_checkpoint<M, O, P>(path: P, module: &mut M, opt: &O) -> ZipResult<()>
+where
+    M: SaveToNpz,
+    O: OptimizerState<M>,
+    P: AsRef<Path>,
+{
+    let f = File::create(path)?;
+    let f = BufWriter::new(f);
+    let mut zip = ZipWriter::new(f);
+    module.write("model.", &mut zip)?;
+    opt.write_state(module, "optimizer.", &mut zip)?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Loads both `module` and the internal state of `opt` from a `.npz` file
+/// created by [save_checkpoint()].
+///
+/// Example:
+/// ```ignore
+/// # use dfdx::prelude::*;
+/// let mut model: (Linear<5, 10>, Linear<10, 5>) = Default::default();
+/// let mut opt: Adam<_> = Default::default();
+/// load_checkpoint("checkpoint.npz", &mut model, &mut opt)?;
+/// ```
+pub fn load_checkpoint<M, O, P>(path: P, module: &mut M, opt: &mut O) -> Result<(), NpzError>
+where
+    M: LoadFromNpz,
+    O: OptimizerState<M>,
+    P: AsRef<Path>,
+{
+    let f = File::open(path).map_err(|e| NpzError::Npy(NpyError::IoError(e)))?;
+    let f = BufReader::new(f);
+    let mut zip = ZipArchive::new(f).map_err(NpzError::Zip)?;
+    module.read("model.", &mut zip)?;
+    opt.read_state(module, "optimizer.", &mut zip)?;
+    Ok(())
+}
+
+/// Writes the entries of `gradients` for each parameter of `module` into `w`. The entry
+/// of the `i`th parameter is written to `{filename_prefix}{i}.npy`.
+///
+/// Parameters without an entry in `gradients` are written as all zeros. The parameters
+/// of `module` are left unchanged.
+pub(crate) fn write_by_position<M, W>(
+    gradients: &Gradients,
+    module: &mut M,
+    filename_prefix: &str,
+    w: &mut ZipWriter<W>,
+) -> ZipResult<()>
+where
+    M: CanUpdateWithGradients,
+    W: Write + Seek,
+{
+    visit_by_position(gradients, module, |position, data| {
+        w.start_file(
+            format!("{filename_prefix}{position}.npy"),
+            Default::default(),
+        )?;
+        numpy::write_slice(w, data)?;
+        Ok(())
+    })
+}
+
+/// Reads the entries for each parameter of `module` into `gradients`. This
+/// is the inverse of [write_by_position()].
+///
+/// Returns [NpzError::UnexpectedFile] if `r` has more entries than `module` has parameters.
+pub(crate) fn read_by_position<M, R>(
+    gradients: &mut Gradients,
+    module: &mut M,
+    filename_prefix: &str,
+    r: &mut ZipArchive<R>,
+) -> Result<(), NpzError>
+where
+    M: CanUpdateWithGradients,
+    R: Read + Seek,
+{
+    let num_params = fill_by_position(gradients, module, |position, data| {
+        let filename = format!("{filename_prefix}{position}.npy");
+        let mut f = r.by_name(&filename).map_err(NpzError::Zip)?;
+        numpy::read_slice(&mut f, data).map_err(|e| NpzError::from_npy(&filename, e))
+    })?;
+    let filename = format!("{}{}.npy", filename_prefix, num_params);
+    if r.by_name(&filename).is_ok() {
+        return Err(NpzError::UnexpectedFile(filename));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use rand::{prelude::StdRng, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    type Model = (Linear<3, 5>, ReLU, Linear<5, 2>);
+
+    fn train_step<O: Optimizer<Model>>(model: &mut Model, opt: &mut O, x: &Tensor2D<4, 3>) {
+        let y = model.forward(x.trace());
+        let loss = mse_loss(y, &Tensor2D::ones());
+        opt.update(model, loss.backward());
+    }
+
+    fn assert_models_eq(a: &Model, b: &Model) {
+        assert_eq!(a.0.weight.data(), b.0.weight.data());
+        assert_eq!(a.0.bias.data(), b.0.bias.data());
+        assert_eq!(a.2.weight.data(), b.2.weight.data());
+        assert_eq!(a.2.bias.data(), b.2.bias.data());
+    }
+
+    fn test_resume_matches<O, F>(make_opt: F)
+    where
+        O: Optimizer<Model> + OptimizerState<Model>,
+        F: Fn() -> O,
+    {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let mut opt = make_opt();
+
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+        }
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        save_checkpoint(file.path(), &mut model, &opt).expect("");
+
+        let mut loaded: Model = Default::default();
+        let mut loaded_opt = make_opt();
+        load_checkpoint(file.path(), &mut loaded, &mut loaded_opt).expect("");
+        assert_models_eq(&model, &loaded);
+
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+            train_step(&mut loaded, &mut loaded_opt, &x);
+            assert_models_eq(&model, &loaded);
+        }
+    }
+
+    #[test]
+    fn test_adam_resume_matches() {
+        test_resume_matches(Adam::default);
+    }
+
+    #[test]
+    fn test_sgd_resume_matches() {
+        test_resume_matches(|| {
+            Sgd::new(SgdConfig {
+                lr: 1e-2,
+                momentum: Some(Momentum::Nesterov(0.9)),
+                gradient_noise: None,
+            })
+        });
+    }
+
+    #[test]
+    fn test_sgd_resume_matches_with_gradient_noise() {
+        test_resume_matches(|| {
+            let cfg = SgdConfig {
+                lr: 1e-2,
+                momentum: Some(Momentum::Classic(0.5)),
+                gradient_noise: Some(0.1),
+            };
+            Sgd::new_with_seed(cfg, u64::MAX - 1)
+        });
+    }
+
+    #[test]
+    fn test_rmsprop_resume_matches() {
+        test_resume_matches(|| {
+            RMSprop::new(RMSpropConfig {
+                momentum: Some(0.5),
+                centered: true,
+                ..Default::default()
+            })
+        });
+    }
+
+    #[test]
+    fn test_adam_checkpoint_contents() {
+        let mut model: Model = Default::default();
+        let opt: Adam<Model> = Default::default();
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        save_checkpoint(file.path(), &mut model, &opt).expect("");
+
+        let f = std::fs::File::open(file.path()).expect("");
+        let zip = zip::ZipArchive::new(f).expect("");
+        let mut names = zip.file_names().collect::<Vec<&str>>();
+        names.sort_unstable();
+        assert_eq!(
+            &names,
+            &[
+                "model.0.bias.npy",
+                "model.0.weight.npy",
+                "model.2.bias.npy",
+                "model.2.weight.npy",
+                "optimizer.gradient_noise.npy",
+                "optimizer.moment1.0.npy",
+                "optimizer.moment1.1.npy",
+                "optimizer.moment1.2.npy",
+                "optimizer.moment1.3.npy",
+                "optimizer.moment2.0.npy",
+                "optimizer.moment2.1.npy",
+                "optimizer.moment2.2.npy",
+                "optimizer.moment2.3.npy",
+                "optimizer.t.npy",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_checkpoint_wrong_structure() {
+        let mut model: Model = Default::default();
+        let opt: Adam<Model> = Default::default();
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        save_checkpoint(file.path(), &mut model, &opt).expect("");
+
+        let mut other: (Linear<3, 5>, ReLU, Linear<5, 2>, Linear<2, 2>) = Default::default();
+        let mut other_opt: Adam<_> = Default::default();
+        assert!(load_checkpoint(file.path(), &mut other, &mut other_opt).is_err());
+    }
+
+    #[test]
+    fn test_adam_save_load_resume_matches() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let mut opt: Adam<Model> = Default::default();
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+        }
+
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        opt.save(file.path(), &mut model).expect("");
+
+        let mut loaded = model.clone();
+        let mut loaded_opt: Adam<Model> = Default::default();
+        loaded_opt.load(file.path(), &mut loaded).expect("");
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+            train_step(&mut loaded, &mut loaded_opt, &x);
+            assert_models_eq(&model, &loaded);
+        }
+    }
+
+    #[test]
+    fn test_optimizer_load_mismatch() {
+        let mut model: Model = Default::default();
+        let opt: Sgd<Model> = Sgd::new(SgdConfig {
+            lr: 1e-2,
+            momentum: Some(Momentum::Classic(0.9)),
+            gradient_noise: None,
+        });
+        let file = NamedTempFile::new().expect("failed to create tempfile");
+        opt.save(file.path(), &mut model).expect("");
+
+        let mut fewer: Linear<3, 5> = Default::default();
+        let mut fewer_opt: Sgd<_> = Default::default();
+        let err = fewer_opt.load(file.path(), &mut fewer).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`velocity.2.npy` does not belong to any parameter"
+        );
+
+        let mut more: (Linear<3, 5>, ReLU, Linear<5, 2>, Linear<2, 2>) = Default::default();
+        let mut more_opt: Sgd<_> = Default::default();
+        assert!(more_opt.load(file.path(), &mut more).is_err());
+
+        let mut wrong_shape: (Linear<3, 5>, ReLU, Linear<5, 3>) = Default::default();
+        let mut wrong_opt: Sgd<_> = Default::default();
+        let err = wrong_opt.load(file.path(), &mut wrong_shape).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`velocity.2.npy` has shape [10], expected [15]"
+        );
+    }
+}
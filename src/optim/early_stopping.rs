@@ -0,0 +1,217 @@
+/// Whether a lower or a higher validation metric is better, for [EarlyStopping].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MetricMode {
+    /// Lower is better, e.g. for a loss.
+    #[default]
+    Minimize,
+
+    /// Higher is better, e.g. for accuracy.
+    Maximize,
+}
+
+/// Configuration of [EarlyStopping].
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// EarlyStoppingConfig {
+///     patience: 3,
+///     min_delta: 1e-3,
+///     mode: MetricMode::Maximize,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EarlyStoppingConfig {
+    /// The number of epochs in a row without improvement after which training should stop.
+    /// Defaults to `10`.
+    pub patience: usize,
+
+    /// The amount the metric has to improve on the best metric so far to count as an improvement.
+    /// Must be `>= 0.0`. Defaults to `0.0`.
+    pub min_delta: f32,
+
+    /// Whether the metric should be minimized or maximized. Defaults to [MetricMode::Minimize].
+    pub mode: MetricMode,
+}
+
+impl Default for EarlyStoppingConfig {
+    fn default() -> Self {
+        Self {
+            patience: 10,
+            min_delta: 0.0,
+            mode: MetricMode::Minimize,
+        }
+    }
+}
+
+/// Stops training when a validation metric hasn't improved for [EarlyStoppingConfig::patience] epochs.
+///
+/// Call [EarlyStopping::should_stop()] with the validation metric once per epoch. An epoch improves if
+/// its metric is better than the best metric so far by more than [EarlyStoppingConfig::min_delta],
+/// which resets the count of epochs without improvement. NaN metrics never count as an improvement.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let mut early_stopping = EarlyStopping::new(EarlyStoppingConfig {
+///     patience: 2,
+///     ..Default::default()
+/// });
+/// for (epoch, val_loss) in [1.0, 0.5, 0.6, 0.4, 0.45, 0.41, 0.3].into_iter().enumerate() {
+///     if early_stopping.should_stop(val_loss) {
+///         assert_eq!(epoch, 5);
+///         break;
+///     }
+/// }
+/// assert_eq!(early_stopping.best(), Some(0.4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EarlyStopping {
+    /// Hyperparameter configuration
+    pub cfg: EarlyStoppingConfig,
+    best: Option<f32>,
+    num_bad_epochs: usize,
+}
+
+impl Default for EarlyStopping {
+    /// See [EarlyStoppingConfig]
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl EarlyStopping {
+    /// Constructs using `cfg`.
+    ///
+    /// **Panics** if `cfg.min_delta` is negative or NaN.
+    pub fn new(cfg: EarlyStoppingConfig) -> Self {
+        assert!(
+            cfg.min_delta >= 0.0,
+            "min_delta must be >= 0.0, found {}",
+            cfg.min_delta
+        );
+        Self {
+            cfg,
+            best: None,
+            num_bad_epochs: 0,
+        }
+    }
+
+    /// Records the validation `metric` of an epoch, and returns `true` if this is the
+    /// [EarlyStoppingConfig::patience]th epoch in a row without improvement.
+    ///
+    /// The first metric is always an improvement.
+    pub fn should_stop(&mut self, metric: f32) -> bool {
+        if self.is_improvement(metric) {
+            self.best = Some(metric);
+            self.num_bad_epochs = 0;
+            false
+        } else {
+            self.num_bad_epochs += 1;
+            self.num_bad_epochs >= self.cfg.patience
+        }
+    }
+
+    /// The best metric so far, or `None` if [EarlyStopping::should_stop()] hasn't been called yet.
+    pub fn best(&self) -> Option<f32> {
+        self.best
+    }
+
+    /// The number of epochs in a row without improvement.
+    pub fn num_bad_epochs(&self) -> usize {
+        self.num_bad_epochs
+    }
+
+    /// Forgets the best metric and the number of epochs without improvement.
+    pub fn reset(&mut self) {
+        self.best = None;
+        self.num_bad_epochs = 0;
+    }
+
+    fn is_improvement(&self, metric: f32) -> bool {
+        if metric.is_nan() {
+            return false;
+        }
+        match (self.best, self.cfg.mode) {
+            (None, _) => true,
+            (Some(best), MetricMode::Minimize) => metric < best - self.cfg.min_delta,
+            (Some(best), MetricMode::Maximize) => metric > best + self.cfg.min_delta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(early_stopping: &mut EarlyStopping, metrics: &[f32]) -> std::vec::Vec<bool> {
+        metrics
+            .iter()
+            .map(|&m| early_stopping.should_stop(m))
+            .collect()
+    }
+
+    #[test]
+    fn test_minimize_resets_on_improvement() {
+        let mut es = EarlyStopping::new(EarlyStoppingConfig {
+            patience: 3,
+            ..Default::default()
+        });
+        let stops = run(&mut es, &[1.0, 0.9, 0.95, 0.9, 0.8, 0.85, 0.81, 0.8, 0.8]);
+        assert_eq!(
+            stops,
+            [false, false, false, false, false, false, false, true, true]
+        );
+        assert_eq!(es.best(), Some(0.8));
+        assert_eq!(es.num_bad_epochs(), 4);
+    }
+
+    #[test]
+    fn test_maximize_with_min_delta() {
+        let mut es = EarlyStopping::new(EarlyStoppingConfig {
+            patience: 2,
+            min_delta: 0.05,
+            mode: MetricMode::Maximize,
+        });
+        // 0.54 doesn't improve on 0.5 by more than 0.05, but 0.6 does. 0.64 & 0.62 don't improve on 0.6
+        assert_eq!(
+            run(&mut es, &[0.5, 0.54, 0.6, 0.64, 0.62]),
+            [false, false, false, false, true]
+        );
+        assert_eq!(es.best(), Some(0.6));
+    }
+
+    #[test]
+    fn test_nan_is_not_an_improvement() {
+        let mut es = EarlyStopping::new(EarlyStoppingConfig {
+            patience: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            run(&mut es, &[f32::NAN, 1.0, f32::NAN, f32::NAN]),
+            [false, false, false, true]
+        );
+        assert_eq!(es.best(), Some(1.0));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut es = EarlyStopping::new(EarlyStoppingConfig {
+            patience: 1,
+            ..Default::default()
+        });
+        assert_eq!(run(&mut es, &[1.0, 2.0]), [false, true]);
+        es.reset();
+        assert_eq!(es.best(), None);
+        assert_eq!(run(&mut es, &[3.0, 2.0, 2.5]), [false, false, true]);
+    }
+
+    #[test]
+    #[should_panic = "min_delta must be >= 0.0, found -1"]
+    fn test_negative_min_delta() {
+        EarlyStopping::new(EarlyStoppingConfig {
+            min_delta: -1.0,
+            ..Default::default()
+        });
+    }
+}
@@ -0,0 +1,115 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
+
+/// Decay rate of the variance of the noise, `gamma` from the paper.
+const GAMMA: f32 = 0.55;
+
+/// Adds gaussian noise with variance `eta / (1 + t)^0.55` to gradients, where `t` is the
+/// number of updates so far. From
+/// [Adding Gradient Noise Improves Learning for Very Deep Networks](https://arxiv.org/abs/1511.06807).
+///
+/// The noise of each update comes from a new rng seeded with both `seed` and `t`, so the
+/// random state can be saved and restored with just those two numbers.
+#[derive(Debug, Clone)]
+pub(crate) struct GradientNoise {
+    seed: u64,
+    t: i32,
+    rng: StdRng,
+}
+
+impl GradientNoise {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self::with_step(seed, 0)
+    }
+
+    /// The state after `t` updates of [GradientNoise::new()] with `seed`.
+    pub(crate) fn with_step(seed: u64, t: i32) -> Self {
+        let mut rng_seed = [0u8; 32];
+        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+        rng_seed[8..12].copy_from_slice(&t.to_le_bytes());
+        Self {
+            seed,
+            t,
+            rng: StdRng::from_seed(rng_seed),
+        }
+    }
+
+    /// The seed this was created with.
+    #[cfg(feature = "serde")]
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The number of updates so far.
+    #[cfg(feature = "serde")]
+    pub(crate) fn t(&self) -> i32 {
+        self.t
+    }
+
+    /// Adds noise with variance `eta / (1 + t)^0.55` to every element of `g`.
+    pub(crate) fn add<A: CountElements<Dtype = f32>>(&mut self, eta: f32, g: &mut A) {
+        let std = (eta / (1.0 + self.t as f32).powf(GAMMA)).sqrt();
+        let dist = Normal::new(0.0, std).unwrap_or_else(|_| invalid_eta(eta));
+        for v in flat_mut(g).iter_mut() {
+            *v += dist.sample(&mut self.rng);
+        }
+    }
+
+    /// Moves on to the next update, decaying the variance.
+    pub(crate) fn step(&mut self) {
+        *self = Self::with_step(self.seed, self.t.checked_add(1).unwrap());
+    }
+}
+
+/// Panics if `eta`, the variance of the gradient noise of an optimizer, is negative, infinite or NaN.
+pub(crate) fn check_eta(eta: Option<f32>) {
+    match eta {
+        Some(eta) if !(eta >= 0.0 && eta.is_finite()) => invalid_eta(eta),
+        _ => {}
+    }
+}
+
+fn invalid_eta(eta: f32) -> ! {
+    panic!("gradient_noise must be a finite, non-negative variance, found {eta}")
+}
+
+#[cfg(feature = "std")]
+impl GradientNoise {
+    /// Writes the seed and the number of updates to `{filename_prefix}gradient_noise.npy`.
+    ///
+    /// `.npy` files only store floats here, so this is `[seed >> 32, seed & 0xFFFFFFFF, t]`,
+    /// which are all exact as an `f64`.
+    pub(crate) fn write<W: Write + Seek>(
+        &self,
+        filename_prefix: &str,
+        w: &mut ZipWriter<W>,
+    ) -> ZipResult<()> {
+        let (hi, lo) = (self.seed >> 32, self.seed & 0xFFFF_FFFF);
+        let data = [hi as f64, lo as f64, self.t as f64];
+        npz_fwrite(w, format!("{}gradient_noise.npy", filename_prefix), &data)
+    }
+
+    /// Reads the state written by [GradientNoise::write()].
+    pub(crate) fn read<R: Read + Seek>(
+        &mut self,
+        filename_prefix: &str,
+        r: &mut ZipArchive<R>,
+    ) -> Result<(), NpzError> {
+        let mut data = [0.0f64; 3];
+        npz_fread(
+            r,
+            format!("{}gradient_noise.npy", filename_prefix),
+            &mut data,
+        )?;
+        let seed = ((data[0] as u64) << 32) | data[1] as u64;
+        *self = Self::with_step(seed, data[2] as i32);
+        Ok(())
+    }
+}
@@ -25,13 +25,43 @@
 //! let gradients: Gradients = loss.backward();
 //! opt.update(&mut model, gradients);
 //! ```
+//!
+//! # Checkpointing
+//!
+//! All the optimizers implement [OptimizerState], which lets you save their internal state
+//! (e.g. [Adam]'s moments) along with the model using [save_checkpoint()], and restore
+//! both with [load_checkpoint()]. Resuming from a checkpoint produces the same updates as
+//! if training was never stopped, including the random state of gradient noise
+//! (e.g. [SgdConfig::gradient_noise]). To save only the state of the optimizer, use
+//! [OptimizerState::save()] and [OptimizerState::load()].
+//!
+//! With the `serde` feature, the optimizers also implement [OptimizerStateDict], which converts
+//! their internal state to and from a serializable [StateDict].
+//!
+//! # Early stopping
+//!
+//! [EarlyStopping] tracks a validation metric across epochs, and tells you when to stop training
+//! because it hasn't improved for a number of epochs.
 
 mod adam;
+#[cfg(any(feature = "std", feature = "serde"))]
+mod by_position;
+#[cfg(feature = "std")]
+mod checkpoint;
+mod early_stopping;
+mod gradient_noise;
 mod optimizer;
 mod rmsprop;
 mod sgd;
+#[cfg(feature = "serde")]
+mod state_dict;
 
 pub use adam::*;
+#[cfg(feature = "std")]
+pub use checkpoint::*;
+pub use early_stopping::*;
 pub use optimizer::*;
 pub use rmsprop::*;
 pub use sgd::*;
+#[cfg(feature = "serde")]
+pub use state_dict::*;
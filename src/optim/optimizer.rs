@@ -15,6 +15,39 @@ use crate::prelude::{CanUpdateWithGradients, Gradients};
 ///
 /// 3. Optimizer itself is generic over M, not the update method. This means a single optimizer object
 /// can only work on objects of type `M`. This also requires you to specify the model up front for the optimizer.
+///
+/// # Examples
+///
+/// Training loops can be written generic over the optimizer, and work with [super::Sgd], [super::Adam],
+/// and [super::RMSprop]:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Model = (Linear<3, 4>, ReLU, Linear<4, 2>);
+///
+/// fn train<O: Optimizer<Model>>(
+///     model: &mut Model,
+///     opt: &mut O,
+///     x: &Tensor2D<8, 3>,
+///     y: &Tensor2D<8, 2>,
+/// ) -> f32 {
+///     let loss = mse_loss(model.forward(x.trace()), y);
+///     let loss_value = *loss.data();
+///     opt.update(model, loss.backward());
+///     loss_value
+/// }
+///
+/// let mut rng = rand::thread_rng();
+/// let x: Tensor2D<8, 3> = Tensor2D::randn(&mut rng);
+/// let y: Tensor2D<8, 2> = Tensor2D::randn(&mut rng);
+///
+/// let mut model: Model = Default::default();
+/// model.reset_params(&mut rng);
+/// let mut sgd: Sgd<Model> = Default::default();
+/// train(&mut model, &mut sgd, &x, &y);
+///
+/// let mut adam: Adam<Model> = Default::default();
+/// train(&mut model, &mut adam, &x, &y);
+/// ```
 pub trait Optimizer<M: CanUpdateWithGradients> {
     /// Updates all of `module`'s parameters using `gradients`.
     ///
@@ -1,5 +1,15 @@
+#[cfg(feature = "std")]
+use super::checkpoint::{read_by_position, write_by_position};
+use super::gradient_noise::{check_eta, GradientNoise};
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use std::boxed::Box;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
 use std::marker::PhantomData;
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 /// RMSprop As described in [Hinton, 2012](http://www.cs.toronto.edu/%7Etijmen/csc321/slides/lecture_slides_lec6.pdf).
 ///
@@ -31,6 +41,7 @@ use std::marker::PhantomData;
 ///     eps: 1e-8,
 ///     momentum: Some(0.5),
 ///     centered: false,
+///     gradient_noise: None,
 /// });
 /// ```
 ///
@@ -45,6 +56,7 @@ pub struct RMSprop<M> {
     square_avg: Gradients,
     grad_avg: Gradients,
     gradients: Gradients,
+    noise: GradientNoise,
 
     marker: PhantomData<*const M>,
 }
@@ -67,6 +79,15 @@ pub struct RMSpropConfig {
     /// Whether the avg should be centered by the grad's avg value.
     /// Defaults to `false`.
     pub centered: bool,
+
+    /// Optional variance `eta` of gaussian noise added to each gradient before the update.
+    /// The variance decays as `eta / (1 + t)^0.55`, where `t` is the number of updates so far.
+    /// Defaults to `None`.
+    ///
+    /// The noise is seeded by [RMSprop::new_with_seed()] (or with `0` by [RMSprop::new()]),
+    /// so it's reproducible across runs. Its random state is saved along with the rest of
+    /// the optimizer's state, see [OptimizerState] (and `OptimizerStateDict` with the `serde` feature).
+    pub gradient_noise: Option<f32>,
 }
 
 impl Default for RMSpropConfig {
@@ -77,6 +98,7 @@ impl Default for RMSpropConfig {
             eps: 1e-8,
             momentum: None,
             centered: false,
+            gradient_noise: None,
         }
     }
 }
@@ -89,8 +111,16 @@ impl<M> Default for RMSprop<M> {
 }
 
 impl<M> RMSprop<M> {
-    /// Constructs using hyperparameters from `cfg`.
+    /// Constructs using hyperparameters from `cfg`, seeding [RMSpropConfig::gradient_noise] with `0`.
     pub fn new(cfg: RMSpropConfig) -> Self {
+        Self::new_with_seed(cfg, 0)
+    }
+
+    /// Constructs using hyperparameters from `cfg`, seeding [RMSpropConfig::gradient_noise] with `seed`.
+    ///
+    /// **Panics** if [RMSpropConfig::gradient_noise] is negative, infinite or NaN.
+    pub fn new_with_seed(cfg: RMSpropConfig, seed: u64) -> Self {
+        check_eta(cfg.gradient_noise);
         Self {
             cfg,
             step: 0,
@@ -98,17 +128,21 @@ impl<M> RMSprop<M> {
             square_avg: Default::default(),
             grad_avg: Default::default(),
             gradients: Default::default(),
+            noise: GradientNoise::new(seed),
             marker: PhantomData,
         }
     }
 }
 
 impl<M> GradientProvider for RMSprop<M> {
-    fn gradient<P>(&mut self, p: &P) -> Box<P::Array>
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
     where
         P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
     {
-        let mut g_t = self.gradients.remove(p);
+        let mut g_t = self.gradients.remove(p)?;
+        if let Some(eta) = self.cfg.gradient_noise {
+            self.noise.add(eta, g_t.as_mut());
+        }
 
         let square_avg = self.square_avg.mut_gradient(p);
         if self.step == 0 {
@@ -150,7 +184,7 @@ impl<M> GradientProvider for RMSprop<M> {
             }
             None => P::Device::foreach_m(g_t.as_mut(), &mut |g| *g *= self.cfg.lr),
         }
-        g_t
+        Some(g_t)
     }
 }
 
@@ -159,6 +193,95 @@ impl<M: CanUpdateWithGradients> Optimizer<M> for RMSprop<M> {
         self.gradients = gradients;
         module.update(self);
         self.step += 1;
+        self.noise.step();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: CanUpdateWithGradients> OptimizerState<M> for RMSprop<M> {
+    /// Writes the step to `step.npy`, the random state of the gradient noise to
+    /// `gradient_noise.npy`, and the state of the `i`th parameter of `module` to
+    /// `momentums.{i}.npy`, `square_avg.{i}.npy` and `grad_avg.{i}.npy`.
+    fn write_state<W: Write + Seek>(
+        &self,
+        module: &mut M,
+        filename_prefix: &str,
+        w: &mut ZipWriter<W>,
+    ) -> ZipResult<()> {
+        npz_fwrite(
+            w,
+            format!("{}step.npy", filename_prefix),
+            &(self.step as f64),
+        )?;
+        self.noise.write(filename_prefix, w)?;
+        let momentums = format!("{}momentums.", filename_prefix);
+        write_by_position(&self.momentums, module, &momentums, w)?;
+        let square_avg = format!("{}square_avg.", filename_prefix);
+        write_by_position(&self.square_avg, module, &square_avg, w)?;
+        let grad_avg = format!("{}grad_avg.", filename_prefix);
+        write_by_position(&self.grad_avg, module, &grad_avg, w)
+    }
+
+    fn read_state<R: Read + Seek>(
+        &mut self,
+        module: &mut M,
+        filename_prefix: &str,
+        r: &mut ZipArchive<R>,
+    ) -> Result<(), NpzError> {
+        let mut step = 0.0f64;
+        npz_fread(r, format!("{}step.npy", filename_prefix), &mut step)?;
+        self.step = step as usize;
+        self.noise.read(filename_prefix, r)?;
+        let momentums = format!("{}momentums.", filename_prefix);
+        read_by_position(&mut self.momentums, module, &momentums, r)?;
+        let square_avg = format!("{}square_avg.", filename_prefix);
+        read_by_position(&mut self.square_avg, module, &square_avg, r)?;
+        let grad_avg = format!("{}grad_avg.", filename_prefix);
+        read_by_position(&mut self.grad_avg, module, &grad_avg, r)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<M: CanUpdateWithGradients> OptimizerStateDict<M> for RMSprop<M> {
+    /// Stores the step as [StateDict::step], the random state of the gradient noise as
+    /// [StateDict::gradient_noise], and the state in the buffers `"momentums"`, `"square_avg"`
+    /// and `"grad_avg"`.
+    fn state_dict(&self, module: &mut M) -> StateDict {
+        use super::state_dict::gather_by_position;
+        StateDict {
+            step: self.step as u64,
+            buffers: vec![
+                (
+                    "momentums".into(),
+                    gather_by_position(&self.momentums, module),
+                ),
+                (
+                    "square_avg".into(),
+                    gather_by_position(&self.square_avg, module),
+                ),
+                (
+                    "grad_avg".into(),
+                    gather_by_position(&self.grad_avg, module),
+                ),
+            ],
+            gradient_noise: (&self.noise).into(),
+        }
+    }
+
+    fn load_state_dict(&mut self, module: &mut M, state: &StateDict) -> Result<(), StateDictError> {
+        use super::state_dict::scatter_by_position;
+        let mut momentums = Default::default();
+        scatter_by_position(&mut momentums, module, state, "momentums")?;
+        let mut square_avg = Default::default();
+        scatter_by_position(&mut square_avg, module, state, "square_avg")?;
+        let mut grad_avg = Default::default();
+        scatter_by_position(&mut grad_avg, module, state, "grad_avg")?;
+        self.step = state.step as usize;
+        self.momentums = momentums;
+        self.square_avg = square_avg;
+        self.grad_avg = grad_avg;
+        self.noise = state.gradient_noise.into();
+        Ok(())
     }
 }
 
@@ -185,6 +308,7 @@ mod tests {
             eps: 1e-8,
             momentum: None,
             centered: false,
+            gradient_noise: None,
         };
         const EXPECTED: [[f32; 5]; 5] = [
             [0.9997892, 0.98245883, 0.9703907, 0.9683808, 0.96837723],
@@ -204,6 +328,7 @@ mod tests {
             eps: 1e-8,
             momentum: Some(0.9),
             centered: false,
+            gradient_noise: None,
         };
         const EXPECTED: [[f32; 5]; 5] = [
             [0.9997892, 0.98245883, 0.9703907, 0.9683808, 0.96837723],
@@ -223,6 +348,7 @@ mod tests {
             eps: 1e-8,
             momentum: None,
             centered: false,
+            gradient_noise: None,
         };
         const EXPECTED: [[f32; 5]; 5] = [
             [0.99971724, 0.9873509, 0.9859671, 0.985858, 0.98585784],
@@ -242,6 +368,7 @@ mod tests {
             eps: 1e-2,
             momentum: None,
             centered: false,
+            gradient_noise: None,
         };
         const EXPECTED: [[f32; 5]; 5] = [
             [0.9997904, 0.98252594, 0.97041094, 0.9683808, 0.96837723],
@@ -261,6 +388,7 @@ mod tests {
             eps: 1e-8,
             momentum: None,
             centered: true,
+            gradient_noise: None,
         };
         const EXPECTED: [[f32; 5]; 5] = [
             [0.9997892, 0.98218256, 0.96900064, 0.9666708, 0.9666667],
@@ -1,5 +1,13 @@
+#[cfg(feature = "std")]
+use super::checkpoint::{read_by_position, write_by_position};
+use super::gradient_noise::{check_eta, GradientNoise};
 use crate::prelude::*;
+use std::boxed::Box;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, Write};
 use std::marker::PhantomData;
+#[cfg(feature = "std")]
+use zip::{result::ZipResult, ZipArchive, ZipWriter};
 
 /// Implementation of Stochastic Gradient Descent. Based on [pytorch's implementation](https://pytorch.org/docs/stable/generated/torch.optim.SGD.html)
 ///
@@ -22,6 +30,7 @@ use std::marker::PhantomData;
 /// let mut opt: Sgd<Model> = Sgd::new(SgdConfig {
 ///     lr: 1e-3,
 ///     momentum: Some(Momentum::Classic(0.5)),
+///     gradient_noise: None,
 /// });
 /// ```
 ///
@@ -33,6 +42,7 @@ pub struct Sgd<M> {
 
     velocity: Gradients,
     gradients: Gradients,
+    noise: GradientNoise,
 
     marker: PhantomData<*const M>,
 }
@@ -44,7 +54,8 @@ pub struct Sgd<M> {
 /// # use dfdx::prelude::*;
 /// SgdConfig {
 ///     lr: 1e-1,
-///     momentum: None
+///     momentum: None,
+///     gradient_noise: None,
 /// };
 /// ```
 ///
@@ -53,7 +64,8 @@ pub struct Sgd<M> {
 /// # use dfdx::prelude::*;
 /// SgdConfig {
 ///     lr: 1e-2,
-///     momentum: Some(Momentum::Classic(0.5))
+///     momentum: Some(Momentum::Classic(0.5)),
+///     gradient_noise: None,
 /// };
 /// ```
 ///
@@ -62,7 +74,8 @@ pub struct Sgd<M> {
 /// # use dfdx::prelude::*;
 /// SgdConfig {
 ///     lr: 1e-3,
-///     momentum: Some(Momentum::Nesterov(0.25))
+///     momentum: Some(Momentum::Nesterov(0.25)),
+///     gradient_noise: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Copy)]
@@ -72,6 +85,15 @@ pub struct SgdConfig {
 
     /// Optional momentum. Defaults to `None`.
     pub momentum: Option<Momentum>,
+
+    /// Optional variance `eta` of gaussian noise added to each gradient before the update.
+    /// The variance decays as `eta / (1 + t)^0.55`, where `t` is the number of updates so far.
+    /// Defaults to `None`.
+    ///
+    /// The noise is seeded by [Sgd::new_with_seed()] (or with `0` by [Sgd::new()]),
+    /// so it's reproducible across runs. Its random state is saved along with the rest of
+    /// the optimizer's state, see [OptimizerState] (and `OptimizerStateDict` with the `serde` feature).
+    pub gradient_noise: Option<f32>,
 }
 
 impl Default for SgdConfig {
@@ -79,6 +101,7 @@ impl Default for SgdConfig {
         Self {
             lr: 1e-2,
             momentum: None,
+            gradient_noise: None,
         }
     }
 }
@@ -101,23 +124,35 @@ impl<M> Default for Sgd<M> {
 }
 
 impl<M> Sgd<M> {
-    /// Constructs using hyperparameters from `cfg`
+    /// Constructs using hyperparameters from `cfg`, seeding [SgdConfig::gradient_noise] with `0`.
     pub fn new(cfg: SgdConfig) -> Self {
+        Self::new_with_seed(cfg, 0)
+    }
+
+    /// Constructs using hyperparameters from `cfg`, seeding [SgdConfig::gradient_noise] with `seed`.
+    ///
+    /// **Panics** if [SgdConfig::gradient_noise] is negative, infinite or NaN.
+    pub fn new_with_seed(cfg: SgdConfig, seed: u64) -> Self {
+        check_eta(cfg.gradient_noise);
         Self {
             cfg,
             velocity: Default::default(),
             gradients: Default::default(),
+            noise: GradientNoise::new(seed),
             marker: PhantomData,
         }
     }
 }
 
 impl<M> GradientProvider for Sgd<M> {
-    fn gradient<P>(&mut self, p: &P) -> Box<P::Array>
+    fn gradient<P>(&mut self, p: &P) -> Option<Box<P::Array>>
     where
         P: HasUniqueId + HasArrayType<Dtype = f32> + HasDevice,
     {
-        let mut g_t = self.gradients.remove(p);
+        let mut g_t = self.gradients.remove(p)?;
+        if let Some(eta) = self.cfg.gradient_noise {
+            self.noise.add(eta, g_t.as_mut());
+        }
         match self.cfg.momentum {
             Some(Momentum::Classic(u)) => {
                 let v_t = self.velocity.mut_gradient(p);
@@ -135,7 +170,7 @@ impl<M> GradientProvider for Sgd<M> {
             }
             None => P::Device::foreach_m(g_t.as_mut(), &mut |g| *g *= self.cfg.lr),
         }
-        g_t
+        Some(g_t)
     }
 }
 
@@ -143,6 +178,68 @@ impl<M: CanUpdateWithGradients> Optimizer<M> for Sgd<M> {
     fn update(&mut self, module: &mut M, gradients: Gradients) {
         self.gradients = gradients;
         module.update(self);
+        self.noise.step();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: CanUpdateWithGradients> OptimizerState<M> for Sgd<M> {
+    /// Writes the velocity of the `i`th parameter of `module` to `velocity.{i}.npy`, and
+    /// the random state of the gradient noise to `gradient_noise.npy`.
+    fn write_state<W: Write + Seek>(
+        &self,
+        module: &mut M,
+        filename_prefix: &str,
+        w: &mut ZipWriter<W>,
+    ) -> ZipResult<()> {
+        self.noise.write(filename_prefix, w)?;
+        write_by_position(
+            &self.velocity,
+            module,
+            &format!("{}velocity.", filename_prefix),
+            w,
+        )
+    }
+
+    fn read_state<R: Read + Seek>(
+        &mut self,
+        module: &mut M,
+        filename_prefix: &str,
+        r: &mut ZipArchive<R>,
+    ) -> Result<(), NpzError> {
+        self.noise.read(filename_prefix, r)?;
+        read_by_position(
+            &mut self.velocity,
+            module,
+            &format!("{}velocity.", filename_prefix),
+            r,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<M: CanUpdateWithGradients> OptimizerStateDict<M> for Sgd<M> {
+    /// Stores the velocity in the buffer `"velocity"`, and the random state of the gradient
+    /// noise as [StateDict::gradient_noise].
+    fn state_dict(&self, module: &mut M) -> StateDict {
+        use super::state_dict::gather_by_position;
+        StateDict {
+            step: 0,
+            buffers: vec![(
+                "velocity".into(),
+                gather_by_position(&self.velocity, module),
+            )],
+            gradient_noise: (&self.noise).into(),
+        }
+    }
+
+    fn load_state_dict(&mut self, module: &mut M, state: &StateDict) -> Result<(), StateDictError> {
+        use super::state_dict::scatter_by_position;
+        let mut velocity = Default::default();
+        scatter_by_position(&mut velocity, module, state, "velocity")?;
+        self.velocity = velocity;
+        self.noise = state.gradient_noise.into();
+        Ok(())
     }
 }
 
@@ -156,6 +253,7 @@ mod tests {
         let mut sgd = Sgd::new(SgdConfig {
             lr: 1.0,
             momentum: None,
+            gradient_noise: None,
         });
 
         let mut pred: Tensor1D<5> = Tensor1D::zeros();
@@ -190,11 +288,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sgd_skips_params_without_gradients() {
+        let mut sgd = Sgd::new(SgdConfig {
+            lr: 1e-2,
+            momentum: Some(Momentum::Classic(0.5)),
+            ..Default::default()
+        });
+        let mut model: (Tensor1D<3>, Tensor1D<3>) = (Tensor1D::ones(), Tensor1D::ones());
+        let gradients = model.1.trace().mean().backward();
+        sgd.update(&mut model, gradients);
+        assert_eq!(model.0.data(), &[1.0; 3]);
+        assert_ne!(model.1.data(), &[1.0; 3]);
+        assert!(sgd.velocity.maybe_ref_gradient(&model.0).is_none());
+    }
+
     #[test]
     fn test_sgd_classic_momentum() {
         let mut sgd = Sgd::new(SgdConfig {
             lr: 1e-2,
             momentum: Some(Momentum::Classic(0.5)),
+            gradient_noise: None,
         });
 
         let mut t: Tensor1D<5> = Tensor1D::ones();
@@ -219,6 +333,7 @@ mod tests {
         let mut sgd = Sgd::new(SgdConfig {
             lr: 1e-2,
             momentum: Some(Momentum::Nesterov(0.5)),
+            gradient_noise: None,
         });
 
         let mut t: Tensor1D<5> = Tensor1D::ones();
@@ -264,4 +379,80 @@ mod tests {
         assert!(model_0.4.weight.data() != model_1.4.weight.data());
         assert!(model_0.4.bias.data() != model_1.4.bias.data());
     }
+
+    fn train_with_noise(seed: u64, gradient_noise: Option<f32>) -> [f32; 5] {
+        let mut sgd = Sgd::new_with_seed(
+            SgdConfig {
+                lr: 1e-2,
+                momentum: Some(Momentum::Classic(0.5)),
+                gradient_noise,
+            },
+            seed,
+        );
+        let mut t: Tensor1D<5> = Tensor1D::ones();
+        let rate = Tensor1D::new([0.1, 1.0, 2.0, 10.0, 100.0]);
+        for _ in 0..5 {
+            let gradients = (t.trace() * &rate).mean().backward();
+            sgd.update(&mut t, gradients);
+        }
+        *t.data()
+    }
+
+    #[test]
+    fn test_sgd_gradient_noise_reproducible() {
+        let a = train_with_noise(0, Some(0.1));
+        assert_eq!(a, train_with_noise(0, Some(0.1)));
+        assert_ne!(a, train_with_noise(1, Some(0.1)));
+        assert_ne!(a, train_with_noise(0, None));
+
+        // zero variance adds exactly zero
+        assert_eq!(train_with_noise(0, Some(0.0)), train_with_noise(0, None));
+    }
+
+    #[test]
+    #[should_panic = "gradient_noise must be a finite, non-negative variance, found -0.1"]
+    fn test_sgd_negative_gradient_noise() {
+        let _: Sgd<Tensor1D<5>> = Sgd::new(SgdConfig {
+            gradient_noise: Some(-0.1),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic = "gradient_noise must be a finite, non-negative variance, found NaN"]
+    fn test_sgd_nan_gradient_noise() {
+        let _: Sgd<Tensor1D<5>> = Sgd::new(SgdConfig {
+            gradient_noise: Some(f32::NAN),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_sgd_gradient_noise_decays() {
+        let mut sgd = Sgd::new(SgdConfig {
+            lr: 1.0,
+            momentum: None,
+            gradient_noise: Some(4.0),
+        });
+        let mut t: Tensor1D<10000> = Tensor1D::zeros();
+        let mut expected_var = 4.0;
+        for i in 0..3 {
+            // the gradient is zero, so the update is only noise
+            let before = *t.data();
+            let gradients = (t.trace() * 0.0).sum().backward();
+            sgd.update(&mut t, gradients);
+
+            let diffs: Vec<f32> = t
+                .data()
+                .iter()
+                .zip(before.iter())
+                .map(|(a, b)| a - b)
+                .collect();
+            let mean = diffs.iter().sum::<f32>() / 10000.0;
+            let var = diffs.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / 10000.0;
+            assert!(mean.abs() < 0.1, "{mean}");
+            assert!((var - expected_var).abs() < 0.1 * expected_var, "{var}");
+            expected_var = 4.0 / (i as f32 + 2.0).powf(0.55);
+        }
+    }
 }
@@ -0,0 +1,331 @@
+use super::by_position::{fill_by_position, visit_by_position};
+use super::gradient_noise::GradientNoise;
+use crate::gradients::{CanUpdateWithGradients, Gradients};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+
+/// The internal state of an optimizer, which can be serialized with [serde].
+///
+/// Like [super::OptimizerState], the per parameter buffers are stored in the order
+/// [CanUpdateWithGradients::update] visits the parameters of the module, instead of by
+/// [crate::unique_id::UniqueId], so that they can be loaded into a different instance of the module.
+///
+/// See [OptimizerStateDict].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDict {
+    /// The step counter of the optimizer, e.g. [super::Adam]'s timestep. `0` for optimizers without one.
+    pub step: u64,
+
+    /// The named buffers of the optimizer (e.g. `"moment1"`), with the elements of each parameter
+    /// in row major order.
+    pub buffers: Vec<(String, Vec<Vec<f32>>)>,
+
+    /// The random state of the gradient noise (e.g. [super::SgdConfig::gradient_noise]).
+    #[serde(default)]
+    pub gradient_noise: GradientNoiseState,
+}
+
+/// The random state of the gradient noise of an optimizer, see [StateDict::gradient_noise].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GradientNoiseState {
+    /// The seed the optimizer was created with, e.g. by [super::Sgd::new_with_seed()].
+    pub seed: u64,
+
+    /// The number of updates so far.
+    pub step: u64,
+}
+
+impl From<&GradientNoise> for GradientNoiseState {
+    fn from(noise: &GradientNoise) -> Self {
+        Self {
+            seed: noise.seed(),
+            step: noise.t() as u64,
+        }
+    }
+}
+
+impl From<GradientNoiseState> for GradientNoise {
+    fn from(state: GradientNoiseState) -> Self {
+        Self::with_step(state.seed, state.step as i32)
+    }
+}
+
+impl StateDict {
+    /// Returns the buffer named `name`.
+    pub fn buffer(&self, name: &str) -> Result<&[Vec<f32>], StateDictError> {
+        self.buffers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, b)| b.as_slice())
+            .ok_or_else(|| StateDictError::MissingBuffer(name.into()))
+    }
+}
+
+/// Error that can happen while loading a [StateDict] with [OptimizerStateDict::load_state_dict()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateDictError {
+    /// The state has no buffer with this name.
+    MissingBuffer(String),
+
+    /// The buffer `name` has `found` entries, but the module has `expected` parameters.
+    NumParams {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+
+    /// The entry of the `position`th parameter in buffer `name` has `found` elements, but
+    /// the parameter has `expected` elements.
+    NumElements {
+        name: String,
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for StateDictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingBuffer(name) => write!(f, "missing buffer `{name}`"),
+            Self::NumParams {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "buffer `{name}` has {found} parameters, expected {expected}"
+            ),
+            Self::NumElements {
+                name,
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "parameter {position} of buffer `{name}` has {found} elements, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateDictError {}
+
+/// An optimizer whose internal state can be converted to and from a [StateDict], for example
+/// to serialize it along with the model using [serde]. Requires the `serde` feature.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// type Model = (Linear<5, 10>, Linear<10, 5>);
+/// let mut model: Model = Default::default();
+/// let opt: Adam<Model> = Default::default();
+/// let state: StateDict = opt.state_dict(&mut model);
+///
+/// let mut opt: Adam<Model> = Default::default();
+/// opt.load_state_dict(&mut model, &state).unwrap();
+/// ```
+pub trait OptimizerStateDict<M> {
+    /// Returns the internal state of the optimizer for all of `module`'s parameters.
+    ///
+    /// `module` is only mutably borrowed to visit its parameters with
+    /// [CanUpdateWithGradients::update()], its parameters are left unchanged.
+    fn state_dict(&self, module: &mut M) -> StateDict;
+
+    /// Replaces the internal state of the optimizer for all of `module`'s parameters with `state`.
+    ///
+    /// `module` does not have to be the same object that was passed to [OptimizerStateDict::state_dict()],
+    /// it only needs to have the same structure.
+    fn load_state_dict(&mut self, module: &mut M, state: &StateDict) -> Result<(), StateDictError>;
+}
+
+/// Collects the entries of `gradients` for each parameter of `module`, in the order they are visited.
+///
+/// Parameters without an entry in `gradients` are all zeros. The parameters
+/// of `module` are left unchanged.
+pub(crate) fn gather_by_position<M: CanUpdateWithGradients>(
+    gradients: &Gradients,
+    module: &mut M,
+) -> Vec<Vec<f32>> {
+    let mut buffer = Vec::new();
+    let result: Result<(), Infallible> = visit_by_position(gradients, module, |_, data| {
+        buffer.push(data.to_vec());
+        Ok(())
+    });
+    result.unwrap();
+    buffer
+}
+
+/// Sets the entries of `gradients` for each parameter of `module` from the buffer `name` of `state`.
+/// This is the inverse of [gather_by_position()].
+///
+/// `gradients` is only changed if the buffer has the right number of elements for every parameter.
+pub(crate) fn scatter_by_position<M: CanUpdateWithGradients>(
+    gradients: &mut Gradients,
+    module: &mut M,
+    state: &StateDict,
+    name: &str,
+) -> Result<(), StateDictError> {
+    let buffer = state.buffer(name)?;
+    let mut expected = Vec::new();
+    let result: Result<(), Infallible> = visit_by_position(&Default::default(), module, |_, e| {
+        expected.push(e.len());
+        Ok(())
+    });
+    result.unwrap();
+    if expected.len() != buffer.len() {
+        return Err(StateDictError::NumParams {
+            name: name.into(),
+            expected: expected.len(),
+            found: buffer.len(),
+        });
+    }
+    for (position, (&e, b)) in expected.iter().zip(buffer.iter()).enumerate() {
+        if e != b.len() {
+            return Err(StateDictError::NumElements {
+                name: name.into(),
+                position,
+                expected: e,
+                found: b.len(),
+            });
+        }
+    }
+    fill_by_position(gradients, module, |position, data| {
+        data.copy_from_slice(&buffer[position]);
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    type Model = (Linear<3, 5>, ReLU, Repeated<Linear<5, 5>, 2>, Linear<5, 2>);
+
+    fn train_step<O: Optimizer<Model>>(model: &mut Model, opt: &mut O, x: &Tensor2D<4, 3>) {
+        let y = model.forward(x.trace());
+        let loss = mse_loss(y, &Tensor2D::ones());
+        opt.update(model, loss.backward());
+    }
+
+    fn test_bincode_resume_matches<O, F>(make_opt: F)
+    where
+        O: Optimizer<Model> + OptimizerStateDict<Model>,
+        F: Fn() -> O,
+    {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let mut opt = make_opt();
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+        }
+
+        let state = opt.state_dict(&mut model);
+        let bytes = bincode::serialize(&(&model, &state)).unwrap();
+        let (mut loaded, state): (Model, StateDict) = bincode::deserialize(&bytes).unwrap();
+        let mut loaded_opt = make_opt();
+        loaded_opt.load_state_dict(&mut loaded, &state).unwrap();
+        assert_eq!(loaded_opt.state_dict(&mut loaded), state);
+
+        for _ in 0..3 {
+            train_step(&mut model, &mut opt, &x);
+            train_step(&mut loaded, &mut loaded_opt, &x);
+            assert_eq!(model.0.weight.data(), loaded.0.weight.data());
+            assert_eq!(
+                model.2.modules[1].bias.data(),
+                loaded.2.modules[1].bias.data()
+            );
+            assert_eq!(model.3.weight.data(), loaded.3.weight.data());
+        }
+    }
+
+    #[test]
+    fn test_adam_bincode_resume_matches() {
+        test_bincode_resume_matches::<Adam<Model>, _>(Default::default);
+    }
+
+    #[test]
+    fn test_sgd_bincode_resume_matches() {
+        test_bincode_resume_matches(|| {
+            Sgd::new(SgdConfig {
+                lr: 1e-2,
+                momentum: Some(Momentum::Nesterov(0.9)),
+                gradient_noise: None,
+            })
+        });
+    }
+
+    #[test]
+    fn test_rmsprop_bincode_resume_matches() {
+        test_bincode_resume_matches(|| {
+            RMSprop::new(RMSpropConfig {
+                momentum: Some(0.5),
+                centered: true,
+                ..Default::default()
+            })
+        });
+    }
+
+    #[test]
+    fn test_gradient_noise_state_is_restored() {
+        let cfg = AdamConfig {
+            gradient_noise: Some(0.1),
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let mut opt: Adam<Model> = Adam::new_with_seed(cfg, 5);
+        for _ in 0..2 {
+            train_step(&mut model, &mut opt, &x);
+        }
+
+        let state = opt.state_dict(&mut model);
+        assert_eq!(
+            state.gradient_noise,
+            GradientNoiseState { seed: 5, step: 2 }
+        );
+        let mut loaded = model.clone();
+        let mut loaded_opt: Adam<Model> = Adam::new(cfg);
+        loaded_opt.load_state_dict(&mut loaded, &state).unwrap();
+        train_step(&mut model, &mut opt, &x);
+        train_step(&mut loaded, &mut loaded_opt, &x);
+        assert_eq!(model.0.weight.data(), loaded.0.weight.data());
+    }
+
+    #[test]
+    fn test_load_state_dict_errors() {
+        let mut model: Model = Default::default();
+        let opt: Adam<Model> = Default::default();
+        let mut state = opt.state_dict(&mut model);
+        assert_eq!(state.step, 0);
+        assert_eq!(state.buffer("moment1").unwrap().len(), 8);
+
+        let mut small: (Linear<3, 5>, Linear<5, 2>) = Default::default();
+        let mut small_opt: Adam<_> = Default::default();
+        let err = small_opt.load_state_dict(&mut small, &state).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "buffer `moment1` has 8 parameters, expected 4"
+        );
+
+        let mut wrong: (Linear<3, 5>, ReLU, Repeated<Linear<5, 5>, 2>, Linear<5, 3>) =
+            Default::default();
+        let mut wrong_opt: Adam<_> = Default::default();
+        let err = wrong_opt.load_state_dict(&mut wrong, &state).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parameter 6 of buffer `moment1` has 10 elements, expected 15"
+        );
+
+        state.buffers.retain(|(name, _)| name != "moment2");
+        let mut opt: Adam<Model> = Default::default();
+        let err = opt.load_state_dict(&mut model, &state).unwrap_err();
+        assert_eq!(err, StateDictError::MissingBuffer("moment2".into()));
+    }
+}
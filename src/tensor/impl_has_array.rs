@@ -21,6 +21,14 @@ impl<$(const $Vs: usize, )* H> HasArrayData for $typename<$($Vs, )* H> {
     /// Returns a mutable reference to the underlying array.
     fn mut_data(&mut self) -> &mut Self::Array { std::rc::Rc::make_mut(&mut self.data) }
 }
+
+impl<$(const $Vs: usize, )* H> $typename<$($Vs, )* H> {
+    /// Returns `true` if any element is NaN.
+    pub fn has_nan(&self) -> bool { flat(self.data()).iter().any(|v| v.is_nan()) }
+
+    /// Returns `true` if any element is positive or negative infinity.
+    pub fn has_inf(&self) -> bool { flat(self.data()).iter().any(|v| v.is_infinite()) }
+}
     };
 }
 
@@ -4,12 +4,19 @@ use std::marker::PhantomData;
 /// A fake tensor that holds a [UniqueId] and a type `T` that is [HasArrayType].
 /// This is created and stored in [GradientTape] operations to access gradient data
 /// for a tensor that the [GradientTape] doesn't have ownership of.
-#[derive(Clone, Copy)]
 pub struct PhantomTensor<T> {
     id: UniqueId,
     marker: PhantomData<*const T>,
 }
 
+impl<T> Clone for PhantomTensor<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for PhantomTensor<T> {}
+
 impl<T> HasUniqueId for PhantomTensor<T> {
     fn id(&self) -> &UniqueId {
         &self.id
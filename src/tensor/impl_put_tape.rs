@@ -1,6 +1,24 @@
 use super::*;
 use crate::gradients::Tape;
 
+/// Puts a [Tape] into a tensor, replacing whatever tape it had. This is the inverse of
+/// [Tensor::split_tape()], and the [UniqueId](crate::unique_id::UniqueId) of the tensor is kept,
+/// so splitting a tape off and putting it back doesn't affect the gradients.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let y = x.trace() * 2.0;
+/// let (y, tape): (Tensor1D<3>, OwnedTape) = y.split_tape();
+/// let y: Tensor1D<3, OwnedTape> = y.put_tape(tape);
+/// let gradients = y.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&x), &[2.0; 3]);
+/// ```
+///
+/// The recorded operations refer to tensors by their id, so the tape has to be put back into the
+/// tensor it was split from (or a tensor with the same id). To move a tape onto a different
+/// tensor, use [transfer_tape()](crate::tensor_ops::transfer_tape()).
 pub trait PutTape<H: Tape> {
     type Output;
     fn put_tape(self, tape: H) -> Self::Output;
@@ -0,0 +1,75 @@
+use super::structs::*;
+use crate::arrays::HasArrayType;
+use crate::numpy::NumpyShape;
+use crate::prelude::{HasArrayData, NoneTape, TensorCreator};
+use crate::tensor_ops::{flat, flat_mut};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H> Serialize for $typename<$($Vs, )* H> {
+    /// Serializes the shape and the elements in row major order, but not the
+    /// [crate::unique_id::UniqueId] or the tape.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let shape = <Self as HasArrayType>::Array::shape();
+        (shape, flat(self.data())).serialize(serializer)
+    }
+}
+
+impl<'de, $(const $Vs: usize, )*> Deserialize<'de> for $typename<$($Vs, )* NoneTape> {
+    /// Deserializes into a tensor with a new [crate::unique_id::UniqueId].
+    ///
+    /// Returns an error if the serialized shape is different from the shape of `Self`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (shape, data): (Vec<usize>, Vec<f32>) = Deserialize::deserialize(deserializer)?;
+        let expected = <Self as HasArrayType>::Array::shape();
+        if shape != expected {
+            return Err(D::Error::custom(format!(
+                "tensor has shape {shape:?}, expected {expected:?}"
+            )));
+        }
+        let mut t = Self::zeros();
+        let t_data = flat_mut(t.mut_data());
+        if data.len() != t_data.len() {
+            return Err(D::Error::invalid_length(data.len(), &"the number of elements of the shape"));
+        }
+        t_data.copy_from_slice(&data);
+        Ok(t)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::unique_id::HasUniqueId;
+
+    #[test]
+    fn test_tensor_serde_roundtrip() {
+        let t: Tensor3D<2, 1, 3> = Tensor3D::new([[[1.0, 2.0, 3.0]], [[-4.0, 0.5, 6.0]]]);
+        let bytes = bincode::serialize(&t.trace()).unwrap();
+        let loaded: Tensor3D<2, 1, 3> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.data(), t.data());
+        assert_ne!(loaded.id(), t.id());
+
+        let t = Tensor0D::new(-1.5);
+        let loaded: Tensor0D = bincode::deserialize(&bincode::serialize(&t).unwrap()).unwrap();
+        assert_eq!(loaded.data(), &-1.5);
+    }
+
+    #[test]
+    fn test_tensor_deserialize_wrong_shape() {
+        let t: Tensor2D<2, 3> = Tensor2D::ones();
+        let bytes = bincode::serialize(&t).unwrap();
+        let err = bincode::deserialize::<Tensor2D<3, 2>>(&bytes).unwrap_err();
+        assert_eq!(err.to_string(), "tensor has shape [2, 3], expected [3, 2]");
+        assert!(bincode::deserialize::<Tensor1D<6>>(&bytes).is_err());
+    }
+}
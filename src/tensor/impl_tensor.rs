@@ -36,10 +36,79 @@ pub trait Tensor:
     type ReducingIndices: CountElements<Dtype = usize>;
 
     /// Removes whatever Tape this tensor has and returns itself without a tape.
+    /// Use [PutTape::put_tape()] to put it back.
     fn split_tape(self) -> (Self::NoTape, Self::Tape);
 
+    /// A reference to the [Tape] this tensor owns. Useful for inspecting what operations were
+    /// recorded, e.g. with [OwnedTape::op_names()].
+    fn tape(&self) -> &Self::Tape;
+
     /// Clones the data & [UniqueId] of this tensor and returns something with [NoneTape].
     fn duplicate(&self) -> Self::NoTape;
+
+    /// Clones the data of this tensor into a new tensor with [NoneTape] and a **new** [UniqueId].
+    /// Unlike [Tensor::duplicate()], the result is completely disconnected from `self`: gradients
+    /// are never recorded for it, and using it in an operation never affects the gradient of `self`.
+    /// This is useful for building targets from predictions (e.g. in self-distillation).
+    ///
+    /// The tape of `self` is left untouched. To consume `self` and drop its tape instead,
+    /// use [Tensor::detach()].
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let pred = x.trace() * 2.0;
+    /// let target = pred.detach_and_clone();
+    /// let loss = mse_loss(pred, &target);
+    /// let gradients = loss.backward();
+    /// assert_eq!(gradients.ref_gradient(&x), &[0.0; 3]);
+    /// ```
+    fn detach_and_clone(&self) -> Self::NoTape;
+
+    /// Stops gradients from flowing through the result: returns a tensor with the same data,
+    /// a **new** [UniqueId] and [NoneTape], along with the tape of `self`. The data is not copied.
+    ///
+    /// This is different from [Tensor::split_tape()], which keeps the [UniqueId] of `self`. Since
+    /// gradients are stored by [UniqueId], using the result of [Tensor::split_tape()] in an operation
+    /// still adds to the gradient of `self`, and flows back into everything `self` was computed from.
+    /// The result of this never has a gradient, so the gradient of `self` is zero.
+    ///
+    /// The tape is returned so it can be put back into another tensor with [PutTape::put_tape()], which
+    /// keeps all the other operations recorded on it. Dropping it (see [Tensor::detach()]) loses them.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let (a, tape) = (x.trace() * 2.0).detach_with_tape();
+    /// let y = add(x.duplicate().put_tape(tape), &a);
+    /// let gradients = y.sum().backward();
+    /// // no gradient flows back through `a`, so it is only the gradient of the `x` in `add`
+    /// assert_eq!(gradients.ref_gradient(&x), &[1.0; 3]);
+    ///
+    /// // with `split_tape()`, the gradient flows through `a` into `x` too
+    /// let (a, tape) = (x.trace() * 2.0).split_tape();
+    /// let y = add(x.duplicate().put_tape(tape), &a);
+    /// let gradients = y.sum().backward();
+    /// assert_eq!(gradients.ref_gradient(&x), &[3.0; 3]);
+    /// ```
+    fn detach_with_tape(self) -> (Self::NoTape, Self::Tape);
+
+    /// Like [Tensor::detach_with_tape()], but **drops** the tape of `self`, along with all the
+    /// operations recorded on it. Use this when `self` is only needed as a constant, e.g. for the
+    /// output of a target network in DQN.
+    ///
+    /// This is [Tensor::detach_and_clone()], but consuming `self`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let target = (x.trace() * 2.0).detach();
+    /// assert_eq!(target.data(), &[2.0, 4.0, 6.0]);
+    /// ```
+    fn detach(self) -> Self::NoTape;
 }
 
 macro_rules! tensor_impl {
@@ -59,6 +128,10 @@ impl<$(const $Vs: usize, )* H: Tape> Tensor for $struct<$($Vs, )* H> {
         )
     }
 
+    fn tape(&self) -> &Self::Tape {
+        &self.tape
+    }
+
     fn duplicate(&self) -> Self::NoTape {
         Self::NoTape {
             id: self.id,
@@ -66,6 +139,28 @@ impl<$(const $Vs: usize, )* H: Tape> Tensor for $struct<$($Vs, )* H> {
             tape: Default::default(),
         }
     }
+
+    fn detach_and_clone(&self) -> Self::NoTape {
+        Self::NoTape {
+            id: unique_id(),
+            data: self.data.clone(),
+            tape: Default::default(),
+        }
+    }
+
+    fn detach_with_tape(self) -> (Self::NoTape, Self::Tape) {
+        let (t, mut tape) = self.split_tape();
+        let detached = t.detach_and_clone();
+        // nothing will add to the gradient of `t`, but the operations that created it need one
+        tape.add_backward_op_named("detach", move |grads| {
+            grads.mut_gradient(&t);
+        });
+        (detached, tape)
+    }
+
+    fn detach(self) -> Self::NoTape {
+        self.detach_and_clone()
+    }
 }
 
 impl<$(const $Vs: usize, )* H: Clone> Clone for $struct<$($Vs, )* H> {
@@ -92,3 +187,101 @@ tensor_impl!(
     [M, N, O],
     [[[usize; O]; N]; M]
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detach_and_clone_new_id() {
+        let x = Tensor1D::new([1.0, 2.0, 3.0]);
+        let y = x.detach_and_clone();
+        assert_eq!(y.data(), x.data());
+        assert!(y.id != x.id);
+        assert_eq!(x.duplicate().id, x.id);
+    }
+
+    #[test]
+    fn test_detach_and_clone_modify_clone() {
+        let x = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let mut y = x.detach_and_clone();
+        y.mut_data()[0][0] = -1.0;
+        assert_eq!(x.data(), &[[1.0, 2.0], [3.0, 4.0]]);
+        assert_eq!(y.data(), &[[-1.0, 2.0], [3.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_detach_and_clone_leaves_tape() {
+        let x = Tensor1D::new([1.0, -2.0, 3.0]);
+        let pred = x.trace().square();
+        let before = format!("{:?}", pred.tape);
+        let target = pred.detach_and_clone();
+        assert_eq!(format!("{:?}", pred.tape), before);
+        assert_eq!(target.data(), &[1.0, 4.0, 9.0]);
+
+        // the target is a constant, so only `pred` contributes to the gradient
+        let gradients = mul(pred, &target).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[2.0, -16.0, 54.0]);
+    }
+
+    #[test]
+    fn test_detach_and_clone_vs_duplicate() {
+        let x = Tensor1D::new([1.0, 2.0, 3.0]);
+
+        // `duplicate()` shares the id of `x`, so both sides of the product flow into `x`
+        let gradients = mul(x.trace(), &x.duplicate()).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[2.0, 4.0, 6.0]);
+
+        let gradients = mul(x.trace(), &x.detach_and_clone()).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_has_nan_has_inf() {
+        let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        assert!(!t.has_nan());
+        assert!(!t.has_inf());
+
+        let t = Tensor1D::new([1.0, f32::NAN, 3.0]);
+        assert!(t.has_nan());
+        assert!(!t.has_inf());
+
+        let t = Tensor0D::new(f32::NEG_INFINITY);
+        assert!(!t.has_nan());
+        assert!(t.has_inf());
+    }
+
+    #[test]
+    fn test_detach_with_tape() {
+        let x = Tensor1D::new([1.0, -2.0, 3.0]);
+        let w = Tensor1D::new([0.5, 0.5, 0.5]);
+        let pred = mul(x.trace(), &w).square();
+        let pred_id = pred.id;
+        let (target, tape) = pred.detach_with_tape();
+        assert!(target.id != pred_id);
+        assert_eq!(target.data(), &[0.25, 1.0, 2.25]);
+
+        // the operations on the tape still run, but no gradient flows through `target`
+        let y = mul(w.duplicate().put_tape(tape), &target);
+        let gradients = y.sum().backward();
+        assert_eq!(gradients.ref_gradient(&w), &[0.25, 1.0, 2.25]);
+        assert_eq!(gradients.ref_gradient(&x), &[0.0; 3]);
+    }
+
+    #[test]
+    fn test_detach_vs_split_tape() {
+        let x = Tensor1D::new([1.0, 2.0, 3.0]);
+        let w = Tensor1D::new([4.0, 5.0, 6.0]);
+
+        // split_tape keeps the id, so the gradient flows into `w` through `rhs`
+        let (rhs, _) = w.trace().split_tape();
+        let gradients = mul(x.trace(), &rhs).sum().backward();
+        assert_eq!(gradients.ref_gradient(&w), &[1.0, 2.0, 3.0]);
+
+        // detach creates a new id, so it doesn't
+        let rhs = w.trace().detach();
+        let gradients = mul(x.trace(), &rhs).sum().backward();
+        assert_eq!(gradients.maybe_ref_gradient(&w), None);
+        assert_eq!(gradients.ref_gradient(&x), &[4.0, 5.0, 6.0]);
+    }
+}
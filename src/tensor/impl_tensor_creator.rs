@@ -3,6 +3,7 @@ use crate::prelude::*;
 use num_traits::One;
 use rand::prelude::Distribution;
 use rand_distr::{Standard, StandardNormal};
+use std::boxed::Box;
 
 /// Something that can be created - currently only implemented for tensors with no tapes.
 pub trait TensorCreator: Sized + HasDevice {
@@ -10,6 +11,10 @@ pub trait TensorCreator: Sized + HasDevice {
     fn new_boxed(data: Box<Self::Array>) -> Self;
 
     /// Create a new tensor with `Self::Array` on the stack. This just boxes `Self::Array` and calls [TensorCreator::new_boxed].
+    ///
+    /// Since `data` is on the stack, prefer [TensorCreator::zeros()] (and then [HasArrayData::mut_data()])
+    /// or [TensorCreator::new_boxed()] for very large tensors. All the other constructors allocate
+    /// directly on the heap.
     fn new(data: Self::Array) -> Self {
         Self::new_boxed(Box::new(data))
     }
@@ -130,4 +135,23 @@ mod tests {
         let mut rng = thread_rng();
         let _t = Tensor1D::<1000>::randn(&mut rng);
     }
+
+    #[test]
+    fn test_large_tensors_on_small_stack() {
+        // each of these is 8MB, which is much larger than the 2MB stack
+        type Big = Tensor4D<8, 4, 256, 256>;
+        std::thread::Builder::new()
+            .stack_size(2 << 20)
+            .spawn(|| {
+                assert_eq!(Big::zeros().data()[7][3][255][255], 0.0);
+                assert_eq!(Big::ones().data()[7][3][255][255], 1.0);
+
+                let t: Big = Tensor4D::ones();
+                let gradients = (t.trace() * &t).mean().backward();
+                assert_eq!(gradients.ref_gradient(&t)[0][0][0][0], 2.0 / 2097152.0);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
 }
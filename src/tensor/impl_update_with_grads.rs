@@ -1,9 +1,11 @@
 use crate::prelude::*;
 
 /// Subtracts the gradient for the tensor from [HasArrayData::mut_data].
+/// If there isn't a gradient for the tensor, it is left unchanged.
 impl<T: Tensor<Dtype = f32>> CanUpdateWithGradients for T {
     fn update<G: GradientProvider>(&mut self, grads: &mut G) {
-        let gradient = grads.gradient(self);
-        <Self as HasDevice>::Device::sub(self.mut_data(), gradient.as_ref());
+        if let Some(gradient) = grads.gradient(self) {
+            <Self as HasDevice>::Device::sub(self.mut_data(), gradient.as_ref());
+        }
     }
 }
@@ -69,6 +69,8 @@ mod impl_has_unique_id;
 mod impl_phantom;
 mod impl_put_tape;
 mod impl_randomize;
+#[cfg(feature = "serde")]
+mod impl_serde;
 mod impl_tensor;
 mod impl_tensor_creator;
 mod impl_trace;
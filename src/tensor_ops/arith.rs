@@ -1,4 +1,6 @@
-use super::binary_map::{add, binary_map, div, minimum, mul, sub};
+use super::binary_map::{
+    add, binary_map, binary_map_merge_tapes, div, minimum, mul, sub, SecondOrder,
+};
 use crate::prelude::*;
 use std::ops::{Add, Div, Mul, Sub};
 
@@ -13,7 +15,15 @@ use std::ops::{Add, Div, Mul, Sub};
 /// assert_eq!(r.data(), &[[2.0, 3.0, 4.0], [0.0, -1.0, -2.0]]);
 /// ```
 pub fn add<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
-    binary_map(lhs, rhs, add::f, add::dfdx, add::dfdy)
+    binary_map(
+        "add",
+        lhs,
+        rhs,
+        add::f,
+        add::dfdx,
+        add::dfdy,
+        SecondOrder::Linear,
+    )
 }
 
 /// `lhs - &rhs` element wise.
@@ -26,7 +36,15 @@ pub fn add<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
 /// let r = sub(a, &b); // or `a - &b`
 /// assert_eq!(r.data(), &[[0.0, 1.0, 2.0], [-2.0, -3.0, -4.0]]);
 pub fn sub<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
-    binary_map(lhs, rhs, sub::f, sub::dfdx, sub::dfdy)
+    binary_map(
+        "sub",
+        lhs,
+        rhs,
+        sub::f,
+        sub::dfdx,
+        sub::dfdy,
+        SecondOrder::Linear,
+    )
 }
 
 /// `lhs * &rhs` element wise.
@@ -39,11 +57,22 @@ pub fn sub<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
 /// let r = mul(a, &b); // or `a * &b`
 /// assert_eq!(r.data(), &[[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
 pub fn mul<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
-    binary_map(lhs, rhs, mul::f, mul::dfdx, mul::dfdy)
+    binary_map(
+        "mul",
+        lhs,
+        rhs,
+        mul::f,
+        mul::dfdx,
+        mul::dfdy,
+        SecondOrder::Bilinear,
+    )
 }
 
 /// `lhs / &rhs` element wise.
 ///
+/// Division by zero follows IEEE 754 like [f32] does, and does not panic: `x / 0.0` is `inf` or `-inf`,
+/// and `0.0 / 0.0` is `NaN`. The gradients of those elements are `inf` or `NaN` as well.
+///
 /// Example:
 /// ```rust
 /// # use dfdx::prelude::*;
@@ -52,7 +81,166 @@ pub fn mul<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
 /// let r = div(a, &b); // or `a / &b`
 /// assert_eq!(r.data(), &[[1.0, 4.0, 3.0], [-2.0, -2.0, -1.0]]);
 pub fn div<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
-    binary_map(lhs, rhs, div::f, div::dfdx, div::dfdy)
+    binary_map(
+        "div",
+        lhs,
+        rhs,
+        div::f,
+        div::dfdx,
+        div::dfdy,
+        SecondOrder::Unsupported,
+    )
+}
+
+/// `lhs + rhs` element wise, where both `lhs` and `rhs` may own a tape. Like [add()], but
+/// the tapes are merged together into the result, so gradients flow into both. See [MergeTape].
+///
+/// The gradient of both `lhs` and `rhs` is `g`, where `g` is the result's gradient.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, -3.0]);
+/// let b = Tensor1D::new([2.0, 0.5]);
+/// let r: Tensor1D<2, OwnedTape> = add_merged(a.trace(), b.trace()); // or `a.trace() + b.trace()`
+/// assert_eq!(r.data(), &[3.0, -2.5]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[1.0, 1.0]);
+/// assert_eq!(gradients.ref_gradient(&b), &[1.0, 1.0]);
+/// ```
+pub fn add_merged<A, B>(
+    lhs: A,
+    rhs: B,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    binary_map_merge_tapes(
+        "add",
+        lhs,
+        rhs,
+        add::f,
+        add::dfdx,
+        add::dfdy,
+        SecondOrder::Linear,
+    )
+}
+
+/// `lhs - rhs` element wise, where both `lhs` and `rhs` may own a tape. Like [sub()], but
+/// the tapes are merged together into the result, so gradients flow into both. See [MergeTape].
+///
+/// The gradient of `lhs` is `g`, and the gradient of `rhs` is `-g`, where `g` is the result's gradient.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, -3.0]);
+/// let b = Tensor1D::new([2.0, 0.5]);
+/// let r: Tensor1D<2, OwnedTape> = sub_merged(a.trace(), b.trace()); // or `a.trace() - b.trace()`
+/// assert_eq!(r.data(), &[-1.0, -3.5]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[1.0, 1.0]);
+/// assert_eq!(gradients.ref_gradient(&b), &[-1.0, -1.0]);
+/// ```
+pub fn sub_merged<A, B>(
+    lhs: A,
+    rhs: B,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    binary_map_merge_tapes(
+        "sub",
+        lhs,
+        rhs,
+        sub::f,
+        sub::dfdx,
+        sub::dfdy,
+        SecondOrder::Linear,
+    )
+}
+
+/// `lhs * rhs` element wise, where both `lhs` and `rhs` may own a tape. Like [mul()], but
+/// the tapes are merged together into the result, so gradients flow into both. See [MergeTape].
+///
+/// The gradient of `lhs` is `g * rhs`, and the gradient of `rhs` is `g * lhs`, where `g` is the result's gradient.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, -3.0]);
+/// let b = Tensor1D::new([2.0, 0.5]);
+/// let r: Tensor1D<2, OwnedTape> = mul_merged(a.trace(), b.trace()); // or `a.trace() * b.trace()`
+/// assert_eq!(r.data(), &[2.0, -1.5]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[2.0, 0.5]);
+/// assert_eq!(gradients.ref_gradient(&b), &[1.0, -3.0]);
+/// ```
+pub fn mul_merged<A, B>(
+    lhs: A,
+    rhs: B,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    binary_map_merge_tapes(
+        "mul",
+        lhs,
+        rhs,
+        mul::f,
+        mul::dfdx,
+        mul::dfdy,
+        SecondOrder::Bilinear,
+    )
+}
+
+/// `lhs / rhs` element wise, where both `lhs` and `rhs` may own a tape. Like [div()], but
+/// the tapes are merged together into the result, so gradients flow into both.
+///
+/// The gradient of `lhs` is `g / rhs`, and the gradient of `rhs` is `-g * lhs / rhs^2`, where `g`
+/// is the result's gradient.
+///
+/// Division by zero is handled the same as [div()].
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, -3.0]);
+/// let b = Tensor1D::new([2.0, 0.5]);
+/// let r: Tensor1D<2, OwnedTape> = div_merged(a.trace(), b.trace()); // or `a.trace() / b.trace()`
+/// assert_eq!(r.data(), &[0.5, -6.0]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[0.5, 2.0]);
+/// assert_eq!(gradients.ref_gradient(&b), &[-0.25, 12.0]);
+/// ```
+pub fn div_merged<A, B>(
+    lhs: A,
+    rhs: B,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    binary_map_merge_tapes(
+        "div",
+        lhs,
+        rhs,
+        div::f,
+        div::dfdx,
+        div::dfdy,
+        SecondOrder::Unsupported,
+    )
 }
 
 /// `min(lhs, &rhs)` element wise.
@@ -65,7 +253,15 @@ pub fn div<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
 /// let r = minimum(a, &b);
 /// assert_eq!(r.data(), &[[1.0, 0.5, 1.0], [-2.0, -2.0, -3.5]]);
 pub fn minimum<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
-    binary_map(lhs, rhs, minimum::f, minimum::dfdx, minimum::dfdy)
+    binary_map(
+        "minimum",
+        lhs,
+        rhs,
+        minimum::f,
+        minimum::dfdx,
+        minimum::dfdy,
+        SecondOrder::Unsupported,
+    )
 }
 
 macro_rules! binary_ops_impl {
@@ -102,6 +298,38 @@ impl<$(const $Vs: usize, )* H: Tape> Div<&$typename<$($Vs, )* NoneTape>> for $ty
     }
 }
 
+impl<$(const $Vs: usize, )* H: MergeTape<R>, R: Tape> Add<$typename<$($Vs, )* R>> for $typename<$($Vs, )* H> {
+    type Output = $typename<$($Vs, )* H::Output>;
+    /// Calls [add_merged()] - implements `T<H> + T<R>`
+    fn add(self, rhs: $typename<$($Vs, )* R>) -> Self::Output {
+        add_merged(self, rhs)
+    }
+}
+
+impl<$(const $Vs: usize, )* H: MergeTape<R>, R: Tape> Sub<$typename<$($Vs, )* R>> for $typename<$($Vs, )* H> {
+    type Output = $typename<$($Vs, )* H::Output>;
+    /// Calls [sub_merged()] - implements `T<H> - T<R>`
+    fn sub(self, rhs: $typename<$($Vs, )* R>) -> Self::Output {
+        sub_merged(self, rhs)
+    }
+}
+
+impl<$(const $Vs: usize, )* H: MergeTape<R>, R: Tape> Mul<$typename<$($Vs, )* R>> for $typename<$($Vs, )* H> {
+    type Output = $typename<$($Vs, )* H::Output>;
+    /// Calls [mul_merged()] - implements `T<H> * T<R>`
+    fn mul(self, rhs: $typename<$($Vs, )* R>) -> Self::Output {
+        mul_merged(self, rhs)
+    }
+}
+
+impl<$(const $Vs: usize, )* H: MergeTape<R>, R: Tape> Div<$typename<$($Vs, )* R>> for $typename<$($Vs, )* H> {
+    type Output = $typename<$($Vs, )* H::Output>;
+    /// Calls [div_merged()] - implements `T<H> / T<R>`
+    fn div(self, rhs: $typename<$($Vs, )* R>) -> Self::Output {
+        div_merged(self, rhs)
+    }
+}
+
     };
 }
 
@@ -114,6 +342,8 @@ binary_ops_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gradcheck::gradcheck2;
+    use crate::tests::AssertClose;
 
     #[test]
     fn test_add_0d() {
@@ -277,6 +507,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_mul_4d() {
+        let a: Tensor4D<2, 1, 2, 2> =
+            Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]], [[[-1.0, -2.0], [-3.0, -4.0]]]]);
+        let b: Tensor4D<2, 1, 2, 2> =
+            Tensor4D::new([[[[0.5, 1.0], [1.5, 2.0]]], [[[2.0, 0.0], [-1.0, 1.0]]]]);
+
+        let r = a.trace() + &b;
+        assert_eq!(
+            r.data(),
+            &[[[[1.5, 3.0], [4.5, 6.0]]], [[[1.0, -2.0], [-4.0, -3.0]]]]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[[[[1.0; 2]; 2]]; 2]);
+        assert_eq!(gradients.ref_gradient(&b), &[[[[1.0; 2]; 2]]; 2]);
+
+        let r = a.trace() * &b;
+        assert_eq!(
+            r.data(),
+            &[[[[0.5, 2.0], [4.5, 8.0]]], [[[-2.0, 0.0], [3.0, -4.0]]]]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), b.data());
+        assert_eq!(gradients.ref_gradient(&b), a.data());
+    }
+
     #[test]
     fn test_div_2d() {
         let a = Tensor2D::new([[0.6570, 0.1708, 0.1500], [0.5658, 0.7010, 0.8342]]);
@@ -307,6 +563,167 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_div_merged_both_tapes() {
+        let a = Tensor2D::new([[0.6570, 0.1708, 0.1500], [0.5658, 0.7010, 0.8342]]);
+        let b = Tensor2D::new([[0.5199, 0.3844, 0.3759], [0.8259, 0.3682, 0.0388]]);
+
+        // the same as test_div_2d, but with gradients computed for both in one pass
+        let r = div_merged(b.trace(), a.trace());
+        assert_eq!(r.data(), (b.duplicate() / &a).data());
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[
+                [-0.20074181, -2.1961217, -2.7844446],
+                [-0.42998204, -0.12488106, -0.009292662]
+            ]
+        );
+        assert_eq!(
+            gradients.ref_gradient(&b),
+            &[
+                [0.25367835, 0.97580016, 1.1111112],
+                [0.29456818, 0.2377556, 0.1997922]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merged_ops_with_recorded_ops_on_both_sides() {
+        let a = Tensor1D::new([0.5, -1.0, 2.0]);
+        let b = Tensor1D::new([1.5, 0.25, -0.5]);
+        let c = Tensor1D::new([-0.3, 1.2, 0.8]);
+        let d = Tensor1D::new([0.1, -0.7, 0.4]);
+
+        // every operand has recorded ops from its own traced graph
+        let p = a.trace().exp() + b.trace().square();
+        let q = c.trace().sin() - d.trace().exp();
+        let gradients = (p * q).sum().backward();
+
+        let p = a.duplicate().exp() + &b.duplicate().square();
+        let q = c.duplicate().sin() - &d.duplicate().exp();
+        let (p, q) = (p.data(), q.data());
+        let a_grad: [f32; 3] = std::array::from_fn(|i| a.data()[i].exp() * q[i]);
+        let b_grad: [f32; 3] = std::array::from_fn(|i| 2.0 * b.data()[i] * q[i]);
+        let c_grad: [f32; 3] = std::array::from_fn(|i| p[i] * c.data()[i].cos());
+        let d_grad: [f32; 3] = std::array::from_fn(|i| -p[i] * d.data()[i].exp());
+        gradients.ref_gradient(&a).assert_close(&a_grad, 1e-6);
+        gradients.ref_gradient(&b).assert_close(&b_grad, 1e-6);
+        gradients.ref_gradient(&c).assert_close(&c_grad, 1e-6);
+        gradients.ref_gradient(&d).assert_close(&d_grad, 1e-6);
+    }
+
+    #[test]
+    fn test_merged_ops_gradcheck() {
+        let a = Tensor2D::new([[0.5, -1.0, 2.0], [1.5, 0.25, -0.5]]);
+        let b = Tensor2D::new([[-0.3, 1.2, 0.8], [0.1, -0.7, 0.4]]);
+        type T = Tensor2D<2, 3, OwnedTape>;
+        let f = |a: T, b: T| add_merged(a.tanh(), b.exp()).square().sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+        let f = |a: T, b: T| sub_merged(a.sin(), b.square()).square().sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+        let f = |a: T, b: T| mul_merged(a.exp(), b.cos()).sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+        let f = |a: T, b: T| (a.square() * b.tanh()).sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+        let f = |a: T, b: T| (a.exp() / (b.square() + 1.0)).sum();
+        assert!(gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    fn test_merged_ops_none_tape() {
+        let a = Tensor1D::new([1.0, 2.0]);
+        let b = Tensor1D::new([3.0, -4.0]);
+        let r: Tensor1D<2, NoneTape> = a.duplicate() * b.duplicate();
+        assert_eq!(r.data(), &[3.0, -8.0]);
+        let r: Tensor1D<2, OwnedTape> = a.duplicate() - b.trace();
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&b), &[-1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_div_merged_finite_differences() {
+        let a = Tensor1D::new([1.5, -2.0, 0.3, 4.0]);
+        let b = Tensor1D::new([0.7, 1.2, -0.9, 2.5]);
+        let r: Tensor1D<4, OwnedTape> = div_merged(a.trace(), b.trace());
+        let gradients = r.square().sum().backward();
+
+        let loss = |a: &[f32; 4], b: &[f32; 4]| -> f32 {
+            a.iter().zip(b.iter()).map(|(a, b)| (a / b).powi(2)).sum()
+        };
+        let h = 1e-3;
+        for i in 0..4 {
+            let (mut a_plus, mut a_minus) = (*a.data(), *a.data());
+            a_plus[i] += h;
+            a_minus[i] -= h;
+            let expected = (loss(&a_plus, b.data()) - loss(&a_minus, b.data())) / (2.0 * h);
+            let actual = gradients.ref_gradient(&a)[i];
+            assert!((actual - expected).abs() < 1e-2 * expected.abs().max(1.0));
+
+            let (mut b_plus, mut b_minus) = (*b.data(), *b.data());
+            b_plus[i] += h;
+            b_minus[i] -= h;
+            let expected = (loss(a.data(), &b_plus) - loss(a.data(), &b_minus)) / (2.0 * h);
+            let actual = gradients.ref_gradient(&b)[i];
+            assert!((actual - expected).abs() < 1e-2 * expected.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_div_merged_same_tensor() {
+        // d/da (a / a) = 1 / a - a / a^2 = 0
+        let a = Tensor1D::new([1.0, -2.0, 4.0]);
+        let r: Tensor1D<3, OwnedTape> = div_merged(a.trace(), a.trace());
+        assert_eq!(r.data(), &[1.0; 3]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[0.0; 3]);
+    }
+
+    #[test]
+    fn test_same_tensor_on_both_sides() {
+        // the gradients of both sides are accumulated into the same entry
+        let x = Tensor1D::new([1.0, -2.0, 3.0]);
+        let gradients = mul(x.trace(), &x).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[2.0, -4.0, 6.0]);
+
+        let gradients = add(x.trace(), &x).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[2.0; 3]);
+
+        let gradients = sub(x.trace(), &x).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[0.0; 3]);
+    }
+
+    #[test]
+    fn test_div_merged_rhs_tape_only() {
+        let a = Tensor0D::new(3.0);
+        let b = Tensor0D::new(2.0);
+        let r: Tensor0D<OwnedTape> = div_merged(a.duplicate(), b.trace());
+        assert_eq!(r.data(), &1.5);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&b), &-0.75);
+    }
+
+    // the gradients are non-finite, which `nan-checks` panics on
+    #[test]
+    #[cfg_attr(
+        feature = "nan-checks",
+        should_panic = "produced a non-finite gradient"
+    )]
+    fn test_div_by_zero() {
+        let a = Tensor1D::new([1.0, -1.0, 0.0]);
+        let b: Tensor1D<3> = Tensor1D::zeros();
+        let r = div_merged(a.trace(), b.trace());
+        assert_eq!(r.data()[0], f32::INFINITY);
+        assert_eq!(r.data()[1], f32::NEG_INFINITY);
+        assert!(r.data()[2].is_nan());
+        let gradients = r.sum().backward();
+        assert!(gradients.ref_gradient(&a).iter().all(|g| g.is_infinite()));
+        assert!(gradients
+            .ref_gradient(&b)
+            .iter()
+            .all(|g| g.is_infinite() || g.is_nan()));
+    }
+
     #[test]
     fn test_minimum_0d_rhs() {
         let a = Tensor0D::new(0.0);
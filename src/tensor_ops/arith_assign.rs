@@ -0,0 +1,367 @@
+use super::{flat, flat_mut};
+use crate::prelude::*;
+use crate::unique_id::unique_id;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+use std::rc::Rc;
+
+macro_rules! assign_ops_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )*> AddAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> += &T<NoneTape>` in place.
+    fn add_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l += r);
+    }
+}
+
+impl<$(const $Vs: usize, )*> SubAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> -= &T<NoneTape>` in place.
+    fn sub_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l -= r);
+    }
+}
+
+impl<$(const $Vs: usize, )*> MulAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> *= &T<NoneTape>` in place.
+    fn mul_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l *= r);
+    }
+}
+
+impl<$(const $Vs: usize, )*> DivAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> /= &T<NoneTape>` in place.
+    fn div_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l /= r);
+    }
+}
+
+impl<$(const $Vs: usize, )*> AddAssign<f32> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> += f32` in place.
+    fn add_assign(&mut self, rhs: f32) {
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l += rhs);
+    }
+}
+
+impl<$(const $Vs: usize, )*> SubAssign<f32> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> -= f32` in place.
+    fn sub_assign(&mut self, rhs: f32) {
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l -= rhs);
+    }
+}
+
+impl<$(const $Vs: usize, )*> MulAssign<f32> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> *= f32` in place.
+    fn mul_assign(&mut self, rhs: f32) {
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l *= rhs);
+    }
+}
+
+impl<$(const $Vs: usize, )*> DivAssign<f32> for $typename<$($Vs, )* NoneTape> {
+    /// Implements `T<NoneTape> /= f32` in place.
+    fn div_assign(&mut self, rhs: f32) {
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l /= rhs);
+    }
+}
+    };
+}
+
+assign_ops_impl!(Tensor0D, []);
+assign_ops_impl!(Tensor1D, [N]);
+assign_ops_impl!(Tensor2D, [M, N]);
+assign_ops_impl!(Tensor3D, [M, N, O]);
+assign_ops_impl!(Tensor4D, [M, N, O, P]);
+
+// With an [OwnedTape], the result of an in place op is a new tensor as far as the tape is concerned,
+// so `self` gets a new [UniqueId] and the backward op adds to the gradient of the old one.
+// The buffer is reused unless the old values are still needed for the backward op (i.e. `*= &rhs`),
+// or another tensor shares the buffer.
+macro_rules! assign_ops_with_tape_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )*> AddAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> += &T<NoneTape>` in place, and records the backward op.
+    fn add_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        let inp = self.phantom();
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l += r);
+        self.id = unique_id();
+        let backward = add_backward(inp, self.phantom(), rhs.phantom(), 1.0);
+        self.tape.add_backward_op_named("add_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> SubAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> -= &T<NoneTape>` in place, and records the backward op.
+    fn sub_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        let inp = self.phantom();
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l -= r);
+        self.id = unique_id();
+        let backward = add_backward(inp, self.phantom(), rhs.phantom(), -1.0);
+        self.tape.add_backward_op_named("sub_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> MulAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> *= &T<NoneTape>` in place, and records the backward op.
+    ///
+    /// The backward op needs the old values of `self`, so this allocates a new buffer.
+    fn mul_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        let (inp, inp_data) = (self.phantom(), self.data.clone());
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l *= r);
+        self.id = unique_id();
+        let backward = mul_backward(inp, inp_data, self.phantom(), rhs.phantom(), rhs.data.clone());
+        self.tape.add_backward_op_named("mul_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> DivAssign<&$typename<$($Vs, )* NoneTape>> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> /= &T<NoneTape>` in place, and records the backward op.
+    ///
+    /// The backward op needs the new values of `self`, so the next in place op on `self`
+    /// allocates a new buffer.
+    fn div_assign(&mut self, rhs: &$typename<$($Vs, )* NoneTape>) {
+        let inp = self.phantom();
+        Cpu::foreach_mr(self.mut_data(), rhs.data(), &mut |l, r| *l /= r);
+        self.id = unique_id();
+        let (out, out_data) = (self.phantom(), self.data.clone());
+        let backward = div_backward(inp, out, out_data, rhs.phantom(), rhs.data.clone());
+        self.tape.add_backward_op_named("div_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> AddAssign<f32> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> += f32` in place, and records the backward op.
+    fn add_assign(&mut self, rhs: f32) {
+        let inp = self.phantom();
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l += rhs);
+        self.id = unique_id();
+        let backward = scale_backward(inp, self.phantom(), 1.0);
+        self.tape.add_backward_op_named("add_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> SubAssign<f32> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> -= f32` in place, and records the backward op.
+    fn sub_assign(&mut self, rhs: f32) {
+        let inp = self.phantom();
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l -= rhs);
+        self.id = unique_id();
+        let backward = scale_backward(inp, self.phantom(), 1.0);
+        self.tape.add_backward_op_named("sub_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> MulAssign<f32> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> *= f32` in place, and records the backward op.
+    fn mul_assign(&mut self, rhs: f32) {
+        let inp = self.phantom();
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l *= rhs);
+        self.id = unique_id();
+        let backward = scale_backward(inp, self.phantom(), rhs);
+        self.tape.add_backward_op_named("mul_assign", backward);
+    }
+}
+
+impl<$(const $Vs: usize, )*> DivAssign<f32> for $typename<$($Vs, )* OwnedTape> {
+    /// Implements `T<OwnedTape> /= f32` in place, and records the backward op.
+    fn div_assign(&mut self, rhs: f32) {
+        let inp = self.phantom();
+        Cpu::foreach_m(self.mut_data(), &mut |l| *l /= rhs);
+        self.id = unique_id();
+        let backward = scale_backward(inp, self.phantom(), rhs.recip());
+        self.tape.add_backward_op_named("div_assign", backward);
+    }
+}
+    };
+}
+
+assign_ops_with_tape_impl!(Tensor0D, []);
+assign_ops_with_tape_impl!(Tensor1D, [N]);
+assign_ops_with_tape_impl!(Tensor2D, [M, N]);
+assign_ops_with_tape_impl!(Tensor3D, [M, N, O]);
+assign_ops_with_tape_impl!(Tensor4D, [M, N, O, P]);
+
+/// The backward op of `out = inp * scale + constant`.
+fn scale_backward<T: Tensor<Dtype = f32>>(
+    inp: PhantomTensor<T>,
+    out: PhantomTensor<T>,
+    scale: f32,
+) -> impl Fn(&mut Gradients) {
+    move |grads| {
+        let (inp_grad, out_grad) = grads.mut_and_ref(&inp, &out);
+        T::Device::foreach_mr(inp_grad, out_grad, &mut |g, o| *g += scale * o);
+    }
+}
+
+/// The backward op of `out = inp + sign * rhs`.
+fn add_backward<T: Tensor<Dtype = f32>, R: Tensor<Dtype = f32, Array = T::Array>>(
+    inp: PhantomTensor<T>,
+    out: PhantomTensor<T>,
+    rhs: PhantomTensor<R>,
+    sign: f32,
+) -> impl Fn(&mut Gradients) {
+    move |grads| {
+        let (inp_grad, out_grad) = grads.mut_and_ref(&inp, &out);
+        T::Device::foreach_mr(inp_grad, out_grad, &mut |g, o| *g += o);
+        let (rhs_grad, out_grad) = grads.mut_and_ref(&rhs, &out);
+        T::Device::foreach_mr(rhs_grad, out_grad, &mut |g, o| *g += sign * o);
+    }
+}
+
+/// The backward op of `out = inp * rhs`.
+fn mul_backward<T: Tensor<Dtype = f32>, R: Tensor<Dtype = f32, Array = T::Array>>(
+    inp: PhantomTensor<T>,
+    inp_data: Rc<T::Array>,
+    out: PhantomTensor<T>,
+    rhs: PhantomTensor<R>,
+    rhs_data: Rc<T::Array>,
+) -> impl Fn(&mut Gradients) {
+    move |grads| {
+        let (inp_grad, out_grad) = grads.mut_and_ref(&inp, &out);
+        T::Device::foreach_mrr(inp_grad, out_grad, &rhs_data, &mut |g, o, r| *g += o * r);
+        let (rhs_grad, out_grad) = grads.mut_and_ref(&rhs, &out);
+        T::Device::foreach_mrr(rhs_grad, out_grad, &inp_data, &mut |g, o, i| *g += o * i);
+    }
+}
+
+/// The backward op of `out = inp / rhs`. Uses `out` instead of `inp`, since `d(out)/d(rhs)`
+/// is `-out / rhs`.
+fn div_backward<T: Tensor<Dtype = f32>, R: Tensor<Dtype = f32, Array = T::Array>>(
+    inp: PhantomTensor<T>,
+    out: PhantomTensor<T>,
+    out_data: Rc<T::Array>,
+    rhs: PhantomTensor<R>,
+    rhs_data: Rc<T::Array>,
+) -> impl Fn(&mut Gradients) {
+    move |grads| {
+        let (inp_grad, out_grad) = grads.mut_and_ref(&inp, &out);
+        T::Device::foreach_mrr(inp_grad, out_grad, &rhs_data, &mut |g, o, r| *g += o / r);
+        let (rhs_grad, out_grad) = grads.mut_and_ref(&rhs, &out);
+        let (out_grad, out_data, rhs_data) = (flat(out_grad), flat(&*out_data), flat(&*rhs_data));
+        for (i, g) in flat_mut(rhs_grad).iter_mut().enumerate() {
+            *g -= out_grad[i] * out_data[i] / rhs_data[i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_assign_ops_1d() {
+        let mut a = Tensor1D::new([1.0, 2.0, 3.0]);
+        let b = Tensor1D::new([2.0, -1.0, 0.5]);
+        a += &b;
+        assert_eq!(a.data(), &[3.0, 1.0, 3.5]);
+        a -= &b;
+        assert_eq!(a.data(), &[1.0, 2.0, 3.0]);
+        a *= &b;
+        assert_eq!(a.data(), &[2.0, -2.0, 1.5]);
+        a /= &b;
+        assert_eq!(a.data(), &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_assign_scalar_ops_2d() {
+        let mut a = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        a += 1.0;
+        assert_eq!(a.data(), &[[2.0, 3.0], [4.0, 5.0]]);
+        a -= 0.5;
+        assert_eq!(a.data(), &[[1.5, 2.5], [3.5, 4.5]]);
+        a *= 2.0;
+        assert_eq!(a.data(), &[[3.0, 5.0], [7.0, 9.0]]);
+        a /= 4.0;
+        assert_eq!(a.data(), &[[0.75, 1.25], [1.75, 2.25]]);
+    }
+
+    #[test]
+    fn test_assign_reuses_buffer() {
+        let mut a: Tensor2D<2, 3> = Tensor2D::ones();
+        let b: Tensor2D<2, 3> = Tensor2D::ones();
+        let ptr = a.data().as_ptr();
+        a += &b;
+        a *= 3.0;
+        assert_eq!(a.data().as_ptr(), ptr);
+        assert_eq!(a.data(), &[[6.0; 3]; 2]);
+    }
+
+    #[test]
+    fn test_assign_does_not_modify_clones() {
+        let mut a = Tensor1D::new([1.0, 2.0]);
+        let c = a.duplicate();
+        a += 1.0;
+        assert_eq!(a.data(), &[2.0, 3.0]);
+        assert_eq!(c.data(), &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_assign_ops_with_tape_same_as_regular_ops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<2, 3> = Tensor2D::randn(&mut rng);
+        let b: Tensor2D<2, 3> = Tensor2D::randn(&mut rng);
+        let c: Tensor2D<2, 3> = Tensor2D::rand(&mut rng) + 1.0;
+
+        let r = ((((((x.trace() + &b) * &c) - &b) / &c) + 1.0) * 0.5 - 2.0) / 4.0;
+        let expected_data = *r.data();
+        let expected = r.square().mean().backward();
+
+        let mut r = x.trace();
+        r += &b;
+        r *= &c;
+        r -= &b;
+        r /= &c;
+        r += 1.0;
+        r *= 0.5;
+        r -= 2.0;
+        r /= 4.0;
+        assert_eq!(
+            r.tape().op_names(),
+            [
+                "add_assign",
+                "mul_assign",
+                "sub_assign",
+                "div_assign",
+                "add_assign",
+                "mul_assign",
+                "sub_assign",
+                "div_assign"
+            ]
+        );
+        r.data().assert_close(&expected_data, 1e-6);
+        let gradients = r.square().mean().backward();
+        gradients
+            .ref_gradient(&x)
+            .assert_close(expected.ref_gradient(&x), 1e-6);
+        gradients
+            .ref_gradient(&b)
+            .assert_close(expected.ref_gradient(&b), 1e-6);
+        gradients
+            .ref_gradient(&c)
+            .assert_close(expected.ref_gradient(&c), 1e-6);
+    }
+
+    #[test]
+    fn test_assign_with_tape_reuses_buffer() {
+        let mut a: Tensor2D<2, 3, OwnedTape> = Tensor2D::ones().traced();
+        let b: Tensor2D<2, 3> = Tensor2D::ones();
+        let (id, ptr) = (*a.id(), a.data().as_ptr());
+        a += &b;
+        a -= &b;
+        a *= 3.0;
+        a += 1.0;
+        assert_eq!(a.data().as_ptr(), ptr);
+        assert_ne!(a.id(), &id);
+        assert_eq!(a.data(), &[[4.0; 3]; 2]);
+    }
+
+    #[test]
+    fn test_assign_with_tape_does_not_modify_input() {
+        let x = Tensor1D::new([1.0, 2.0]);
+        let mut a = x.trace();
+        a += 1.0;
+        assert_eq!(a.data(), &[2.0, 3.0]);
+        assert_eq!(x.data(), &[1.0, 2.0]);
+        let gradients = a.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[1.0, 1.0]);
+    }
+}
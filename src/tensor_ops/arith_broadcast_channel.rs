@@ -0,0 +1,202 @@
+use super::binary_map::{add, binary_map_broadcast_rhs_channel, div, mul, sub};
+use crate::prelude::*;
+
+/// `lhs + &rhs`. `rhs` has one value per channel, and is broadcasted across the batch & spatial dimensions of `lhs`.
+///
+/// E.g. If Lhs has dimension `(B, C, H, W)`, then Rhs has to be dimension `(C,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[1.0, 2.0]], [[3.0, 4.0]]]]);
+/// let b = Tensor1D::new([1.0, -1.0]);
+/// let r = add_broadcast_rhs_channel(a, &b);
+/// assert_eq!(r.data(), &[[[[2.0, 3.0]], [[2.0, 3.0]]]]);
+/// ```
+pub fn add_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    binary_map_broadcast_rhs_channel(
+        "add_broadcast_rhs_channel",
+        lhs,
+        rhs,
+        add::f,
+        add::dfdx,
+        add::dfdy,
+    )
+}
+
+/// `lhs - &rhs`. `rhs` has one value per channel, and is broadcasted across the batch & spatial dimensions of `lhs`.
+///
+/// E.g. If Lhs has dimension `(B, C, H, W)`, then Rhs has to be dimension `(C,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[1.0, 2.0]], [[3.0, 4.0]]]]);
+/// let b = Tensor1D::new([1.0, -1.0]);
+/// let r = sub_broadcast_rhs_channel(a, &b);
+/// assert_eq!(r.data(), &[[[[0.0, 1.0]], [[4.0, 5.0]]]]);
+/// ```
+pub fn sub_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    binary_map_broadcast_rhs_channel(
+        "sub_broadcast_rhs_channel",
+        lhs,
+        rhs,
+        sub::f,
+        sub::dfdx,
+        sub::dfdy,
+    )
+}
+
+/// `lhs * &rhs`. `rhs` has one value per channel, and is broadcasted across the batch & spatial dimensions of `lhs`.
+///
+/// E.g. If Lhs has dimension `(B, C, H, W)`, then Rhs has to be dimension `(C,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[1.0, 2.0]], [[3.0, 4.0]]]]);
+/// let b = Tensor1D::new([1.0, -1.0]);
+/// let r = mul_broadcast_rhs_channel(a, &b);
+/// assert_eq!(r.data(), &[[[[1.0, 2.0]], [[-3.0, -4.0]]]]);
+/// ```
+pub fn mul_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    binary_map_broadcast_rhs_channel(
+        "mul_broadcast_rhs_channel",
+        lhs,
+        rhs,
+        mul::f,
+        mul::dfdx,
+        mul::dfdy,
+    )
+}
+
+/// `lhs / &rhs`. `rhs` has one value per channel, and is broadcasted across the batch & spatial dimensions of `lhs`.
+///
+/// E.g. If Lhs has dimension `(B, C, H, W)`, then Rhs has to be dimension `(C,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[1.0, 2.0]], [[3.0, 4.0]]]]);
+/// let b = Tensor1D::new([2.0, 4.0]);
+/// let r = div_broadcast_rhs_channel(a, &b);
+/// assert_eq!(r.data(), &[[[[0.5, 1.0]], [[0.75, 1.0]]]]);
+/// ```
+pub fn div_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    binary_map_broadcast_rhs_channel(
+        "div_broadcast_rhs_channel",
+        lhs,
+        rhs,
+        div::f,
+        div::dfdx,
+        div::dfdy,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: [[[[f32; 2]; 1]; 2]; 2] = [[[[1.0, 2.0]], [[3.0, 4.0]]], [[[5.0, 6.0]], [[7.0, 8.0]]]];
+
+    #[test]
+    fn test_add_broadcast_rhs_channel() {
+        let a = Tensor4D::new(A);
+        let b = Tensor1D::new([1.0, -1.0]);
+        let r = add_broadcast_rhs_channel(a.trace(), &b);
+        assert_eq!(
+            r.data(),
+            &[[[[2.0, 3.0]], [[2.0, 3.0]]], [[[6.0, 7.0]], [[6.0, 7.0]]]]
+        );
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[[[[0.125; 2]]; 2]; 2]);
+        assert_eq!(gradients.ref_gradient(&b), &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_sub_broadcast_rhs_channel() {
+        let a = Tensor4D::new(A);
+        let b = Tensor1D::new([1.0, -1.0]);
+        let r = sub_broadcast_rhs_channel(a.trace(), &b);
+        assert_eq!(
+            r.data(),
+            &[[[[0.0, 1.0]], [[4.0, 5.0]]], [[[4.0, 5.0]], [[8.0, 9.0]]]]
+        );
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[[[[0.125; 2]]; 2]; 2]);
+        assert_eq!(gradients.ref_gradient(&b), &[-0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_mul_broadcast_rhs_channel() {
+        let a = Tensor4D::new(A);
+        let b = Tensor1D::new([1.0, -1.0]);
+        let r = mul_broadcast_rhs_channel(a.trace(), &b);
+        assert_eq!(
+            r.data(),
+            &[
+                [[[1.0, 2.0]], [[-3.0, -4.0]]],
+                [[[5.0, 6.0]], [[-7.0, -8.0]]]
+            ]
+        );
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[[[0.125; 2]], [[-0.125; 2]]]; 2]
+        );
+        assert_eq!(gradients.ref_gradient(&b), &[1.75, 2.75]);
+    }
+
+    #[test]
+    fn test_div_broadcast_rhs_channel() {
+        let a = Tensor4D::new(A);
+        let b = Tensor1D::new([2.0, 4.0]);
+        let r = div_broadcast_rhs_channel(a.trace(), &b);
+        assert_eq!(
+            r.data(),
+            &[[[[0.5, 1.0]], [[0.75, 1.0]]], [[[2.5, 3.0]], [[1.75, 2.0]]]]
+        );
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[[[0.0625; 2]], [[0.03125; 2]]]; 2]
+        );
+        assert_eq!(gradients.ref_gradient(&b), &[-0.4375, -0.171875]);
+    }
+}
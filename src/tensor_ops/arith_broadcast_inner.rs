@@ -15,7 +15,14 @@ pub fn add_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     lhs: T,
     rhs: &<T::LastDimReduced as Tensor>::NoTape,
 ) -> T {
-    binary_map_broadcast_rhs_last(lhs, rhs, add::f, add::dfdx, add::dfdy)
+    binary_map_broadcast_rhs_last(
+        "add_broadcast_rhs_last",
+        lhs,
+        rhs,
+        add::f,
+        add::dfdx,
+        add::dfdy,
+    )
 }
 
 /// `lhs - &rhs`. `rhs`'s last dimension is broadcasted to be the same size as `lhs`.
@@ -32,7 +39,14 @@ pub fn sub_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     lhs: T,
     rhs: &<T::LastDimReduced as Tensor>::NoTape,
 ) -> T {
-    binary_map_broadcast_rhs_last(lhs, rhs, sub::f, sub::dfdx, sub::dfdy)
+    binary_map_broadcast_rhs_last(
+        "sub_broadcast_rhs_last",
+        lhs,
+        rhs,
+        sub::f,
+        sub::dfdx,
+        sub::dfdy,
+    )
 }
 
 /// `lhs * &rhs`. `rhs`'s last dimension is broadcasted to be the same size as `lhs`.
@@ -49,7 +63,14 @@ pub fn mul_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     lhs: T,
     rhs: &<T::LastDimReduced as Tensor>::NoTape,
 ) -> T {
-    binary_map_broadcast_rhs_last(lhs, rhs, mul::f, mul::dfdx, mul::dfdy)
+    binary_map_broadcast_rhs_last(
+        "mul_broadcast_rhs_last",
+        lhs,
+        rhs,
+        mul::f,
+        mul::dfdx,
+        mul::dfdy,
+    )
 }
 
 /// `lhs / &rhs`. `rhs`'s last dimension is broadcasted to be the same size as `lhs`.
@@ -66,7 +87,14 @@ pub fn div_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     lhs: T,
     rhs: &<T::LastDimReduced as Tensor>::NoTape,
 ) -> T {
-    binary_map_broadcast_rhs_last(lhs, rhs, div::f, div::dfdx, div::dfdy)
+    binary_map_broadcast_rhs_last(
+        "div_broadcast_rhs_last",
+        lhs,
+        rhs,
+        div::f,
+        div::dfdx,
+        div::dfdy,
+    )
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use super::binary_map::{add, binary_map_broadcast_rhs_first, div, mul, sub};
+use super::binary_map::{add, binary_map_broadcast_rhs_first, div, mul, sub, SecondOrder};
 use crate::prelude::*;
 
 /// `lhs + &rhs`. `rhs` is broadcasted `M` times, where `M` is the first dimension of `lhs`.
@@ -18,7 +18,15 @@ where
     Lhs: Tensor<Array = [Rhs::Array; M], Dtype = f32>,
     Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
 {
-    binary_map_broadcast_rhs_first(lhs, rhs, add::f, add::dfdx, add::dfdy)
+    binary_map_broadcast_rhs_first(
+        "add_broadcast_rhs_first",
+        lhs,
+        rhs,
+        add::f,
+        add::dfdx,
+        add::dfdy,
+        SecondOrder::Linear,
+    )
 }
 
 /// `lhs - &rhs`. `rhs` is broadcasted `M` times, where `M` is the first dimension of `lhs`.
@@ -38,7 +46,15 @@ where
     Lhs: Tensor<Array = [Rhs::Array; M], Dtype = f32>,
     Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
 {
-    binary_map_broadcast_rhs_first(lhs, rhs, sub::f, sub::dfdx, sub::dfdy)
+    binary_map_broadcast_rhs_first(
+        "sub_broadcast_rhs_first",
+        lhs,
+        rhs,
+        sub::f,
+        sub::dfdx,
+        sub::dfdy,
+        SecondOrder::Linear,
+    )
 }
 
 /// `lhs * &rhs`. `rhs` is broadcasted `M` times, where `M` is the first dimension of `lhs`.
@@ -58,7 +74,15 @@ where
     Lhs: Tensor<Array = [Rhs::Array; M], Dtype = f32>,
     Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
 {
-    binary_map_broadcast_rhs_first(lhs, rhs, mul::f, mul::dfdx, mul::dfdy)
+    binary_map_broadcast_rhs_first(
+        "mul_broadcast_rhs_first",
+        lhs,
+        rhs,
+        mul::f,
+        mul::dfdx,
+        mul::dfdy,
+        SecondOrder::Unsupported,
+    )
 }
 
 /// `lhs / &rhs`. `rhs` is broadcasted `M` times, where `M` is the first dimension of `lhs`.
@@ -78,7 +102,15 @@ where
     Lhs: Tensor<Array = [Rhs::Array; M], Dtype = f32>,
     Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
 {
-    binary_map_broadcast_rhs_first(lhs, rhs, div::f, div::dfdx, div::dfdy)
+    binary_map_broadcast_rhs_first(
+        "div_broadcast_rhs_first",
+        lhs,
+        rhs,
+        div::f,
+        div::dfdx,
+        div::dfdy,
+        SecondOrder::Unsupported,
+    )
 }
 
 #[cfg(test)]
@@ -286,7 +318,12 @@ mod tests {
         assert_eq!(gradients.ref_gradient(&b), &0.0);
     }
 
+    // the gradients are non-finite, which `nan-checks` panics on
     #[test]
+    #[cfg_attr(
+        feature = "nan-checks",
+        should_panic = "produced a non-finite gradient"
+    )]
     fn test_div_broadcast_rhs_first_2d() {
         let a = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, 4.0, -3.0]]);
         let b = Tensor1D::new([-1.0, 0.0, 1.0]);
@@ -1,4 +1,4 @@
-use super::utils::move_tape_and_add_backward_op;
+use super::utils::move_tape_and_add_backward_op_taped;
 use crate::prelude::*;
 use std::ops::{Add, Div, Mul, Sub};
 
@@ -13,12 +13,18 @@ use std::ops::{Add, Div, Mul, Sub};
 /// ```
 pub fn add_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
     let result = T::NoTape::new_boxed(T::Device::map(t.data(), |x| x + val));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
-        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
-        T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
-            *t += r;
-        });
-    })
+    move_tape_and_add_backward_op_taped(
+        "add_scalar",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
+                *t += r;
+            });
+        },
+        move |t, result, grads| taped_scalar_backward::<T>(&t, &result, 1.0, grads),
+    )
 }
 
 /// `t - val`. `val` is used for all elements of `t`.
@@ -32,12 +38,18 @@ pub fn add_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
 /// ```
 pub fn sub_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
     let result = T::NoTape::new_boxed(T::Device::map(t.data(), |x| x - val));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
-        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
-        T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
-            *t += r;
-        });
-    })
+    move_tape_and_add_backward_op_taped(
+        "sub_scalar",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
+                *t += r;
+            });
+        },
+        move |t, result, grads| taped_scalar_backward::<T>(&t, &result, 1.0, grads),
+    )
 }
 
 /// `t * val`. `val` is used for all elements of `t`.
@@ -51,12 +63,18 @@ pub fn sub_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
 /// ```
 pub fn mul_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
     let result = T::NoTape::new_boxed(T::Device::map(t.data(), |x| x * val));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
-        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
-        T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
-            *t += r * val;
-        });
-    })
+    move_tape_and_add_backward_op_taped(
+        "mul_scalar",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
+                *t += r * val;
+            });
+        },
+        move |t, result, grads| taped_scalar_backward::<T>(&t, &result, val, grads),
+    )
 }
 
 /// `t / val`. `val` is used for all elements of `t`.
@@ -70,12 +88,31 @@ pub fn mul_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
 /// ```
 pub fn div_scalar<T: Tensor<Dtype = f32>>(t: T, val: T::Dtype) -> T {
     let result = T::NoTape::new_boxed(T::Device::map(t.data(), |x| x / val));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
-        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
-        T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
-            *t += r / val;
-        });
-    })
+    move_tape_and_add_backward_op_taped(
+        "div_scalar",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            T::Device::foreach_mr(t_grad, result_grad, &mut |t, r| {
+                *t += r / val;
+            });
+        },
+        move |t, result, grads| taped_scalar_backward::<T>(&t, &result, 1.0 / val, grads),
+    )
+}
+
+/// The backward operation of the scalar ops with tensor operations, where `d` is the derivative
+/// of the result wrt. `t`. See [GradientTape::execute_with_tape()].
+fn taped_scalar_backward<T: Tensor<Dtype = f32>>(
+    t: &T::NoTape,
+    result: &PhantomTensor<T::NoTape>,
+    d: f32,
+    grads: &mut TapedGradients,
+) {
+    if let Some(result_grad) = grads.traced::<T::OwnedTape>(result) {
+        grads.accumulate(t, mul_scalar(result_grad, d));
+    }
 }
 
 macro_rules! scalar_ops_impl {
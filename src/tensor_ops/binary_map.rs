@@ -1,5 +1,8 @@
-use super::utils::move_tape_and_add_backward_binop;
+use super::utils::{move_tape_and_add_backward_binop, move_tape_and_add_backward_binop_taped};
+use crate::devices::par_addmul;
+use crate::gradients::no_taped_operation;
 use crate::prelude::*;
+use std::{boxed::Box, rc::Rc};
 
 pub(super) mod add {
     pub fn f(x: &f32, y: &f32) -> f32 {
@@ -38,6 +41,9 @@ pub(super) mod mul {
 }
 
 pub(super) mod div {
+    #[cfg(not(feature = "std"))]
+    use num_traits::Float;
+
     pub fn f(x: &f32, y: &f32) -> f32 {
         x * y.recip()
     }
@@ -74,16 +80,54 @@ pub(super) mod minimum {
     }
 }
 
+pub(super) mod prelu {
+    pub fn f(x: &f32, y: &f32) -> f32 {
+        if x > &0.0 {
+            *x
+        } else {
+            x * y
+        }
+    }
+    pub fn dfdx(x: &f32, y: &f32) -> f32 {
+        if x > &0.0 {
+            1.0
+        } else {
+            *y
+        }
+    }
+    pub fn dfdy(x: &f32, _y: &f32) -> f32 {
+        if x > &0.0 {
+            0.0
+        } else {
+            *x
+        }
+    }
+}
+
+/// How the backward operation of [binary_map()] can be differentiated again.
+/// See [GradientTape::execute_with_tape()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum SecondOrder {
+    /// The backward operation has no taped version.
+    Unsupported,
+    /// `dfdx` and `dfdy` are constants, e.g. for [add()] and [sub()].
+    Linear,
+    /// `dfdx` is `y` and `dfdy` is `x`, i.e. [mul()].
+    Bilinear,
+}
+
 /// Applies a binary function `f`, it's partial wrt. x `dfdx`, and its partial wrt. y `dfdy`
 /// to a pair of [Tensor]s `lhs` and `rhs.
 ///
 /// This is primarily used to implement [add()], [sub()], [mul()], and [div()].
 pub(super) fn binary_map<T: Tensor<Dtype = f32>>(
+    name: &'static str,
     mut lhs: T,
     rhs: &T::NoTape,
     f: fn(&f32, &f32) -> f32,
     dfdx: fn(&f32, &f32) -> f32,
     dfdy: fn(&f32, &f32) -> f32,
+    second_order: SecondOrder,
 ) -> T {
     let mut result = T::NoTape::zeros();
     let mut rhs_deriv: Box<T::Array> = T::Device::zeros();
@@ -95,13 +139,115 @@ pub(super) fn binary_map<T: Tensor<Dtype = f32>>(
     let (o, l, r) = (result.mut_data(), lhs.mut_data(), rhs_deriv.as_mut());
     f_and_dfs::<T::Array, T::Device>(o, l, r, f, dfdx, dfdy);
 
-    move_tape_and_add_backward_binop(lhs, rhs, result, move |lhs, rhs, result, grads| {
-        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        T::Device::addmul(lhs_grad, lhs.data(), result_grad);
+    let rhs_deriv = Rc::new(rhs_deriv);
+    let taped_rhs_deriv = rhs_deriv.clone();
+    move_tape_and_add_backward_binop_taped(
+        name,
+        lhs,
+        rhs,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            par_addmul(lhs_grad, lhs.data(), result_grad);
 
-        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
-        T::Device::addmul(rhs_grad, rhs_deriv.as_ref(), result_grad);
-    })
+            let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            par_addmul(rhs_grad, rhs_deriv.as_ref(), result_grad);
+        },
+        move |lhs, rhs, result, grads| {
+            let (lhs_deriv, rhs_deriv) = (lhs.data(), taped_rhs_deriv.as_ref().as_ref());
+            let derivs = (lhs.phantom(), lhs_deriv, rhs, rhs_deriv);
+            taped_binary_backward::<T::OwnedTape, _>(name, second_order, derivs, result, grads);
+        },
+    )
+}
+
+/// Like [binary_map()], but `rhs` may also own a tape, in which case the tapes of `lhs` and `rhs` are
+/// merged together into the result.
+///
+/// This is primarily used to implement [add_merged()], [sub_merged()], [mul_merged()], and [div_merged()].
+pub(super) fn binary_map_merge_tapes<A, B>(
+    name: &'static str,
+    lhs: A,
+    rhs: B,
+    f: fn(&f32, &f32) -> f32,
+    dfdx: fn(&f32, &f32) -> f32,
+    dfdy: fn(&f32, &f32) -> f32,
+    second_order: SecondOrder,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    let (mut lhs, lhs_tape) = lhs.split_tape();
+    let (mut rhs, rhs_tape) = rhs.split_tape();
+
+    // compute result & derivatives. the derivatives are stored in lhs & rhs
+    let mut result = A::NoTape::zeros();
+    let (o, l, r) = (result.mut_data(), lhs.mut_data(), rhs.mut_data());
+    f_and_dfs::<A::Array, A::Device>(o, l, r, f, dfdx, dfdy);
+
+    let mut tape = lhs_tape.merge_tape(rhs_tape);
+    let phantom_result = result.phantom();
+    let (taped_lhs, taped_rhs) = (lhs.duplicate(), rhs.duplicate());
+    tape.add_backward_op_taped(
+        name,
+        move |grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+            par_addmul(lhs_grad, lhs.data(), result_grad);
+
+            let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+            par_addmul(rhs_grad, rhs.data(), result_grad);
+        },
+        move |grads| {
+            let (lhs, rhs) = (&taped_lhs, &taped_rhs);
+            let derivs = (lhs.phantom(), lhs.data(), rhs.phantom(), rhs.data());
+            taped_binary_backward::<A::OwnedTape, _>(
+                name,
+                second_order,
+                derivs,
+                phantom_result,
+                grads,
+            );
+        },
+    );
+    PutTape::put_tape(result, tape)
+}
+
+/// The backward operation of [binary_map()] with tensor operations, so that it can be differentiated
+/// again. `derivs` has `lhs`, `dfdx`, `rhs` and `dfdy`, where `dfdx` and `dfdy` were computed in the
+/// forward pass.
+fn taped_binary_backward<T, N>(
+    name: &'static str,
+    second_order: SecondOrder,
+    derivs: (PhantomTensor<N>, &T::Array, PhantomTensor<N>, &T::Array),
+    result: PhantomTensor<N>,
+    grads: &mut TapedGradients,
+) where
+    T: Tensor<Dtype = f32, Tape = OwnedTape>,
+    N: 'static + HasArrayType<Array = T::Array, Dtype = f32> + HasDevice,
+{
+    let (lhs, dfdx, rhs, dfdy) = derivs;
+    let (dfdx, dfdy): (T::NoTape, T::NoTape) = match second_order {
+        SecondOrder::Unsupported => no_taped_operation(name),
+        SecondOrder::Linear => (constant(dfdx), constant(dfdy)),
+        // `dfdx` is the values of `rhs` and `dfdy` is the values of `lhs`
+        SecondOrder::Bilinear => (grads.alias(&rhs, dfdx), grads.alias(&lhs, dfdy)),
+    };
+    if let Some(result_grad) = grads.traced::<T>(&result) {
+        grads.accumulate(&lhs, mul(result_grad, &dfdx));
+    }
+    if let Some(result_grad) = grads.traced::<T>(&result) {
+        grads.accumulate(&rhs, mul(result_grad, &dfdy));
+    }
+}
+
+/// A tensor with a copy of `data`, which nothing else depends on.
+fn constant<T: Tensor + TensorCreator>(data: &T::Array) -> T {
+    let mut t = T::zeros();
+    t.mut_data().clone_from(data);
+    t
 }
 
 /// Apply binary function `f` to `lhs` and `rhs`, where `rhs` is broadcasted `M` times to be the same shape as `lhs`.
@@ -111,12 +257,16 @@ pub(super) fn binary_map<T: Tensor<Dtype = f32>>(
 ///
 /// Generics:
 /// - `M`: The first dimension of `lhs`.
+///
+/// Only [SecondOrder::Linear] has a taped backward operation.
 pub(super) fn binary_map_broadcast_rhs_first<const M: usize, Lhs, Rhs>(
+    name: &'static str,
     mut lhs: Lhs,
     rhs: &Rhs,
     f: fn(&f32, &f32) -> f32,
     dfdx: fn(&f32, &f32) -> f32,
     dfdy: fn(&f32, &f32) -> f32,
+    second_order: SecondOrder,
 ) -> Lhs
 where
     Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
@@ -134,16 +284,63 @@ where
     let (o, l, r) = (result.mut_data(), lhs.mut_data(), rhs_deriv.as_mut());
     f_and_dfs::<Lhs::Array, Lhs::Device>(o, l, r, f, dfdx, dfdy);
 
-    move_tape_and_add_backward_binop(lhs, rhs, result, move |lhs, rhs, result, grads| {
-        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        Lhs::Device::addmul(lhs_grad, lhs.data(), result_grad);
+    let rhs_deriv = Rc::new(rhs_deriv);
+    let taped_rhs_deriv = rhs_deriv.clone();
+    move_tape_and_add_backward_binop_taped(
+        name,
+        lhs,
+        rhs,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            Lhs::Device::addmul(lhs_grad, lhs.data(), result_grad);
+
+            let (rhs_grad, result_grad): (&mut Rhs::Array, &Lhs::Array) =
+                grads.mut_and_ref(&rhs, &result);
+            for i in 0..M {
+                Rhs::Device::addmul(rhs_grad, &rhs_deriv[i], &result_grad[i]);
+            }
+        },
+        move |lhs, rhs, result, grads| {
+            if second_order != SecondOrder::Linear {
+                no_taped_operation(name);
+            }
+            let dfdx: <Lhs::OwnedTape as Tensor>::NoTape = constant(lhs.data());
+            let dfdy: <Lhs::OwnedTape as Tensor>::NoTape =
+                constant(taped_rhs_deriv.as_ref().as_ref());
+            if let Some(result_grad) = grads.traced::<Lhs::OwnedTape>(&result) {
+                grads.accumulate(&lhs, mul(result_grad, &dfdx));
+            }
+            if let Some(result_grad) = grads.traced::<Lhs::OwnedTape>(&result) {
+                let rhs_grad = mul(result_grad, &dfdy);
+                grads.accumulate(&rhs, sum_first::<M, _, Rhs::OwnedTape>(rhs_grad));
+            }
+        },
+    )
+}
 
-        let (rhs_grad, result_grad): (&mut Rhs::Array, &Lhs::Array) =
-            grads.mut_and_ref(&rhs, &result);
-        for i in 0..M {
-            Rhs::Device::addmul(rhs_grad, &rhs_deriv[i], &result_grad[i]);
+/// Sums `t` over its first dimension. This is the taped version of how the backward operation of
+/// [binary_map_broadcast_rhs_first()] reduces the gradient of `rhs`.
+fn sum_first<const M: usize, T, R>(t: T) -> R
+where
+    T: Tensor<Dtype = f32, Tape = OwnedTape, Array = [R::Array; M]>,
+    R: Tensor<Dtype = f32, Tape = OwnedTape>,
+{
+    let mut result = R::NoTape::zeros();
+    for t_i in t.data().iter() {
+        R::Device::add(result.mut_data(), t_i);
+    }
+
+    let (t, mut tape) = t.split_tape();
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("sum_first", move |grads| {
+        let (t_grad, result_grad): (&mut T::Array, &R::Array) =
+            grads.mut_and_ref(&t, &phantom_result);
+        for t_grad_i in t_grad.iter_mut() {
+            R::Device::add(t_grad_i, result_grad);
         }
-    })
+    });
+    result.put_tape(tape)
 }
 
 /// Applies a binary function `f`, it's partial wrt. x `dfdx`, and its partial wrt. y `dfdy`
@@ -153,6 +350,7 @@ where
 /// This is primarily used to implement [add_broadcast_rhs_last()],
 /// [sub_broadcast_rhs_last()], [mul_broadcast_rhs_last()], and [div_broadcast_rhs_last()].
 pub(super) fn binary_map_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
+    name: &'static str,
     mut lhs: T,
     rhs: &<T::LastDimReduced as Tensor>::NoTape,
     f: fn(&f32, &f32) -> f32,
@@ -171,7 +369,7 @@ pub(super) fn binary_map_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     let (o, l, r) = (result.mut_data(), lhs.mut_data(), rhs_deriv.as_mut());
     f_and_dfs::<T::Array, T::Device>(o, l, r, f, dfdx, dfdy);
 
-    move_tape_and_add_backward_binop(lhs, rhs, result, move |lhs, rhs, result, grads| {
+    move_tape_and_add_backward_binop(name, lhs, rhs, result, move |lhs, rhs, result, grads| {
         let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
         T::Device::addmul(lhs_grad, lhs.data(), result_grad);
 
@@ -183,6 +381,56 @@ pub(super) fn binary_map_broadcast_rhs_last<T: Tensor<Dtype = f32>>(
     })
 }
 
+/// Applies a binary function `f`, it's partial wrt. x `dfdx`, and its partial wrt. y `dfdy`
+/// to a [Tensor4D] `lhs` with shape `(B, C, H, W)` and a [Tensor1D] `rhs` with shape `(C, )`.
+/// `rhs` is broadcasted across the batch (`B`) and spatial (`H`, `W`) dimensions of `lhs`.
+///
+/// This is primarily used to implement [add_broadcast_rhs_channel()],
+/// [sub_broadcast_rhs_channel()], [mul_broadcast_rhs_channel()], and [div_broadcast_rhs_channel()].
+pub(super) fn binary_map_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    name: &'static str,
+    mut lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+    f: fn(&f32, &f32) -> f32,
+    dfdx: fn(&f32, &f32) -> f32,
+    dfdy: fn(&f32, &f32) -> f32,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    let mut result = Tensor4D::zeros();
+    let mut rhs_deriv: Box<[[[[f32; W]; H]; C]; B]> = Cpu::zeros();
+
+    // clone rhs.data() into rhs_deriv
+    for rhs_deriv_b in rhs_deriv.iter_mut() {
+        for (rhs_deriv_bc, r) in rhs_deriv_b.iter_mut().zip(rhs.data().iter()) {
+            Cpu::fill(rhs_deriv_bc, &mut |v| *v = *r);
+        }
+    }
+
+    // compute result & derivatives
+    let (o, l, r) = (result.mut_data(), lhs.mut_data(), rhs_deriv.as_mut());
+    f_and_dfs::<[[[[f32; W]; H]; C]; B], Cpu>(o, l, r, f, dfdx, dfdy);
+
+    move_tape_and_add_backward_binop(name, lhs, rhs, result, move |lhs, rhs, result, grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+        Cpu::addmul(lhs_grad, lhs.data(), result_grad);
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+        let result_grad: &[[[[f32; W]; H]; C]; B] = result_grad;
+        for (rhs_deriv_b, result_grad_b) in rhs_deriv.iter().zip(result_grad.iter()) {
+            for c in 0..C {
+                let d = rhs_deriv_b[c].iter().flat_map(|row| row.iter());
+                let r = result_grad_b[c].iter().flat_map(|row| row.iter());
+                rhs_grad[c] += d.zip(r).map(|(d, r)| d * r).sum::<f32>();
+            }
+        }
+    })
+}
+
 fn f_and_dfs<T: CountElements, Device: ForEachElement<T>>(
     out: &mut T,
     lhs: &mut T,
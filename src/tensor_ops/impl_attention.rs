@@ -0,0 +1,413 @@
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Causes a compile time error if `E` is not divisible by `HEADS`.
+pub(crate) struct AssertDivisibleByHeads<const E: usize, const HEADS: usize>;
+
+impl<const E: usize, const HEADS: usize> AssertDivisibleByHeads<E, HEADS> {
+    pub(crate) const OK: () = assert!(
+        HEADS > 0 && E.is_multiple_of(HEADS),
+        "embedding size must be divisible by the number of heads"
+    );
+}
+
+/// Computes the attention of every head, storing the softmax probabilities of head `h`, query `i`
+/// and key `j` in `probs[(h * S + i) * S2 + j]`.
+fn attention_forward<const E: usize>(
+    q: &[[f32; E]],
+    k: &[[f32; E]],
+    v: &[[f32; E]],
+    heads: usize,
+    causal: bool,
+    probs: &mut [f32],
+    out: &mut [[f32; E]],
+) {
+    let (s, s2, d) = (q.len(), k.len(), E / heads);
+    let scale = 1.0 / (d as f32).sqrt();
+    for h in 0..heads {
+        let cols = h * d..(h + 1) * d;
+        for (i, (q_i, out_i)) in q.iter().zip(out.iter_mut()).enumerate() {
+            let p = &mut probs[(h * s + i) * s2..(h * s + i + 1) * s2];
+            for (j, (p_j, k_j)) in p.iter_mut().zip(k.iter()).enumerate() {
+                *p_j = if causal && j > i {
+                    f32::NEG_INFINITY
+                } else {
+                    let dot: f32 = q_i[cols.clone()]
+                        .iter()
+                        .zip(k_j[cols.clone()].iter())
+                        .map(|(a, b)| a * b)
+                        .sum();
+                    dot * scale
+                };
+            }
+            let max = p.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let mut total = 0.0;
+            for p_j in p.iter_mut() {
+                *p_j = (*p_j - max).exp();
+                total += *p_j;
+            }
+            for (p_j, v_j) in p.iter_mut().zip(v.iter()) {
+                *p_j /= total;
+                for (o, v_jc) in out_i[cols.clone()].iter_mut().zip(v_j[cols.clone()].iter()) {
+                    *o += *p_j * v_jc;
+                }
+            }
+        }
+    }
+}
+
+/// Accumulates the gradient of `v`, and stores the gradient of the (scaled) scores in `scores_grad`,
+/// with the same layout as `probs`.
+fn attention_backward_v<const E: usize>(
+    v: &[[f32; E]],
+    heads: usize,
+    probs: &[f32],
+    out_grad: &[[f32; E]],
+    v_grad: &mut [[f32; E]],
+    scores_grad: &mut [f32],
+) {
+    let (s, s2, d) = (out_grad.len(), v.len(), E / heads);
+    let scale = 1.0 / (d as f32).sqrt();
+    for h in 0..heads {
+        let cols = h * d..(h + 1) * d;
+        for (i, g_i) in out_grad.iter().enumerate() {
+            let idx = (h * s + i) * s2..(h * s + i + 1) * s2;
+            let (p, ds) = (&probs[idx.clone()], &mut scores_grad[idx]);
+            for ((p_j, ds_j), (v_j, v_grad_j)) in p
+                .iter()
+                .zip(ds.iter_mut())
+                .zip(v.iter().zip(v_grad.iter_mut()))
+            {
+                let mut dp = 0.0;
+                for ((g, v_jc), v_grad_jc) in g_i[cols.clone()]
+                    .iter()
+                    .zip(v_j[cols.clone()].iter())
+                    .zip(v_grad_j[cols.clone()].iter_mut())
+                {
+                    *v_grad_jc += p_j * g;
+                    dp += g * v_jc;
+                }
+                *ds_j = dp;
+            }
+            // softmax backward, then the scale of the scores
+            let dot: f32 = p.iter().zip(ds.iter()).map(|(p_j, ds_j)| p_j * ds_j).sum();
+            for (p_j, ds_j) in p.iter().zip(ds.iter_mut()) {
+                *ds_j = p_j * (*ds_j - dot) * scale;
+            }
+        }
+    }
+}
+
+/// Accumulates `scores_grad * other` into `grad`, where `transpose` selects whether
+/// `grad` is the query (`false`) or the key (`true`) side of the scores.
+fn attention_backward_qk<const E: usize>(
+    other: &[[f32; E]],
+    heads: usize,
+    scores_grad: &[f32],
+    grad: &mut [[f32; E]],
+    transpose: bool,
+) {
+    let (n, m, d) = (grad.len(), other.len(), E / heads);
+    let (s, s2) = if transpose { (m, n) } else { (n, m) };
+    for h in 0..heads {
+        let cols = h * d..(h + 1) * d;
+        for (a, grad_a) in grad.iter_mut().enumerate() {
+            for (b, other_b) in other.iter().enumerate() {
+                let (i, j) = if transpose { (b, a) } else { (a, b) };
+                let ds = scores_grad[(h * s + i) * s2 + j];
+                for (g, o) in grad_a[cols.clone()]
+                    .iter_mut()
+                    .zip(other_b[cols.clone()].iter())
+                {
+                    *g += ds * o;
+                }
+            }
+        }
+    }
+}
+
+/// A batch of `S` rows of `E` elements, so 2d & 3d tensors can share [attention()].
+trait Rows<const S: usize, const E: usize> {
+    fn batches(&self) -> &[[[f32; E]; S]];
+    fn batches_mut(&mut self) -> &mut [[[f32; E]; S]];
+}
+
+impl<const S: usize, const E: usize> Rows<S, E> for [[f32; E]; S] {
+    fn batches(&self) -> &[[[f32; E]; S]] {
+        std::slice::from_ref(self)
+    }
+    fn batches_mut(&mut self) -> &mut [[[f32; E]; S]] {
+        std::slice::from_mut(self)
+    }
+}
+
+impl<const B: usize, const S: usize, const E: usize> Rows<S, E> for [[[f32; E]; S]; B] {
+    fn batches(&self) -> &[[[f32; E]; S]] {
+        self
+    }
+    fn batches_mut(&mut self) -> &mut [[[f32; E]; S]] {
+        self
+    }
+}
+
+fn attention<const HEADS: usize, const S: usize, const S2: usize, const E: usize, Q, KV>(
+    q: Q,
+    k: &KV,
+    v: &KV,
+    causal: bool,
+) -> Q
+where
+    Q: Tensor<Dtype = f32>,
+    Q::Array: Rows<S, E>,
+    KV: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
+    KV::Array: Rows<S2, E>,
+{
+    #[allow(clippy::let_unit_value)]
+    let _ = AssertDivisibleByHeads::<E, HEADS>::OK;
+
+    let per_batch = HEADS * S * S2;
+    let num_batches = q.data().batches().len();
+    let mut probs = vec![0.0; num_batches * per_batch];
+    let mut result = Q::NoTape::zeros();
+    for (((q_b, k_b), v_b), (r_b, p_b)) in q
+        .data()
+        .batches()
+        .iter()
+        .zip(k.data().batches().iter())
+        .zip(v.data().batches().iter())
+        .zip(
+            result
+                .mut_data()
+                .batches_mut()
+                .iter_mut()
+                .zip(probs.chunks_mut(per_batch)),
+        )
+    {
+        attention_forward(q_b, k_b, v_b, HEADS, causal, p_b, r_b);
+    }
+
+    let (k, v) = (k.duplicate(), v.duplicate());
+    let phantom_result = result.phantom();
+    let (q, mut tape) = q.split_tape();
+    tape.add_backward_op_named("attention", move |grads| {
+        let mut scores_grad = vec![0.0; num_batches * per_batch];
+        let (v_grad, result_grad) = grads.mut_and_ref(&v, &phantom_result);
+        for (((v_b, g_b), v_grad_b), (p_b, ds_b)) in v
+            .data()
+            .batches()
+            .iter()
+            .zip(result_grad.batches().iter())
+            .zip(v_grad.batches_mut().iter_mut())
+            .zip(
+                probs
+                    .chunks(per_batch)
+                    .zip(scores_grad.chunks_mut(per_batch)),
+            )
+        {
+            attention_backward_v(v_b, HEADS, p_b, g_b, v_grad_b, ds_b);
+        }
+
+        let q_grad = grads.mut_gradient(&q);
+        for ((k_b, q_grad_b), ds_b) in k
+            .data()
+            .batches()
+            .iter()
+            .zip(q_grad.batches_mut().iter_mut())
+            .zip(scores_grad.chunks(per_batch))
+        {
+            attention_backward_qk(k_b, HEADS, ds_b, q_grad_b, false);
+        }
+
+        let k_grad = grads.mut_gradient(&k);
+        for ((q_b, k_grad_b), ds_b) in q
+            .data()
+            .batches()
+            .iter()
+            .zip(k_grad.batches_mut().iter_mut())
+            .zip(scores_grad.chunks(per_batch))
+        {
+            attention_backward_qk(q_b, HEADS, ds_b, k_grad_b, true);
+        }
+    });
+    result.put_tape(tape)
+}
+
+/// Multi-head scaled dot product attention: `softmax(q * k^T / sqrt(D)) * v` for each head,
+/// where `D = E / HEADS`. Head `h` uses columns `h * D..(h + 1) * D` of `q`, `k` and `v`, and
+/// its output is put in the same columns of the result, which concatenates the heads.
+///
+/// If `causal` is true, query `i` can't attend to keys `j > i`.
+///
+/// `k` and `v` don't have tapes, but their gradients are still computed, so they can be the
+/// result of other operations on the same tape as `q` (see [crate::nn::MultiHeadAttention]).
+///
+/// `E` must be divisible by `HEADS`, which is checked at compile time.
+///
+/// # Generics
+/// - `HEADS`: the number of heads.
+/// - `S`: the number of queries.
+/// - `S2`: the number of keys & values.
+/// - `E`: the embedding size.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let q: Tensor2D<2, 2> = Tensor2D::zeros();
+/// let k: Tensor2D<2, 2> = Tensor2D::zeros();
+/// let v: Tensor2D<2, 2> = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+/// // every key has the same score, so the values are averaged
+/// let r = multi_head_attention::<2, 2, 2, 2, _>(q.duplicate(), &k, &v, false);
+/// assert_eq!(r.data(), &[[2.0, 3.0], [2.0, 3.0]]);
+/// // unless the first query can't see the second key
+/// let r = multi_head_attention::<2, 2, 2, 2, _>(q, &k, &v, true);
+/// assert_eq!(r.data(), &[[1.0, 2.0], [2.0, 3.0]]);
+/// ```
+///
+/// An embedding size that isn't divisible by `HEADS` is a compile time error:
+/// ```compile_fail
+/// # use dfdx::prelude::*;
+/// let q: Tensor2D<1, 3> = Tensor2D::zeros();
+/// let r = multi_head_attention::<2, 1, 1, 3, _>(q, &Tensor2D::zeros(), &Tensor2D::zeros(), false);
+/// ```
+pub fn multi_head_attention<
+    const HEADS: usize,
+    const S: usize,
+    const S2: usize,
+    const E: usize,
+    TAPE: Tape,
+>(
+    q: Tensor2D<S, E, TAPE>,
+    k: &Tensor2D<S2, E, NoneTape>,
+    v: &Tensor2D<S2, E, NoneTape>,
+    causal: bool,
+) -> Tensor2D<S, E, TAPE> {
+    attention::<HEADS, S, S2, E, _, _>(q, k, v, causal)
+}
+
+/// Batched [multi_head_attention()], where every batch of `q` attends to the same batch of `k` and `v`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let q: Tensor3D<2, 3, 4> = Tensor3D::zeros();
+/// let k: Tensor3D<2, 5, 4> = Tensor3D::zeros();
+/// let r: Tensor3D<2, 3, 4> = batch_multi_head_attention::<2, 2, 3, 5, 4, _>(q, &k, &k, false);
+/// ```
+pub fn batch_multi_head_attention<
+    const HEADS: usize,
+    const B: usize,
+    const S: usize,
+    const S2: usize,
+    const E: usize,
+    TAPE: Tape,
+>(
+    q: Tensor3D<B, S, E, TAPE>,
+    k: &Tensor3D<B, S2, E, NoneTape>,
+    v: &Tensor3D<B, S2, E, NoneTape>,
+    causal: bool,
+) -> Tensor3D<B, S, E, TAPE> {
+    attention::<HEADS, S, S2, E, _, _>(q, k, v, causal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    /// `sum(multi_head_attention(q, k, v) * w)` without a tape.
+    fn loss(q: [[f32; 4]; 3], k: [[f32; 4]; 2], v: [[f32; 4]; 2], w: &Tensor2D<3, 4>) -> f32 {
+        let (q, k, v) = (Tensor2D::new(q), Tensor2D::new(k), Tensor2D::new(v));
+        *mul(multi_head_attention::<2, 3, 2, 4, _>(q, &k, &v, false), w)
+            .sum()
+            .data()
+    }
+
+    #[test]
+    fn test_attention_grads_match_finite_differences() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let q: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let k: Tensor2D<2, 4> = Tensor2D::randn(&mut rng);
+        let v: Tensor2D<2, 4> = Tensor2D::randn(&mut rng);
+        let w: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+
+        let r = multi_head_attention::<2, 3, 2, 4, _>(q.trace(), &k, &v, false);
+        let gradients = mul(r, &w).sum().backward();
+
+        const EPS: f32 = 1e-2;
+        let (qd, kd, vd) = (*q.data(), *k.data(), *v.data());
+        let mut q_grad = [[0.0; 4]; 3];
+        for (i, row) in q_grad.iter_mut().enumerate() {
+            for (j, g) in row.iter_mut().enumerate() {
+                let (mut p, mut m) = (qd, qd);
+                p[i][j] += EPS;
+                m[i][j] -= EPS;
+                *g = (loss(p, kd, vd, &w) - loss(m, kd, vd, &w)) / (2.0 * EPS);
+            }
+        }
+        let (mut k_grad, mut v_grad) = ([[0.0; 4]; 2], [[0.0; 4]; 2]);
+        for i in 0..2 {
+            for j in 0..4 {
+                let (mut p, mut m) = (kd, kd);
+                p[i][j] += EPS;
+                m[i][j] -= EPS;
+                k_grad[i][j] = (loss(qd, p, vd, &w) - loss(qd, m, vd, &w)) / (2.0 * EPS);
+                let (mut p, mut m) = (vd, vd);
+                p[i][j] += EPS;
+                m[i][j] -= EPS;
+                v_grad[i][j] = (loss(qd, kd, p, &w) - loss(qd, kd, m, &w)) / (2.0 * EPS);
+            }
+        }
+        gradients.ref_gradient(&q).assert_close(&q_grad, 1e-3);
+        gradients.ref_gradient(&k).assert_close(&k_grad, 1e-3);
+        gradients.ref_gradient(&v).assert_close(&v_grad, 1e-3);
+    }
+
+    #[test]
+    fn test_attention_causal() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x: Tensor2D<3, 2> = Tensor2D::randn(&mut rng);
+        let r = multi_head_attention::<1, 3, 3, 2, _>(x.trace(), &x, &x, true);
+        // the first query can only attend to the first key
+        r.data()[0].assert_close(&x.data()[0], 1e-6);
+        let w: Tensor2D<3, 2> = Tensor2D::new([[1.0, 1.0], [0.0; 2], [0.0; 2]]);
+        let gradients = mul(r, &w).sum().backward();
+        // so the other keys & values get no gradient from it
+        let g = gradients.ref_gradient(&x);
+        assert_eq!(g[1], [0.0; 2]);
+        assert_eq!(g[2], [0.0; 2]);
+    }
+
+    #[test]
+    fn test_attention_heads_are_independent() {
+        let q: Tensor2D<1, 2> = Tensor2D::new([[1.0, 0.0]]);
+        let k: Tensor2D<2, 2> = Tensor2D::new([[1.0, 0.0], [-1.0, 0.0]]);
+        let v: Tensor2D<2, 2> = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let r = multi_head_attention::<2, 1, 2, 2, _>(q.duplicate(), &k, &v, false);
+        let p = 1.0 / (1.0 + (-2.0f32).exp());
+        r.data()
+            .assert_close(&[[p * 1.0 + (1.0 - p) * 3.0, 3.0]], 1e-6);
+        // with a single head, both columns use the same probabilities
+        let r = multi_head_attention::<1, 1, 2, 2, _>(q, &k, &v, false);
+        let p = 1.0 / (1.0 + (-2.0f32 / 2.0f32.sqrt()).exp());
+        r.data().assert_close(
+            &[[p * 1.0 + (1.0 - p) * 3.0, p * 2.0 + (1.0 - p) * 4.0]],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_batch_attention_matches_2d() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let q: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let k: Tensor3D<2, 5, 4> = Tensor3D::randn(&mut rng);
+        let r = batch_multi_head_attention::<2, 2, 3, 5, 4, _>(q.duplicate(), &k, &k, true);
+        for b in 0..2 {
+            let q_b = Tensor2D::new(q.data()[b]);
+            let k_b = Tensor2D::new(k.data()[b]);
+            let r_b = multi_head_attention::<2, 3, 5, 4, _>(q_b, &k_b, &k_b, true);
+            assert_eq!(&r.data()[b], r_b.data());
+        }
+    }
+}
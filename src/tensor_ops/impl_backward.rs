@@ -7,12 +7,74 @@ use crate::prelude::*;
 /// Note that `t` is required to have [OwnedTape], which means it currently owns the [crate::gradients::GradientTape].
 pub fn backward<T: Tensor<Dtype = f32, Tape = OwnedTape>>(t: T) -> Gradients {
     let (t, mut tape) = t.split_tape();
-    tape.add_backward_op(move |grads| {
+    tape.add_backward_op_named("backward", move |grads| {
         T::Device::fill(grads.mut_gradient(&t), &mut |v| *v = 1.0);
     });
     tape.0.execute()
 }
 
+/// Runs backprop like [backward()], but also measures how long each backward operation takes.
+/// See [crate::gradients::GradientTape::execute_timed()].
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let (gradients, timings) = backward_timed(x.trace().exp().sum());
+/// let names: Vec<&str> = timings.iter().map(|(name, _)| *name).collect();
+/// assert_eq!(names, ["backward", "sum", "exp"]);
+/// ```
+#[cfg(feature = "std")]
+pub fn backward_timed<T: Tensor<Dtype = f32, Tape = OwnedTape>>(
+    t: T,
+) -> (Gradients, Vec<(&'static str, std::time::Duration)>) {
+    let (t, mut tape) = t.split_tape();
+    tape.add_backward_op_named("backward", move |grads| {
+        T::Device::fill(grads.mut_gradient(&t), &mut |v| *v = 1.0);
+    });
+    tape.0.execute_timed()
+}
+
+/// Runs backprop like [backward()], but uses `seed` as the gradient of `t` instead of all ones.
+/// This computes the vector-Jacobian product `seed * d(t)/d(x)` for every `x` that `t` depends on.
+///
+/// `t` can have any number of dimensions, and `seed` has the same shape as `t`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let y = x.trace().square();
+/// let gradients = backward_with(y, [1.0, 0.5, -1.0]);
+/// assert_eq!(gradients.ref_gradient(&x), &[2.0, 2.0, -6.0]);
+/// ```
+pub fn backward_with<T: Tensor<Dtype = f32, Tape = OwnedTape>>(t: T, seed: T::Array) -> Gradients {
+    let (t, mut tape) = t.split_tape();
+    // the tape executes in reverse order, so this runs before all the operations that created `t`
+    tape.add_backward_op_named("backward_with", move |grads| {
+        *grads.mut_gradient(&t) = seed.clone();
+    });
+    tape.0.execute()
+}
+
+/// Runs backprop like [backward()], but computes the gradients with taped tensor operations, so
+/// that they can be differentiated again. See [crate::gradients::GradientTape::execute_with_tape()]
+/// for which operations support this, and [TapedGradients] for an example.
+pub fn backward_with_tape<T: Tensor<Dtype = f32, Tape = OwnedTape>>(t: T) -> TapedGradients {
+    let (t, mut tape) = t.split_tape();
+    let phantom_t = t.phantom();
+    // the operations are kept on the tape of the gradients, so the first one runs when the gradients
+    // are differentiated again. `t` doesn't affect them, so its gradient is zeros instead of ones.
+    tape.add_backward_op_taped(
+        "backward",
+        move |grads| {
+            grads.mut_gradient(&phantom_t);
+        },
+        move |grads| grads.accumulate(&t, traced::<T>(T::NoTape::ones())),
+    );
+    tape.0.execute_with_tape()
+}
+
 macro_rules! tensor_impl {
     ($typename:ident, [$($Vs:tt),*]) => {
 impl<$(const $Vs: usize, )*> $typename<$($Vs, )* OwnedTape> {
@@ -20,6 +82,22 @@ impl<$(const $Vs: usize, )*> $typename<$($Vs, )* OwnedTape> {
     pub fn backward(self) -> Gradients {
         backward(self)
     }
+
+    /// Calls [backward_timed()] on `self`
+    #[cfg(feature = "std")]
+    pub fn backward_timed(self) -> (Gradients, Vec<(&'static str, std::time::Duration)>) {
+        backward_timed(self)
+    }
+
+    /// Calls [backward_with_tape()] on `self`
+    pub fn backward_with_tape(self) -> TapedGradients {
+        backward_with_tape(self)
+    }
+
+    /// Calls [backward_with()] on `self`
+    pub fn backward_with(self, seed: <Self as HasArrayType>::Array) -> Gradients {
+        backward_with(self, seed)
+    }
 }
     };
 }
@@ -29,3 +107,221 @@ tensor_impl!(Tensor1D, [M]);
 tensor_impl!(Tensor2D, [M, N]);
 tensor_impl!(Tensor3D, [M, N, O]);
 tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradcheck::gradcheck;
+    use crate::tests::assert_close;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_backward_with_ones_is_backward() {
+        let x = Tensor2D::new([[1.0, -2.0, 3.0], [0.5, 0.0, -1.5]]);
+        let g1 = x.trace().exp().backward();
+        let g2 = x.trace().exp().backward_with([[1.0; 3]; 2]);
+        assert_eq!(g1.ref_gradient(&x), g2.ref_gradient(&x));
+    }
+
+    #[test]
+    fn test_backward_with_1d_vjp() {
+        let x = Tensor1D::new([1.0, 2.0, 3.0]);
+        let w = Tensor2D::new([[1.0, 0.0, -1.0], [2.0, 1.0, 0.5]]);
+        // y = w * x, so the vjp is `seed * w`
+        let y: Tensor1D<2, OwnedTape> = vecmat_mul_transpose(x.trace(), &w);
+        let gradients = y.backward_with([0.5, -2.0]);
+        assert_eq!(gradients.ref_gradient(&x), &[-3.5, -2.0, -1.5]);
+    }
+
+    #[test]
+    fn test_backward_with_3d() {
+        let x: Tensor3D<2, 1, 2> = Tensor3D::new([[[1.0, 2.0]], [[-1.0, 0.5]]]);
+        let gradients = x
+            .trace()
+            .square()
+            .backward_with([[[1.0, 0.0]], [[2.0, -1.0]]]);
+        assert_eq!(gradients.ref_gradient(&x), &[[[2.0, 0.0]], [[-4.0, -1.0]]]);
+    }
+
+    #[test]
+    fn test_backward_timed() {
+        type Model = (Linear<64, 64>, Tanh, Linear<64, 64>, Tanh, Linear<64, 1>);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model: Model = Default::default();
+        model.reset_params(&mut rng);
+        let x: Tensor2D<32, 64> = Tensor2D::randn(&mut rng);
+
+        let expected = model.forward(x.trace()).mean().backward();
+
+        let loss = model.forward(x.trace()).mean();
+        let mut names = loss.tape().op_names();
+        let start = std::time::Instant::now();
+        let (gradients, timings) = loss.backward_timed();
+        let total = start.elapsed();
+
+        names.push("backward");
+        names.reverse();
+        assert_eq!(timings.iter().map(|(n, _)| *n).collect::<Vec<_>>(), names);
+        let timed: std::time::Duration = timings.iter().map(|(_, t)| *t).sum();
+        assert!(timed <= total);
+        assert_eq!(
+            gradients.ref_gradient(&model.0.weight),
+            expected.ref_gradient(&model.0.weight)
+        );
+        assert_eq!(
+            gradients.ref_gradient(&model.2.bias),
+            expected.ref_gradient(&model.2.bias)
+        );
+        assert_eq!(
+            gradients.ref_gradient(&model.4.weight),
+            expected.ref_gradient(&model.4.weight)
+        );
+    }
+
+    /// `matmul(relu(matmul(x, w1) + b1), w2)`, a small discriminator for a gradient penalty.
+    fn discriminator(
+        x: Tensor2D<4, 3, OwnedTape>,
+        w1: &Tensor2D<3, 5>,
+        b1: &Tensor2D<4, 5>,
+        w2: &Tensor2D<5, 1>,
+    ) -> Tensor2D<4, 1, OwnedTape> {
+        matmul(relu(matmul(x, w1) + b1), w2)
+    }
+
+    #[test]
+    fn test_backward_with_tape_same_as_backward() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let w1: Tensor2D<3, 5> = Tensor2D::randn(&mut rng);
+        let b1: Tensor2D<4, 5> = Tensor2D::randn(&mut rng);
+        let w2: Tensor2D<5, 1> = Tensor2D::randn(&mut rng);
+
+        let expected = discriminator(x.trace(), &w1, &b1, &w2).backward();
+        let gradients = discriminator(x.trace(), &w1, &b1, &w2).backward_with_tape();
+        assert_eq!(
+            gradients.gradient(&x).unwrap().data(),
+            expected.ref_gradient(&x)
+        );
+        assert_eq!(
+            gradients.gradient(&w1).unwrap().data(),
+            expected.ref_gradient(&w1)
+        );
+        assert_eq!(
+            gradients.gradient(&b1).unwrap().data(),
+            expected.ref_gradient(&b1)
+        );
+        assert_eq!(
+            gradients.gradient(&w2).unwrap().data(),
+            expected.ref_gradient(&w2)
+        );
+    }
+
+    #[test]
+    fn test_backward_with_tape_gradient_penalty() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let w1: Tensor2D<3, 5> = Tensor2D::randn(&mut rng);
+        let b1: Tensor2D<4, 5> = Tensor2D::randn(&mut rng);
+        let w2: Tensor2D<5, 1> = Tensor2D::randn(&mut rng);
+
+        // the penalty is `sum(d(discriminator)/dx ^ 2)`, differentiated wrt. `w1` and `w2`
+        let penalty =
+            |x_grad: Tensor2D<4, 3>, tape: OwnedTape| x_grad.put_tape(tape).square().sum();
+        let w1_penalty = |w1: Tensor2D<3, 5, OwnedTape>| {
+            let (w1, tape) = w1.split_tape();
+            let gradients =
+                discriminator(x.duplicate().put_tape(tape), &w1, &b1, &w2).backward_with_tape();
+            penalty(gradients.gradient(&x).unwrap(), gradients.into_tape())
+        };
+        let w2_penalty = |w2: Tensor2D<5, 1, OwnedTape>| {
+            let (w2, tape) = w2.split_tape();
+            let gradients =
+                discriminator(x.duplicate().put_tape(tape), &w1, &b1, &w2).backward_with_tape();
+            penalty(gradients.gradient(&x).unwrap(), gradients.into_tape())
+        };
+        assert!(gradcheck(w1_penalty, &w1, 1e-2) < 1e-2);
+        assert!(gradcheck(w2_penalty, &w2, 1e-2) < 1e-2);
+    }
+
+    #[test]
+    fn test_backward_with_tape_mul() {
+        let x = Tensor1D::new([1.0, -2.0, 3.0]);
+        let y = Tensor1D::new([0.5, 2.0, -1.0]);
+
+        // d(x * y * x)/dx is `2 * x * y`, so the gradient of its sum wrt. y is `2 * x`
+        let gradients = mul_merged(x.trace() * &y, x.trace()).backward_with_tape();
+        let x_grad = gradients.gradient(&x).unwrap();
+        assert_eq!(x_grad.data(), &[1.0, -8.0, -6.0]);
+
+        let penalty_gradients = x_grad.put_tape(gradients.into_tape()).sum().backward();
+        assert_eq!(penalty_gradients.ref_gradient(&y), &[2.0, -4.0, 6.0]);
+        assert_eq!(penalty_gradients.ref_gradient(&x), &[1.0, 4.0, -2.0]);
+    }
+
+    #[test]
+    fn test_backward_with_tape_linear() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut model: (Linear<3, 5>, ReLU, Linear<5, 2>) = Default::default();
+        model.reset_params(&mut rng);
+        model.2.bias = Tensor1D::randn(&mut rng);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+        let x1: Tensor1D<3> = Tensor1D::randn(&mut rng);
+
+        let expected = (model.forward(x.trace()).mean() * 2.0 - 1.0).backward();
+        let gradients = (model.forward(x.trace()).mean() * 2.0 - 1.0).backward_with_tape();
+        assert_close(
+            gradients.gradient(&x).unwrap().data(),
+            expected.ref_gradient(&x),
+        );
+        let w = &model.0.weight;
+        assert_close(
+            gradients.gradient(w).unwrap().data(),
+            expected.ref_gradient(w),
+        );
+        let b = &model.2.bias;
+        assert_close(
+            gradients.gradient(b).unwrap().data(),
+            expected.ref_gradient(b),
+        );
+
+        let expected = model.forward(x1.trace()).sum().backward();
+        let gradients = model.forward(x1.trace()).sum().backward_with_tape();
+        assert_close(
+            gradients.gradient(&x1).unwrap().data(),
+            expected.ref_gradient(&x1),
+        );
+        assert_close(
+            gradients.gradient(w).unwrap().data(),
+            expected.ref_gradient(w),
+        );
+    }
+
+    #[test]
+    fn test_backward_with_tape_linear_gradient_penalty() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut model: (Linear<3, 5>, ReLU, Linear<5, 1>) = Default::default();
+        model.reset_params(&mut rng);
+        model.0.bias = Tensor1D::randn(&mut rng);
+        let x: Tensor2D<4, 3> = Tensor2D::randn(&mut rng);
+
+        // the penalty is `mean(d(model)/dx ^ 2)`, differentiated wrt. the first weight
+        let w_penalty = |w: Tensor2D<5, 3, OwnedTape>| {
+            let (w, tape) = w.split_tape();
+            let mut model = model.clone();
+            model.0.weight = w;
+            let gradients = model
+                .forward(x.duplicate().put_tape(tape))
+                .backward_with_tape();
+            let x_grad = gradients.gradient(&x).unwrap();
+            x_grad.put_tape(gradients.into_tape()).square().mean()
+        };
+        assert!(gradcheck(w_penalty, &model.0.weight, 1e-2) < 1e-2);
+    }
+
+    #[test]
+    #[should_panic = "backward op `tanh` has no taped version"]
+    fn test_backward_with_tape_unsupported_op() {
+        let x = Tensor1D::new([1.0, -2.0, 3.0]);
+        let _ = x.trace().tanh().backward_with_tape();
+    }
+}
@@ -0,0 +1,286 @@
+use crate::prelude::*;
+
+/// `mask * a + (1 - mask) * b`. Chooses values from `a` where `mask` is `1.0`, and from `b` where `mask` is `0.0`.
+///
+/// Gradients flow into `a` where `mask` is `1.0`, and into `b` where `mask` is `0.0`. No gradient
+/// flows into `mask`.
+///
+/// Both `a` and `b` may own a tape, in which case the tapes are merged together into the result.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let mask = Tensor1D::new([1.0, 0.0, 1.0]);
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([-1.0, -2.0, -3.0]);
+/// let r = choose(&mask, a, b);
+/// assert_eq!(r.data(), &[1.0, -2.0, 3.0]);
+/// ```
+///
+/// Both tensors can have a tape:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let mask = Tensor1D::new([1.0, 0.0, 1.0]);
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([-1.0, -2.0, -3.0]);
+/// let r: Tensor1D<3, OwnedTape> = choose(&mask, a.trace(), b.trace());
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[1.0, 0.0, 1.0]);
+/// assert_eq!(gradients.ref_gradient(&b), &[0.0, 1.0, 0.0]);
+/// ```
+pub fn choose<A, B>(
+    mask: &A::NoTape,
+    a: A,
+    b: B,
+) -> <A::NoTape as PutTape<<A::Tape as MergeTape<B::Tape>>::Output>>::Output
+where
+    A: Tensor<Dtype = f32>,
+    B: Tensor<Dtype = f32, Array = A::Array, NoTape = A::NoTape>,
+    A::Tape: MergeTape<B::Tape>,
+    A::NoTape: PutTape<<A::Tape as MergeTape<B::Tape>>::Output>,
+{
+    let (mut a, a_tape) = a.split_tape();
+    let (mut b, b_tape) = b.split_tape();
+
+    let mut result = A::NoTape::zeros();
+    A::Device::foreach_mrr(result.mut_data(), mask.data(), a.data(), &mut |r, m, a| {
+        *r = m * a;
+    });
+    A::Device::foreach_mrr(result.mut_data(), mask.data(), b.data(), &mut |r, m, b| {
+        *r += (1.0 - m) * b;
+    });
+
+    // store derivatives in a & b
+    A::Device::foreach_mr(a.mut_data(), mask.data(), &mut |a, m| *a = *m);
+    A::Device::foreach_mr(b.mut_data(), mask.data(), &mut |b, m| *b = 1.0 - m);
+
+    let mut tape = a_tape.merge_tape(b_tape);
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("choose", move |grads| {
+        let (a_grad, result_grad) = grads.mut_and_ref(&a, &phantom_result);
+        A::Device::addmul(a_grad, a.data(), result_grad);
+
+        let (b_grad, result_grad) = grads.mut_and_ref(&b, &phantom_result);
+        A::Device::addmul(b_grad, b.data(), result_grad);
+    });
+    PutTape::put_tape(result, tape)
+}
+
+/// `cond ? a : b`. Chooses values from `a` where `cond` is `true`, and from `b` where `cond` is `false`.
+/// Like [choose()], but with a `bool` mask.
+///
+/// Each element of the result's gradient flows into whichever of `a` or `b` it was chosen from,
+/// and the other one gets `0.0`.
+///
+/// Both `a` and `b` may own a tape, in which case the tapes are merged together into the result.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let cond = [[true, false], [false, true]];
+/// let a = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+/// let b = Tensor2D::new([[-1.0, -2.0], [-3.0, -4.0]]);
+/// let r: Tensor2D<2, 2, OwnedTape> = where_(&cond, a.trace(), b.trace());
+/// assert_eq!(r.data(), &[[1.0, -2.0], [-3.0, 4.0]]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&a), &[[1.0, 0.0], [0.0, 1.0]]);
+/// assert_eq!(gradients.ref_gradient(&b), &[[0.0, 1.0], [1.0, 0.0]]);
+/// ```
+pub fn where_<const M: usize, const N: usize, HA, HB>(
+    cond: &[[bool; N]; M],
+    a: Tensor2D<M, N, HA>,
+    b: Tensor2D<M, N, HB>,
+) -> Tensor2D<M, N, HA::Output>
+where
+    HA: MergeTape<HB>,
+    HB: Tape,
+{
+    let (a, a_tape) = a.split_tape();
+    let (b, b_tape) = b.split_tape();
+
+    let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+    for (((r_i, c_i), a_i), b_i) in result
+        .mut_data()
+        .iter_mut()
+        .zip(cond)
+        .zip(a.data())
+        .zip(b.data())
+    {
+        for (((r, &c), a), b) in r_i.iter_mut().zip(c_i).zip(a_i).zip(b_i) {
+            *r = if c { *a } else { *b };
+        }
+    }
+
+    let cond = *cond;
+    let mut tape = a_tape.merge_tape(b_tape);
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("where", move |grads| {
+        let (a_grad, result_grad) = grads.mut_and_ref(&a, &phantom_result);
+        add_where(a_grad, result_grad, &cond, true);
+
+        let (b_grad, result_grad) = grads.mut_and_ref(&b, &phantom_result);
+        add_where(b_grad, result_grad, &cond, false);
+    });
+    result.put_tape(tape)
+}
+
+/// `grad += result_grad` wherever `cond == value`.
+fn add_where<const M: usize, const N: usize>(
+    grad: &mut [[f32; N]; M],
+    result_grad: &[[f32; N]; M],
+    cond: &[[bool; N]; M],
+    value: bool,
+) {
+    for ((g_i, r_i), c_i) in grad.iter_mut().zip(result_grad).zip(cond) {
+        for ((g, r), &c) in g_i.iter_mut().zip(r_i).zip(c_i) {
+            if c == value {
+                *g += r;
+            }
+        }
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Calls [where_()] with `self` as `a`.
+    pub fn where_<HB: Tape>(
+        self,
+        cond: &[[bool; N]; M],
+        b: Tensor2D<M, N, HB>,
+    ) -> Tensor2D<M, N, <H as MergeTape<HB>>::Output>
+    where
+        H: MergeTape<HB>,
+    {
+        where_(cond, self, b)
+    }
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [choose()] with `self` as `a`.
+    pub fn choose<HB: Tape>(
+        self,
+        mask: &$typename<$($Vs, )* NoneTape>,
+        b: $typename<$($Vs, )* HB>,
+    ) -> $typename<$($Vs, )* <H as MergeTape<HB>>::Output>
+    where
+        H: MergeTape<HB>,
+    {
+        choose(mask, self, b)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_no_tapes() {
+        let m = Tensor2D::new([[1.0, 0.0], [0.0, 1.0]]);
+        let a = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let b = Tensor2D::new([[-1.0, -2.0], [-3.0, -4.0]]);
+        let r: Tensor2D<2, 2, NoneTape> = a.choose(&m, b);
+        assert_eq!(r.data(), &[[1.0, -2.0], [-3.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_choose_lhs_tape() {
+        let m = Tensor1D::new([1.0, 0.0, 1.0]);
+        let a = Tensor1D::new([1.0, 2.0, 3.0]);
+        let b = Tensor1D::new([-1.0, -2.0, -3.0]);
+        let r = a.trace().choose(&m, b.duplicate());
+        assert_eq!(r.data(), &[1.0, -2.0, 3.0]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[2.7182817, 0.0, 20.085537]);
+        assert_eq!(gradients.ref_gradient(&b), &[0.0, 0.13533528, 0.0]);
+    }
+
+    #[test]
+    fn test_choose_rhs_tape() {
+        let m = Tensor1D::new([1.0, 0.0, 1.0]);
+        let a = Tensor1D::new([1.0, 2.0, 3.0]);
+        let b = Tensor1D::new([-1.0, -2.0, -3.0]);
+        let r: Tensor1D<3, OwnedTape> = a.duplicate().choose(&m, b.trace());
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[1.0 / 3.0, 0.0, 1.0 / 3.0]);
+        assert_eq!(gradients.ref_gradient(&b), &[0.0, 1.0 / 3.0, 0.0]);
+    }
+
+    #[test]
+    fn test_choose_both_tapes() {
+        let m = Tensor1D::new([1.0, 0.0, 1.0]);
+        let x = Tensor1D::new([1.0, 2.0, 3.0]);
+        let y = Tensor1D::new([-1.0, -2.0, -3.0]);
+        let a = x.trace() * 2.0;
+        let b = y.trace().square();
+        let r = a.choose(&m, b);
+        assert_eq!(r.data(), &[2.0, 4.0, 6.0]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[2.0, 0.0, 2.0]);
+        assert_eq!(gradients.ref_gradient(&y), &[0.0, -4.0, 0.0]);
+    }
+
+    #[test]
+    fn test_choose_both_tapes_same_source() {
+        let m = Tensor1D::new([1.0, 0.0]);
+        let x = Tensor1D::new([1.0, 2.0]);
+        let a = x.trace() * 3.0;
+        let b = x.trace() * -1.0;
+        let r = a.choose(&m, b);
+        assert_eq!(r.data(), &[3.0, -2.0]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[3.0, -1.0]);
+    }
+
+    #[test]
+    fn test_where_lhs_tape() {
+        let cond = [[true, false, true], [false, false, true]];
+        let a = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let b = Tensor2D::new([[-1.0, -2.0, -3.0], [-4.0, -5.0, -6.0]]);
+        let r = a.trace().where_(&cond, b.duplicate());
+        assert_eq!(r.data(), &[[1.0, -2.0, 3.0], [-4.0, -5.0, 6.0]]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[2.7182817, 0.0, 20.085537], [0.0, 0.0, 403.4288]]
+        );
+        assert_eq!(
+            gradients.ref_gradient(&b),
+            &[[0.0, 0.13533528, 0.0], [0.01831564, 0.006737947, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_where_both_tapes() {
+        let cond = [[true, false], [false, true]];
+        let x = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let y = Tensor2D::new([[-1.0, -2.0], [-3.0, -4.0]]);
+        let a = x.trace() * 2.0;
+        let b = y.trace().square();
+        let r = where_(&cond, a, b);
+        assert_eq!(r.data(), &[[2.0, 4.0], [9.0, 8.0]]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[2.0, 0.0], [0.0, 2.0]]);
+        assert_eq!(gradients.ref_gradient(&y), &[[0.0, -4.0], [-6.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_where_same_source() {
+        let cond = [[true, false]];
+        let x = Tensor2D::new([[1.0, 2.0]]);
+        let r = where_(&cond, x.trace() * 3.0, x.trace() * -1.0);
+        assert_eq!(r.data(), &[[3.0, -2.0]]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[3.0, -1.0]]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::prelude::*;
+
+/// Applies `f` to each pair of elements in `lhs` and `rhs`, producing `1.0` where `f` is true and `0.0` otherwise.
+fn cmp_mask<T: Tensor<Dtype = f32>>(
+    lhs: &T,
+    rhs: &T::NoTape,
+    mut f: impl FnMut(&f32, &f32) -> bool,
+) -> T::NoTape {
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_mrr(result.mut_data(), lhs.data(), rhs.data(), &mut |o, l, r| {
+        *o = if f(l, r) { 1.0 } else { 0.0 };
+    });
+    result
+}
+
+/// Applies `f` to each element in `t`, producing `1.0` where `f` is true and `0.0` otherwise.
+fn cmp_scalar_mask<T: Tensor<Dtype = f32>>(t: &T, f: impl Fn(&f32) -> bool) -> T::NoTape {
+    T::NoTape::new_boxed(T::Device::map(t.data(), |x| if f(x) { 1.0 } else { 0.0 }))
+}
+
+/// `lhs < rhs`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([2.0, 2.0, 2.0]);
+/// assert_eq!(lt(&a, &b).data(), &[1.0, 0.0, 0.0]);
+/// ```
+pub fn lt<T: Tensor<Dtype = f32>>(lhs: &T, rhs: &T::NoTape) -> T::NoTape {
+    cmp_mask(lhs, rhs, |l, r| l < r)
+}
+
+/// `lhs <= rhs`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([2.0, 2.0, 2.0]);
+/// assert_eq!(le(&a, &b).data(), &[1.0, 1.0, 0.0]);
+/// ```
+pub fn le<T: Tensor<Dtype = f32>>(lhs: &T, rhs: &T::NoTape) -> T::NoTape {
+    cmp_mask(lhs, rhs, |l, r| l <= r)
+}
+
+/// `lhs > rhs`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([2.0, 2.0, 2.0]);
+/// assert_eq!(gt(&a, &b).data(), &[0.0, 0.0, 1.0]);
+/// ```
+pub fn gt<T: Tensor<Dtype = f32>>(lhs: &T, rhs: &T::NoTape) -> T::NoTape {
+    cmp_mask(lhs, rhs, |l, r| l > r)
+}
+
+/// `lhs >= rhs`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([2.0, 2.0, 2.0]);
+/// assert_eq!(ge(&a, &b).data(), &[0.0, 1.0, 1.0]);
+/// ```
+pub fn ge<T: Tensor<Dtype = f32>>(lhs: &T, rhs: &T::NoTape) -> T::NoTape {
+    cmp_mask(lhs, rhs, |l, r| l >= r)
+}
+
+/// `|lhs - rhs| <= epsilon`. Returns a mask with `1.0` where the values are equal (within `epsilon`),
+/// and `0.0` otherwise. Use an `epsilon` of `0.0` for exact equality.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let b = Tensor1D::new([1.0 + 1e-7, 2.5, 3.0]);
+/// assert_eq!(eq(&a, &b, 1e-6).data(), &[1.0, 0.0, 1.0]);
+/// ```
+pub fn eq<T: Tensor<Dtype = f32>>(lhs: &T, rhs: &T::NoTape, epsilon: f32) -> T::NoTape {
+    cmp_mask(lhs, rhs, |l, r| (l - r).abs() <= epsilon)
+}
+
+/// `t < val`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+/// assert_eq!(lt_scalar(&t, 2.0).data(), &[1.0, 0.0, 0.0]);
+/// ```
+pub fn lt_scalar<T: Tensor<Dtype = f32>>(t: &T, val: T::Dtype) -> T::NoTape {
+    cmp_scalar_mask(t, |x| *x < val)
+}
+
+/// `t <= val`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+/// assert_eq!(le_scalar(&t, 2.0).data(), &[1.0, 1.0, 0.0]);
+/// ```
+pub fn le_scalar<T: Tensor<Dtype = f32>>(t: &T, val: T::Dtype) -> T::NoTape {
+    cmp_scalar_mask(t, |x| *x <= val)
+}
+
+/// `t > val`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+/// assert_eq!(gt_scalar(&t, 2.0).data(), &[0.0, 0.0, 1.0]);
+/// ```
+pub fn gt_scalar<T: Tensor<Dtype = f32>>(t: &T, val: T::Dtype) -> T::NoTape {
+    cmp_scalar_mask(t, |x| *x > val)
+}
+
+/// `t >= val`. Returns a mask with `1.0` where the comparison is true, and `0.0` otherwise.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+/// assert_eq!(ge_scalar(&t, 2.0).data(), &[0.0, 1.0, 1.0]);
+/// ```
+pub fn ge_scalar<T: Tensor<Dtype = f32>>(t: &T, val: T::Dtype) -> T::NoTape {
+    cmp_scalar_mask(t, |x| *x >= val)
+}
+
+/// `|t - val| <= epsilon`. Returns a mask with `1.0` where the values are equal (within `epsilon`),
+/// and `0.0` otherwise. Use an `epsilon` of `0.0` for exact equality.
+///
+/// The result does not have a tape, since comparisons are not differentiable.
+///
+/// Example:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0 + 1e-7, 3.0]);
+/// assert_eq!(eq_scalar(&t, 2.0, 1e-6).data(), &[0.0, 1.0, 0.0]);
+/// ```
+pub fn eq_scalar<T: Tensor<Dtype = f32>>(t: &T, val: T::Dtype, epsilon: T::Dtype) -> T::NoTape {
+    cmp_scalar_mask(t, |x| (x - val).abs() <= epsilon)
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [lt()] on `self`.
+    pub fn lt(&self, rhs: &$typename<$($Vs, )* NoneTape>) -> $typename<$($Vs, )* NoneTape> {
+        lt(self, rhs)
+    }
+
+    /// Calls [le()] on `self`.
+    pub fn le(&self, rhs: &$typename<$($Vs, )* NoneTape>) -> $typename<$($Vs, )* NoneTape> {
+        le(self, rhs)
+    }
+
+    /// Calls [gt()] on `self`.
+    pub fn gt(&self, rhs: &$typename<$($Vs, )* NoneTape>) -> $typename<$($Vs, )* NoneTape> {
+        gt(self, rhs)
+    }
+
+    /// Calls [ge()] on `self`.
+    pub fn ge(&self, rhs: &$typename<$($Vs, )* NoneTape>) -> $typename<$($Vs, )* NoneTape> {
+        ge(self, rhs)
+    }
+
+    /// Calls [eq()] on `self`.
+    pub fn eq(&self, rhs: &$typename<$($Vs, )* NoneTape>, epsilon: f32) -> $typename<$($Vs, )* NoneTape> {
+        eq(self, rhs, epsilon)
+    }
+
+    /// Calls [lt_scalar()] on `self`.
+    pub fn lt_scalar(&self, val: f32) -> $typename<$($Vs, )* NoneTape> {
+        lt_scalar(self, val)
+    }
+
+    /// Calls [le_scalar()] on `self`.
+    pub fn le_scalar(&self, val: f32) -> $typename<$($Vs, )* NoneTape> {
+        le_scalar(self, val)
+    }
+
+    /// Calls [gt_scalar()] on `self`.
+    pub fn gt_scalar(&self, val: f32) -> $typename<$($Vs, )* NoneTape> {
+        gt_scalar(self, val)
+    }
+
+    /// Calls [ge_scalar()] on `self`.
+    pub fn ge_scalar(&self, val: f32) -> $typename<$($Vs, )* NoneTape> {
+        ge_scalar(self, val)
+    }
+
+    /// Calls [eq_scalar()] on `self`.
+    pub fn eq_scalar(&self, val: f32, epsilon: f32) -> $typename<$($Vs, )* NoneTape> {
+        eq_scalar(self, val, epsilon)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmp_0d() {
+        let a = Tensor0D::new(1.0);
+        let b = Tensor0D::new(2.0);
+        assert_eq!(a.lt(&b).data(), &1.0);
+        assert_eq!(a.le(&b).data(), &1.0);
+        assert_eq!(a.gt(&b).data(), &0.0);
+        assert_eq!(a.ge(&b).data(), &0.0);
+        assert_eq!(a.eq(&b, 0.0).data(), &0.0);
+        assert_eq!(a.eq(&b, 1.0).data(), &1.0);
+    }
+
+    #[test]
+    fn test_cmp_2d() {
+        let a = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, 0.0, 1.0]]);
+        let b = Tensor2D::new([[2.0, 2.0, 2.0], [0.0, 0.0, 0.0]]);
+        assert_eq!(a.lt(&b).data(), &[[1.0, 0.0, 0.0], [1.0, 0.0, 0.0]]);
+        assert_eq!(a.le(&b).data(), &[[1.0, 1.0, 0.0], [1.0, 1.0, 0.0]]);
+        assert_eq!(a.gt(&b).data(), &[[0.0, 0.0, 1.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(a.ge(&b).data(), &[[0.0, 1.0, 1.0], [0.0, 1.0, 1.0]]);
+        assert_eq!(a.eq(&b, 0.0).data(), &[[0.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_cmp_scalar_1d() {
+        let t = Tensor1D::new([-1.0, 0.0, 1.0, 2.0]);
+        assert_eq!(t.lt_scalar(0.0).data(), &[1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(t.le_scalar(0.0).data(), &[1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(t.gt_scalar(0.0).data(), &[0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(t.ge_scalar(0.0).data(), &[0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(t.eq_scalar(0.0, 0.0).data(), &[0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(t.eq_scalar(0.0, 1.0).data(), &[1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_eq_epsilon() {
+        let a = Tensor1D::new([1.0 + 1e-6, 1.0]);
+        let b = Tensor1D::new([1.0, 1.0 + 1e-3]);
+        assert_eq!(a.eq(&b, 0.0).data(), &[0.0, 0.0]);
+        assert_eq!(a.eq(&b, 1e-5).data(), &[1.0, 0.0]);
+        assert_eq!(a.eq(&b, 1e-2).data(), &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_cmp_with_tape_is_not_tracked() {
+        let t = Tensor1D::new([-1.0, 2.0, 3.0]);
+        let r = t.trace();
+        let mask: Tensor1D<3, NoneTape> = r.gt_scalar(0.0);
+        let gradients = mul(r, &mask).sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[0.0, 1.0, 1.0]);
+    }
+}
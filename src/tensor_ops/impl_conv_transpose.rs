@@ -0,0 +1,307 @@
+use super::utils::move_tape_and_add_backward_binop;
+use crate::prelude::*;
+
+/// Causes a compile time error if `N2 != (N - 1) * S + K - 2 * P`.
+struct AssertConvTransposeSize<
+    const N: usize,
+    const K: usize,
+    const S: usize,
+    const P: usize,
+    const N2: usize,
+>;
+
+impl<const N: usize, const K: usize, const S: usize, const P: usize, const N2: usize>
+    AssertConvTransposeSize<N, K, S, P, N2>
+{
+    const OK: () = assert!(
+        S > 0 && (N - 1) * S + K >= 2 * P && N2 == (N - 1) * S + K - 2 * P,
+        "output size of a transposed convolution must be (N - 1) * S + K - 2 * P"
+    );
+}
+
+/// For input index `i` and kernel index `k`, returns the output index it is scattered into,
+/// or `None` if it lands in the padding.
+fn output_index(i: usize, k: usize, stride: usize, padding: usize, n2: usize) -> Option<usize> {
+    (i * stride + k).checked_sub(padding).filter(|&o| o < n2)
+}
+
+#[allow(clippy::type_complexity)]
+fn conv_transpose_forward<
+    const C: usize,
+    const O: usize,
+    const K: usize,
+    const H: usize,
+    const W: usize,
+    const H2: usize,
+    const W2: usize,
+>(
+    inp: &[[[f32; W]; H]; C],
+    filters: &[[[[f32; K]; K]; O]; C],
+    out: &mut [[[f32; W2]; H2]; O],
+    stride: usize,
+    padding: usize,
+) {
+    for (inp_c, filters_c) in inp.iter().zip(filters.iter()) {
+        for (out_o, f_o) in out.iter_mut().zip(filters_c.iter()) {
+            for (i, inp_i) in inp_c.iter().enumerate() {
+                for (j, x) in inp_i.iter().enumerate() {
+                    for (ki, f_ki) in f_o.iter().enumerate() {
+                        let oi = match output_index(i, ki, stride, padding, H2) {
+                            Some(oi) => oi,
+                            None => continue,
+                        };
+                        for (kj, f) in f_ki.iter().enumerate() {
+                            if let Some(oj) = output_index(j, kj, stride, padding, W2) {
+                                out_o[oi][oj] += x * f;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Accumulates the gradient of the input of [conv_transpose_forward()], which is a regular
+/// convolution of `out_grad` with `filters`.
+#[allow(clippy::type_complexity)]
+fn conv_transpose_backward_inp<
+    const C: usize,
+    const O: usize,
+    const K: usize,
+    const H: usize,
+    const W: usize,
+    const H2: usize,
+    const W2: usize,
+>(
+    inp_grad: &mut [[[f32; W]; H]; C],
+    filters: &[[[[f32; K]; K]; O]; C],
+    out_grad: &[[[f32; W2]; H2]; O],
+    stride: usize,
+    padding: usize,
+) {
+    for (inp_grad_c, filters_c) in inp_grad.iter_mut().zip(filters.iter()) {
+        for (g_o, f_o) in out_grad.iter().zip(filters_c.iter()) {
+            for (i, inp_grad_i) in inp_grad_c.iter_mut().enumerate() {
+                for (j, x_grad) in inp_grad_i.iter_mut().enumerate() {
+                    for (ki, f_ki) in f_o.iter().enumerate() {
+                        let oi = match output_index(i, ki, stride, padding, H2) {
+                            Some(oi) => oi,
+                            None => continue,
+                        };
+                        for (kj, f) in f_ki.iter().enumerate() {
+                            if let Some(oj) = output_index(j, kj, stride, padding, W2) {
+                                *x_grad += g_o[oi][oj] * f;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Accumulates the gradient of the filters of [conv_transpose_forward()].
+#[allow(clippy::type_complexity)]
+fn conv_transpose_backward_filters<
+    const C: usize,
+    const O: usize,
+    const K: usize,
+    const H: usize,
+    const W: usize,
+    const H2: usize,
+    const W2: usize,
+>(
+    inp: &[[[f32; W]; H]; C],
+    filters_grad: &mut [[[[f32; K]; K]; O]; C],
+    out_grad: &[[[f32; W2]; H2]; O],
+    stride: usize,
+    padding: usize,
+) {
+    for (inp_c, filters_grad_c) in inp.iter().zip(filters_grad.iter_mut()) {
+        for (g_o, f_grad_o) in out_grad.iter().zip(filters_grad_c.iter_mut()) {
+            for (i, inp_i) in inp_c.iter().enumerate() {
+                for (j, x) in inp_i.iter().enumerate() {
+                    for (ki, f_grad_ki) in f_grad_o.iter_mut().enumerate() {
+                        let oi = match output_index(i, ki, stride, padding, H2) {
+                            Some(oi) => oi,
+                            None => continue,
+                        };
+                        for (kj, f_grad) in f_grad_ki.iter_mut().enumerate() {
+                            if let Some(oj) = output_index(j, kj, stride, padding, W2) {
+                                *f_grad += g_o[oi][oj] * x;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<const B: usize, const C: usize, const H: usize, const W: usize, TAPE: Tape>
+    Tensor4D<B, C, H, W, TAPE>
+{
+    /// Transposed 2d convolution (sometimes called a deconvolution) of a batch of images
+    /// with `filters`, which has shape `(C, O, K, K)`, the same as pytorch's `ConvTranspose2d.weight`.
+    ///
+    /// Every input value is multiplied by its `K x K` kernel, and scattered into the output at
+    /// `(i * S - P, j * S - P)`, where overlapping values are summed. This is the gradient of
+    /// a convolution with the same `S` and `P` w.r.t. its input, and is used to increase the
+    /// spatial dimensions.
+    ///
+    /// The output size must be `(H - 1) * S + K - 2 * P` by `(W - 1) * S + K - 2 * P`, which
+    /// is checked at compile time. It can be inferred by passing `_` for `H2` and `W2`.
+    ///
+    /// Computes the gradients of both `self` and `filters`.
+    ///
+    /// # Generics
+    /// - `S`: the stride.
+    /// - `P`: the padding, which is removed from each side of the output.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let x: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+    /// let filters: Tensor4D<1, 1, 2, 2> = Tensor4D::ones();
+    /// let r = x.conv_transpose2d::<1, 0, _, _, _, _>(&filters);
+    /// assert_eq!(
+    ///     r.data(),
+    ///     &[[[[1.0, 3.0, 2.0], [4.0, 10.0, 6.0], [3.0, 7.0, 4.0]]]]
+    /// );
+    /// ```
+    ///
+    /// The wrong output size is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let x: Tensor4D<1, 1, 2, 2> = Tensor4D::zeros();
+    /// let filters: Tensor4D<1, 1, 3, 3> = Tensor4D::zeros();
+    /// let r: Tensor4D<1, 1, 4, 4> = x.conv_transpose2d::<2, 0, _, _, _, _>(&filters);
+    /// ```
+    pub fn conv_transpose2d<
+        const S: usize,
+        const P: usize,
+        const O: usize,
+        const K: usize,
+        const H2: usize,
+        const W2: usize,
+    >(
+        self,
+        filters: &Tensor4D<C, O, K, K>,
+    ) -> Tensor4D<B, O, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (
+            AssertConvTransposeSize::<H, K, S, P, H2>::OK,
+            AssertConvTransposeSize::<W, K, S, P, W2>::OK,
+        );
+
+        let mut result: Tensor4D<B, O, H2, W2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            conv_transpose_forward(t_b, filters.data(), r_b, S, P);
+        }
+
+        // copy filters data for use later when computing gradients
+        let filters_data = filters.data.clone();
+
+        move_tape_and_add_backward_binop(
+            "conv_transpose2d",
+            self,
+            filters,
+            result,
+            move |t, f, result, grads| {
+                let (t_grad, result_grad): (_, &[[[[f32; W2]; H2]; O]; B]) =
+                    grads.mut_and_ref(&t, &result);
+                for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                    conv_transpose_backward_inp(t_b, filters_data.as_ref(), r_b, S, P);
+                }
+
+                let (f_grad, result_grad): (_, &[[[[f32; W2]; H2]; O]; B]) =
+                    grads.mut_and_ref(&f, &result);
+                for (t_b, r_b) in t.data().iter().zip(result_grad.iter()) {
+                    conv_transpose_backward_filters(t_b, f_grad, r_b, S, P);
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+
+    #[test]
+    fn test_conv_transpose2d_stride_2_padding_1() {
+        let x: Tensor4D<1, 2, 2, 2> =
+            Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]], [[-1.0, 0.5], [0.0, 2.0]]]]);
+        let filters: Tensor4D<2, 1, 3, 3> = Tensor4D::new([
+            [[[1.0, 0.0, -1.0], [2.0, 1.0, 0.0], [0.0, 1.0, 1.0]]],
+            [[[0.5, 0.5, 0.0], [0.0, -1.0, 1.0], [1.0, 0.0, 2.0]]],
+        ]);
+        let r: Tensor4D<1, 1, 3, 3, OwnedTape> =
+            x.trace().conv_transpose2d::<2, 1, _, _, _, _>(&filters);
+        assert_eq!(
+            r.data(),
+            &[[[[2.0, 3.0, 1.5], [1.0, 1.5, 3.0], [3.0, 8.0, 2.0]]]]
+        );
+
+        let w: Tensor4D<1, 1, 3, 3> =
+            Tensor4D::new([[[[0.1, 0.2, 0.3], [0.4, 0.5, 0.6], [0.7, 0.8, 0.9]]]]);
+        let gradients = mul(r, &w).sum().backward();
+        gradients.ref_gradient(&x).assert_close(
+            &[[[[1.0, 1.3], [0.2, 3.0]], [[1.1, 0.2], [0.3, -0.35]]]],
+            1e-6,
+        );
+        gradients.ref_gradient(&filters).assert_close(
+            &[
+                [[[2.0, 3.6, 1.5], [3.6, 6.4, 2.6], [1.0, 1.6, 0.5]]],
+                [[[1.0, 1.2, 0.0], [1.7, 1.85, -0.2], [0.25, -0.1, -0.5]]],
+            ],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_conv_transpose2d_batched() {
+        let x: Tensor4D<2, 1, 2, 2> =
+            Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]], [[[0.0, -1.0], [1.0, 0.0]]]]);
+        let filters: Tensor4D<1, 2, 2, 2> =
+            Tensor4D::new([[[[1.0; 2]; 2], [[1.0, 0.0], [0.0, 0.0]]]]);
+        let r: Tensor4D<2, 2, 3, 3, OwnedTape> =
+            x.trace().conv_transpose2d::<1, 0, _, _, _, _>(&filters);
+        assert_eq!(
+            r.data(),
+            &[
+                [
+                    [[1.0, 3.0, 2.0], [4.0, 10.0, 6.0], [3.0, 7.0, 4.0]],
+                    [[1.0, 2.0, 0.0], [3.0, 4.0, 0.0], [0.0; 3]],
+                ],
+                [
+                    [[0.0, -1.0, -1.0], [1.0, 0.0, -1.0], [1.0, 1.0, 0.0]],
+                    [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0; 3]],
+                ],
+            ]
+        );
+        // every input value is scattered into 4 + 1 outputs
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[[[[5.0; 2]; 2]]; 2]);
+        // the sum of each batch of inputs
+        assert_eq!(gradients.ref_gradient(&filters), &[[[[10.0; 2]; 2]; 2]]);
+    }
+
+    #[test]
+    fn test_conv_transpose2d_stride_larger_than_kernel() {
+        let x: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let filters: Tensor4D<1, 1, 1, 1> = Tensor4D::new([[[[2.0]]]]);
+        let r: Tensor4D<1, 1, 4, 4> = x.conv_transpose2d::<3, 0, _, _, _, _>(&filters);
+        assert_eq!(
+            r.data(),
+            &[[[
+                [2.0, 0.0, 0.0, 4.0],
+                [0.0; 4],
+                [0.0; 4],
+                [6.0, 0.0, 0.0, 8.0]
+            ]]]
+        );
+    }
+}
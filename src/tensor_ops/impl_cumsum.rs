@@ -0,0 +1,100 @@
+use super::impl_reshape::{flat, flat_mut};
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Cumulative sum along the last dimension, so that element `i` of the result is the sum of
+/// elements `0..=i` of `t`. This is the same as pytorch's `cumsum(dim=-1)`.
+///
+/// The backward is the reverse cumulative sum of the gradient, so that the gradient of
+/// element `i` is the sum of the result's gradients `i..N`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, 0.5, 0.5]]);
+/// let r = cumsum_last_dim(t);
+/// assert_eq!(r.data(), &[[1.0, 3.0, 6.0], [-1.0, -0.5, 0.0]]);
+/// ```
+pub fn cumsum_last_dim<T: Tensor<Dtype = f32>>(t: T) -> T
+where
+    T::Array: MultiDimensional,
+{
+    let n = <T::Array as MultiDimensional>::LAST_DIM_SIZE;
+    let mut result = T::NoTape::zeros();
+    let rows = flat_mut(result.mut_data()).chunks_mut(n.max(1));
+    for (r, t) in rows.zip(flat(t.data()).chunks(n.max(1))) {
+        let mut total = 0.0;
+        for (r_i, t_i) in r.iter_mut().zip(t.iter()) {
+            total += t_i;
+            *r_i = total;
+        }
+    }
+    move_tape_and_add_backward_op("cumsum_last_dim", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        let rows = flat_mut(t_grad).chunks_mut(n.max(1));
+        for (g, r) in rows.zip(flat(result_grad).chunks(n.max(1))) {
+            let mut total = 0.0;
+            for (g_i, r_i) in g.iter_mut().zip(r.iter()).rev() {
+                total += r_i;
+                *g_i += total;
+            }
+        }
+    })
+}
+
+macro_rules! cumsum_last_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [cumsum_last_dim()] on `self`.
+    pub fn cumsum_last_dim(self) -> Self {
+        cumsum_last_dim(self)
+    }
+}
+    };
+}
+
+cumsum_last_impl!(Tensor1D, [M]);
+cumsum_last_impl!(Tensor2D, [M, N]);
+cumsum_last_impl!(Tensor3D, [M, N, O]);
+cumsum_last_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+
+    #[test]
+    fn test_cumsum_1d() {
+        let t = Tensor1D::new([1.0, -2.0, 3.0, 0.5]);
+        let r = t.trace().cumsum_last_dim();
+        assert_eq!(r.data(), &[1.0, -1.0, 2.0, 2.5]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[4.0, 3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_cumsum_2d_backward_is_reverse_cumsum() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let r = t.trace().cumsum_last_dim();
+        assert_eq!(r.data(), &[[1.0, 3.0, 6.0], [4.0, 9.0, 15.0]]);
+        let w: Tensor2D<2, 3> = Tensor2D::new([[1.0, 10.0, 100.0], [-1.0, 0.5, 2.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_close(
+            gradients.ref_gradient(&t),
+            &[[111.0, 110.0, 100.0], [1.5, 2.5, 2.0]],
+        );
+    }
+
+    #[test]
+    fn test_cumsum_3d_rows_independent() {
+        let t: Tensor3D<2, 1, 2> = Tensor3D::new([[[1.0, 2.0]], [[3.0, 4.0]]]);
+        let r = t.trace().cumsum_last_dim();
+        assert_eq!(r.data(), &[[[1.0, 3.0]], [[3.0, 7.0]]]);
+        let gradients = r.exp().sum().backward();
+        let e = |x: f32| x.exp();
+        assert_close(
+            gradients.ref_gradient(&t),
+            &[[[e(1.0) + e(3.0), e(3.0)]], [[e(3.0) + e(7.0), e(7.0)]]],
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Sums the main diagonal of a square matrix. This is the same as `torch.trace(t)` in pytorch.
+///
+/// This is named `matrix_trace` because [trace()] already starts tracking gradients of a tensor.
+///
+/// The gradient of each element on the diagonal is the gradient of the result, and `0.0` everywhere else.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+/// let r = matrix_trace(t); // or t.matrix_trace()
+/// assert_eq!(r.data(), &5.0);
+/// ```
+pub fn matrix_trace<const N: usize, H: Tape>(t: Tensor2D<N, N, H>) -> Tensor0D<H> {
+    let mut result = Tensor0D::zeros();
+    *result.mut_data() = t.data().iter().enumerate().map(|(i, t_i)| t_i[i]).sum();
+    move_tape_and_add_backward_op("matrix_trace", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        for (i, t_i) in t_grad.iter_mut().enumerate() {
+            t_i[i] += result_grad;
+        }
+    })
+}
+
+impl<const N: usize, H: Tape> Tensor2D<N, N, H> {
+    /// Extracts the main diagonal of a square matrix into a [Tensor1D]. This is the same as
+    /// `torch.diag(t)` in pytorch for 2d tensors.
+    ///
+    /// The gradient is scattered back onto the diagonal, and the rest of the matrix gets `0.0`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+    /// assert_eq!(t.diag().data(), &[1.0, 4.0]);
+    /// ```
+    pub fn diag(self) -> Tensor1D<N, H> {
+        let mut result: Tensor1D<N> = Tensor1D::zeros();
+        for (i, (r, t_i)) in result.mut_data().iter_mut().zip(self.data()).enumerate() {
+            *r = t_i[i];
+        }
+        move_tape_and_add_backward_op("diag", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[f32; N]) = grads.mut_and_ref(&t, &result);
+            for (i, (t_i, g)) in t_grad.iter_mut().zip(result_grad).enumerate() {
+                t_i[i] += g;
+            }
+        })
+    }
+
+    /// Calls [matrix_trace()] on `self`.
+    pub fn matrix_trace(self) -> Tensor0D<H> {
+        matrix_trace(self)
+    }
+}
+
+impl<const N: usize, H: Tape> Tensor1D<N, H> {
+    /// Builds a square matrix with `self` on the main diagonal, and `0.0` everywhere else. This is
+    /// the same as `torch.diag(t)` in pytorch for 1d tensors.
+    ///
+    /// The gradient is the diagonal of the result's gradient.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0]);
+    /// assert_eq!(t.diag().data(), &[[1.0, 0.0], [0.0, 2.0]]);
+    /// ```
+    pub fn diag(self) -> Tensor2D<N, N, H> {
+        let mut result: Tensor2D<N, N> = Tensor2D::zeros();
+        for (i, (r_i, t)) in result.mut_data().iter_mut().zip(self.data()).enumerate() {
+            r_i[i] = *t;
+        }
+        move_tape_and_add_backward_op("diag", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; N]; N]) = grads.mut_and_ref(&t, &result);
+            for (i, (t, g_i)) in t_grad.iter_mut().zip(result_grad).enumerate() {
+                *t += g_i[i];
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diag_2d_to_1d() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let r = t.trace().diag();
+        assert_eq!(r.data(), &[1.0, 5.0, 9.0]);
+        let w = Tensor1D::new([1.0, -2.0, 3.0]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[1.0, 0.0, 0.0], [0.0, -2.0, 0.0], [0.0, 0.0, 3.0]]
+        );
+    }
+
+    #[test]
+    fn test_diag_1d_to_2d() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0]);
+        let r = t.trace().diag();
+        assert_eq!(
+            r.data(),
+            &[[1.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 3.0]]
+        );
+        let w = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[1.0, 5.0, 9.0]);
+    }
+
+    #[test]
+    fn test_diag_roundtrip() {
+        let t = Tensor1D::new([1.0, -2.0]);
+        let r = t.trace().diag().diag();
+        assert_eq!(r.data(), t.data());
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[2.7182817, 0.13533528]);
+    }
+
+    #[test]
+    fn test_matrix_trace() {
+        let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let r = t.trace().matrix_trace();
+        assert_eq!(r.data(), &5.0);
+        let gradients = r.square().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[10.0, 0.0], [0.0, 10.0]]);
+    }
+
+    #[test]
+    fn test_off_diagonal_penalty() {
+        // penalizing the off diagonal entries of a matrix: sum(t^2) - sum(diag(t)^2)
+        let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let (x, tape) = t.trace().split_tape();
+        let (diag_sq, tape) = x
+            .duplicate()
+            .put_tape(tape)
+            .diag()
+            .square()
+            .sum()
+            .split_tape();
+        let r = sub(x.put_tape(tape).square().sum(), &diag_sq);
+        assert_eq!(r.data(), &13.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[0.0, 4.0], [6.0, 0.0]]);
+    }
+}
@@ -0,0 +1,306 @@
+use super::utils::move_tape_and_add_backward_binop;
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// `dot(a, b) / (max(||a||, epsilon) * max(||b||, epsilon))`. Computes the cosine similarity between
+/// `a` and `b` along the last dimension. Resulting [Tensor] has the last dimension removed
+/// (e.g. two 2d tensors will result in the row wise similarities as a 1d tensor).
+///
+/// `epsilon` is the smallest value that each of the norms can be. If either of the vectors
+/// are all zeros, the similarity is `0.0` and no gradient flows into either vector.
+///
+/// Gradients flow into both `a` and `b`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([1.0, 0.0]);
+/// let b = Tensor1D::new([1.0, 1.0]);
+/// let r = cosine_similarity(a, &b, 1e-8);
+/// assert_eq!(r.data(), &0.70710677);
+/// ```
+///
+/// Row wise:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[1.0, 0.0], [0.0, 2.0]]);
+/// let b = Tensor2D::new([[-2.0, 0.0], [0.0, 3.0]]);
+/// let r: Tensor1D<2> = cosine_similarity(a, &b, 1e-8);
+/// assert_eq!(r.data(), &[-1.0, 1.0]);
+/// ```
+///
+/// This is equivalent to `torch.nn.functional.cosine_similarity(a, b, dim=-1, eps=epsilon)`.
+pub fn cosine_similarity<T: Tensor<Dtype = f32>>(
+    a: T,
+    b: &T::NoTape,
+    epsilon: T::Dtype,
+) -> T::LastDimReduced {
+    type Reduced<T> = <T as Tensor>::LastDimReduced;
+    type ReducedDevice<T> = <Reduced<T> as HasDevice>::Device;
+
+    let mut tmp = T::NoTape::zeros();
+    let mut dot = <Reduced<T> as Tensor>::NoTape::zeros();
+    T::Device::foreach_mrr(tmp.mut_data(), a.data(), b.data(), &mut |r, a, b| {
+        *r = a * b
+    });
+    T::Device::reduce_last_dim_into(tmp.data(), dot.mut_data(), &mut |x, y| x + y);
+
+    let mut a_norm = <Reduced<T> as Tensor>::NoTape::zeros();
+    T::Device::foreach_mr(tmp.mut_data(), a.data(), &mut |r, a| *r = a * a);
+    T::Device::reduce_last_dim_into(tmp.data(), a_norm.mut_data(), &mut |x, y| x + y);
+    ReducedDevice::<T>::foreach_m(a_norm.mut_data(), &mut |n| *n = n.sqrt());
+
+    let mut b_norm = <Reduced<T> as Tensor>::NoTape::zeros();
+    T::Device::foreach_mr(tmp.mut_data(), b.data(), &mut |r, b| *r = b * b);
+    T::Device::reduce_last_dim_into(tmp.data(), b_norm.mut_data(), &mut |x, y| x + y);
+    ReducedDevice::<T>::foreach_m(b_norm.mut_data(), &mut |n| *n = n.sqrt());
+
+    // `inv_norms = 1 / (max(||a||, epsilon) * max(||b||, epsilon))`, or 0 for degenerate rows
+    let mut inv_norms = <Reduced<T> as Tensor>::NoTape::zeros();
+    ReducedDevice::<T>::foreach_mrr(
+        inv_norms.mut_data(),
+        a_norm.data(),
+        b_norm.data(),
+        &mut |r, a, b| {
+            *r = if *a == 0.0 || *b == 0.0 {
+                0.0
+            } else {
+                (a.max(epsilon) * b.max(epsilon)).recip()
+            }
+        },
+    );
+
+    let mut result = <Reduced<T> as Tensor>::NoTape::zeros();
+    ReducedDevice::<T>::foreach_mrr(
+        result.mut_data(),
+        dot.data(),
+        inv_norms.data(),
+        &mut |r, d, s| *r = d * s,
+    );
+
+    let b_data = b.duplicate();
+    let cos = result.duplicate();
+    move_tape_and_add_backward_binop(
+        "cosine_similarity",
+        a,
+        b,
+        result,
+        move |a, b, result, grads| {
+            // `d cos / d a = b / (|a| |b|) - cos * a / |a|^2`, and the second term is 0
+            // when `|a|` is clamped to `epsilon`.
+            let mut a_coeff = <Reduced<T> as Tensor>::NoTape::zeros();
+            let mut b_coeff = <Reduced<T> as Tensor>::NoTape::zeros();
+            let mut g_coeff = <Reduced<T> as Tensor>::NoTape::zeros();
+            {
+                let result_grad = grads.ref_gradient(&result);
+                ReducedDevice::<T>::foreach_mrr(
+                    g_coeff.mut_data(),
+                    result_grad,
+                    inv_norms.data(),
+                    &mut |c, g, s| *c = g * s,
+                );
+                ReducedDevice::<T>::foreach_mrr(
+                    a_coeff.mut_data(),
+                    result_grad,
+                    a_norm.data(),
+                    &mut |c, g, n| *c = if *n > epsilon { g / (n * n) } else { 0.0 },
+                );
+                ReducedDevice::<T>::foreach_mrr(
+                    b_coeff.mut_data(),
+                    result_grad,
+                    b_norm.data(),
+                    &mut |c, g, n| *c = if *n > epsilon { g / (n * n) } else { 0.0 },
+                );
+            }
+            ReducedDevice::<T>::foreach_mr(a_coeff.mut_data(), cos.data(), &mut |c, r| *c *= r);
+            ReducedDevice::<T>::foreach_mr(b_coeff.mut_data(), cos.data(), &mut |c, r| *c *= r);
+
+            let a_grad = grads.mut_gradient(&a);
+            T::Device::foreach_mrb(
+                a_grad,
+                b_data.data(),
+                Broadcast(g_coeff.data()),
+                &mut |ag, b, c| *ag += b * c,
+            );
+            T::Device::foreach_mrb(
+                a_grad,
+                a.data(),
+                Broadcast(a_coeff.data()),
+                &mut |ag, a, c| *ag -= a * c,
+            );
+
+            let b_grad = grads.mut_gradient(&b);
+            T::Device::foreach_mrb(
+                b_grad,
+                a.data(),
+                Broadcast(g_coeff.data()),
+                &mut |bg, a, c| *bg += a * c,
+            );
+            T::Device::foreach_mrb(
+                b_grad,
+                b_data.data(),
+                Broadcast(b_coeff.data()),
+                &mut |bg, b, c| *bg -= b * c,
+            );
+        },
+    )
+}
+
+/// `||a - b + epsilon||`. Computes the l2 distance between `a` and `b` along the last dimension.
+/// Resulting [Tensor] has the last dimension removed (e.g. two 2d tensors will result in the
+/// row wise distances as a 1d tensor).
+///
+/// `epsilon` is added to the difference to avoid a `NaN` gradient when `a == b`.
+///
+/// Gradients flow into both `a` and `b`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[1.0, 2.0], [0.0, 0.0]]);
+/// let b = Tensor2D::new([[4.0, 6.0], [0.0, 0.0]]);
+/// let r: Tensor1D<2> = pairwise_distance(a, &b, 0.0);
+/// assert_eq!(r.data(), &[5.0, 0.0]);
+/// ```
+///
+/// This is equivalent to `torch.nn.functional.pairwise_distance(a, b, p=2, eps=epsilon)`.
+pub fn pairwise_distance<T: Tensor<Dtype = f32>>(
+    a: T,
+    b: &T::NoTape,
+    epsilon: T::Dtype,
+) -> T::LastDimReduced {
+    sqrt(sum_last_dim(square(add_scalar(sub(a, b), epsilon))))
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [cosine_similarity()] on `self`.
+    pub fn cosine_similarity(
+        self,
+        rhs: &$typename<$($Vs, )* NoneTape>,
+        epsilon: f32,
+    ) -> <Self as Tensor>::LastDimReduced {
+        cosine_similarity(self, rhs, epsilon)
+    }
+
+    /// Calls [pairwise_distance()] on `self`.
+    pub fn pairwise_distance(
+        self,
+        rhs: &$typename<$($Vs, )* NoneTape>,
+        epsilon: f32,
+    ) -> <Self as Tensor>::LastDimReduced {
+        pairwise_distance(self, rhs, epsilon)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+
+    fn cosine_similarity_f32(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+        let dot: f32 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+        let a_norm = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+        let b_norm = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+        dot / (a_norm * b_norm)
+    }
+
+    #[test]
+    fn test_cosine_similarity_1d() {
+        let a = Tensor1D::new([1.0, 2.0, 2.0]);
+        let b = Tensor1D::new([0.0, 3.0, 4.0]);
+        let r = a.trace().cosine_similarity(&b, 1e-8);
+        assert!((r.data() - 14.0 / 15.0).abs() < 1e-6);
+        let gradients = r.backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[-0.10370371, -0.007407397, 0.059259266],
+        );
+        assert_close(
+            gradients.ref_gradient(&b),
+            &[0.06666667, 0.021333333, -0.016],
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity_2d() {
+        let a = Tensor2D::new([[1.0, 0.0], [0.0, 2.0]]);
+        let b = Tensor2D::new([[-2.0, 0.0], [1.0, 1.0]]);
+        let r = a.trace().cosine_similarity(&b, 1e-8);
+        assert_eq!(r.data(), &[-1.0, 0.70710677]);
+        let gradients = r.sum().backward();
+        assert_close(gradients.ref_gradient(&a), &[[0.0, 0.0], [0.35355338, 0.0]]);
+        assert_close(
+            gradients.ref_gradient(&b),
+            &[[0.0, 0.0], [-0.35355338, 0.35355338]],
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity_zero_vector() {
+        let a: Tensor1D<3> = Tensor1D::zeros();
+        let b = Tensor1D::new([1.0, 2.0, 3.0]);
+        let r = a.trace().cosine_similarity(&b, 1e-8);
+        assert_eq!(r.data(), &0.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&a), &[0.0; 3]);
+        assert_eq!(gradients.ref_gradient(&b), &[0.0; 3]);
+    }
+
+    #[test]
+    fn test_cosine_similarity_2d_zero_row() {
+        let a = Tensor2D::new([[0.0, 0.0], [3.0, 4.0]]);
+        let b = Tensor2D::new([[1.0, 2.0], [4.0, 3.0]]);
+        let r = a.trace().cosine_similarity(&b, 1e-8);
+        assert_eq!(r.data(), &[0.0, 0.96]);
+        let gradients = r.sum().backward();
+        assert_close(gradients.ref_gradient(&a), &[[0.0, 0.0], [0.0448, -0.0336]]);
+        assert_close(gradients.ref_gradient(&b), &[[0.0, 0.0], [-0.0336, 0.0448]]);
+    }
+
+    #[test]
+    fn test_cosine_similarity_finite_difference() {
+        const H: f32 = 1e-3;
+        let x = [0.5, -1.0, 2.0];
+        let y = [1.5, 0.25, -0.75];
+        let a = Tensor1D::new(x);
+        let b = Tensor1D::new(y);
+        let gradients = a.trace().cosine_similarity(&b, 1e-8).backward();
+        let (a_grad, b_grad) = (gradients.ref_gradient(&a), gradients.ref_gradient(&b));
+        for i in 0..3 {
+            let (mut x_plus, mut x_minus) = (x, x);
+            x_plus[i] += H;
+            x_minus[i] -= H;
+            let approx = (cosine_similarity_f32(&x_plus, &y) - cosine_similarity_f32(&x_minus, &y))
+                / (2.0 * H);
+            assert!((a_grad[i] - approx).abs() < 1e-3, "{a_grad:?} {approx}");
+
+            let (mut y_plus, mut y_minus) = (y, y);
+            y_plus[i] += H;
+            y_minus[i] -= H;
+            let approx = (cosine_similarity_f32(&x, &y_plus) - cosine_similarity_f32(&x, &y_minus))
+                / (2.0 * H);
+            assert!((b_grad[i] - approx).abs() < 1e-3, "{b_grad:?} {approx}");
+        }
+    }
+
+    #[test]
+    fn test_pairwise_distance_2d() {
+        let a = Tensor2D::new([[1.0, 2.0], [0.0, 1.0]]);
+        let b = Tensor2D::new([[4.0, 6.0], [0.0, -1.0]]);
+        let r = a.trace().pairwise_distance(&b, 0.0);
+        assert_eq!(r.data(), &[5.0, 2.0]);
+        let gradients = r.sum().backward();
+        assert_close(gradients.ref_gradient(&a), &[[-0.6, -0.8], [0.0, 1.0]]);
+        assert_close(gradients.ref_gradient(&b), &[[0.6, 0.8], [0.0, -1.0]]);
+    }
+}
@@ -22,7 +22,7 @@ pub fn dropout<T: Tensor<Dtype = f32>, R: Rng>(t: T, p: f32, rng: &mut R) -> T {
         let mut result = T::NoTape::zeros();
         T::Device::addmul(result.mut_data(), t.data(), deriv.as_ref());
 
-        move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+        move_tape_and_add_backward_op("dropout", t, result, move |t, result, grads| {
             let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
             T::Device::addmul(t_grad, deriv.as_ref(), result_grad);
         })
@@ -0,0 +1,293 @@
+use super::impl_reshape::{flat, flat_mut};
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+use std::vec::Vec;
+
+/// Causes a compile time error if `I` is not an axis of a tensor with `RANK` dimensions.
+struct AssertValidAxis<const I: usize, const RANK: usize>;
+
+impl<const I: usize, const RANK: usize> AssertValidAxis<I, RANK> {
+    const OK: () = assert!(I < RANK, "axis must be less than the number of dimensions");
+}
+
+/// Moves the elements of `t` along axis `axis` of `dims`, so that coordinate `c` of the result
+/// comes from coordinate `src(c)` of `t`. `src` must be a permutation of `0..dims[axis]`.
+///
+/// The backward moves each element of the gradient back to where it came from, which is the
+/// inverse permutation.
+fn permute_axis<T, F>(name: &'static str, t: T, dims: &[usize], axis: usize, src: F) -> T
+where
+    T: Tensor<Dtype = f32>,
+    F: Fn(usize) -> usize,
+{
+    let n = dims[axis];
+    let stride: usize = dims[axis + 1..].iter().product();
+    let indices: Vec<usize> = (0..T::Array::NUM_ELEMENTS)
+        .map(|i| {
+            let c = (i / stride) % n;
+            i - c * stride + src(c) * stride
+        })
+        .collect();
+
+    let mut result = T::NoTape::zeros();
+    for (r, &i) in flat_mut(result.mut_data()).iter_mut().zip(indices.iter()) {
+        *r = flat(t.data())[i];
+    }
+    move_tape_and_add_backward_op(name, t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        let t_grad = flat_mut(t_grad);
+        for (r, &i) in flat(result_grad).iter().zip(indices.iter()) {
+            t_grad[i] += r;
+        }
+    })
+}
+
+/// Circularly shifts the elements of `t` by `shift`, so that element `i` moves to `(i + shift) % N`.
+/// Shifts larger than `N` wrap around, and a shift of `0` (or any multiple of `N`) does nothing.
+///
+/// This is [Tensor1D::roll_axis()] along the only axis. The backward rolls the gradient back by `-shift`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([1.0, 2.0, 3.0, 4.0]);
+/// assert_eq!(roll(t.duplicate(), 1).data(), &[4.0, 1.0, 2.0, 3.0]);
+/// assert_eq!(roll(t, 6).data(), &[3.0, 4.0, 1.0, 2.0]);
+/// ```
+pub fn roll<const N: usize, H: Tape>(t: Tensor1D<N, H>, shift: usize) -> Tensor1D<N, H> {
+    t.roll_axis::<0>((shift % N.max(1)) as isize)
+}
+
+/// Reverses the order of the elements along the last dimension, e.g. to run a sequence backwards
+/// in a bidirectional model. This is [Tensor2D::flip_axis()] along the last axis.
+///
+/// The backward flips the gradient back, so flipping twice is an identity.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+/// let r = flip_last_axis(t);
+/// assert_eq!(r.data(), &[[3.0, 2.0, 1.0], [6.0, 5.0, 4.0]]);
+/// ```
+pub fn flip_last_axis<T: Tensor<Dtype = f32>>(t: T) -> T
+where
+    T::Array: MultiDimensional,
+{
+    let n = <T::Array as MultiDimensional>::LAST_DIM_SIZE;
+    let dims = [T::Array::NUM_ELEMENTS / n.max(1), n];
+    permute_axis("flip_last_axis", t, &dims, 1, |c| n - 1 - c)
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*], $rank:expr) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Reverses the order of the elements along axis `I`. This is the same as pytorch's
+    /// `flip(dims=[I])`.
+    ///
+    /// The backward flips the gradient back.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    /// assert_eq!(t.duplicate().flip_axis::<0>().data(), &[[4.0, 5.0, 6.0], [1.0, 2.0, 3.0]]);
+    /// assert_eq!(t.flip_axis::<1>().data(), &[[3.0, 2.0, 1.0], [6.0, 5.0, 4.0]]);
+    /// ```
+    ///
+    /// Using an axis that doesn't exist is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor2D<2, 3> = Tensor2D::zeros();
+    /// let r = t.flip_axis::<2>();
+    /// ```
+    pub fn flip_axis<const I: usize>(self) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertValidAxis::<I, $rank>::OK;
+        let dims = [$($Vs),*];
+        let n = dims[I];
+        permute_axis("flip", self, &dims, I, |c| n - 1 - c)
+    }
+
+    /// Calls [flip_last_axis()] on `self`.
+    pub fn flip_last_axis(self) -> Self {
+        flip_last_axis(self)
+    }
+
+    /// Circularly shifts the elements along axis `I` by `shift`, so that element `i` moves to
+    /// `(i + shift) % n`. Elements shifted past the end wrap around to the start. This is the same
+    /// as pytorch's `roll(shift, dims=I)`.
+    ///
+    /// Negative shifts move elements towards the start, and shifts larger than the size of the axis
+    /// are taken modulo the size of the axis. If the axis has no elements, `self` is returned unchanged.
+    ///
+    /// The backward rolls the gradient by `-shift`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    /// assert_eq!(t.duplicate().roll_axis::<1>(1).data(), &[[3.0, 1.0, 2.0], [6.0, 4.0, 5.0]]);
+    /// assert_eq!(t.duplicate().roll_axis::<1>(-1).data(), &[[2.0, 3.0, 1.0], [5.0, 6.0, 4.0]]);
+    /// assert_eq!(t.roll_axis::<0>(3).data(), &[[4.0, 5.0, 6.0], [1.0, 2.0, 3.0]]);
+    /// ```
+    pub fn roll_axis<const I: usize>(self, shift: isize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertValidAxis::<I, $rank>::OK;
+        let dims = [$($Vs),*];
+        let n = dims[I];
+        if n == 0 {
+            return self;
+        }
+        let shift = shift.rem_euclid(n as isize) as usize;
+        permute_axis("roll", self, &dims, I, |c| (c + n - shift) % n)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor1D, [M], 1);
+tensor_impl!(Tensor2D, [M, N], 2);
+tensor_impl!(Tensor3D, [M, N, O], 3);
+tensor_impl!(Tensor4D, [M, N, O, P], 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_flip_1d() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0]);
+        let r = t.trace().flip_axis::<0>();
+        assert_eq!(r.data(), &[3.0, 2.0, 1.0]);
+        let w = Tensor1D::new([1.0, 2.0, 3.0]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_flip_3d_middle_axis() {
+        let t = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let r = t.trace().flip_axis::<1>();
+        assert_eq!(
+            r.data(),
+            &[[[3.0, 4.0], [1.0, 2.0]], [[7.0, 8.0], [5.0, 6.0]]]
+        );
+        let w = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[3.0, 4.0], [1.0, 2.0]], [[7.0, 8.0], [5.0, 6.0]]]
+        );
+    }
+
+    #[test]
+    fn test_flip_last_axis() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0]);
+        assert_eq!(flip_last_axis(t).data(), &[3.0, 2.0, 1.0]);
+
+        let t = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let r = t.trace().flip_last_axis();
+        assert_eq!(
+            r.data(),
+            &[[[2.0, 1.0], [4.0, 3.0]], [[6.0, 5.0], [8.0, 7.0]]]
+        );
+        let w = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[2.0, 1.0], [4.0, 3.0]], [[6.0, 5.0], [8.0, 7.0]]]
+        );
+    }
+
+    #[test]
+    fn test_flip_last_axis_twice_is_identity() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let t: Tensor1D<5> = Tensor1D::randn(&mut rng);
+        let r = flip_last_axis(flip_last_axis(t.trace()));
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+
+        let t: Tensor2D<3, 4> = Tensor2D::randn(&mut rng);
+        let r = flip_last_axis(flip_last_axis(t.trace()));
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+
+        let t: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let r = t.trace().flip_last_axis().flip_last_axis();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+    }
+
+    #[test]
+    fn test_roll_2d() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+        let r = t.trace().roll_axis::<1>(1);
+        assert_eq!(r.data(), &[[4.0, 1.0, 2.0, 3.0], [8.0, 5.0, 6.0, 7.0]]);
+        let w = Tensor2D::new([[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[2.0, 3.0, 4.0, 1.0], [6.0, 7.0, 8.0, 5.0]]
+        );
+    }
+
+    #[test]
+    fn test_roll_wraps_large_and_negative_shifts() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0, 4.0, 5.0]);
+        let expected = t.duplicate().roll_axis::<0>(2);
+        assert_eq!(expected.data(), &[4.0, 5.0, 1.0, 2.0, 3.0]);
+        assert_eq!(t.duplicate().roll_axis::<0>(7).data(), expected.data());
+        assert_eq!(t.duplicate().roll_axis::<0>(-3).data(), expected.data());
+        assert_eq!(t.duplicate().roll_axis::<0>(-13).data(), expected.data());
+        assert_eq!(t.duplicate().roll_axis::<0>(5).data(), t.data());
+    }
+
+    #[test]
+    fn test_flip_roll_4d_inverse() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor4D<2, 3, 4, 5> = Tensor4D::randn(&mut rng);
+        let r = t.trace().flip_axis::<3>().flip_axis::<3>();
+        assert_eq!(r.data(), t.data());
+        let r = r.roll_axis::<2>(3).roll_axis::<2>(-3);
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+    }
+
+    #[test]
+    fn test_roll_1d() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(roll(t.duplicate(), 0).data(), t.data());
+        assert_eq!(roll(t.duplicate(), 5).data(), t.data());
+        assert_eq!(roll(t.duplicate(), 2).data(), &[4.0, 5.0, 1.0, 2.0, 3.0]);
+        assert_eq!(roll(t.duplicate(), 12).data(), &[4.0, 5.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_roll_empty_axis() {
+        let t: Tensor2D<2, 0> = Tensor2D::zeros();
+        assert_eq!(t.duplicate().roll_axis::<1>(3).data(), &[[0.0; 0]; 2]);
+        assert_eq!(t.roll_axis::<0>(-1).data(), &[[0.0; 0]; 2]);
+        let t: Tensor1D<0> = Tensor1D::zeros();
+        assert_eq!(roll(t, 2).data(), &[0.0; 0]);
+    }
+
+    #[test]
+    fn test_roll_1d_adjoint_is_inverse_roll() {
+        // <roll(x, s), y> == <x, roll(y, N - s)>
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor1D<7> = Tensor1D::randn(&mut rng);
+        let y: Tensor1D<7> = Tensor1D::randn(&mut rng);
+        for shift in [0, 1, 3, 7, 10] {
+            let gradients = mul(roll(x.trace(), shift), &y).sum().backward();
+            let inverse = roll(y.duplicate(), 7 - shift % 7);
+            assert_eq!(gradients.ref_gradient(&x), inverse.data());
+        }
+    }
+}
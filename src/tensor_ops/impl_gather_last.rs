@@ -39,7 +39,7 @@ where
         t[*i] = 1.0;
     });
 
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+    move_tape_and_add_backward_op("gather_last_dim", t, result, move |t, result, grads| {
         let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
         T::Device::foreach_mrb(t_grad, t.data(), Broadcast(result_grad), &mut |g, t, r| {
             *g += t * r;
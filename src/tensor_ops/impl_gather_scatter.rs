@@ -0,0 +1,200 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+use std::boxed::Box;
+
+/// Selects `K` values from each row of `t`, so that `result[m][k] == t[m][indices[m][k]]`.
+/// Indices can be repeated and in any order. Unlike [gather_last_dim()], this keeps the last dimension.
+///
+/// The backward pass is [scatter_add_last_axis()] of the gradient of the result, so the gradients of
+/// repeated indices are added together.
+///
+/// **Panics** if any index is `>= N`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+/// let r: Tensor2D<2, 4> = gather_last_axis(t, &[[2, 0, 0, 1], [1, 1, 1, 1]]); // or t.gather_last_axis(...)
+/// assert_eq!(r.data(), &[[3.0, 1.0, 1.0, 2.0], [-2.0, -2.0, -2.0, -2.0]]);
+/// ```
+///
+/// This is equivalent to `torch.gather(t, -1, indices)` in pytorch.
+pub fn gather_last_axis<const M: usize, const N: usize, const K: usize, H: Tape>(
+    t: Tensor2D<M, N, H>,
+    indices: &[[usize; K]; M],
+) -> Tensor2D<M, K, H> {
+    let indices = boxed_indices::<M, K, N>(indices);
+    let mut result: Tensor2D<M, K> = Tensor2D::zeros();
+    gather_rows(result.mut_data(), t.data(), &indices);
+    move_tape_and_add_backward_op("gather_last_axis", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        scatter_add_rows(t_grad, result_grad, &indices);
+    })
+}
+
+/// Adds each value of `t` into the position of its index in a tensor of zeros, so that
+/// `result[m][n]` is the sum of `t[m][k]` for all `k` where `indices[m][k] == n`.
+/// Positions without any index are `0.0`, and values with the same index are added together.
+///
+/// The backward pass is [gather_last_axis()] of the gradient of the result.
+///
+/// **Panics** if any index is `>= N`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+/// let r: Tensor2D<2, 4> = scatter_add_last_axis(t, &[[3, 0, 3], [1, 1, 2]]); // or t.scatter_add_last_axis(...)
+/// assert_eq!(r.data(), &[[2.0, 0.0, 0.0, 4.0], [0.0, -3.0, -3.0, 0.0]]);
+/// ```
+///
+/// This is equivalent to `torch.zeros(M, N).scatter_add(-1, indices, t)` in pytorch.
+pub fn scatter_add_last_axis<const M: usize, const K: usize, const N: usize, H: Tape>(
+    t: Tensor2D<M, K, H>,
+    indices: &[[usize; K]; M],
+) -> Tensor2D<M, N, H> {
+    let indices = boxed_indices::<M, K, N>(indices);
+    let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+    scatter_add_rows(result.mut_data(), t.data(), &indices);
+    move_tape_and_add_backward_op(
+        "scatter_add_last_axis",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            let mut gathered: Box<[[f32; K]; M]> = Cpu::zeros();
+            gather_rows(gathered.as_mut(), result_grad, &indices);
+            Cpu::add(t_grad, gathered.as_ref());
+        },
+    )
+}
+
+/// Checks that all `indices` are `< N`, and copies them onto the heap so the backward op can keep them.
+fn boxed_indices<const M: usize, const K: usize, const N: usize>(
+    indices: &[[usize; K]; M],
+) -> Box<[[usize; K]; M]> {
+    for &i in indices.iter().flatten() {
+        assert!(
+            i < N,
+            "index {i} is out of bounds for a last axis of size {N}"
+        );
+    }
+    let mut boxed: Box<[[usize; K]; M]> = Cpu::zeros();
+    boxed.as_mut().clone_from(indices);
+    boxed
+}
+
+/// `out[m][k] = t[m][indices[m][k]]`
+fn gather_rows<const M: usize, const N: usize, const K: usize>(
+    out: &mut [[f32; K]; M],
+    t: &[[f32; N]; M],
+    indices: &[[usize; K]; M],
+) {
+    for ((out_m, t_m), idx_m) in out.iter_mut().zip(t.iter()).zip(indices.iter()) {
+        for (o, &i) in out_m.iter_mut().zip(idx_m.iter()) {
+            *o = t_m[i];
+        }
+    }
+}
+
+/// `out[m][indices[m][k]] += t[m][k]`
+fn scatter_add_rows<const M: usize, const N: usize, const K: usize>(
+    out: &mut [[f32; N]; M],
+    t: &[[f32; K]; M],
+    indices: &[[usize; K]; M],
+) {
+    for ((out_m, t_m), idx_m) in out.iter_mut().zip(t.iter()).zip(indices.iter()) {
+        for (v, &i) in t_m.iter().zip(idx_m.iter()) {
+            out_m[i] += v;
+        }
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Calls [gather_last_axis()] on `self`.
+    pub fn gather_last_axis<const K: usize>(self, indices: &[[usize; K]; M]) -> Tensor2D<M, K, H> {
+        gather_last_axis(self, indices)
+    }
+
+    /// Calls [scatter_add_last_axis()] on `self`.
+    pub fn scatter_add_last_axis<const O: usize>(
+        self,
+        indices: &[[usize; N]; M],
+    ) -> Tensor2D<M, O, H> {
+        scatter_add_last_axis(self, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradcheck::gradcheck;
+
+    const INDICES: [[usize; 4]; 2] = [[2, 0, 2, 2], [1, 0, 2, 1]];
+
+    #[test]
+    fn test_gather_last_axis() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+        let r: Tensor2D<2, 4, OwnedTape> = t.trace().gather_last_axis(&INDICES);
+        assert_eq!(r.data(), &[[3.0, 1.0, 3.0, 3.0], [-2.0, -1.0, -3.0, -2.0]]);
+
+        // repeated indices accumulate their gradients
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[1.0, 0.0, 3.0], [1.0, 2.0, 1.0]]
+        );
+    }
+
+    #[test]
+    fn test_scatter_add_last_axis_accumulates() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0, 4.0], [0.5, 0.25, 0.125, 1.0]]);
+        let r: Tensor2D<2, 3, OwnedTape> = t.trace().scatter_add_last_axis(&INDICES);
+        assert_eq!(r.data(), &[[2.0, 0.0, 8.0], [0.25, 1.5, 0.125]]);
+
+        let w = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let gradients = (r * &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[3.0, 1.0, 3.0, 3.0], [5.0, 4.0, 6.0, 5.0]]
+        );
+    }
+
+    #[test]
+    fn test_gather_backward_is_scatter_add() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[0.1, -0.2, 0.3], [1.0, 2.0, -1.5]]);
+        let w: Tensor2D<2, 4> = Tensor2D::new([[0.5, -1.0, 2.0, 0.25], [3.0, 1.5, -2.0, 4.0]]);
+        let r = t.trace().gather_last_axis(&INDICES);
+        let gradients = (r * &w).sum().backward();
+
+        let expected: Tensor2D<2, 3> = scatter_add_last_axis(w.duplicate(), &INDICES);
+        assert_eq!(gradients.ref_gradient(&t), expected.data());
+
+        // and the backward of scatter_add is gather
+        let r: Tensor2D<2, 3, OwnedTape> = w.trace().scatter_add_last_axis(&INDICES);
+        let gradients = (r * &t).sum().backward();
+        let expected = gather_last_axis(t.duplicate(), &INDICES);
+        assert_eq!(gradients.ref_gradient(&w), expected.data());
+    }
+
+    #[test]
+    fn test_gather_scatter_gradcheck() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[0.1, -0.2, 0.3], [1.0, 2.0, -1.5]]);
+        let f = |t: Tensor2D<2, 3, OwnedTape>| t.gather_last_axis(&INDICES).square().sum();
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+
+        let t: Tensor2D<2, 4> = Tensor2D::new([[0.1, -0.2, 0.3, 0.4], [1.0, 2.0, -1.5, 0.5]]);
+        let f = |t: Tensor2D<2, 4, OwnedTape>| {
+            let r: Tensor2D<2, 3, OwnedTape> = t.scatter_add_last_axis(&INDICES);
+            r.square().sum()
+        };
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    #[should_panic = "index 3 is out of bounds for a last axis of size 3"]
+    fn test_gather_out_of_bounds() {
+        let t: Tensor2D<1, 3> = Tensor2D::zeros();
+        let _ = t.gather_last_axis(&[[0, 3]]);
+    }
+}
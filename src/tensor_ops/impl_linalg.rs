@@ -0,0 +1,475 @@
+use super::matmul::{mm, mm_at, mm_bt};
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use std::boxed::Box;
+
+/// Error returned by linear algebra operations that are not defined for every matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinalgError {
+    /// The matrix is singular (or too close to singular to be inverted in `f32`).
+    Singular,
+
+    /// The matrix is not positive definite, so it has no cholesky decomposition.
+    NotPositiveDefinite,
+}
+
+impl std::fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Singular => write!(f, "matrix is singular"),
+            Self::NotPositiveDefinite => write!(f, "matrix is not positive definite"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LinalgError {}
+
+/// Inverts `a` with Gauss-Jordan elimination and partial pivoting.
+///
+/// Returns [LinalgError::Singular] if a pivot is too small relative to the largest element of `a`.
+fn invert<const N: usize>(a: &[[f32; N]; N]) -> Result<Box<[[f32; N]; N]>, LinalgError> {
+    let max_abs = a.iter().flatten().fold(0.0f32, |m, v| m.max(v.abs()));
+    let tolerance = max_abs * N as f32 * f32::EPSILON;
+
+    let mut a = {
+        let mut boxed: Box<[[f32; N]; N]> = Cpu::zeros();
+        boxed.as_mut().clone_from(a);
+        boxed
+    };
+    let mut inv: Box<[[f32; N]; N]> = Cpu::zeros();
+    for (i, inv_i) in inv.iter_mut().enumerate() {
+        inv_i[i] = 1.0;
+    }
+
+    for col in 0..N {
+        let pivot = (col..N)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        let pivot_abs = a[pivot][col].abs();
+        if pivot_abs.is_nan() || pivot_abs <= tolerance {
+            return Err(LinalgError::Singular);
+        }
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let scale = 1.0 / a[col][col];
+        for j in 0..N {
+            a[col][j] *= scale;
+            inv[col][j] *= scale;
+        }
+
+        for row in 0..N {
+            let factor = a[row][col];
+            if row == col || factor == 0.0 {
+                continue;
+            }
+            for j in 0..N {
+                a[row][j] -= factor * a[col][j];
+                inv[row][j] -= factor * inv[col][j];
+            }
+        }
+    }
+    Ok(inv)
+}
+
+/// Computes the inverse of the square matrix `t`, using Gauss-Jordan elimination with partial pivoting.
+/// This is meant for small matrices (up to ~32x32).
+///
+/// The gradient is `-A^{-T} G A^{-T}`, where `A^{-1}` is the result and `G` is the result's gradient.
+///
+/// Returns [LinalgError::Singular] if `t` is singular, instead of a result full of `NaN`s or `inf`s.
+/// Since `t` is consumed either way, any tape it owned is dropped on error.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[4.0, 7.0], [2.0, 6.0]]);
+/// let r = inverse(t).unwrap(); // or t.inverse()
+/// assert_eq!(r.data(), &[[0.6, -0.7], [-0.2, 0.4]]);
+///
+/// let t = Tensor2D::new([[1.0, 2.0], [2.0, 4.0]]);
+/// assert_eq!(t.inverse().unwrap_err(), LinalgError::Singular);
+/// ```
+pub fn inverse<const N: usize, H: Tape>(
+    t: Tensor2D<N, N, H>,
+) -> Result<Tensor2D<N, N, H>, LinalgError> {
+    let inv = invert(t.data())?;
+    let mut result: Tensor2D<N, N> = Tensor2D::zeros();
+    *result.mut_data() = *inv;
+    Ok(move_tape_and_add_backward_op(
+        "inverse",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; N]; N]) = grads.mut_and_ref(&t, &result);
+            // g_inv_t = G A^{-T}
+            let mut g_inv_t: Box<[[f32; N]; N]> = Cpu::zeros();
+            mm_bt(result_grad, inv.as_ref(), g_inv_t.as_mut());
+            // t_grad -= A^{-T} G A^{-T}
+            let mut delta: Box<[[f32; N]; N]> = Cpu::zeros();
+            mm_at(inv.as_ref(), g_inv_t.as_ref(), delta.as_mut());
+            Cpu::sub(t_grad, delta.as_ref());
+        },
+    ))
+}
+
+/// Solves `a * x = b` for `x`, where `a` is a square matrix and `b` has `K` columns (use `K = 1` for a
+/// single vector). This is meant for small matrices (up to ~32x32), and is computed with the inverse
+/// of `a` (see [inverse()]).
+///
+/// The gradients are `A^{-T} G` for `b`, and `-A^{-T} G x^T` for `a`, where `G` is the result's gradient.
+///
+/// Both `a` and `b` may own a tape, in which case the tapes are merged together into the result.
+///
+/// Returns [LinalgError::Singular] if `a` is singular.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[2.0, 0.0], [1.0, 1.0]]);
+/// let b = Tensor2D::new([[4.0], [5.0]]);
+/// let x = solve(a, b).unwrap();
+/// assert_eq!(x.data(), &[[2.0], [3.0]]);
+/// ```
+pub fn solve<const N: usize, const K: usize, HA, HB>(
+    a: Tensor2D<N, N, HA>,
+    b: Tensor2D<N, K, HB>,
+) -> Result<Tensor2D<N, K, HA::Output>, LinalgError>
+where
+    HA: MergeTape<HB>,
+    HB: Tape,
+{
+    let inv = invert(a.data())?;
+    let (a, a_tape) = a.split_tape();
+    let (b, b_tape) = b.split_tape();
+
+    let mut result: Tensor2D<N, K> = Tensor2D::zeros();
+    mm(inv.as_ref(), b.data(), result.mut_data());
+
+    let x = result.duplicate();
+    let mut tape = a_tape.merge_tape(b_tape);
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("solve", move |grads| {
+        // b_grad = A^{-T} G
+        let mut inv_t_g: Box<[[f32; K]; N]> = Cpu::zeros();
+        let (b_grad, result_grad) = grads.mut_and_ref(&b, &phantom_result);
+        mm_at(inv.as_ref(), result_grad, inv_t_g.as_mut());
+        Cpu::add(b_grad, inv_t_g.as_ref());
+
+        // a_grad = -A^{-T} G x^T
+        let mut delta: Box<[[f32; N]; N]> = Cpu::zeros();
+        mm_bt(inv_t_g.as_ref(), x.data(), delta.as_mut());
+        let a_grad = grads.mut_gradient(&a);
+        Cpu::sub(a_grad, delta.as_ref());
+    });
+    Ok(result.put_tape(tape))
+}
+
+/// Computes the lower triangular `l` such that `l * l^T == a`. Only the lower triangle of `a` is read.
+fn cholesky_decompose<const N: usize>(
+    a: &[[f32; N]; N],
+) -> Result<Box<[[f32; N]; N]>, LinalgError> {
+    let mut l: Box<[[f32; N]; N]> = Cpu::zeros();
+    for j in 0..N {
+        let d = a[j][j] - l[j][..j].iter().map(|v| v * v).sum::<f32>();
+        if d.is_nan() || d <= 0.0 {
+            return Err(LinalgError::NotPositiveDefinite);
+        }
+        l[j][j] = d.sqrt();
+        for i in j + 1..N {
+            let dot: f32 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            l[i][j] = (a[i][j] - dot) / l[j][j];
+        }
+    }
+    Ok(l)
+}
+
+/// Inverts the lower triangular matrix `l` with forward substitution.
+fn invert_lower<const N: usize>(l: &[[f32; N]; N]) -> Box<[[f32; N]; N]> {
+    let mut inv: Box<[[f32; N]; N]> = Cpu::zeros();
+    for col in 0..N {
+        for row in col..N {
+            let identity = if row == col { 1.0 } else { 0.0 };
+            let dot: f32 = (col..row).map(|k| l[row][k] * inv[k][col]).sum();
+            inv[row][col] = (identity - dot) / l[row][row];
+        }
+    }
+    inv
+}
+
+/// Computes the cholesky decomposition of the symmetric positive definite matrix `t`. Returns the lower
+/// triangular matrix `L` such that `L * L^T == t`. Only the lower triangle of `t` is read.
+/// This is meant for small matrices (up to ~32x32).
+///
+/// The gradient is computed with the formula from
+/// [Differentiation of the Cholesky decomposition](https://arxiv.org/abs/1602.07527), and is symmetric,
+/// since the upper and lower triangles of `t` are assumed to be equal.
+///
+/// Returns [LinalgError::NotPositiveDefinite] if `t` is not positive definite. A common fix is to add
+/// a small value ("jitter") to the diagonal and try again.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[4.0, 2.0], [2.0, 2.0]]);
+/// let l = cholesky(t).unwrap(); // or t.cholesky()
+/// assert_eq!(l.data(), &[[2.0, 0.0], [1.0, 1.0]]);
+///
+/// let t = Tensor2D::new([[1.0, 2.0], [2.0, 1.0]]);
+/// assert_eq!(t.cholesky().unwrap_err(), LinalgError::NotPositiveDefinite);
+/// ```
+pub fn cholesky<const N: usize, H: Tape>(
+    t: Tensor2D<N, N, H>,
+) -> Result<Tensor2D<N, N, H>, LinalgError> {
+    let l = cholesky_decompose(t.data())?;
+    let mut result: Tensor2D<N, N> = Tensor2D::zeros();
+    *result.mut_data() = *l;
+    Ok(move_tape_and_add_backward_op(
+        "cholesky",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; N]; N]) = grads.mut_and_ref(&t, &result);
+
+            // only the lower triangle of the result depends on `t`
+            let mut l_grad: Box<[[f32; N]; N]> = Cpu::zeros();
+            l_grad.as_mut().clone_from(result_grad);
+            for (i, g_i) in l_grad.iter_mut().enumerate() {
+                g_i[i + 1..].fill(0.0);
+            }
+
+            // phi(L^T G), where phi takes the lower triangle and halves the diagonal
+            let mut p: Box<[[f32; N]; N]> = Cpu::zeros();
+            mm_at(l.as_ref(), l_grad.as_ref(), p.as_mut());
+            for (i, p_i) in p.iter_mut().enumerate() {
+                p_i[i] *= 0.5;
+                p_i[i + 1..].fill(0.0);
+            }
+
+            // s = L^{-T} phi(L^T G) L^{-1}
+            let l_inv = invert_lower(l.as_ref());
+            let mut p_l_inv: Box<[[f32; N]; N]> = Cpu::zeros();
+            mm(p.as_ref(), l_inv.as_ref(), p_l_inv.as_mut());
+            let mut s: Box<[[f32; N]; N]> = Cpu::zeros();
+            mm_at(l_inv.as_ref(), p_l_inv.as_ref(), s.as_mut());
+
+            // t_grad += (s + s^T) / 2
+            for (i, t_i) in t_grad.iter_mut().enumerate() {
+                for (j, t_ij) in t_i.iter_mut().enumerate() {
+                    *t_ij += 0.5 * (s[i][j] + s[j][i]);
+                }
+            }
+        },
+    ))
+}
+
+impl<const N: usize, H: Tape> Tensor2D<N, N, H> {
+    /// Calls [inverse()] on `self`.
+    pub fn inverse(self) -> Result<Self, LinalgError> {
+        inverse(self)
+    }
+
+    /// Calls [cholesky()] on `self`.
+    pub fn cholesky(self) -> Result<Self, LinalgError> {
+        cholesky(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_close, AssertClose};
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_inverse_2x2() {
+        let t = Tensor2D::new([[4.0, 7.0], [2.0, 6.0]]);
+        let r = t.trace().inverse().unwrap();
+        assert_close(r.data(), &[[0.6, -0.7], [-0.2, 0.4]]);
+        let w = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        // -A^{-T} W A^{-T}
+        gradients
+            .ref_gradient(&t)
+            .assert_close(&[[0.28, -0.16], [-0.16, 0.02]], 1e-6);
+    }
+
+    #[test]
+    fn test_inverse_needs_pivoting() {
+        let t = Tensor2D::new([[0.0, 1.0, 2.0], [1.0, 0.0, 3.0], [4.0, -3.0, 8.0]]);
+        let r = t.trace().inverse().unwrap();
+        let eye = matmul(r.duplicate(), &Tensor2D::new(*t.data()));
+        eye.data()
+            .assert_close(&[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], 1e-5);
+    }
+
+    #[test]
+    fn test_inverse_finite_differences() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor2D<4, 4> = Tensor2D::randn(&mut rng);
+        let t = add(
+            t,
+            &Tensor2D::new([
+                [3.0, 0.0, 0.0, 0.0],
+                [0.0, 3.0, 0.0, 0.0],
+                [0.0, 0.0, 3.0, 0.0],
+                [0.0, 0.0, 0.0, 3.0],
+            ]),
+        );
+        let w: Tensor2D<4, 4> = Tensor2D::randn(&mut rng);
+        let loss = |a: [[f32; 4]; 4]| -> f32 {
+            *mul(Tensor2D::new(a).inverse().unwrap(), &w).sum().data()
+        };
+
+        let gradients = mul(t.trace().inverse().unwrap(), &w).sum().backward();
+
+        let h = 1e-2;
+        let mut expected = [[0.0; 4]; 4];
+        for (i, expected_i) in expected.iter_mut().enumerate() {
+            for (j, e) in expected_i.iter_mut().enumerate() {
+                let mut plus = *t.data();
+                plus[i][j] += h;
+                let mut minus = *t.data();
+                minus[i][j] -= h;
+                *e = (loss(plus) - loss(minus)) / (2.0 * h);
+            }
+        }
+        gradients.ref_gradient(&t).assert_close(&expected, 1e-2);
+    }
+
+    #[test]
+    fn test_inverse_singular() {
+        let t: Tensor2D<3, 3> = Tensor2D::zeros();
+        assert_eq!(t.inverse().unwrap_err(), LinalgError::Singular);
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        assert_eq!(t.trace().inverse().unwrap_err(), LinalgError::Singular);
+        assert_eq!(LinalgError::Singular.to_string(), "matrix is singular");
+    }
+
+    #[test]
+    fn test_solve() {
+        let a = Tensor2D::new([[2.0, 1.0], [1.0, 3.0]]);
+        let b = Tensor2D::new([[3.0, 1.0], [5.0, 0.0]]);
+        let x = solve(a.trace(), b.trace()).unwrap();
+        assert_close(x.data(), &[[0.8, 0.6], [1.4, -0.2]]);
+        let gradients = x.sum().backward();
+
+        // matches solving with the explicit inverse
+        let (inv, tape) = a.trace().inverse().unwrap().split_tape();
+        let expected = matmul(inv.put_tape(tape), &b).sum().backward();
+        gradients
+            .ref_gradient(&a)
+            .assert_close(expected.ref_gradient(&a), 1e-6);
+
+        // b_grad = A^{-T} 1
+        gradients
+            .ref_gradient(&b)
+            .assert_close(&[[0.4, 0.4], [0.2, 0.2]], 1e-6);
+    }
+
+    #[test]
+    fn test_solve_singular() {
+        let a = Tensor2D::new([[1.0, 2.0], [2.0, 4.0]]);
+        let b: Tensor2D<2, 1> = Tensor2D::ones();
+        assert_eq!(solve(a, b).unwrap_err(), LinalgError::Singular);
+    }
+
+    #[test]
+    fn test_cholesky_3x3() {
+        let t = Tensor2D::new([
+            [4.0, 12.0, -16.0],
+            [12.0, 37.0, -43.0],
+            [-16.0, -43.0, 98.0],
+        ]);
+        let l = t.trace().cholesky().unwrap();
+        assert_eq!(
+            l.data(),
+            &[[2.0, 0.0, 0.0], [6.0, 1.0, 0.0], [-8.0, 5.0, 3.0]]
+        );
+        let lt = Tensor2D::new(*l.data());
+        let (l, tape) = l.split_tape();
+        let llt = matmul_transpose(l.put_tape(tape), &lt);
+        llt.data().assert_close(t.data(), 1e-5);
+    }
+
+    #[test]
+    fn test_cholesky_finite_differences() {
+        let mut rng = StdRng::seed_from_u64(1);
+        // a * a^T + 4 * I is symmetric positive definite
+        let a: Tensor2D<3, 3> = Tensor2D::randn(&mut rng);
+        let mut spd = [[0.0; 3]; 3];
+        mm_bt(a.data(), a.data(), &mut spd);
+        for (i, spd_i) in spd.iter_mut().enumerate() {
+            spd_i[i] += 4.0;
+        }
+        let t = Tensor2D::new(spd);
+        let w: Tensor2D<3, 3> = Tensor2D::randn(&mut rng);
+        let loss = |a: [[f32; 3]; 3]| -> f32 {
+            *mul(Tensor2D::new(a).cholesky().unwrap().square(), &w)
+                .sum()
+                .data()
+        };
+
+        let gradients = mul(t.trace().cholesky().unwrap().square(), &w)
+            .sum()
+            .backward();
+        let g = gradients.ref_gradient(&t);
+
+        // perturb symmetrically, so the derivative is g[i][j] + g[j][i] off the diagonal
+        let h = 1e-2;
+        for i in 0..3 {
+            for j in 0..=i {
+                let mut plus = spd;
+                let mut minus = spd;
+                plus[i][j] += h;
+                minus[i][j] -= h;
+                if i != j {
+                    plus[j][i] += h;
+                    minus[j][i] -= h;
+                }
+                let expected = (loss(plus) - loss(minus)) / (2.0 * h);
+                let actual = if i == j { g[i][i] } else { g[i][j] + g[j][i] };
+                assert!(
+                    (actual - expected).abs() < 1e-2,
+                    "{i},{j}: {actual} vs {expected}"
+                );
+                assert_eq!(g[i][j], g[j][i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cholesky_log_det_gradient() {
+        // d/dA log(det(A)) = A^{-1}, and log(det(A)) = 2 * sum(log(diag(L)))
+        let t = Tensor2D::new([[4.0, 2.0, 0.4], [2.0, 3.0, 0.5], [0.4, 0.5, 2.0]]);
+        let l = t.trace().cholesky().unwrap();
+        let log_det = l.diag().ln().sum() * 2.0;
+        let gradients = log_det.backward();
+        let inv = t.duplicate().inverse().unwrap();
+        gradients.ref_gradient(&t).assert_close(inv.data(), 1e-5);
+    }
+
+    #[test]
+    fn test_cholesky_not_positive_definite() {
+        let t = Tensor2D::new([[1.0, 2.0], [2.0, 1.0]]);
+        assert_eq!(t.cholesky().unwrap_err(), LinalgError::NotPositiveDefinite);
+        let t: Tensor2D<2, 2> = Tensor2D::zeros();
+        assert_eq!(
+            t.trace().cholesky().unwrap_err(),
+            LinalgError::NotPositiveDefinite
+        );
+        assert_eq!(
+            LinalgError::NotPositiveDefinite.to_string(),
+            "matrix is not positive definite"
+        );
+
+        // adding jitter to the diagonal fixes it
+        let t = Tensor2D::new([[1.0, 1.0], [1.0, 1.0]]);
+        assert!(t.duplicate().cholesky().is_err());
+        let jitter = Tensor2D::new([[1e-3, 0.0], [0.0, 1e-3]]);
+        assert!(add(t, &jitter).cholesky().is_ok());
+    }
+}
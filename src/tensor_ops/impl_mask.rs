@@ -22,12 +22,130 @@ pub fn value_mask<T: Tensor<Dtype = f32>>(mut t: T, mask: &T::NoTape, value: T::
         *t = if o == &value { 0.0 } else { 1.0 }
     });
 
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+    move_tape_and_add_backward_op("value_mask", t, result, move |t, result, grads| {
         let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
         T::Device::addmul(t_grad, t.data(), result_grad);
     })
 }
 
+/// `t[mask] = value`. Sets `t` to `value` anywhere `mask` is `true`.
+///
+/// Gradients flow normally into the positions where `mask` is `false`, and
+/// are zero where `mask` is `true`, since those values were overwritten.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+/// let r = masked_fill(t.trace(), &[[false, true], [true, false]], -1e9);
+/// assert_eq!(r.data(), &[[1.0, -1e9], [-1e9, 4.0]]);
+/// ```
+///
+/// The shape of `mask` must match the shape of `t`:
+/// ```compile_fail
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+/// let r = masked_fill(t, &[[false, true, false], [true, false, false]], -1e9);
+/// ```
+pub fn masked_fill<const M: usize, const N: usize, H: Tape>(
+    t: Tensor2D<M, N, H>,
+    mask: &[[bool; N]; M],
+    value: f32,
+) -> Tensor2D<M, N, H> {
+    let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+    fill_masked(result.mut_data(), t.data(), mask, value);
+
+    let mask = *mask;
+    move_tape_and_add_backward_op("masked_fill", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        add_unmasked(t_grad, result_grad, &mask);
+    })
+}
+
+/// `t[:, mask] = value`. Sets `t[b]` to `value` anywhere `mask` is `true`, for every `b`.
+/// This is [masked_fill()] where the same `mask` is broadcast along the first dimension of `t`,
+/// without copying it `B` times. For example a causal attention mask applied to a batch of attention scores.
+///
+/// Gradients flow normally into the positions where `mask` is `false`, and
+/// are zero where `mask` is `true`, since those values were overwritten.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let scores: Tensor3D<2, 2, 2> = Tensor3D::ones();
+/// let causal = [[false, true], [false, false]];
+/// let r = masked_fill_broadcast_first(scores, &causal, f32::NEG_INFINITY);
+/// assert_eq!(r.data(), &[[[1.0, f32::NEG_INFINITY], [1.0, 1.0]]; 2]);
+/// ```
+pub fn masked_fill_broadcast_first<const B: usize, const M: usize, const N: usize, H: Tape>(
+    t: Tensor3D<B, M, N, H>,
+    mask: &[[bool; N]; M],
+    value: f32,
+) -> Tensor3D<B, M, N, H> {
+    let mut result: Tensor3D<B, M, N> = Tensor3D::zeros();
+    for (r, t) in result.mut_data().iter_mut().zip(t.data()) {
+        fill_masked(r, t, mask, value);
+    }
+
+    let mask = *mask;
+    move_tape_and_add_backward_op(
+        "masked_fill_broadcast_first",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[f32; N]; M]; B]) = grads.mut_and_ref(&t, &result);
+            for (t_grad, result_grad) in t_grad.iter_mut().zip(result_grad) {
+                add_unmasked(t_grad, result_grad, &mask);
+            }
+        },
+    )
+}
+
+/// Sets `r` to `value` where `mask` is `true`, and to `t` everywhere else.
+fn fill_masked<const M: usize, const N: usize>(
+    r: &mut [[f32; N]; M],
+    t: &[[f32; N]; M],
+    mask: &[[bool; N]; M],
+    value: f32,
+) {
+    for ((r_i, t_i), m_i) in r.iter_mut().zip(t).zip(mask) {
+        for ((r, t), &m) in r_i.iter_mut().zip(t_i).zip(m_i) {
+            *r = if m { value } else { *t };
+        }
+    }
+}
+
+/// `t_grad += result_grad` where `mask` is `false`. Masked positions are skipped instead of being
+/// multiplied by `0.0`, so that non-finite gradients of filled values (e.g. from a softmax over
+/// a fully masked row) don't leak into `t_grad`.
+fn add_unmasked<const M: usize, const N: usize>(
+    t_grad: &mut [[f32; N]; M],
+    result_grad: &[[f32; N]; M],
+    mask: &[[bool; N]; M],
+) {
+    for ((t_i, r_i), m_i) in t_grad.iter_mut().zip(result_grad).zip(mask) {
+        for ((t, r), &m) in t_i.iter_mut().zip(r_i).zip(m_i) {
+            if !m {
+                *t += r;
+            }
+        }
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Calls [masked_fill()] on self
+    pub fn masked_fill(self, mask: &[[bool; N]; M], value: f32) -> Self {
+        masked_fill(self, mask, value)
+    }
+}
+
+impl<const B: usize, const M: usize, const N: usize, H: Tape> Tensor3D<B, M, N, H> {
+    /// Calls [masked_fill_broadcast_first()] on self
+    pub fn masked_fill_broadcast_first(self, mask: &[[bool; N]; M], value: f32) -> Self {
+        masked_fill_broadcast_first(self, mask, value)
+    }
+}
+
 macro_rules! tensor_impl {
     ($typename:ident, [$($Vs:tt),*]) => {
 impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
@@ -48,6 +166,7 @@ tensor_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::assert_close;
 
     #[test]
     fn test_mask_0d() {
@@ -82,4 +201,101 @@ mod tests {
             &[[0.0, 1.0 / 6.0, 0.0], [1.0 / 6.0, 0.0, 1.0 / 6.0]]
         );
     }
+
+    #[test]
+    fn test_masked_fill_2d() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let mask = [[true, false, true], [false, true, false]];
+        let r = t.trace().masked_fill(&mask, -1e9);
+        assert_eq!(r.data(), &[[-1e9, 2.0, -1e9], [4.0, -1e9, 6.0]]);
+        // NOTE: .exp() so we cover the case where masked_fill() has to use result grad
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.0, 7.389056, 0.0], [54.59815, 0.0, 403.4288]]
+        );
+    }
+
+    #[test]
+    fn test_masked_fill_2d_softmax() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let mask = [[false, false, true], [false, true, false]];
+        let r = t.trace().masked_fill(&mask, f32::NEG_INFINITY).softmax();
+        assert_close(
+            r.data(),
+            &[[0.26894143, 0.7310586, 0.0], [0.11920292, 0.0, 0.8807971]],
+        );
+        let gradients = r.square().sum().backward();
+        let g = gradients.ref_gradient(&t);
+        assert_eq!(g[0][2], 0.0);
+        assert_eq!(g[1][1], 0.0);
+        assert!(g[0][0] != 0.0 && g[1][2] != 0.0);
+    }
+
+    #[test]
+    fn test_masked_fill_broadcast_first() {
+        let t: Tensor3D<2, 2, 2> =
+            Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let mask = [[false, true], [false, false]];
+        let r = t.trace().masked_fill_broadcast_first(&mask, -1e9);
+        assert_eq!(
+            r.data(),
+            &[[[1.0, -1e9], [3.0, 4.0]], [[5.0, -1e9], [7.0, 8.0]]]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[1.0, 0.0], [1.0, 1.0]], [[1.0, 0.0], [1.0, 1.0]]]
+        );
+    }
+
+    #[test]
+    fn test_masked_fill_broadcast_first_causal_softmax() {
+        let t: Tensor3D<2, 3, 3> = Tensor3D::new([
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]],
+            [[-1.0, 0.0, 1.0], [0.5, 0.5, 0.5], [2.0, -2.0, 0.0]],
+        ]);
+        let mut causal = [[false; 3]; 3];
+        for (i, row) in causal.iter_mut().enumerate() {
+            for (j, m) in row.iter_mut().enumerate() {
+                *m = j > i;
+            }
+        }
+        let r = t
+            .trace()
+            .masked_fill_broadcast_first(&causal, f32::NEG_INFINITY)
+            .softmax();
+        assert_eq!(r.data()[0][0], [1.0, 0.0, 0.0]);
+        assert_close(&r.data()[1][1], &[0.5, 0.5, 0.0]);
+        let gradients = r.square().sum().backward();
+        let g = gradients.ref_gradient(&t);
+        for g_b in g.iter() {
+            for (g_i, m_i) in g_b.iter().zip(causal.iter()) {
+                for (g, &m) in g_i.iter().zip(m_i.iter()) {
+                    assert!(g.is_finite());
+                    if m {
+                        assert_eq!(*g, 0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_masked_fill_fully_masked_row_softmax() {
+        let t: Tensor3D<1, 2, 3> = Tensor3D::new([[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]]);
+        let mask = [[true; 3], [false, true, false]];
+        let r = t
+            .trace()
+            .masked_fill_broadcast_first(&mask, f32::NEG_INFINITY)
+            .softmax();
+        assert_eq!(r.data()[0][0], [0.0; 3]);
+        assert_close(&r.data()[0][1], &[0.11920292, 0.0, 0.8807971]);
+        let gradients = r.square().sum().backward();
+        let g = gradients.ref_gradient(&t);
+        assert_eq!(g[0][0], [0.0; 3]);
+        assert!(g[0][1].iter().all(|g| g.is_finite()));
+        assert_eq!(g[0][1][1], 0.0);
+        assert!(g[0][1][0] != 0.0);
+    }
 }
@@ -13,18 +13,33 @@ use crate::prelude::*;
 /// ```
 ///
 /// This is equivalent to calling `t.max(-1)[0]` in pytorch.
+///
+/// The gradient only flows to the maximum value of each row. If there are multiple maximums
+/// in the same row, the gradient flows to the first one.
 pub fn max_last_dim<T: Tensor<Dtype = f32>>(mut t: T) -> T::LastDimReduced {
     let result = <T::LastDimReduced as Tensor>::NoTape::new_boxed(T::Device::reduce_last_dim(
         t.data(),
         &mut f32::max,
     ));
 
-    // store derivative in t
+    // store derivative in t, only marking the first maximum of each row
+    let last_dim = <T::Device as ReduceLastDim<T::Array>>::LAST_DIM;
+    let mut i = 0;
+    let mut found = false;
     T::Device::foreach_mb(t.mut_data(), Broadcast(result.data()), &mut |l, r| {
-        *l = if l == r { 1.0 } else { 0.0 }
+        if i % last_dim == 0 {
+            found = false;
+        }
+        i += 1;
+        *l = if !found && l == r {
+            found = true;
+            1.0
+        } else {
+            0.0
+        }
     });
 
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+    move_tape_and_add_backward_op("max_last_dim", t, result, move |t, result, grads| {
         let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
         T::Device::foreach_mrb(t_grad, t.data(), Broadcast(result_grad), &mut |g, t, r| {
             *g += t * r;
@@ -108,4 +123,34 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_max_last_2d_ties() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[3.0, 1.0, 3.0], [2.0, 2.0, 2.0]]);
+        let r: Tensor1D<2, OwnedTape> = t.trace().max_last_dim();
+        assert_eq!(r.data(), &[3.0, 2.0]);
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.5, 0.0, 0.0], [0.5, 0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_max_last_4d() {
+        let t: Tensor4D<2, 1, 2, 3> = Tensor4D::new([
+            [[[1.0, 2.0, 3.0], [6.0, 5.0, 4.0]]],
+            [[[-1.0, -2.0, -3.0], [-4.0, 5.0, -6.0]]],
+        ]);
+        let r: Tensor3D<2, 1, 2, OwnedTape> = t.trace().max_last_dim();
+        assert_eq!(r.data(), &[[[3.0, 6.0]], [[-1.0, 5.0]]]);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[
+                [[[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]],
+                [[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]],
+            ]
+        );
+    }
 }
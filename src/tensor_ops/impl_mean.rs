@@ -3,6 +3,16 @@ use crate::prelude::*;
 /// `sum(t) / numel(t)`, where `numel(t)` is the number of elements in `t`.
 ///
 /// Returns a [Tensor0D] (i.e. one number).
+///
+/// The gradient of every element of `t` is `1.0 / numel(t)` times the gradient of the result.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+/// let r: Tensor0D = t.mean();
+/// assert_eq!(r.data(), &3.5);
+/// ```
 pub fn mean<T: Tensor<Dtype = f32>>(t: T) -> Tensor0D<T::Tape> {
     div_scalar(sum(t), T::Array::NUM_ELEMENTS as f32)
 }
@@ -67,4 +77,13 @@ mod tests {
         let gradients = r.backward();
         assert_eq!(gradients.ref_gradient(&t), &[[[1.0 / 24.0; 3]; 2]; 4]);
     }
+
+    #[test]
+    fn test_mean_4d() {
+        let t: Tensor4D<2, 4, 2, 3> = Tensor4D::ones();
+        let r: Tensor0D<OwnedTape> = t.trace().mean();
+        assert_eq!(r.data(), &1.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[[1.0 / 48.0; 3]; 2]; 4]; 2]);
+    }
 }
@@ -0,0 +1,131 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// `t.min(-1)`. Reduces the last dimension of the tensor by gathering the minimum value from that dimension.
+/// Resulting [Tensor] has the last dimension removed (e.g. a 2d tensor will become 1d).
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+/// let r: Tensor1D<2> = min_last_dim(t);
+/// assert_eq!(r.data(), &[1.0, -3.0]);
+/// ```
+///
+/// This is equivalent to calling `t.min(-1)[0]` in pytorch.
+///
+/// The gradient only flows to the minimum value of each row. If there are multiple minimums
+/// in the same row, the gradient flows to the first one.
+pub fn min_last_dim<T: Tensor<Dtype = f32>>(mut t: T) -> T::LastDimReduced {
+    let result = <T::LastDimReduced as Tensor>::NoTape::new_boxed(T::Device::reduce_last_dim(
+        t.data(),
+        &mut f32::min,
+    ));
+
+    // store derivative in t, only marking the first minimum of each row
+    let last_dim = <T::Device as ReduceLastDim<T::Array>>::LAST_DIM;
+    let mut i = 0;
+    let mut found = false;
+    T::Device::foreach_mb(t.mut_data(), Broadcast(result.data()), &mut |l, r| {
+        if i % last_dim == 0 {
+            found = false;
+        }
+        i += 1;
+        *l = if !found && l == r {
+            found = true;
+            1.0
+        } else {
+            0.0
+        }
+    });
+
+    move_tape_and_add_backward_op("min_last_dim", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        T::Device::foreach_mrb(t_grad, t.data(), Broadcast(result_grad), &mut |g, t, r| {
+            *g += t * r;
+        });
+    })
+}
+
+macro_rules! min_last_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [min_last_dim()] on `self`.
+    pub fn min_last_dim(self) -> <Self as Tensor>::LastDimReduced {
+        min_last_dim(self)
+    }
+}
+    };
+}
+
+min_last_impl!(Tensor0D, []);
+min_last_impl!(Tensor1D, [M]);
+min_last_impl!(Tensor2D, [M, N]);
+min_last_impl!(Tensor3D, [M, N, O]);
+min_last_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_last_0d() {
+        let t = Tensor0D::new(2.0);
+        let r: Tensor0D<OwnedTape> = t.trace().min_last_dim();
+        assert_eq!(r.data(), &2.0);
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&t), &1.0);
+    }
+
+    #[test]
+    fn test_min_last_1d() {
+        let t: Tensor1D<3> = Tensor1D::new([1.0, 2.0, 3.0]);
+        let r: Tensor0D<OwnedTape> = t.trace().min_last_dim();
+        assert_eq!(r.data(), &1.0);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[2.7182817, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_min_last_2d() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+        let r: Tensor1D<2, OwnedTape> = t.trace().min_last_dim();
+        assert_eq!(r.data(), &[1.0, -3.0]);
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.5, 0.0, 0.0], [0.0, 0.0, 0.5]]
+        );
+    }
+
+    #[test]
+    fn test_min_last_2d_ties() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[3.0, 1.0, 1.0], [2.0, 2.0, 2.0]]);
+        let r: Tensor1D<2, OwnedTape> = t.trace().min_last_dim();
+        assert_eq!(r.data(), &[1.0, 2.0]);
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.0, 0.5, 0.0], [0.5, 0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_min_last_3d() {
+        let t: Tensor3D<2, 2, 3> = Tensor3D::new([
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            [[-1.0, -2.0, -3.0], [-6.0, 5.0, -4.0]],
+        ]);
+        let r: Tensor2D<2, 2, OwnedTape> = t.trace().min_last_dim();
+        assert_eq!(r.data(), &[[1.0, 4.0], [-3.0, -6.0]]);
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[
+                [[0.25, 0.0, 0.0], [0.25, 0.0, 0.0]],
+                [[0.0, 0.0, 0.25], [0.25, 0.0, 0.0]]
+            ]
+        );
+    }
+}
@@ -10,7 +10,8 @@ use crate::prelude::*;
 /// assert_eq!(r.data(), &[1.0, 0.0, 0.0, 4.0]);
 /// ```
 pub fn nans_to<T: Tensor<Dtype = f32>>(t: T, value: T::Dtype) -> T {
-    map(
+    map_named(
+        "nans_to",
         t,
         move |x| x.is_nan().then_some(value).unwrap_or(*x),
         move |x| x.is_nan().then_some(0.0).unwrap_or(1.0),
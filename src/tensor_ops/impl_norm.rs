@@ -0,0 +1,223 @@
+use super::impl_reshape::flat;
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// `sqrt(sum(t^2))`. Computes the frobenius (L2) norm of all the values in `t`. Returns a [Tensor0D].
+///
+/// The gradient of every element of `t` is `t / norm` times the gradient of the result.
+///
+/// The gradient is undefined when `t` is all zeros, so in that case the gradient is zero instead of `NaN`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[3.0, 0.0], [0.0, -4.0]]);
+/// let r: Tensor0D = frobenius_norm(t); // or t.frobenius_norm()
+/// assert_eq!(r.data(), &5.0);
+/// ```
+pub fn frobenius_norm<T: Tensor<Dtype = f32>>(t: T) -> Tensor0D<T::Tape> {
+    let mut squares = T::NoTape::zeros();
+    T::Device::foreach_mr(squares.mut_data(), t.data(), &mut |s, t| *s = t * t);
+    let norm = T::Device::reduce(squares.data(), &mut |a, b| a + b).sqrt();
+    let result = Tensor0D::<NoneTape>::new(norm);
+    move_tape_and_add_backward_op("frobenius_norm", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        if norm != 0.0 {
+            let scale = result_grad / norm;
+            T::Device::foreach_mr(t_grad, t.data(), &mut |g, t| *g += t * scale);
+        }
+    })
+}
+
+/// Scales `t` down so its frobenius (L2) norm is at most `max_norm`. This is `t * min(1, max_norm / norm)`,
+/// where `norm` is [frobenius_norm()] of all the values in `t`. If the norm is already at most `max_norm`
+/// (including when `t` is all zeros), `t` is returned unchanged.
+///
+/// The scale is treated as a constant in the backward pass, so the gradient of `t` is the gradient
+/// of the result times the same scale. See [renorm_straight_through()] to pass the gradient through unscaled.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([3.0, -4.0]);
+/// let r = renorm(t.trace(), 1.0); // or t.trace().renorm(1.0)
+/// assert_eq!(r.data(), &[0.6, -0.8]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&t), &[0.2, 0.2]);
+/// ```
+pub fn renorm<T: Tensor<Dtype = f32>>(t: T, max_norm: T::Dtype) -> T {
+    let scale = renorm_scale(&t, max_norm);
+    scale_with_grad_scale(t, scale, scale)
+}
+
+/// Like [renorm()], but the gradient of `t` is the gradient of the result, without scaling.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor1D::new([3.0, -4.0]);
+/// let r = renorm_straight_through(t.trace(), 1.0);
+/// assert_eq!(r.data(), &[0.6, -0.8]);
+/// let gradients = r.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&t), &[1.0, 1.0]);
+/// ```
+pub fn renorm_straight_through<T: Tensor<Dtype = f32>>(t: T, max_norm: T::Dtype) -> T {
+    let scale = renorm_scale(&t, max_norm);
+    scale_with_grad_scale(t, scale, 1.0)
+}
+
+/// `min(1, max_norm / norm)`, which is `1` if `norm` is `0`.
+fn renorm_scale<T: Tensor<Dtype = f32>>(t: &T, max_norm: f32) -> f32 {
+    assert!(max_norm >= 0.0, "max_norm must be >= 0.0, found {max_norm}");
+    let norm = flat(t.data()).iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > max_norm {
+        max_norm / norm
+    } else {
+        1.0
+    }
+}
+
+/// `t * scale`, where the gradient of `t` is the gradient of the result times `grad_scale`.
+fn scale_with_grad_scale<T: Tensor<Dtype = f32>>(t: T, scale: f32, grad_scale: f32) -> T {
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_mr(result.mut_data(), t.data(), &mut |r, t| *r = t * scale);
+    move_tape_and_add_backward_op("renorm", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        T::Device::foreach_mr(t_grad, result_grad, &mut |g, r| *g += r * grad_scale);
+    })
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [frobenius_norm()] on `self`.
+    pub fn frobenius_norm(self) -> Tensor0D<<Self as Tensor>::Tape> {
+        frobenius_norm(self)
+    }
+
+    /// Calls [renorm()] on `self`.
+    pub fn renorm(self, max_norm: f32) -> Self {
+        renorm(self, max_norm)
+    }
+
+    /// Calls [renorm_straight_through()] on `self`.
+    pub fn renorm_straight_through(self, max_norm: f32) -> Self {
+        renorm_straight_through(self, max_norm)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_frobenius_norm_1d() {
+        let t: Tensor1D<2> = Tensor1D::new([3.0, -4.0]);
+        let r = t.trace().frobenius_norm();
+        assert_eq!(r.data(), &5.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &[0.6, -0.8]);
+    }
+
+    #[test]
+    fn test_frobenius_norm_0d() {
+        let t = Tensor0D::new(-2.0);
+        let r = t.trace().frobenius_norm();
+        assert_eq!(r.data(), &2.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &-1.0);
+    }
+
+    #[test]
+    fn test_frobenius_norm_zeros() {
+        let t: Tensor2D<2, 3> = Tensor2D::zeros();
+        let r = t.trace().frobenius_norm();
+        assert_eq!(r.data(), &0.0);
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[0.0; 3]; 2]);
+    }
+
+    #[test]
+    fn test_frobenius_norm_uses_result_grad() {
+        let t: Tensor2D<2, 2> = Tensor2D::new([[1.0, 2.0], [2.0, 4.0]]);
+        let r = t.trace().frobenius_norm();
+        assert_eq!(r.data(), &5.0);
+        let gradients = (r * 3.0).backward();
+        assert_close(gradients.ref_gradient(&t), &[[0.6, 1.2], [1.2, 2.4]]);
+    }
+
+    #[test]
+    fn test_frobenius_norm_finite_differences() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let gradients = t.trace().frobenius_norm().backward();
+        let g = gradients.ref_gradient(&t);
+
+        let h = 1e-2;
+        let flat = t.data().iter().flatten().flatten();
+        for (i, g) in g.iter().flatten().flatten().enumerate() {
+            let norm = |delta: f32| -> f32 {
+                flat.clone()
+                    .enumerate()
+                    .map(|(j, v)| if i == j { v + delta } else { *v })
+                    .map(|v| v * v)
+                    .sum::<f32>()
+                    .sqrt()
+            };
+            let expected = (norm(h) - norm(-h)) / (2.0 * h);
+            assert!((g - expected).abs() < 1e-3, "{i}: {g} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn test_renorm_below_max_norm_is_identity() {
+        let t = Tensor2D::new([[1.0, -2.0], [0.5, 2.0]]);
+        let r = t.trace().renorm(4.0);
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+
+        let r = t.trace().renorm_straight_through(4.0);
+        assert_eq!(r.data(), t.data());
+    }
+
+    #[test]
+    fn test_renorm_zeros() {
+        let t: Tensor1D<3> = Tensor1D::zeros();
+        let r = t.trace().renorm(0.0);
+        assert_eq!(r.data(), &[0.0; 3]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[1.0; 3]);
+    }
+
+    #[test]
+    fn test_renorm_above_max_norm() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let norm = *t.duplicate().frobenius_norm().data();
+        let scale = 0.5 / norm;
+
+        let r = t.trace().renorm(0.5);
+        assert!((r.duplicate().frobenius_norm().data() - 0.5).abs() < 1e-6);
+        let gradients = r.square().sum().backward();
+        let expected = t.duplicate() * (2.0 * scale * scale);
+        assert_close(gradients.ref_gradient(&t), expected.data());
+
+        let r = t.trace().renorm_straight_through(0.5);
+        let gradients = r.square().sum().backward();
+        let expected = t.duplicate() * (2.0 * scale);
+        assert_close(gradients.ref_gradient(&t), expected.data());
+    }
+}
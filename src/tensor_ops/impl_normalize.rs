@@ -1,4 +1,7 @@
+use super::utils::move_tape_and_add_backward_op;
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 /// `(t - t.mean(-1)) / t.std(-1, epsilon)`. Normalizes `t` to have mean `0.0` and stddev `1.0`.
 ///
@@ -22,6 +25,77 @@ pub fn normalize<T: Tensor<Dtype = f32>>(t: T, epsilon: T::Dtype) -> T {
     div_broadcast_rhs_last(centered, &std)
 }
 
+/// `t / (t.square().sum(-1).sqrt() + epsilon)`. Normalizes the last dimension of `t` to have an L2 norm of `1.0`.
+///
+/// This is a single fused operation. The gradient is the projection `(I - y y^T) / ||t||` (for `epsilon == 0.0`)
+/// applied to the upstream gradient, where `y` is the result.
+///
+/// Rows that are all zeros produce zeros, and have a gradient of zero, instead of `NaN`.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[3.0, 4.0], [0.0, 0.0]]);
+/// let r = normalize_l2(a, 0.0); // or a.normalize_l2(0.0);
+/// assert_eq!(r.data(), &[[0.6, 0.8], [0.0, 0.0]]);
+/// ```
+pub fn normalize_l2<T: Tensor<Dtype = f32>>(t: T, epsilon: T::Dtype) -> T {
+    type Reduced<T> = <T as Tensor>::LastDimReduced;
+
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_mr(result.mut_data(), t.data(), &mut |r, t| *r = t * t);
+    let mut norm = <Reduced<T> as Tensor>::NoTape::zeros();
+    T::Device::reduce_last_dim_into(result.data(), norm.mut_data(), &mut |a, b| a + b);
+    <Reduced<T> as HasDevice>::Device::foreach_m(norm.mut_data(), &mut |n| *n = n.sqrt());
+    T::Device::foreach_mrb(
+        result.mut_data(),
+        t.data(),
+        Broadcast(norm.data()),
+        &mut |r, t, n| *r = if *n == 0.0 { 0.0 } else { t / (n + epsilon) },
+    );
+
+    move_tape_and_add_backward_op("normalize_l2", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+
+        // dot product of each row of `t` with its gradient
+        let mut t_dot_g = T::NoTape::zeros();
+        T::Device::foreach_mrr(t_dot_g.mut_data(), t.data(), result_grad, &mut |r, t, g| {
+            *r = t * g
+        });
+        let mut t_dot_g = T::Device::reduce_last_dim(t_dot_g.data(), &mut |a, b| a + b);
+        <Reduced<T> as HasDevice>::Device::foreach_mr(
+            t_dot_g.as_mut(),
+            norm.data(),
+            &mut |d, n| {
+                *d = if *n == 0.0 {
+                    0.0
+                } else {
+                    *d / (n * (n + epsilon).powi(2))
+                }
+            },
+        );
+
+        T::Device::foreach_mrb(
+            t_grad,
+            result_grad,
+            Broadcast(norm.data()),
+            &mut |tg, g, n| {
+                if *n != 0.0 {
+                    *tg += g / (n + epsilon);
+                }
+            },
+        );
+        T::Device::foreach_mrb(
+            t_grad,
+            t.data(),
+            Broadcast(t_dot_g.as_ref()),
+            &mut |tg, t, d| {
+                *tg -= t * d;
+            },
+        );
+    })
+}
+
 macro_rules! tensor_impl {
     ($typename:ident, [$($Vs:tt),*]) => {
 impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H>
@@ -30,6 +104,11 @@ impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H>
     pub fn normalize(self, epsilon: f32) -> Self {
         normalize(self, epsilon)
     }
+
+    /// Calls [normalize_l2()] on `self`.
+    pub fn normalize_l2(self, epsilon: f32) -> Self {
+        normalize_l2(self, epsilon)
+    }
 }
     };
 }
@@ -43,6 +122,7 @@ tensor_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::{assert_close, AssertClose};
 
     #[test]
     fn test_0d_normalize() {
@@ -95,4 +175,83 @@ mod tests {
         let gradients = r.exp().mean().backward();
         assert_eq!(gradients.ref_gradient(&a), &[[[0.0; 3]; 2]; 4]);
     }
+
+    #[test]
+    fn test_1d_normalize_l2() {
+        let a = Tensor1D::new([-2.0, 0.0, 5.0]);
+        let r = a.trace().normalize_l2(0.0);
+        assert_close(r.data(), &[-0.37139067, 0.0, 0.9284767]);
+        // NOTE: .exp() so we can make sure normalize_l2 is using result grad properly
+        let gradients = r.exp().mean().backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[0.090821825, 0.061898448, 0.036328733],
+        );
+    }
+
+    #[test]
+    fn test_2d_normalize_l2_matches_composed() {
+        let a: Tensor2D<2, 3> = Tensor2D::new([[-2.0, 0.5, 5.0], [1.0, 2.0, 3.0]]);
+        let r = a.trace().normalize_l2(1e-3);
+        let gradients = r.exp().mean().backward();
+
+        let (x, tape) = a.trace().split_tape();
+        let (norm, tape) = add_scalar(
+            x.duplicate().put_tape(tape).square().sum_last_dim().sqrt(),
+            1e-3,
+        )
+        .split_tape();
+        let expected = div_broadcast_rhs_last(x.put_tape(tape), &norm);
+        let expected_gradients = expected.exp().mean().backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            expected_gradients.ref_gradient(&a),
+        );
+    }
+
+    #[test]
+    fn test_3d_normalize_l2_zeros() {
+        let a: Tensor3D<4, 2, 3> = Tensor3D::zeros();
+        let r = a.trace().normalize_l2(0.0);
+        assert_eq!(r.data(), &[[[0.0; 3]; 2]; 4]);
+        let gradients = r.exp().mean().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[[[0.0; 3]; 2]; 4]);
+    }
+
+    #[test]
+    fn test_2d_normalize_l2_finite_differences() {
+        let a = [[-2.0, 0.5, 5.0], [1.0, 2.0, 3.0], [0.1, -0.3, 0.2]];
+        let w = Tensor2D::new([[1.0, -2.0, 0.5], [0.3, 0.2, -1.0], [2.0, 1.0, -0.5]]);
+        let loss = |a: [[f32; 3]; 3]| -> f32 {
+            let r = Tensor2D::new(a).normalize_l2(1e-6);
+            *mul(r, &w).sum().data()
+        };
+
+        let x = Tensor2D::new(a);
+        let gradients = mul(x.trace().normalize_l2(1e-6), &w).sum().backward();
+
+        let h = 1e-3;
+        let mut expected = [[0.0; 3]; 3];
+        for (i, expected_i) in expected.iter_mut().enumerate() {
+            for (j, e) in expected_i.iter_mut().enumerate() {
+                let mut plus = a;
+                plus[i][j] += h;
+                let mut minus = a;
+                minus[i][j] -= h;
+                *e = (loss(plus) - loss(minus)) / (2.0 * h);
+            }
+        }
+        gradients.ref_gradient(&x).assert_close(&expected, 2e-3);
+    }
+
+    #[test]
+    fn test_2d_normalize_l2_zero_row_with_epsilon() {
+        let a = Tensor2D::new([[0.0, 0.0, 0.0], [1.0, 2.0, 2.0]]);
+        let r = a.trace().normalize_l2(1e-5);
+        assert_close(r.data(), &[[0.0; 3], [0.33333224, 0.6666645, 0.6666645]]);
+        let gradients = r.exp().sum().backward();
+        let a_grad = gradients.ref_gradient(&a);
+        assert_eq!(a_grad[0], [0.0; 3]);
+        assert!(a_grad[1].iter().all(|g| g.is_finite()));
+    }
 }
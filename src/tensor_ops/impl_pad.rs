@@ -0,0 +1,439 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Pads the last two (spatial) dimensions of `t` with `padding` elements of `value` on every side.
+///
+/// The output size is specified by the type of the result, since stable rust can't compute
+/// `H + 2 * padding` in a type. **Panics** if `H2 != H + 2 * padding` or `W2 != W + 2 * padding`.
+///
+/// Gradients only flow back from the interior of the result, since the border is constant.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+/// let r: Tensor3D<1, 4, 4> = pad2d(t, 1, 0.0);
+/// assert_eq!(
+///     r.data(),
+///     &[[
+///         [0.0, 0.0, 0.0, 0.0],
+///         [0.0, 1.0, 2.0, 0.0],
+///         [0.0, 3.0, 4.0, 0.0],
+///         [0.0, 0.0, 0.0, 0.0],
+///     ]]
+/// );
+/// ```
+pub fn pad2d<
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    const H2: usize,
+    const W2: usize,
+    TAPE: Tape,
+>(
+    t: Tensor3D<C, H, W, TAPE>,
+    padding: usize,
+    value: f32,
+) -> Tensor3D<C, H2, W2, TAPE> {
+    assert_eq!(H2, H + 2 * padding, "output height must be H + 2 * padding");
+    assert_eq!(W2, W + 2 * padding, "output width must be W + 2 * padding");
+
+    let mut result: Tensor3D<C, H2, W2> = Tensor3D::zeros();
+    for (r_c, t_c) in result.mut_data().iter_mut().zip(t.data().iter()) {
+        for (h, r_h) in r_c.iter_mut().enumerate() {
+            for (w, r) in r_h.iter_mut().enumerate() {
+                *r = if (padding..H + padding).contains(&h) && (padding..W + padding).contains(&w) {
+                    t_c[h - padding][w - padding]
+                } else {
+                    value
+                };
+            }
+        }
+    }
+
+    move_tape_and_add_backward_op("pad2d", t, result, move |t, result, grads| {
+        let (t_grad, result_grad): (_, &[[[f32; W2]; H2]; C]) = grads.mut_and_ref(&t, &result);
+        for (t_c, r_c) in t_grad.iter_mut().zip(result_grad.iter()) {
+            for (t_h, r_h) in t_c.iter_mut().zip(r_c[padding..].iter()) {
+                for (t, r) in t_h.iter_mut().zip(r_h[padding..].iter()) {
+                    *t += r;
+                }
+            }
+        }
+    })
+}
+
+impl<const C: usize, const H: usize, const W: usize, TAPE: Tape> Tensor3D<C, H, W, TAPE> {
+    /// Calls [pad2d()] on self
+    pub fn pad2d<const H2: usize, const W2: usize>(
+        self,
+        padding: usize,
+        value: f32,
+    ) -> Tensor3D<C, H2, W2, TAPE> {
+        pad2d(self, padding, value)
+    }
+}
+
+/// How the border added by [Tensor2D::pad()] and [Tensor4D::pad()] is filled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadMode {
+    /// Fill the border with a constant value.
+    Constant(f32),
+
+    /// Fill the border by reflecting the values across the edge, without repeating the edge.
+    /// E.g. padding `[1, 2, 3]` by 2 on both sides results in `[3, 2, 1, 2, 3, 2, 1]`.
+    ///
+    /// Each padding amount must be less than the size of the dimension it pads.
+    Reflect,
+}
+
+impl Default for PadMode {
+    /// `PadMode::Constant(0.0)`
+    fn default() -> Self {
+        Self::Constant(0.0)
+    }
+}
+
+/// Causes a compile time error if `N + BEFORE + AFTER != N2`.
+struct AssertPaddedSize<const N: usize, const BEFORE: usize, const AFTER: usize, const N2: usize>;
+
+impl<const N: usize, const BEFORE: usize, const AFTER: usize, const N2: usize>
+    AssertPaddedSize<N, BEFORE, AFTER, N2>
+{
+    const OK: () = assert!(
+        N + BEFORE + AFTER == N2,
+        "padded size must be the original size plus both padding amounts"
+    );
+}
+
+/// Returns the index into the unpadded dimension of size `n` that index `i` of the padded
+/// dimension comes from, or `None` if it is filled with a constant.
+fn source_index(i: usize, before: usize, n: usize, mode: PadMode) -> Option<usize> {
+    if (before..before + n).contains(&i) {
+        Some(i - before)
+    } else {
+        match mode {
+            PadMode::Constant(_) => None,
+            PadMode::Reflect if i < before => Some(before - i),
+            PadMode::Reflect => Some(2 * (n - 1) + before - i),
+        }
+    }
+}
+
+fn pad_forward<const M: usize, const N: usize, const M2: usize, const N2: usize>(
+    out: &mut [[f32; N2]; M2],
+    inp: &[[f32; N]; M],
+    (top, left): (usize, usize),
+    mode: PadMode,
+) {
+    for (i, out_i) in out.iter_mut().enumerate() {
+        for (j, o) in out_i.iter_mut().enumerate() {
+            *o = match (
+                source_index(i, top, M, mode),
+                source_index(j, left, N, mode),
+            ) {
+                (Some(si), Some(sj)) => inp[si][sj],
+                _ => match mode {
+                    PadMode::Constant(value) => value,
+                    PadMode::Reflect => unreachable!(),
+                },
+            };
+        }
+    }
+}
+
+fn pad_backward<const M: usize, const N: usize, const M2: usize, const N2: usize>(
+    inp_grad: &mut [[f32; N]; M],
+    out_grad: &[[f32; N2]; M2],
+    (top, left): (usize, usize),
+    mode: PadMode,
+) {
+    for (i, g_i) in out_grad.iter().enumerate() {
+        for (j, g) in g_i.iter().enumerate() {
+            if let (Some(si), Some(sj)) = (
+                source_index(i, top, M, mode),
+                source_index(j, left, N, mode),
+            ) {
+                inp_grad[si][sj] += g;
+            }
+        }
+    }
+}
+
+fn check_reflect_padding(mode: PadMode, amounts: [usize; 4], m: usize, n: usize) {
+    if mode == PadMode::Reflect {
+        assert!(
+            amounts[0] < m && amounts[1] < m && amounts[2] < n && amounts[3] < n,
+            "reflect padding must be less than the size of the padded dimension"
+        );
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Pads the rows of `self` with `TOP` rows before and `BOTTOM` rows after, and the columns
+    /// with `LEFT` columns before and `RIGHT` columns after. The border is filled according to `mode`.
+    ///
+    /// The output size must be `M + TOP + BOTTOM` by `N + LEFT + RIGHT`, which is checked at compile time.
+    /// It can be inferred by passing `_` for `M2` and `N2`.
+    ///
+    /// Gradients flow back into the positions each value was copied from, so for [PadMode::Constant]
+    /// the gradient is just cropped back to `M x N`.
+    ///
+    /// **Panics** with [PadMode::Reflect] if any padding amount is not less than the size of its dimension.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+    /// let r: Tensor2D<3, 5> = t.duplicate().pad::<0, 1, 2, 1, _, _>(PadMode::Constant(0.0));
+    /// assert_eq!(
+    ///     r.data(),
+    ///     &[
+    ///         [0.0, 0.0, 1.0, 2.0, 0.0],
+    ///         [0.0, 0.0, 3.0, 4.0, 0.0],
+    ///         [0.0, 0.0, 0.0, 0.0, 0.0],
+    ///     ]
+    /// );
+    /// let r: Tensor2D<2, 4> = t.pad::<0, 0, 1, 1, _, _>(PadMode::Reflect);
+    /// assert_eq!(r.data(), &[[2.0, 1.0, 2.0, 1.0], [4.0, 3.0, 4.0, 3.0]]);
+    /// ```
+    ///
+    /// The wrong output size is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+    /// let r: Tensor2D<4, 4> = t.pad::<1, 1, 1, 0, _, _>(PadMode::default());
+    /// ```
+    pub fn pad<
+        const TOP: usize,
+        const BOTTOM: usize,
+        const LEFT: usize,
+        const RIGHT: usize,
+        const M2: usize,
+        const N2: usize,
+    >(
+        self,
+        mode: PadMode,
+    ) -> Tensor2D<M2, N2, H> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (
+            AssertPaddedSize::<M, TOP, BOTTOM, M2>::OK,
+            AssertPaddedSize::<N, LEFT, RIGHT, N2>::OK,
+        );
+        check_reflect_padding(mode, [TOP, BOTTOM, LEFT, RIGHT], M, N);
+
+        let mut result: Tensor2D<M2, N2> = Tensor2D::zeros();
+        pad_forward(result.mut_data(), self.data(), (TOP, LEFT), mode);
+        move_tape_and_add_backward_op("pad", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; N2]; M2]) = grads.mut_and_ref(&t, &result);
+            pad_backward(t_grad, result_grad, (TOP, LEFT), mode);
+        })
+    }
+}
+
+impl<const B: usize, const C: usize, const M: usize, const N: usize, H: Tape>
+    Tensor4D<B, C, M, N, H>
+{
+    /// Pads the last two (spatial) dimensions of `self`. This is [Tensor2D::pad()] applied to every
+    /// `M x N` image in the batch.
+    ///
+    /// The output size must be `M + TOP + BOTTOM` by `N + LEFT + RIGHT`, which is checked at compile time.
+    ///
+    /// **Panics** with [PadMode::Reflect] if any padding amount is not less than the size of its dimension.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<2, 3, 4, 4> = Tensor4D::ones();
+    /// let r: Tensor4D<2, 3, 6, 6> = t.pad::<1, 1, 1, 1, _, _>(PadMode::Constant(0.0));
+    /// assert_eq!(r.data()[1][2][0], [0.0; 6]);
+    /// assert_eq!(r.data()[1][2][1], [0.0, 1.0, 1.0, 1.0, 1.0, 0.0]);
+    /// ```
+    pub fn pad<
+        const TOP: usize,
+        const BOTTOM: usize,
+        const LEFT: usize,
+        const RIGHT: usize,
+        const M2: usize,
+        const N2: usize,
+    >(
+        self,
+        mode: PadMode,
+    ) -> Tensor4D<B, C, M2, N2, H> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (
+            AssertPaddedSize::<M, TOP, BOTTOM, M2>::OK,
+            AssertPaddedSize::<N, LEFT, RIGHT, N2>::OK,
+        );
+        check_reflect_padding(mode, [TOP, BOTTOM, LEFT, RIGHT], M, N);
+
+        let mut result: Tensor4D<B, C, M2, N2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            for (r_c, t_c) in r_b.iter_mut().zip(t_b.iter()) {
+                pad_forward(r_c, t_c, (TOP, LEFT), mode);
+            }
+        }
+        move_tape_and_add_backward_op("pad", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[[f32; N2]; M2]; C]; B]) =
+                grads.mut_and_ref(&t, &result);
+            for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                for (t_c, r_c) in t_b.iter_mut().zip(r_b.iter()) {
+                    pad_backward(t_c, r_c, (TOP, LEFT), mode);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad2d() {
+        let t: Tensor3D<2, 1, 2> = Tensor3D::new([[[1.0, 2.0]], [[3.0, 4.0]]]);
+        let r: Tensor3D<2, 3, 4, OwnedTape> = t.trace().pad2d(1, -1.0);
+        assert_eq!(
+            r.data(),
+            &[
+                [
+                    [-1.0, -1.0, -1.0, -1.0],
+                    [-1.0, 1.0, 2.0, -1.0],
+                    [-1.0, -1.0, -1.0, -1.0]
+                ],
+                [
+                    [-1.0, -1.0, -1.0, -1.0],
+                    [-1.0, 3.0, 4.0, -1.0],
+                    [-1.0, -1.0, -1.0, -1.0]
+                ]
+            ]
+        );
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[2.7182817, 7.389056]], [[20.085537, 54.59815]]]
+        );
+    }
+
+    #[test]
+    fn test_pad2d_zero_padding() {
+        let t: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+        let r: Tensor3D<1, 2, 2, OwnedTape> = t.trace().pad2d(0, 5.0);
+        assert_eq!(r.data(), t.data());
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[1.0; 2]; 2]]);
+    }
+
+    #[test]
+    fn test_pad2d_then_crop() {
+        let t: Tensor3D<2, 2, 3> = Tensor3D::new([
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            [[-1.0, -2.0, -3.0], [-4.0, -5.0, -6.0]],
+        ]);
+        let r: Tensor3D<2, 6, 7> = t.duplicate().pad2d(2, 9.0);
+        let mut cropped = [[[0.0; 3]; 2]; 2];
+        for (cropped_c, r_c) in cropped.iter_mut().zip(r.data().iter()) {
+            for (cropped_h, r_h) in cropped_c.iter_mut().zip(r_c[2..].iter()) {
+                cropped_h.copy_from_slice(&r_h[2..5]);
+            }
+        }
+        assert_eq!(&cropped, t.data());
+    }
+
+    #[test]
+    #[should_panic = "output height must be H + 2 * padding"]
+    fn test_pad2d_wrong_size() {
+        let t: Tensor3D<1, 2, 2> = Tensor3D::zeros();
+        let _: Tensor3D<1, 5, 4> = t.pad2d(1, 0.0);
+    }
+
+    #[test]
+    fn test_pad_2d_constant() {
+        let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let r: Tensor2D<4, 3, OwnedTape> =
+            t.trace().pad::<1, 1, 0, 1, _, _>(PadMode::Constant(-1.0));
+        assert_eq!(
+            r.data(),
+            &[
+                [-1.0, -1.0, -1.0],
+                [1.0, 2.0, -1.0],
+                [3.0, 4.0, -1.0],
+                [-1.0, -1.0, -1.0]
+            ]
+        );
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[2.7182817, 7.389056], [20.085537, 54.59815]]
+        );
+    }
+
+    #[test]
+    fn test_pad_2d_default_is_zeros() {
+        let t = Tensor2D::new([[1.0]]);
+        let r: Tensor2D<3, 3> = t.pad::<1, 1, 1, 1, _, _>(Default::default());
+        assert_eq!(r.data(), &[[0.0; 3], [0.0, 1.0, 0.0], [0.0; 3]]);
+    }
+
+    #[test]
+    fn test_pad_2d_reflect() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let r: Tensor2D<3, 7, OwnedTape> = t.trace().pad::<0, 1, 2, 2, _, _>(PadMode::Reflect);
+        assert_eq!(
+            r.data(),
+            &[
+                [3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0],
+                [6.0, 5.0, 4.0, 5.0, 6.0, 5.0, 4.0],
+                [3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0],
+            ]
+        );
+        // each input gets the gradient of every position it was copied to
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[4.0, 6.0, 4.0], [2.0, 3.0, 2.0]]
+        );
+    }
+
+    #[test]
+    #[should_panic = "reflect padding must be less than the size of the padded dimension"]
+    fn test_pad_2d_reflect_too_large() {
+        let t = Tensor2D::new([[1.0, 2.0]]);
+        let _: Tensor2D<1, 4> = t.pad::<0, 0, 2, 0, _, _>(PadMode::Reflect);
+    }
+
+    #[test]
+    fn test_pad_4d() {
+        let t: Tensor4D<2, 1, 1, 2> = Tensor4D::new([[[[1.0, 2.0]]], [[[3.0, 4.0]]]]);
+        let r: Tensor4D<2, 1, 3, 2, OwnedTape> =
+            t.trace().pad::<1, 1, 0, 0, _, _>(PadMode::Constant(5.0));
+        assert_eq!(
+            r.data(),
+            &[
+                [[[5.0, 5.0], [1.0, 2.0], [5.0, 5.0]]],
+                [[[5.0, 5.0], [3.0, 4.0], [5.0, 5.0]]]
+            ]
+        );
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[[2.7182817, 7.389056]]], [[[20.085537, 54.59815]]]]
+        );
+    }
+
+    #[test]
+    fn test_pad_4d_matches_pad2d() {
+        let t: Tensor4D<1, 2, 2, 3> = Tensor4D::new([[
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            [[-1.0, -2.0, -3.0], [-4.0, -5.0, -6.0]],
+        ]]);
+        let r: Tensor4D<1, 2, 6, 7> = t
+            .duplicate()
+            .pad::<2, 2, 2, 2, _, _>(PadMode::Constant(9.0));
+        let t: Tensor3D<2, 2, 3> = Tensor3D::new(t.data()[0]);
+        let expected: Tensor3D<2, 6, 7> = t.pad2d(2, 9.0);
+        assert_eq!(&r.data()[0], expected.data());
+    }
+}
@@ -0,0 +1,236 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Causes a compile time error if `C != C2 * R * R`, `H * R != H2`, or `W * R != W2`.
+struct AssertShuffled<
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    const R: usize,
+    const C2: usize,
+    const H2: usize,
+    const W2: usize,
+>;
+
+impl<
+        const C: usize,
+        const H: usize,
+        const W: usize,
+        const R: usize,
+        const C2: usize,
+        const H2: usize,
+        const W2: usize,
+    > AssertShuffled<C, H, W, R, C2, H2, W2>
+{
+    const OK: () = assert!(
+        C == C2 * R * R && H * R == H2 && W * R == W2,
+        "pixel shuffle must move channels `C2 * R * R` into an `R x R` block of space"
+    );
+}
+
+/// `space[c][h * r + i][w * r + j] += channels[c * r * r + i * r + j][h][w]`
+fn shuffle_add<
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    const C2: usize,
+    const H2: usize,
+    const W2: usize,
+>(
+    space: &mut [[[f32; W2]; H2]; C2],
+    channels: &[[[f32; W]; H]; C],
+    r: usize,
+) {
+    for (k, ch_k) in channels.iter().enumerate() {
+        let (c, i, j) = (k / (r * r), (k / r) % r, k % r);
+        for (h, ch_h) in ch_k.iter().enumerate() {
+            for (w, v) in ch_h.iter().enumerate() {
+                space[c][h * r + i][w * r + j] += v;
+            }
+        }
+    }
+}
+
+/// `channels[c * r * r + i * r + j][h][w] += space[c][h * r + i][w * r + j]`
+fn unshuffle_add<
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    const C2: usize,
+    const H2: usize,
+    const W2: usize,
+>(
+    channels: &mut [[[f32; W]; H]; C],
+    space: &[[[f32; W2]; H2]; C2],
+    r: usize,
+) {
+    for (k, ch_k) in channels.iter_mut().enumerate() {
+        let (c, i, j) = (k / (r * r), (k / r) % r, k % r);
+        for (h, ch_h) in ch_k.iter_mut().enumerate() {
+            for (w, v) in ch_h.iter_mut().enumerate() {
+                *v += space[c][h * r + i][w * r + j];
+            }
+        }
+    }
+}
+
+impl<const B: usize, const C: usize, const H: usize, const W: usize, TAPE: Tape>
+    Tensor4D<B, C, H, W, TAPE>
+{
+    /// Rearranges each group of `R * R` channels into an `R x R` block of space, turning a
+    /// `Tensor4D<B, C2 * R * R, H, W>` into a `Tensor4D<B, C2, H * R, W * R>`. This is the same as
+    /// pytorch's `pixel_shuffle`, and is the inverse of [Tensor4D::pixel_unshuffle()].
+    ///
+    /// The output sizes are checked at compile time. `H2` and `W2` can be inferred by passing `_`.
+    ///
+    /// The backward of this is [Tensor4D::pixel_unshuffle()] applied to the gradient.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 4, 1, 1> = Tensor4D::new([[[[1.0]], [[2.0]], [[3.0]], [[4.0]]]]);
+    /// let r = t.pixel_shuffle::<2, 1, _, _>();
+    /// assert_eq!(r.data(), &[[[[1.0, 2.0], [3.0, 4.0]]]]);
+    /// ```
+    ///
+    /// The number of channels must be divisible by `R * R`:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 6, 1, 1> = Tensor4D::zeros();
+    /// let r = t.pixel_shuffle::<2, 1, _, _>();
+    /// ```
+    pub fn pixel_shuffle<const R: usize, const C2: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor4D<B, C2, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertShuffled::<C, H, W, R, C2, H2, W2>::OK;
+
+        let mut result: Tensor4D<B, C2, H2, W2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            shuffle_add(r_b, t_b, R);
+        }
+        move_tape_and_add_backward_op("pixel_shuffle", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[[f32; W2]; H2]; C2]; B]) =
+                grads.mut_and_ref(&t, &result);
+            for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                unshuffle_add(t_b, r_b, R);
+            }
+        })
+    }
+
+    /// Rearranges each `R x R` block of space into `R * R` channels, turning a
+    /// `Tensor4D<B, C, H2 * R, W2 * R>` into a `Tensor4D<B, C * R * R, H2, W2>`. This is the same as
+    /// pytorch's `pixel_unshuffle`, and is the inverse of [Tensor4D::pixel_shuffle()].
+    ///
+    /// The output sizes are checked at compile time. `C2` can be inferred by passing `_`.
+    ///
+    /// The backward of this is [Tensor4D::pixel_shuffle()] applied to the gradient.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+    /// let r = t.pixel_unshuffle::<2, _, 1, 1>();
+    /// assert_eq!(r.data(), &[[[[1.0]], [[2.0]], [[3.0]], [[4.0]]]]);
+    /// ```
+    ///
+    /// The spatial dimensions must be divisible by `R`:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 1, 3, 2> = Tensor4D::zeros();
+    /// let r = t.pixel_unshuffle::<2, _, 1, 1>();
+    /// ```
+    pub fn pixel_unshuffle<const R: usize, const C2: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor4D<B, C2, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = AssertShuffled::<C2, H2, W2, R, C, H, W>::OK;
+
+        let mut result: Tensor4D<B, C2, H2, W2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            unshuffle_add(r_b, t_b, R);
+        }
+        move_tape_and_add_backward_op("pixel_unshuffle", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[[f32; W2]; H2]; C2]; B]) =
+                grads.mut_and_ref(&t, &result);
+            for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                shuffle_add(t_b, r_b, R);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_pixel_shuffle_2x() {
+        let t: Tensor4D<1, 8, 1, 2> = Tensor4D::new([[
+            [[0.0, 1.0]],
+            [[2.0, 3.0]],
+            [[4.0, 5.0]],
+            [[6.0, 7.0]],
+            [[8.0, 9.0]],
+            [[10.0, 11.0]],
+            [[12.0, 13.0]],
+            [[14.0, 15.0]],
+        ]]);
+        let r: Tensor4D<1, 2, 2, 4, OwnedTape> = t.trace().pixel_shuffle::<2, 2, _, _>();
+        assert_eq!(
+            r.data(),
+            &[[
+                [[0.0, 2.0, 1.0, 3.0], [4.0, 6.0, 5.0, 7.0]],
+                [[8.0, 10.0, 9.0, 11.0], [12.0, 14.0, 13.0, 15.0]],
+            ]]
+        );
+        let w: Tensor4D<1, 2, 2, 4> = Tensor4D::new([[
+            [[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]],
+            [[-1.0, -2.0, -3.0, -4.0], [-5.0, -6.0, -7.0, -8.0]],
+        ]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[
+                [[1.0, 3.0]],
+                [[2.0, 4.0]],
+                [[5.0, 7.0]],
+                [[6.0, 8.0]],
+                [[-1.0, -3.0]],
+                [[-2.0, -4.0]],
+                [[-5.0, -7.0]],
+                [[-6.0, -8.0]],
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_pixel_unshuffle_2x() {
+        let t: Tensor4D<1, 1, 2, 4> =
+            Tensor4D::new([[[[0.0, 1.0, 2.0, 3.0], [4.0, 5.0, 6.0, 7.0]]]]);
+        let r: Tensor4D<1, 4, 1, 2, OwnedTape> = t.trace().pixel_unshuffle::<2, _, 1, 2>();
+        assert_eq!(
+            r.data(),
+            &[[[[0.0, 2.0]], [[1.0, 3.0]], [[4.0, 6.0]], [[5.0, 7.0]]]]
+        );
+        let w: Tensor4D<1, 4, 1, 2> =
+            Tensor4D::new([[[[1.0, 2.0]], [[3.0, 4.0]], [[5.0, 6.0]], [[7.0, 8.0]]]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[[1.0, 3.0, 2.0, 4.0], [5.0, 7.0, 6.0, 8.0]]]]
+        );
+    }
+
+    #[test]
+    fn test_pixel_shuffle_unshuffle_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor4D<2, 9, 2, 3> = Tensor4D::randn(&mut rng);
+        let r: Tensor4D<2, 1, 6, 9, OwnedTape> = t.trace().pixel_shuffle::<3, 1, _, _>();
+        let r: Tensor4D<2, 9, 2, 3, OwnedTape> = r.pixel_unshuffle::<3, _, 2, 3>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), t.duplicate().exp().data());
+    }
+}
@@ -0,0 +1,143 @@
+use super::binary_map::{
+    binary_map, binary_map_broadcast_rhs_channel, binary_map_broadcast_rhs_first, prelu,
+    SecondOrder,
+};
+use crate::prelude::*;
+
+/// Parametric relu: `max(0, lhs) + &rhs * min(0, lhs)`, where `rhs` is the slope for negative
+/// values of `lhs`. The gradient of `rhs` is the sum of `lhs` over the negative positions.
+///
+/// See [prelu_broadcast_rhs_first()] and [prelu_broadcast_rhs_channel()] to use one slope
+/// per feature or channel.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([-2.0, -1.0, 0.0, 1.0]);
+/// let b = Tensor1D::new([0.5, 0.1, 0.5, 0.5]);
+/// let r = prelu(a, &b);
+/// assert_eq!(r.data(), &[-1.0, -0.1, 0.0, 1.0]);
+/// ```
+pub fn prelu<T: Tensor<Dtype = f32>>(lhs: T, rhs: &T::NoTape) -> T {
+    binary_map(
+        "prelu",
+        lhs,
+        rhs,
+        prelu::f,
+        prelu::dfdx,
+        prelu::dfdy,
+        SecondOrder::Unsupported,
+    )
+}
+
+/// [prelu()] where `rhs` is broadcasted `M` times, where `M` is the first dimension of `lhs`.
+///
+/// E.g If Lhs has dimension `(2, 3)`, then Rhs has to be dimension `(3,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor2D::new([[-1.0, 2.0], [3.0, -4.0]]);
+/// let b = Tensor1D::new([0.1, 0.5]);
+/// let r = prelu_broadcast_rhs_first(a, &b);
+/// assert_eq!(r.data(), &[[-0.1, 2.0], [3.0, -2.0]]);
+/// ```
+pub fn prelu_broadcast_rhs_first<Lhs, Rhs, const M: usize>(lhs: Lhs, rhs: &Rhs) -> Lhs
+where
+    Lhs: Tensor<Array = [Rhs::Array; M], Dtype = f32>,
+    Rhs: 'static + Tensor<Dtype = f32, Tape = NoneTape>,
+{
+    binary_map_broadcast_rhs_first(
+        "prelu_broadcast_rhs_first",
+        lhs,
+        rhs,
+        prelu::f,
+        prelu::dfdx,
+        prelu::dfdy,
+        SecondOrder::Unsupported,
+    )
+}
+
+/// [prelu()] where `rhs` has one value per channel, and is broadcasted across the batch & spatial
+/// dimensions of `lhs`.
+///
+/// E.g. If Lhs has dimension `(B, C, H, W)`, then Rhs has to be dimension `(C,)`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[-1.0, 2.0]], [[-3.0, 4.0]]]]);
+/// let b = Tensor1D::new([0.5, 0.0]);
+/// let r = prelu_broadcast_rhs_channel(a, &b);
+/// assert_eq!(r.data(), &[[[[-0.5, 2.0]], [[0.0, 4.0]]]]);
+/// ```
+pub fn prelu_broadcast_rhs_channel<
+    const B: usize,
+    const C: usize,
+    const H: usize,
+    const W: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor4D<B, C, H, W, TAPE>,
+    rhs: &Tensor1D<C, NoneTape>,
+) -> Tensor4D<B, C, H, W, TAPE> {
+    binary_map_broadcast_rhs_channel(
+        "prelu_broadcast_rhs_channel",
+        lhs,
+        rhs,
+        prelu::f,
+        prelu::dfdx,
+        prelu::dfdy,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+
+    #[test]
+    fn test_prelu_1d() {
+        let a = Tensor1D::new([-2.0, -1.0, 0.0, 1.0, 2.0]);
+        let b = Tensor1D::new([0.25, 0.5, 0.75, 1.5, -1.0]);
+        let r = prelu(a.trace(), &b);
+        assert_eq!(r.data(), &[-0.5, -0.5, 0.0, 1.0, 2.0]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[0.25, 0.5, 0.75, 1.0, 1.0]);
+        assert_eq!(gradients.ref_gradient(&b), &[-2.0, -1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_prelu_broadcast_rhs_first_sums_slope_grad() {
+        let a = Tensor2D::new([[-1.0, 2.0], [-3.0, -4.0], [5.0, 6.0]]);
+        let b = Tensor1D::new([0.1, 0.2]);
+        let r = prelu_broadcast_rhs_first(a.trace(), &b);
+        r.data()
+            .assert_close(&[[-0.1, 2.0], [-0.3, -0.8], [5.0, 6.0]], 1e-6);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[0.1, 1.0], [0.1, 0.2], [1.0, 1.0]]
+        );
+        assert_eq!(gradients.ref_gradient(&b), &[-4.0, -4.0]);
+    }
+
+    #[test]
+    fn test_prelu_broadcast_rhs_channel() {
+        let a: Tensor4D<2, 2, 1, 2> = Tensor4D::new([
+            [[[-1.0, 2.0]], [[-3.0, 4.0]]],
+            [[[-5.0, -6.0]], [[7.0, 8.0]]],
+        ]);
+        let b = Tensor1D::new([0.5, 2.0]);
+        let r = prelu_broadcast_rhs_channel(a.trace(), &b);
+        assert_eq!(
+            r.data(),
+            &[
+                [[[-0.5, 2.0]], [[-6.0, 4.0]]],
+                [[[-2.5, -3.0]], [[7.0, 8.0]]]
+            ]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&b), &[-12.0, -3.0]);
+    }
+}
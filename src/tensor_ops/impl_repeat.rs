@@ -0,0 +1,127 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+impl<const N: usize, H: Tape> Tensor1D<N, H> {
+    /// Repeats `self` `R` times along a new first axis, so that every row of the result is a copy of `self`.
+    ///
+    /// Since every copy is used independently, the gradient of `self` is the **sum** of the
+    /// gradients of all `R` rows.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 2.0, 3.0]);
+    /// let r: Tensor2D<2, 3> = t.repeat();
+    /// assert_eq!(r.data(), &[[1.0, 2.0, 3.0], [1.0, 2.0, 3.0]]);
+    /// ```
+    pub fn repeat<const R: usize>(self) -> Tensor2D<R, N, H> {
+        let mut result: Tensor2D<R, N> = Tensor2D::zeros();
+        for r in result.mut_data().iter_mut() {
+            r.copy_from_slice(self.data());
+        }
+        move_tape_and_add_backward_op("repeat", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; N]; R]) = grads.mut_and_ref(&t, &result);
+            for r in result_grad.iter() {
+                Cpu::add(t_grad, r);
+            }
+        })
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Repeats `self` `R` times along a new first axis, so that every matrix of the result is a copy of `self`.
+    ///
+    /// Since every copy is used independently, the gradient of `self` is the **sum** of the
+    /// gradients of all `R` copies.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+    /// let r: Tensor3D<3, 2, 2> = t.repeat();
+    /// assert_eq!(r.data(), &[[[1.0, 2.0], [3.0, 4.0]]; 3]);
+    /// ```
+    pub fn repeat<const R: usize>(self) -> Tensor3D<R, M, N, H> {
+        let mut result: Tensor3D<R, M, N> = Tensor3D::zeros();
+        for r in result.mut_data().iter_mut() {
+            *r = *self.data();
+        }
+        move_tape_and_add_backward_op("repeat", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[f32; N]; M]; R]) = grads.mut_and_ref(&t, &result);
+            for r in result_grad.iter() {
+                Cpu::add(t_grad, r);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_close;
+
+    #[test]
+    fn test_repeat_1d() {
+        let t = Tensor1D::new([1.0, 2.0, 3.0]);
+        let r: Tensor2D<2, 3, OwnedTape> = t.trace().repeat();
+        assert_eq!(r.data(), &[[1.0, 2.0, 3.0], [1.0, 2.0, 3.0]]);
+        let w = Tensor2D::new([[1.0, 2.0, 3.0], [-4.0, 5.0, 0.5]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[-3.0, 7.0, 3.5]);
+    }
+
+    #[test]
+    fn test_repeat_1d_sums_copies() {
+        let t = Tensor1D::new([1.0, -2.0]);
+        let r: Tensor2D<4, 2, OwnedTape> = t.trace().repeat();
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_repeat_1d_composes() {
+        // summing R copies of f(t) is the same as R * f(t)
+        let t = Tensor1D::new([0.5, -1.0, 2.0]);
+        let r: Tensor2D<3, 3, OwnedTape> = t.trace().repeat();
+        let gradients = r.square().exp().sum().backward();
+        let expected = mul_scalar(t.trace().square().exp(), 3.0).sum().backward();
+        assert_close(gradients.ref_gradient(&t), expected.ref_gradient(&t));
+    }
+
+    #[test]
+    fn test_repeat_2d() {
+        let t = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let r: Tensor3D<2, 2, 2, OwnedTape> = t.trace().repeat();
+        assert_eq!(r.data(), &[[[1.0, 2.0], [3.0, 4.0]]; 2]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_close(
+            gradients.ref_gradient(&t),
+            &[[5.4365635, 14.778112], [40.171074, 109.1963]],
+        );
+    }
+
+    #[test]
+    fn test_repeat_learned_query_across_batch() {
+        // a learned query vector broadcast across a batch still gets trained
+        let mut query: Tensor1D<3> = Tensor1D::new([1.0, 0.0, -1.0]);
+        let batch: Tensor2D<4, 3> = Tensor2D::new([
+            [1.0, 2.0, 3.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, 0.5, 2.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let scores = mul(query.trace().repeat::<4>(), &batch).sum_last_dim();
+        assert_eq!(scores.data(), &[-2.0, 0.0, -3.0, -1.0]);
+        let gradients = scores.sum().backward();
+        assert_eq!(gradients.ref_gradient(&query), &[0.0, 3.5, 6.0]);
+
+        let mut opt: Sgd<Tensor1D<3>> = Sgd::new(SgdConfig {
+            lr: 1.0,
+            momentum: None,
+            gradient_noise: None,
+        });
+        opt.update(&mut query, gradients);
+        assert_eq!(query.data(), &[1.0, -3.5, -7.0]);
+    }
+}
@@ -0,0 +1,128 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+use std::marker::PhantomData;
+
+/// Causes a compile time error if `A` and `B` have a different number of elements.
+struct AssertSameNumElements<A, B>(PhantomData<(A, B)>);
+
+impl<A: CountElements, B: CountElements> AssertSameNumElements<A, B> {
+    const OK: () = assert!(
+        A::NUM_ELEMENTS == B::NUM_ELEMENTS,
+        "reshape must preserve the number of elements"
+    );
+}
+
+/// Views all the elements of `a` as a flat slice, in row major order.
+pub(crate) fn flat<A: CountElements<Dtype = f32>>(a: &A) -> &[f32] {
+    // SAFETY: all arrays are nested `[f32; N]`s, which are contiguous.
+    unsafe { std::slice::from_raw_parts(a as *const A as *const f32, A::NUM_ELEMENTS) }
+}
+
+/// Views all the elements of `a` as a flat mutable slice, in row major order.
+pub(crate) fn flat_mut<A: CountElements<Dtype = f32>>(a: &mut A) -> &mut [f32] {
+    // SAFETY: all arrays are nested `[f32; N]`s, which are contiguous.
+    unsafe { std::slice::from_raw_parts_mut(a as *mut A as *mut f32, A::NUM_ELEMENTS) }
+}
+
+/// Reshapes `t` into a tensor of type `R`, keeping the elements in row major order. This is the same
+/// as `t.reshape(...)` in pytorch.
+///
+/// `R` must have the same number of elements as `T`, which is checked at compile time.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+/// let r: Tensor1D<6> = reshape(t.duplicate());
+/// assert_eq!(r.data(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let r: Tensor2D<3, 2> = t.reshape();
+/// assert_eq!(r.data(), &[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+/// ```
+///
+/// Changing the number of elements is a compile time error:
+/// ```compile_fail
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+/// let r: Tensor1D<5> = reshape(t);
+/// ```
+pub fn reshape<T, R>(t: T) -> R
+where
+    T: Tensor<Dtype = f32>,
+    R: Tensor<Dtype = f32, Tape = T::Tape>,
+{
+    #[allow(clippy::let_unit_value)]
+    let _ = AssertSameNumElements::<T::Array, R::Array>::OK;
+    let mut result = R::NoTape::zeros();
+    flat_mut(result.mut_data()).copy_from_slice(flat(t.data()));
+    move_tape_and_add_backward_op("reshape", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        for (t, r) in flat_mut(t_grad).iter_mut().zip(flat(result_grad).iter()) {
+            *t += r;
+        }
+    })
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [reshape()] on `self`.
+    pub fn reshape<R: Tensor<Dtype = f32, Tape = H>>(self) -> R {
+        reshape(self)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reshape_0d_to_1d() {
+        let t = Tensor0D::new(2.0);
+        let r: Tensor1D<1, OwnedTape> = t.trace().reshape();
+        assert_eq!(r.data(), &[2.0]);
+        let gradients = r.exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &7.389056);
+    }
+
+    #[test]
+    fn test_reshape_3d_to_2d() {
+        let t = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let r: Tensor2D<4, 2, OwnedTape> = t.trace().reshape();
+        assert_eq!(r.data(), &[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0], [7.0, 8.0]]);
+        let w = Tensor2D::new([[1.0, 2.0], [3.0, 4.0], [5.0, 6.0], [7.0, 8.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]
+        );
+    }
+
+    #[test]
+    fn test_reshape_4d_to_2d_and_back() {
+        let t: Tensor4D<2, 3, 1, 2> = Tensor4D::new([
+            [[[1.0, 2.0]], [[3.0, 4.0]], [[5.0, 6.0]]],
+            [[[-1.0, -2.0]], [[-3.0, -4.0]], [[-5.0, -6.0]]],
+        ]);
+        let r: Tensor2D<2, 6, OwnedTape> = t.trace().reshape();
+        assert_eq!(
+            r.data(),
+            &[
+                [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+                [-1.0, -2.0, -3.0, -4.0, -5.0, -6.0]
+            ]
+        );
+        let r: Tensor4D<2, 3, 1, 2, OwnedTape> = r.reshape();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.square().sum().backward();
+        let expected = t.trace().square().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), expected.ref_gradient(&t));
+    }
+}
@@ -0,0 +1,171 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Causes a compile time error if `START + LEN > N`.
+struct AssertInBounds<const START: usize, const LEN: usize, const N: usize>;
+
+impl<const START: usize, const LEN: usize, const N: usize> AssertInBounds<START, LEN, N> {
+    const OK: () = assert!(
+        START + LEN <= N,
+        "slice must be within the bounds of the dimension"
+    );
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Returns the `L0 x L1` sub matrix of `self` starting at row `S0` and column `S1`.
+    ///
+    /// Gradients flow back into the positions that were sliced out, and are zero everywhere else.
+    ///
+    /// The slice must be within the bounds of `self`, which is checked at compile time.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    /// let r: Tensor2D<2, 2> = t.slice::<0, 2, 1, 2>();
+    /// assert_eq!(r.data(), &[[2.0, 3.0], [5.0, 6.0]]);
+    /// ```
+    ///
+    /// Slicing outside of the tensor is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    /// let r = t.slice::<0, 2, 2, 2>();
+    /// ```
+    pub fn slice<const S0: usize, const L0: usize, const S1: usize, const L1: usize>(
+        self,
+    ) -> Tensor2D<L0, L1, H> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (
+            AssertInBounds::<S0, L0, M>::OK,
+            AssertInBounds::<S1, L1, N>::OK,
+        );
+        let mut result: Tensor2D<L0, L1> = Tensor2D::zeros();
+        for (r, t) in result.mut_data().iter_mut().zip(self.data()[S0..].iter()) {
+            r.copy_from_slice(&t[S1..S1 + L1]);
+        }
+        move_tape_and_add_backward_op("slice", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[f32; L1]; L0]) = grads.mut_and_ref(&t, &result);
+            for (t, r) in t_grad[S0..].iter_mut().zip(result_grad.iter()) {
+                for (t, r) in t[S1..].iter_mut().zip(r.iter()) {
+                    *t += r;
+                }
+            }
+        })
+    }
+}
+
+impl<const M: usize, const N: usize, const O: usize, H: Tape> Tensor3D<M, N, O, H> {
+    /// Returns the `L0 x L1 x L2` sub tensor of `self` starting at index `[S0, S1, S2]`.
+    ///
+    /// Gradients flow back into the positions that were sliced out, and are zero everywhere else.
+    ///
+    /// The slice must be within the bounds of `self`, which is checked at compile time.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+    /// let r: Tensor3D<2, 1, 1> = t.slice::<0, 2, 1, 1, 0, 1>();
+    /// assert_eq!(r.data(), &[[[3.0]], [[7.0]]]);
+    /// ```
+    pub fn slice<
+        const S0: usize,
+        const L0: usize,
+        const S1: usize,
+        const L1: usize,
+        const S2: usize,
+        const L2: usize,
+    >(
+        self,
+    ) -> Tensor3D<L0, L1, L2, H> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (
+            AssertInBounds::<S0, L0, M>::OK,
+            AssertInBounds::<S1, L1, N>::OK,
+            AssertInBounds::<S2, L2, O>::OK,
+        );
+        let mut result: Tensor3D<L0, L1, L2> = Tensor3D::zeros();
+        for (r, t) in result.mut_data().iter_mut().zip(self.data()[S0..].iter()) {
+            for (r, t) in r.iter_mut().zip(t[S1..].iter()) {
+                r.copy_from_slice(&t[S2..S2 + L2]);
+            }
+        }
+        move_tape_and_add_backward_op("slice", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[f32; L2]; L1]; L0]) = grads.mut_and_ref(&t, &result);
+            for (t, r) in t_grad[S0..].iter_mut().zip(result_grad.iter()) {
+                for (t, r) in t[S1..].iter_mut().zip(r.iter()) {
+                    for (t, r) in t[S2..].iter_mut().zip(r.iter()) {
+                        *t += r;
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_2d() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let r: Tensor2D<2, 1, OwnedTape> = t.trace().slice::<1, 2, 1, 1>();
+        assert_eq!(r.data(), &[[5.0], [8.0]]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.0, 0.0, 0.0], [0.0, 148.41316, 0.0], [0.0, 2980.958, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_slice_2d_full_is_identity() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let r: Tensor2D<2, 3, OwnedTape> = t.trace().slice::<0, 2, 0, 3>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.exp().sum().backward();
+        let expected = t.trace().exp().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), expected.ref_gradient(&t));
+    }
+
+    #[test]
+    fn test_slice_3d() {
+        let t = Tensor3D::new([
+            [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            [[7.0, 8.0, 9.0], [10.0, 11.0, 12.0]],
+        ]);
+        let r: Tensor3D<1, 2, 2, OwnedTape> = t.trace().slice::<1, 1, 0, 2, 1, 2>();
+        assert_eq!(r.data(), &[[[8.0, 9.0], [11.0, 12.0]]]);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[
+                [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+                [[0.0, 1.0, 1.0], [0.0, 1.0, 1.0]]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_3d_full_is_identity() {
+        let t = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let r: Tensor3D<2, 2, 2, OwnedTape> = t.trace().slice::<0, 2, 0, 2, 0, 2>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.square().sum().backward();
+        let expected = t.trace().square().sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), expected.ref_gradient(&t));
+    }
+
+    #[test]
+    fn test_pad_then_slice() {
+        let t: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+        let r: Tensor3D<1, 4, 4, OwnedTape> = t.trace().pad2d(1, 9.0);
+        let r: Tensor3D<1, 2, 2, OwnedTape> = r.slice::<0, 1, 1, 2, 1, 2>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[1.0; 2]; 2]]);
+    }
+}
@@ -1,4 +1,7 @@
+use super::utils::move_tape_and_add_backward_op;
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 /// `t.exp().sum(-1).log()`. Computes the [LogSumExp](https://en.wikipedia.org/wiki/LogSumExp) function.
 ///
@@ -30,21 +33,109 @@ pub fn logsumexp<T: Tensor<Dtype = f32>>(mut t: T) -> T::LastDimReduced {
 
 /// `log(softmax(t))` in numerically stable way. Does `t - logsumexp(t)` under the hood.
 ///
-/// See [logsumexp()] and [softmax()] for related functions
+/// This is a single fused operation, so it is more precise than calling `softmax(t).ln()`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([-1e4, 0.0, 1e4]);
+/// let r = a.log_softmax();
+/// assert_eq!(r.data(), &[-2e4, -1e4, 0.0]);
+/// ```
+///
+/// See [logsumexp()], [softmax()], and [log_softmax_t()] for related functions
 pub fn log_softmax<T: Tensor<Dtype = f32>>(t: T) -> T {
-    let (t, tape) = t.split_tape();
-    let (lse, tape) = logsumexp(t.duplicate().put_tape(tape)).split_tape();
-    sub_broadcast_rhs_last(t.put_tape(tape), &lse)
+    log_softmax_t(t, 1.0)
+}
+
+/// `log(softmax(t / temperature))` in numerically stable way. Does `t / temperature - logsumexp(t / temperature)`
+/// under the hood, without allocating an extra tensor for the division.
+///
+/// Higher temperatures make the distribution more uniform, and lower temperatures make it peakier.
+/// `temperature` must be positive.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([0.0, 2.0f32.ln()]);
+/// let r = a.log_softmax_t(0.5);
+/// assert_eq!(r.data(), &[-1.609438, -0.22314355]);
+/// ```
+///
+/// See [log_softmax()] and [softmax_t()] for related functions.
+pub fn log_softmax_t<T: Tensor<Dtype = f32>>(mut t: T, temperature: T::Dtype) -> T {
+    type Reduced<T> = <T as Tensor>::LastDimReduced;
+
+    // (t - max) / temperature. subtracting the max first keeps the values small, which
+    // keeps precision even when `t` has large values.
+    let mut max = T::Device::reduce_last_dim(t.data(), &mut f32::max);
+    // rows that are all `-inf` (e.g. fully masked) would otherwise compute `-inf - -inf = NaN`
+    <Reduced<T> as HasDevice>::Device::foreach_m(max.as_mut(), &mut |m| {
+        if *m == f32::NEG_INFINITY {
+            *m = 0.0;
+        }
+    });
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_mrb(
+        result.mut_data(),
+        t.data(),
+        Broadcast(max.as_ref()),
+        &mut |r, t, max| *r = (t - max) / temperature,
+    );
+
+    // subtract the log of the sum of the exps
+    T::Device::foreach_mr(t.mut_data(), result.data(), &mut |t, r| *t = r.exp());
+    let mut lse = T::Device::reduce_last_dim(t.data(), &mut |a, b| a + b);
+    <Reduced<T> as HasDevice>::Device::foreach_m(lse.as_mut(), &mut |l| {
+        // all `-inf` rows have a sum of 0, so their softmax is all 0s instead of NaN
+        *l = if *l == 0.0 { 0.0 } else { l.ln() }
+    });
+    T::Device::bsub(result.mut_data(), Broadcast(lse.as_ref()));
+
+    // store derivative (softmax) in t
+    T::Device::foreach_mr(t.mut_data(), result.data(), &mut |t, r| *t = r.exp());
+
+    move_tape_and_add_backward_op("log_softmax", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        let grad_sum = T::Device::reduce_last_dim(result_grad, &mut |a, b| a + b);
+        T::Device::foreach_mr(t_grad, result_grad, &mut |g, r| *g += r / temperature);
+        T::Device::foreach_mrb(
+            t_grad,
+            t.data(),
+            Broadcast(grad_sum.as_ref()),
+            &mut |g, s, r| {
+                *g -= s * r / temperature;
+            },
+        );
+    })
 }
 
 /// `exp(t) / sum(exp(t))`. Computes the [softmax function](https://en.wikipedia.org/wiki/Softmax_function).
 /// Equivalent to `exp(log_softmax(t))`.
 ///
-/// See [logsumexp()] and [log_softmax()] for related functions.
+/// Rows where every value is `-inf` (e.g. fully masked rows) result in all `0.0`s instead of `NaN`.
+///
+/// See [logsumexp()], [log_softmax()], and [softmax_t()] for related functions.
 pub fn softmax<T: Tensor<Dtype = f32>>(t: T) -> T {
     exp(log_softmax(t))
 }
 
+/// `exp(t / temperature) / sum(exp(t / temperature))`. Computes the softmax of `t` with a `temperature`.
+/// Equivalent to `exp(log_softmax_t(t, temperature))`.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let a = Tensor1D::new([0.0, 2.0f32.ln()]);
+/// let r = a.softmax_t(0.5);
+/// assert_eq!(r.data(), &[0.19999999, 0.8]);
+/// ```
+///
+/// See [softmax()] and [log_softmax_t()] for related functions.
+pub fn softmax_t<T: Tensor<Dtype = f32>>(t: T, temperature: T::Dtype) -> T {
+    exp(log_softmax_t(t, temperature))
+}
+
 macro_rules! tensor_impl {
     ($typename:ident, [$($Vs:tt),*]) => {
 impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
@@ -62,6 +153,16 @@ impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
     pub fn softmax(self) -> Self {
         softmax(self)
     }
+
+    /// Calls [log_softmax_t()] on `self`
+    pub fn log_softmax_t(self, temperature: f32) -> Self {
+        log_softmax_t(self, temperature)
+    }
+
+    /// Calls [softmax_t()] on `self`
+    pub fn softmax_t(self, temperature: f32) -> Self {
+        softmax_t(self, temperature)
+    }
 }
     };
 }
@@ -75,6 +176,8 @@ tensor_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::assert_close;
+    use std::f32::consts::LN_2;
 
     #[test]
     fn test_logsumexp_0d() {
@@ -121,18 +224,18 @@ mod tests {
         let r = a.trace().log_softmax();
         assert_eq!(
             r.data(),
-            &[-4.4519143, -3.4519143, -2.4519143, -1.4519143, -0.4519143]
+            &[-4.4519143, -3.4519143, -2.4519143, -1.4519144, -0.4519144]
         );
         let gradients = backward(r.mean());
-        assert_eq!(
+        assert_close(
             gradients.ref_gradient(&a),
             &[
                 0.18834378,
                 0.16831508,
                 0.11387146,
                 -0.034121647,
-                -0.43640864
-            ]
+                -0.43640864,
+            ],
         );
     }
 
@@ -140,22 +243,22 @@ mod tests {
     fn test_softmax_1d() {
         let a: Tensor1D<5> = Tensor1D::new([-2.0, -1.0, 0.0, 1.0, 2.0]);
         let r = a.trace().softmax();
-        assert_eq!(
+        assert_close(
             r.data(),
-            &[0.011656232, 0.031684924, 0.086128555, 0.23412168, 0.6364087]
+            &[0.011656232, 0.031684924, 0.086128555, 0.23412168, 0.6364087],
         );
         let l = mul(r, &Tensor1D::new([0.0, 0.0, 1.0, 0.0, 0.0]));
         assert_eq!(l.data(), &[0.0, 0.0, 0.086128555, 0.0, 0.0]);
         let gradients = backward(l.mean());
-        assert_eq!(
+        assert_close(
             gradients.ref_gradient(&a),
             &[
                 -0.00020078686,
                 -0.00054579525,
                 0.015742086,
                 -0.0040329117,
-                -0.010962591
-            ]
+                -0.010962591,
+            ],
         );
     }
 
@@ -178,20 +281,20 @@ mod tests {
     fn test_log_softmax_2d() {
         let a: Tensor2D<2, 3> = Tensor2D::new([[-2.0, -1.0, 0.0], [1.0, 4.0, 7.0]]);
         let r = a.trace().log_softmax();
-        assert_eq!(
+        assert_close(
             r.data(),
             &[
                 [-2.407606, -1.4076059, -0.40760595],
-                [-6.0509458, -3.0509458, -0.05094576]
-            ]
+                [-6.0509458, -3.0509458, -0.05094576],
+            ],
         );
         let gradients = backward(r.mean());
-        assert_eq!(
+        assert_close(
             gradients.ref_gradient(&a),
             &[
                 [0.12165138, 0.044302434, -0.1659538],
-                [0.16548885, 0.14300959, -0.30849844]
-            ]
+                [0.16548885, 0.14300959, -0.30849844],
+            ],
         );
     }
 
@@ -199,22 +302,71 @@ mod tests {
     fn test_softmax_2d() {
         let a: Tensor2D<2, 3> = Tensor2D::new([[-2.0, -1.0, 0.0], [1.0, 4.0, 7.0]]);
         let r = a.trace().softmax();
-        assert_eq!(
+        assert_close(
             r.data(),
             &[
                 [0.09003058, 0.24472849, 0.66524094],
-                [0.002355633, 0.047314156, 0.9503302]
-            ]
+                [0.002355633, 0.047314156, 0.9503302],
+            ],
         );
         let l = mul(r, &Tensor2D::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]));
         assert_eq!(l.data(), &[[0.09003058, 0.0, 0.0], [0.0, 0.047314156, 0.0]]);
         let gradients = backward(l.mean());
-        assert_eq!(
+        assert_close(
             gradients.ref_gradient(&a),
             &[
                 [0.01365418, -0.0036721744, -0.009982005],
-                [-1.85758e-5, 0.0075125876, -0.0074940124]
-            ]
+                [-1.85758e-5, 0.0075125876, -0.0074940124],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_log_softmax_large_logits() {
+        let a = Tensor2D::new([[-1e4, 0.0, 1e4], [1e4, 1e4, -1e4]]);
+        let r = a.trace().log_softmax();
+        assert_eq!(r.data(), &[[-2e4, -1e4, 0.0], [-LN_2, -LN_2, -20000.693]]);
+        let gradients = backward(r.sum());
+        assert!(gradients
+            .ref_gradient(&a)
+            .iter()
+            .flatten()
+            .all(|g| g.is_finite()));
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[[1.0, 1.0, -2.0], [-0.5, -0.5, 1.0]],
+        );
+    }
+
+    #[test]
+    fn test_softmax_large_logits() {
+        let a = Tensor1D::new([-1e4, 1e4, 1e4]);
+        let r = a.trace().softmax();
+        assert_eq!(r.data(), &[0.0, 0.5, 0.5]);
+        let gradients = backward(r.exp().sum());
+        assert!(gradients.ref_gradient(&a).iter().all(|g| g.is_finite()));
+    }
+
+    #[test]
+    fn test_log_softmax_t_1d() {
+        let a: Tensor1D<3> = Tensor1D::new([-2.0, 0.0, 2.0]);
+        let r = a.trace().log_softmax_t(2.0);
+        assert_eq!(r.data(), &[-2.407606, -1.4076059, -0.40760595]);
+        let gradients = backward(r.mean());
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[0.12165138, 0.044302426, -0.1659538],
         );
     }
+
+    #[test]
+    fn test_softmax_t_matches_scaled_softmax() {
+        let a: Tensor2D<2, 3> = Tensor2D::new([[-2.0, 0.0, 2.0], [1.0, 4.0, -3.0]]);
+        let r1 = a.trace().softmax_t(0.5);
+        let r2 = (a.trace() / 0.5).softmax();
+        assert_close(r1.data(), r2.data());
+        let g1 = backward(r1.exp().sum());
+        let g2 = backward(r2.exp().sum());
+        assert_close(g1.ref_gradient(&a), g2.ref_gradient(&a));
+    }
 }
@@ -0,0 +1,215 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Returns the permutation that sorts `row`, so that `row[perm[0]], row[perm[1]], ...` is sorted.
+/// The sort is stable, so equal values are kept in order of lowest index.
+fn sort_indices<const N: usize>(row: &[f32; N], ascending: bool) -> [usize; N] {
+    let mut perm = [0; N];
+    for (i, p) in perm.iter_mut().enumerate() {
+        *p = i;
+    }
+    if ascending {
+        perm.sort_by(|&i, &j| row[i].total_cmp(&row[j]));
+    } else {
+        perm.sort_by(|&i, &j| row[j].total_cmp(&row[i]));
+    }
+    perm
+}
+
+/// Sets `dst[j] = src[perm[j]]` for every `j`.
+fn permute<const N: usize>(dst: &mut [f32; N], src: &[f32; N], perm: &[usize; N]) {
+    for (d, &p) in dst.iter_mut().zip(perm.iter()) {
+        *d = src[p];
+    }
+}
+
+/// Sets `dst[perm[j]] += src[j]` for every `j`, which is the inverse of [permute()].
+fn add_inverse_permuted<const N: usize>(dst: &mut [f32; N], src: &[f32; N], perm: &[usize; N]) {
+    for (s, &p) in src.iter().zip(perm.iter()) {
+        dst[p] += s;
+    }
+}
+
+impl<const N: usize, H: Tape> Tensor1D<N, H> {
+    /// Sorts `self`, returning the sorted values and the permutation that was used,
+    /// so that `values[j] == self[indices[j]]`. The sort is stable.
+    ///
+    /// Gradients of the values flow back to the positions they were taken from,
+    /// which permutes the gradient by the inverse of `indices`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 3.0, 2.0]);
+    /// let (values, indices) = t.sort_last_axis(true);
+    /// assert_eq!(values.data(), &[1.0, 2.0, 3.0]);
+    /// assert_eq!(indices, [0, 2, 1]);
+    /// ```
+    pub fn sort_last_axis(self, ascending: bool) -> (Self, [usize; N]) {
+        let perm = sort_indices(self.data(), ascending);
+        let mut result: Tensor1D<N> = Tensor1D::zeros();
+        permute(result.mut_data(), self.data(), &perm);
+        let result = move_tape_and_add_backward_op(
+            "sort_last_axis",
+            self,
+            result,
+            move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[f32; N]) = grads.mut_and_ref(&t, &result);
+                add_inverse_permuted(t_grad, result_grad, &perm);
+            },
+        );
+        (result, perm)
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Sorts each row of `self`, returning the sorted values and the permutation that was used
+    /// for each row, so that `values[m][j] == self[m][indices[m][j]]`. The sort is stable.
+    ///
+    /// Gradients of the values flow back to the positions they were taken from,
+    /// which permutes the gradient by the inverse of `indices`.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 3.0, 2.0], [-1.0, -3.0, -2.0]]);
+    /// let (values, indices) = t.sort_last_axis(false);
+    /// assert_eq!(values.data(), &[[3.0, 2.0, 1.0], [-1.0, -2.0, -3.0]]);
+    /// assert_eq!(indices, [[1, 2, 0], [0, 2, 1]]);
+    /// ```
+    pub fn sort_last_axis(self, ascending: bool) -> (Self, [[usize; N]; M]) {
+        let mut perms = [[0; N]; M];
+        let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+        for ((perm, r), row) in perms
+            .iter_mut()
+            .zip(result.mut_data().iter_mut())
+            .zip(self.data().iter())
+        {
+            *perm = sort_indices(row, ascending);
+            permute(r, row, perm);
+        }
+        let result = move_tape_and_add_backward_op(
+            "sort_last_axis",
+            self,
+            result,
+            move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[[f32; N]; M]) = grads.mut_and_ref(&t, &result);
+                for m in 0..M {
+                    add_inverse_permuted(&mut t_grad[m], &result_grad[m], &perms[m]);
+                }
+            },
+        );
+        (result, perms)
+    }
+}
+
+impl<const M: usize, const N: usize, const O: usize, H: Tape> Tensor3D<M, N, O, H> {
+    /// Sorts `self` along the last axis, returning the sorted values and the permutations that were used,
+    /// so that `values[m][n][j] == self[m][n][indices[m][n][j]]`. The sort is stable.
+    ///
+    /// Gradients of the values flow back to the positions they were taken from,
+    /// which permutes the gradient by the inverse of `indices`.
+    pub fn sort_last_axis(self, ascending: bool) -> (Self, [[[usize; O]; N]; M]) {
+        let mut perms = [[[0; O]; N]; M];
+        let mut result: Tensor3D<M, N, O> = Tensor3D::zeros();
+        for ((perms_m, r_m), t_m) in perms
+            .iter_mut()
+            .zip(result.mut_data().iter_mut())
+            .zip(self.data().iter())
+        {
+            for ((perm, r), row) in perms_m.iter_mut().zip(r_m.iter_mut()).zip(t_m.iter()) {
+                *perm = sort_indices(row, ascending);
+                permute(r, row, perm);
+            }
+        }
+        let result = move_tape_and_add_backward_op(
+            "sort_last_axis",
+            self,
+            result,
+            move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[[[f32; O]; N]; M]) =
+                    grads.mut_and_ref(&t, &result);
+                for m in 0..M {
+                    for n in 0..N {
+                        add_inverse_permuted(&mut t_grad[m][n], &result_grad[m][n], &perms[m][n]);
+                    }
+                }
+            },
+        );
+        (result, perms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_sort_1d() {
+        let t = Tensor1D::new([0.5, -1.0, 2.0, 1.5, 2.0]);
+        let (r, indices) = t.trace().sort_last_axis(false);
+        assert_eq!(r.data(), &[2.0, 2.0, 1.5, 0.5, -1.0]);
+        assert_eq!(indices, [2, 4, 3, 0, 1]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[1.6487212, 0.36787945, 7.389056, 4.481689, 7.389056]
+        );
+    }
+
+    #[test]
+    fn test_sort_1d_ascending() {
+        let t = Tensor1D::new([0.5, -1.0, 2.0, 1.5, 2.0]);
+        let (r, indices) = t.trace().sort_last_axis(true);
+        assert_eq!(r.data(), &[-1.0, 0.5, 1.5, 2.0, 2.0]);
+        assert_eq!(indices, [1, 0, 3, 2, 4]);
+        let w = Tensor1D::new([1.0, 2.0, 3.0, 4.0, 5.0]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[2.0, 1.0, 4.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn test_sort_2d() {
+        let t = Tensor2D::new([[1.0, 3.0, 2.0], [-1.0, -3.0, -2.0]]);
+        let (r, indices) = t.trace().sort_last_axis(true);
+        assert_eq!(r.data(), &[[1.0, 2.0, 3.0], [-3.0, -2.0, -1.0]]);
+        assert_eq!(indices, [[0, 2, 1], [1, 2, 0]]);
+        let w = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[1.0, 3.0, 2.0], [6.0, 4.0, 5.0]]
+        );
+    }
+
+    #[test]
+    fn test_sort_3d() {
+        let t = Tensor3D::new([[[2.0, 1.0], [3.0, 4.0]], [[0.0, -1.0], [-2.0, 5.0]]]);
+        let (r, indices) = t.trace().sort_last_axis(true);
+        assert_eq!(
+            r.data(),
+            &[[[1.0, 2.0], [3.0, 4.0]], [[-1.0, 0.0], [-2.0, 5.0]]]
+        );
+        assert_eq!(indices, [[[1, 0], [0, 1]], [[1, 0], [0, 1]]]);
+        let w = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[2.0, 1.0], [3.0, 4.0]], [[6.0, 5.0], [7.0, 8.0]]]
+        );
+    }
+
+    #[test]
+    fn test_sort_then_sum_matches_sum() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for ascending in [true, false] {
+            let t: Tensor2D<4, 7> = Tensor2D::randn(&mut rng);
+            let (r, _) = t.trace().sort_last_axis(ascending);
+            let sorted_grads = r.square().sum().backward();
+            let grads = t.trace().square().sum().backward();
+            assert_eq!(sorted_grads.ref_gradient(&t), grads.ref_gradient(&t));
+        }
+    }
+}
@@ -3,9 +3,12 @@ use crate::prelude::*;
 /// `t.std(-1)`.Reduces the last dimension of the tensor by computing std deviation of all values in the last dimension.
 /// Result [Tensor] has smaller number of dimensions.
 ///
-/// Computes: `(t + epsilon).var_last_dim().sqrt()`
+/// Computes: `(t.var_last_dim() + epsilon).sqrt()`
 ///
-/// See [var_last_dim()] and [sqrt()].
+/// `epsilon` keeps the gradient finite when the variance is 0 (e.g. all the values are the same),
+/// since the gradient of [sqrt()] at 0 is infinite.
+///
+/// See [var_last_dim()], [std_last_dim_unbiased()] and [sqrt()].
 ///
 /// Examples:
 /// ```rust
@@ -33,15 +36,52 @@ pub fn std_last_dim<T: Tensor<Dtype = f32>>(t: T, epsilon: T::Dtype) -> T::LastD
 /// assert_eq!(r.data(), &[0.6666667, 6.0]);
 /// ```
 ///
-/// Note: equivalent to pytorch: `t.var(-1, unbiased=False)`.
+/// Note: equivalent to pytorch: `t.var(-1, unbiased=False)`. See [var_last_dim_unbiased()]
+/// for the unbiased estimate.
 pub fn var_last_dim<T: Tensor<Dtype = f32>>(t: T) -> T::LastDimReduced {
     let num_elements: f32 = <T::Device as ReduceLastDim<T::Array>>::LAST_DIM as f32;
+    div_scalar(sum_squared_deviations(t), num_elements)
+}
+
+/// `t.std(-1, unbiased=True)`. The same as [std_last_dim()], but using [var_last_dim_unbiased()].
+///
+/// Computes: `(t.var_last_dim_unbiased() + epsilon).sqrt()`
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[2.0, 3.0, 4.0], [3.0, 6.0, 9.0]]);
+/// let r: Tensor1D<2> = std_last_dim_unbiased(t, 0.0);
+/// assert_eq!(r.data(), &[1.0, 3.0]);
+/// ```
+pub fn std_last_dim_unbiased<T: Tensor<Dtype = f32>>(t: T, epsilon: T::Dtype) -> T::LastDimReduced {
+    sqrt(add_scalar(var_last_dim_unbiased(t), epsilon))
+}
+
+/// `t.var(-1, unbiased=True)`. The unbiased estimate of the variance of the values in the
+/// last dimension, which divides by `NUM_ELEMENTS - 1` instead of `NUM_ELEMENTS` like [var_last_dim()].
+///
+/// Computes: `(t - t.mean_last_dim()).square().sum_last_dim() / (NUM_ELEMENTS - 1)`
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[2.0, 3.0, 4.0], [3.0, 6.0, 9.0]]);
+/// let r: Tensor1D<2> = var_last_dim_unbiased(t);
+/// assert_eq!(r.data(), &[1.0, 9.0]);
+/// ```
+///
+/// Note: the result is `NaN` if the last dimension has a single element, the same as pytorch.
+pub fn var_last_dim_unbiased<T: Tensor<Dtype = f32>>(t: T) -> T::LastDimReduced {
+    let num_elements: f32 = <T::Device as ReduceLastDim<T::Array>>::LAST_DIM as f32;
+    div_scalar(sum_squared_deviations(t), num_elements - 1.0)
+}
+
+/// `(t - t.mean_last_dim()).square().sum_last_dim()`
+fn sum_squared_deviations<T: Tensor<Dtype = f32>>(t: T) -> T::LastDimReduced {
     let (t, tape) = t.split_tape();
     let (mean, tape) = mean_last_dim(t.duplicate().put_tape(tape)).split_tape();
-    div_scalar(
-        sum_last_dim(square(sub_broadcast_rhs_last(t.put_tape(tape), &mean))),
-        num_elements,
-    )
+    sum_last_dim(square(sub_broadcast_rhs_last(t.put_tape(tape), &mean)))
 }
 
 macro_rules! std_last_impl {
@@ -56,6 +96,16 @@ impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
     pub fn var_last_dim(self) -> <Self as Tensor>::LastDimReduced {
         var_last_dim(self)
     }
+
+    /// Calls [std_last_dim_unbiased()] on `self`.
+    pub fn std_last_dim_unbiased(self, epsilon: f32) -> <Self as Tensor>::LastDimReduced {
+        std_last_dim_unbiased(self, epsilon)
+    }
+
+    /// Calls [var_last_dim_unbiased()] on `self`.
+    pub fn var_last_dim_unbiased(self) -> <Self as Tensor>::LastDimReduced {
+        var_last_dim_unbiased(self)
+    }
 }
     };
 }
@@ -69,6 +119,7 @@ std_last_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::{assert_close, AssertClose};
 
     #[test]
     fn test_var_last_0d() {
@@ -128,4 +179,47 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_var_last_2d_unbiased() {
+        let t: Tensor2D<2, 4> = Tensor2D::new([[1.0, 2.0, 3.0, 4.0], [0.0, 2.0, 5.0, 10.0]]);
+        let r = t.trace().var_last_dim_unbiased();
+        assert_close(r.data(), &[5.0 / 3.0, 56.75 / 3.0]);
+        // d/dx_i = 2 * (x_i - mean) / (N - 1)
+        let gradients = r.sum().backward();
+        gradients.ref_gradient(&t).assert_close(
+            &[
+                [-1.0, -1.0 / 3.0, 1.0 / 3.0, 1.0],
+                [-8.5 / 3.0, -4.5 / 3.0, 1.5 / 3.0, 11.5 / 3.0],
+            ],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_std_last_1d_unbiased() {
+        let t: Tensor1D<3> = Tensor1D::new([1.0, 4.0, 8.0]);
+        let biased = t.duplicate().std_last_dim(0.0);
+        let r = t.trace().std_last_dim_unbiased(0.0);
+        let std = *r.data();
+        assert!((std - biased.data() * 1.5f32.sqrt()).abs() < 1e-6);
+        let gradients = r.backward();
+        // d/dx_i = (x_i - mean) / ((N - 1) * std)
+        let mean = 13.0 / 3.0;
+        let expected = [1.0, 4.0, 8.0].map(|x| (x - mean) / (2.0 * std));
+        assert_close(gradients.ref_gradient(&t), &expected);
+    }
+
+    #[test]
+    fn test_std_last_constant_with_epsilon() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[2.0; 3], [-1.0; 3]]);
+        let r = t.trace().std_last_dim_unbiased(1e-5);
+        assert_close(r.data(), &[1e-5f32.sqrt(); 2]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[0.0; 3]; 2]);
+
+        let r = t.trace().std_last_dim(1e-5);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[0.0; 3]; 2]);
+    }
 }
@@ -1,13 +1,48 @@
-use super::utils::move_tape_and_add_backward_op;
+use super::utils::move_tape_and_add_backward_op_taped;
 use crate::prelude::*;
 
 /// `sum(t)`. Sums all the values in `self`. Returns a [Tensor0D] (i.e. one number).
+///
+/// The gradient of every element of `t` is `1.0` times the gradient of the result.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t = Tensor2D::new([[1.0, 2.0, 3.0], [-1.0, -2.0, -3.0]]);
+/// let r: Tensor0D = t.sum();
+/// assert_eq!(r.data(), &0.0);
+/// ```
 pub fn sum<T: Tensor<Dtype = f32>>(t: T) -> Tensor0D<T::Tape> {
     let result = Tensor0D::<NoneTape>::new(T::Device::reduce(t.data(), &mut |a, b| a + b));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
-        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
-        T::Device::foreach_m(t_grad, &mut |v| *v += result_grad);
-    })
+    move_tape_and_add_backward_op_taped(
+        "sum",
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            T::Device::foreach_m(t_grad, &mut |v| *v += result_grad);
+        },
+        move |t, result, grads| {
+            if let Some(result_grad) = grads.traced::<Tensor0D<OwnedTape>>(&result) {
+                grads.accumulate(&t, broadcast_sum::<T::OwnedTape>(result_grad));
+            }
+        },
+    )
+}
+
+/// A tensor where every element is `t`. This is the taped version of the backward operation
+/// of [sum()]. See [GradientTape::execute_with_tape()].
+fn broadcast_sum<T: Tensor<Dtype = f32, Tape = OwnedTape>>(t: Tensor0D<OwnedTape>) -> T {
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_m(result.mut_data(), &mut |v| *v = *t.data());
+
+    let (t, mut tape) = t.split_tape();
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("broadcast_sum", move |grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &phantom_result);
+        *t_grad += T::Device::reduce(result_grad, &mut |a, b| a + b);
+    });
+    result.put_tape(tape)
 }
 
 macro_rules! tensor_impl {
@@ -67,4 +102,13 @@ mod tests {
         let gradients = r.backward();
         assert_eq!(gradients.ref_gradient(&t), &[[[1.0; 3]; 2]; 4]);
     }
+
+    #[test]
+    fn test_sum_4d() {
+        let t: Tensor4D<2, 3, 4, 5> = Tensor4D::ones();
+        let r: Tensor0D<OwnedTape> = t.trace().sum();
+        assert_eq!(r.data(), &(2.0 * 3.0 * 4.0 * 5.0));
+        let gradients = r.backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[[1.0; 5]; 4]; 3]; 2]);
+    }
 }
@@ -16,7 +16,7 @@ pub fn sum_last_dim<T: Tensor<Dtype = f32>>(t: T) -> T::LastDimReduced {
         t.data(),
         &mut |a, b| a + b,
     ));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+    move_tape_and_add_backward_op("sum_last_dim", t, result, move |t, result, grads| {
         let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
         T::Device::badd(t_grad, Broadcast(result_grad));
     })
@@ -90,4 +90,16 @@ mod tests {
         let gradients = r.mean().backward();
         assert_eq!(gradients.ref_gradient(&t), &[[[1.0 / 8.0; 3]; 2]; 4]);
     }
+
+    #[test]
+    fn test_sum_last_4d() {
+        let t: Tensor4D<2, 1, 2, 3> = Tensor4D::new([
+            [[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]],
+            [[[-1.0, -2.0, -3.0], [-4.0, -5.0, -6.0]]],
+        ]);
+        let r: Tensor3D<2, 1, 2, OwnedTape> = t.trace().sum_last_dim();
+        assert_eq!(r.data(), &[[[6.0, 15.0]], [[-6.0, -15.0]]]);
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[[0.25; 3]; 2]]; 2]);
+    }
 }
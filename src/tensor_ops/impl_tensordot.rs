@@ -0,0 +1,178 @@
+use super::matmul::{mm, mm_at, mm_bt};
+use crate::prelude::*;
+
+/// Causes a compile time error if the contracted axes are not a supported pairing.
+/// Currently only the last axis of `lhs` with the first axis of `rhs` is supported.
+struct AssertSupportedAxes<const LHS_AXIS: usize, const RHS_AXIS: usize>;
+
+impl<const LHS_AXIS: usize, const RHS_AXIS: usize> AssertSupportedAxes<LHS_AXIS, RHS_AXIS> {
+    const OK: () = assert!(
+        LHS_AXIS == 2 && RHS_AXIS == 0,
+        "only contracting the last axis of lhs with the first axis of rhs is supported"
+    );
+}
+
+/// Contracts axis `LHS_AXIS` of `lhs` with axis `RHS_AXIS` of `rhs`, by summing the product over
+/// the contracted axis. For a `Tensor3D<B, S, K>` and `Tensor2D<K, N>` this multiplies every
+/// `S x K` matrix in the batch by `rhs`, which is useful for applying a weight matrix to every
+/// timestep of a sequence without reshaping.
+///
+/// Currently the only supported pairing is the last axis of `lhs` with the first axis of `rhs`
+/// (`LHS_AXIS = 2`, `RHS_AXIS = 0`), which is checked at compile time.
+///
+/// Both `lhs` and `rhs` may own a tape, in which case the tapes are merged together into the result.
+///
+/// # Generics
+/// - `LHS_AXIS`: the axis of `lhs` to contract.
+/// - `RHS_AXIS`: the axis of `rhs` to contract.
+///
+/// # Examples
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x: Tensor3D<2, 1, 2> = Tensor3D::new([[[1.0, 2.0]], [[-1.0, 0.5]]]);
+/// let w: Tensor2D<2, 3> = Tensor2D::new([[1.0, 0.0, 1.0], [0.0, 1.0, 1.0]]);
+/// let r: Tensor3D<2, 1, 3> = tensordot::<2, 0, _, _, _, _, _, _>(x, w);
+/// assert_eq!(r.data(), &[[[1.0, 2.0, 3.0]], [[-1.0, 0.5, -0.5]]]);
+/// ```
+///
+/// Contracting any other axes is a compile time error:
+/// ```compile_fail
+/// # use dfdx::prelude::*;
+/// let x: Tensor3D<2, 2, 2> = Tensor3D::zeros();
+/// let w: Tensor2D<2, 2> = Tensor2D::zeros();
+/// let r: Tensor3D<2, 2, 2> = x.tensordot::<1, 0, _, _>(w);
+/// ```
+pub fn tensordot<
+    const LHS_AXIS: usize,
+    const RHS_AXIS: usize,
+    const B: usize,
+    const S: usize,
+    const K: usize,
+    const N: usize,
+    HA,
+    HB,
+>(
+    lhs: Tensor3D<B, S, K, HA>,
+    rhs: Tensor2D<K, N, HB>,
+) -> Tensor3D<B, S, N, HA::Output>
+where
+    HA: MergeTape<HB>,
+    HB: Tape,
+{
+    #[allow(clippy::let_unit_value)]
+    let _ = AssertSupportedAxes::<LHS_AXIS, RHS_AXIS>::OK;
+
+    let (lhs, lhs_tape) = lhs.split_tape();
+    let (rhs, rhs_tape) = rhs.split_tape();
+
+    let mut result: Tensor3D<B, S, N> = Tensor3D::zeros();
+    for (r, l) in result.mut_data().iter_mut().zip(lhs.data().iter()) {
+        mm(l, rhs.data(), r);
+    }
+
+    let mut tape = lhs_tape.merge_tape(rhs_tape);
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("tensordot", move |grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+        for (l, r) in lhs_grad.iter_mut().zip(result_grad.iter()) {
+            mm_bt(r, rhs.data(), l);
+        }
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+        for (l, r) in lhs.data().iter().zip(result_grad.iter()) {
+            mm_at(l, r, rhs_grad);
+        }
+    });
+    result.put_tape(tape)
+}
+
+impl<const B: usize, const S: usize, const K: usize, HA: Tape> Tensor3D<B, S, K, HA> {
+    /// Calls [tensordot()] on `self`.
+    pub fn tensordot<const LHS_AXIS: usize, const RHS_AXIS: usize, const N: usize, HB>(
+        self,
+        rhs: Tensor2D<K, N, HB>,
+    ) -> Tensor3D<B, S, N, HA::Output>
+    where
+        HA: MergeTape<HB>,
+        HB: Tape,
+    {
+        tensordot::<LHS_AXIS, RHS_AXIS, B, S, K, N, HA, HB>(self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::AssertClose;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_tensordot_matches_matmul() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x: Tensor3D<2, 3, 4> = Tensor3D::randn(&mut rng);
+        let w: Tensor2D<4, 5> = Tensor2D::randn(&mut rng);
+
+        let r = x.trace().tensordot::<2, 0, _, _>(w.trace());
+        let r_data = *r.data();
+        let weights: Tensor3D<2, 3, 5> = Tensor3D::randn(&mut rng);
+        let gradients = mul(r, &weights).sum().backward();
+
+        // the rhs gradient is the sum of x[b]^T * weights[b] over the batch
+        let mut expected_w_grad = [[0.0; 5]; 4];
+        let x_grad = gradients.ref_gradient(&x);
+        for (b, (r_b_data, x_b_grad)) in r_data.iter().zip(x_grad.iter()).enumerate() {
+            let x_b = Tensor2D::new(x.data()[b]);
+            let r_b = matmul(x_b.trace(), &w);
+            r_b.data().assert_close(r_b_data, 1e-6);
+
+            let w_b = Tensor2D::new(weights.data()[b]);
+            let expected = mul(r_b, &w_b).sum().backward();
+            expected.ref_gradient(&x_b).assert_close(x_b_grad, 1e-6);
+
+            for (e_k, x_k) in expected_w_grad.iter_mut().zip(transpose(x_b.data()).iter()) {
+                for (e, w_n) in e_k.iter_mut().zip(transpose(w_b.data()).iter()) {
+                    *e += x_k.iter().zip(w_n.iter()).map(|(a, b)| a * b).sum::<f32>();
+                }
+            }
+        }
+        gradients
+            .ref_gradient(&w)
+            .assert_close(&expected_w_grad, 1e-5);
+    }
+
+    fn transpose<const M: usize, const N: usize>(a: &[[f32; N]; M]) -> [[f32; M]; N] {
+        let mut t = [[0.0; M]; N];
+        for (i, a_i) in a.iter().enumerate() {
+            for (j, a_ij) in a_i.iter().enumerate() {
+                t[j][i] = *a_ij;
+            }
+        }
+        t
+    }
+
+    #[test]
+    fn test_tensordot_rhs_tape() {
+        let x: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+        let w: Tensor2D<2, 1> = Tensor2D::new([[0.5], [-1.0]]);
+        let r = tensordot::<2, 0, _, _, _, _, _, _>(x.duplicate(), w.trace());
+        assert_eq!(r.data(), &[[[-1.5], [-2.5]]]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&w), &[[4.0], [6.0]]);
+    }
+
+    #[test]
+    fn test_tensordot_both_tapes() {
+        let x: Tensor3D<1, 1, 2> = Tensor3D::new([[[1.0, 2.0]]]);
+        let w: Tensor2D<2, 2> = Tensor2D::new([[1.0, 2.0], [3.0, 4.0]]);
+        let r = x.trace().tensordot::<2, 0, _, _>(w.trace());
+        assert_eq!(r.data(), &[[[7.0, 10.0]]]);
+        let gradients = r.exp().sum().backward();
+        let (e7, e10) = (7.0f32.exp(), 10.0f32.exp());
+        gradients
+            .ref_gradient(&x)
+            .assert_close(&[[[e7 + 2.0 * e10, 3.0 * e7 + 4.0 * e10]]], 1.0);
+        gradients
+            .ref_gradient(&w)
+            .assert_close(&[[e7, e10], [2.0 * e7, 2.0 * e10]], 1.0);
+    }
+}
@@ -0,0 +1,146 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+use std::vec::Vec;
+
+/// Causes a compile time error if `K > N`.
+struct AssertKLessEqN<const K: usize, const N: usize>;
+
+impl<const K: usize, const N: usize> AssertKLessEqN<K, N> {
+    const OK: () = assert!(
+        K <= N,
+        "K must be less than or equal to the size of the last dimension"
+    );
+}
+
+/// Returns the indices of the `K` largest values in `row`, in descending order of value.
+/// Ties are broken by the lower index.
+fn topk_indices<const K: usize, const N: usize>(row: &[f32; N]) -> [usize; K] {
+    #[allow(clippy::let_unit_value)]
+    let _ = AssertKLessEqN::<K, N>::OK;
+    let mut order: Vec<usize> = (0..N).collect();
+    // NOTE: sort_by is stable, so ties are kept in order of lowest index
+    order.sort_by(|&i, &j| row[j].total_cmp(&row[i]));
+    let mut indices = [0; K];
+    indices.copy_from_slice(&order[..K]);
+    indices
+}
+
+impl<const N: usize, H: Tape> Tensor1D<N, H> {
+    /// Returns the `K` largest values of `self` in descending order, along with their indices.
+    /// Ties are broken by the lower index.
+    ///
+    /// Gradients of the values flow back to the positions they were selected from.
+    ///
+    /// `K` must be less than or equal to `N`, which is checked at compile time.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 3.0, 2.0, 3.0]);
+    /// let (values, indices): (Tensor1D<2>, _) = t.topk::<2>();
+    /// assert_eq!(values.data(), &[3.0, 3.0]);
+    /// assert_eq!(indices, [1, 3]);
+    /// ```
+    ///
+    /// Asking for more values than there are is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor1D::new([1.0, 3.0]);
+    /// let (values, indices) = t.topk::<3>();
+    /// ```
+    pub fn topk<const K: usize>(self) -> (Tensor1D<K, H>, [usize; K]) {
+        let indices = topk_indices::<K, N>(self.data());
+        let mut result: Tensor1D<K> = Tensor1D::zeros();
+        for (r, &i) in result.mut_data().iter_mut().zip(indices.iter()) {
+            *r = self.data()[i];
+        }
+        let result =
+            move_tape_and_add_backward_op("topk", self, result, move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[f32; K]) = grads.mut_and_ref(&t, &result);
+                for (&i, r) in indices.iter().zip(result_grad.iter()) {
+                    t_grad[i] += r;
+                }
+            });
+        (result, indices)
+    }
+}
+
+impl<const M: usize, const N: usize, H: Tape> Tensor2D<M, N, H> {
+    /// Returns the `K` largest values of each row of `self` in descending order, along with their indices.
+    /// Ties are broken by the lower index.
+    ///
+    /// Gradients of the values flow back to the positions they were selected from.
+    ///
+    /// `K` must be less than or equal to `N`, which is checked at compile time.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t = Tensor2D::new([[1.0, 3.0, 2.0], [-1.0, -3.0, -2.0]]);
+    /// let (values, indices): (Tensor2D<2, 2>, _) = t.topk::<2>();
+    /// assert_eq!(values.data(), &[[3.0, 2.0], [-1.0, -2.0]]);
+    /// assert_eq!(indices, [[1, 2], [0, 2]]);
+    /// ```
+    pub fn topk<const K: usize>(self) -> (Tensor2D<M, K, H>, [[usize; K]; M]) {
+        let mut indices = [[0; K]; M];
+        let mut result: Tensor2D<M, K> = Tensor2D::zeros();
+        for (m, row) in self.data().iter().enumerate() {
+            indices[m] = topk_indices::<K, N>(row);
+            for (r, &i) in result.mut_data()[m].iter_mut().zip(indices[m].iter()) {
+                *r = row[i];
+            }
+        }
+        let result =
+            move_tape_and_add_backward_op("topk", self, result, move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[[f32; K]; M]) = grads.mut_and_ref(&t, &result);
+                for m in 0..M {
+                    for (&i, r) in indices[m].iter().zip(result_grad[m].iter()) {
+                        t_grad[m][i] += r;
+                    }
+                }
+            });
+        (result, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topk_1d() {
+        let t = Tensor1D::new([0.5, -1.0, 2.0, 1.5, 2.0]);
+        let (r, indices) = t.trace().topk::<3>();
+        assert_eq!(r.data(), &[2.0, 2.0, 1.5]);
+        assert_eq!(indices, [2, 4, 3]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[0.0, 0.0, 7.389056, 4.481689, 7.389056]
+        );
+    }
+
+    #[test]
+    fn test_topk_all_1d() {
+        let t = Tensor1D::new([1.0, 3.0, 2.0]);
+        let (r, indices) = t.trace().topk::<3>();
+        assert_eq!(r.data(), &[3.0, 2.0, 1.0]);
+        assert_eq!(indices, [1, 2, 0]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[1.0; 3]);
+    }
+
+    #[test]
+    fn test_topk_2d() {
+        let t = Tensor2D::new([[1.0, 2.0, 3.0, 4.0], [4.0, 3.0, 3.0, -1.0]]);
+        let (r, indices) = t.trace().topk::<2>();
+        assert_eq!(r.data(), &[[4.0, 3.0], [4.0, 3.0]]);
+        assert_eq!(indices, [[3, 2], [0, 1]]);
+        let gradients = r.mean().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[0.0, 0.0, 0.25, 0.25], [0.25, 0.25, 0.0, 0.0]]
+        );
+    }
+}
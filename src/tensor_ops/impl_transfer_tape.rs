@@ -0,0 +1,90 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Moves the tape of `from` onto `to`, so operations on `to` are recorded onto the same tape as `from`.
+/// This is useful for bridging a non-differentiable transform of `from`, like rounding or sampling.
+///
+/// The gradient of `to` is passed through to `from` unchanged (i.e. a straight-through estimator),
+/// so the operations recorded before the transfer still receive their gradients. If `to` has the
+/// same [UniqueId] as `from`, this is the same as [PutTape::put_tape()].
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([0.4, 1.6, 2.2]);
+/// let y = x.trace() * 2.0;
+/// let rounded = Tensor1D::new(y.data().map(f32::round));
+/// let z = transfer_tape(y, rounded); // or y.transfer_tape(rounded)
+/// assert_eq!(z.data(), &[1.0, 3.0, 4.0]);
+/// let gradients = z.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&x), &[2.0; 3]);
+/// ```
+pub fn transfer_tape<T: Tensor<Dtype = f32>>(from: T, to: T::NoTape) -> T {
+    if from.id() == to.id() {
+        let (_, tape) = from.split_tape();
+        return to.put_tape(tape);
+    }
+    move_tape_and_add_backward_op("transfer_tape", from, to, move |from, to, grads| {
+        let (from_grad, to_grad) = grads.mut_and_ref(&from, &to);
+        T::Device::add(from_grad, to_grad);
+    })
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [transfer_tape()] on `self`.
+    pub fn transfer_tape(self, to: $typename<$($Vs, )* NoneTape>) -> Self {
+        transfer_tape(self, to)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor0D, []);
+tensor_impl!(Tensor1D, [M]);
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_put_tape_keeps_gradients() {
+        let x = Tensor2D::new([[1.0, -2.0], [3.0, 0.5]]);
+        let expected = (x.trace() * 2.0).square().mean().backward();
+
+        let y = x.trace() * 2.0;
+        let (y, tape) = y.split_tape();
+        let y: Tensor2D<2, 2, OwnedTape> = y.put_tape(tape);
+        let gradients = y.square().mean().backward();
+        assert_eq!(gradients.ref_gradient(&x), expected.ref_gradient(&x));
+    }
+
+    #[test]
+    fn test_transfer_tape() {
+        let x = Tensor1D::new([0.4, 1.6, 2.2]);
+        let y = x.trace() * 2.0;
+        let rounded = Tensor1D::new(y.data().map(f32::round));
+        let r_id = *rounded.id();
+        let z = y.transfer_tape(rounded);
+        assert_eq!(z.id(), &r_id);
+        assert_eq!(z.tape().op_names(), ["mul_scalar", "transfer_tape"]);
+
+        let gradients = z.square().sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[4.0, 12.0, 16.0]);
+    }
+
+    #[test]
+    fn test_transfer_tape_same_id() {
+        let x = Tensor1D::new([1.0, 2.0]);
+        let y = x.trace() * 3.0;
+        let to = y.duplicate();
+        let z = transfer_tape(y, to);
+        assert_eq!(z.tape().op_names(), ["mul_scalar"]);
+        let gradients = z.sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[3.0; 2]);
+    }
+}
@@ -0,0 +1,188 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Zeros out the elements of the last two dimensions of `t` where `keep(row, col)` is `false`.
+fn tri_mask<T: Tensor<Dtype = f32>, F: Fn(usize, usize) -> bool>(mut t: T, keep: F) -> T {
+    type Reduced<T> = <T as Tensor>::LastDimReduced;
+    let num_cols = <T::Device as ReduceLastDim<T::Array>>::LAST_DIM;
+    let num_rows = <<Reduced<T> as HasDevice>::Device as ReduceLastDim<
+        <Reduced<T> as HasArrayType>::Array,
+    >>::LAST_DIM;
+
+    let mut mask = T::NoTape::zeros();
+    let mut k = 0;
+    T::Device::foreach_m(mask.mut_data(), &mut |m| {
+        let (row, col) = ((k / num_cols) % num_rows, k % num_cols);
+        *m = if keep(row, col) { 1.0 } else { 0.0 };
+        k += 1;
+    });
+
+    let mut result = T::NoTape::zeros();
+    T::Device::foreach_mrr(result.mut_data(), t.data(), mask.data(), &mut |r, t, m| {
+        *r = t * m;
+    });
+
+    // store derivative in t
+    T::Device::foreach_mr(t.mut_data(), mask.data(), &mut |t, m| *t = *m);
+
+    move_tape_and_add_backward_op("tri_mask", t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        T::Device::addmul(t_grad, t.data(), result_grad);
+    })
+}
+
+/// Keeps the lower triangle of the last two dimensions of `t`, and sets everything else to `0.0`.
+///
+/// `offset` is the diagonal above which elements are zeroed out. `0` is the main diagonal,
+/// a positive `offset` is above the main diagonal, and a negative `offset` is below it.
+/// For 3d & 4d tensors, this is applied to each matrix in the last two dimensions.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t: Tensor2D<3, 3> = Tensor2D::ones();
+/// assert_eq!(tril(t.duplicate(), 0).data(), &[[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]]);
+/// assert_eq!(tril(t.duplicate(), 1).data(), &[[1.0, 1.0, 0.0], [1.0, 1.0, 1.0], [1.0, 1.0, 1.0]]);
+/// assert_eq!(tril(t, -1).data(), &[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+/// ```
+///
+/// This is equivalent to `torch.tril(t, offset)` in pytorch.
+pub fn tril<T: Tensor<Dtype = f32>>(t: T, offset: isize) -> T {
+    tri_mask(t, move |row, col| col as isize - row as isize <= offset)
+}
+
+/// Keeps the upper triangle of the last two dimensions of `t`, and sets everything else to `0.0`.
+///
+/// `offset` is the diagonal below which elements are zeroed out. `0` is the main diagonal,
+/// a positive `offset` is above the main diagonal, and a negative `offset` is below it.
+/// For 3d & 4d tensors, this is applied to each matrix in the last two dimensions.
+///
+/// Examples:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let t: Tensor2D<3, 3> = Tensor2D::ones();
+/// assert_eq!(triu(t.duplicate(), 0).data(), &[[1.0, 1.0, 1.0], [0.0, 1.0, 1.0], [0.0, 0.0, 1.0]]);
+/// assert_eq!(triu(t.duplicate(), 1).data(), &[[0.0, 1.0, 1.0], [0.0, 0.0, 1.0], [0.0, 0.0, 0.0]]);
+/// assert_eq!(triu(t, -1).data(), &[[1.0, 1.0, 1.0], [1.0, 1.0, 1.0], [0.0, 1.0, 1.0]]);
+/// ```
+///
+/// This is equivalent to `torch.triu(t, offset)` in pytorch.
+pub fn triu<T: Tensor<Dtype = f32>>(t: T, offset: isize) -> T {
+    tri_mask(t, move |row, col| col as isize - row as isize >= offset)
+}
+
+impl<const M: usize, const N: usize> Tensor2D<M, N, NoneTape> {
+    /// Creates a lower triangular matrix of `1.0`s, which is useful as a causal attention mask.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let mask: Tensor2D<2, 3> = Tensor2D::tril_ones();
+    /// assert_eq!(mask.data(), &[[1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+    /// ```
+    pub fn tril_ones() -> Self {
+        tril(Self::ones(), 0)
+    }
+}
+
+macro_rules! tensor_impl {
+    ($typename:ident, [$($Vs:tt),*]) => {
+impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
+    /// Calls [tril()] on `self`.
+    pub fn tril(self, offset: isize) -> Self {
+        tril(self, offset)
+    }
+
+    /// Calls [triu()] on `self`.
+    pub fn triu(self, offset: isize) -> Self {
+        triu(self, offset)
+    }
+}
+    };
+}
+
+tensor_impl!(Tensor2D, [M, N]);
+tensor_impl!(Tensor3D, [M, N, O]);
+tensor_impl!(Tensor4D, [M, N, O, P]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tril_2d() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let r = t.trace().tril(0);
+        assert_eq!(r.data(), &[[1.0, 0.0, 0.0], [4.0, 5.0, 0.0]]);
+        // NOTE: .exp() so we make sure its using result grad properly
+        let gradients = r.exp().sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[2.7182817, 0.0, 0.0], [54.59815, 148.41316, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_triu_2d() {
+        let t: Tensor2D<2, 3> = Tensor2D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let r = t.trace().triu(0);
+        assert_eq!(r.data(), &[[1.0, 2.0, 3.0], [0.0, 5.0, 6.0]]);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[1.0, 1.0, 1.0], [0.0, 1.0, 1.0]]
+        );
+    }
+
+    #[test]
+    fn test_tri_offsets() {
+        let t: Tensor2D<3, 2> = Tensor2D::ones();
+        assert_eq!(
+            t.duplicate().tril(-1).data(),
+            &[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]
+        );
+        assert_eq!(t.duplicate().tril(-3).data(), &[[0.0; 2]; 3]);
+        assert_eq!(t.duplicate().tril(1).data(), &[[1.0; 2]; 3]);
+        assert_eq!(
+            t.duplicate().triu(1).data(),
+            &[[0.0, 1.0], [0.0, 0.0], [0.0, 0.0]]
+        );
+        assert_eq!(
+            t.duplicate().triu(-1).data(),
+            &[[1.0, 1.0], [1.0, 1.0], [0.0, 1.0]]
+        );
+        assert_eq!(t.triu(2).data(), &[[0.0; 2]; 3]);
+    }
+
+    #[test]
+    fn test_tril_3d() {
+        let t: Tensor3D<2, 2, 2> =
+            Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]);
+        let r = t.trace().tril(0);
+        assert_eq!(
+            r.data(),
+            &[[[1.0, 0.0], [3.0, 4.0]], [[5.0, 0.0], [7.0, 8.0]]]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[1.0, 0.0], [1.0, 1.0]], [[1.0, 0.0], [1.0, 1.0]]]
+        );
+    }
+
+    #[test]
+    fn test_triu_4d() {
+        let t: Tensor4D<2, 1, 2, 3> = Tensor4D::ones();
+        let r = t.triu(1);
+        assert_eq!(r.data(), &[[[[0.0, 1.0, 1.0], [0.0, 0.0, 1.0]]]; 2]);
+    }
+
+    #[test]
+    fn test_tril_ones() {
+        let mask: Tensor2D<3, 3> = Tensor2D::tril_ones();
+        assert_eq!(
+            mask.data(),
+            &[[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]]
+        );
+    }
+}
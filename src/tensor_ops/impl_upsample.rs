@@ -0,0 +1,380 @@
+use super::utils::move_tape_and_add_backward_op;
+use crate::prelude::*;
+
+/// Causes a compile time error if `N * S != N2`.
+struct AssertScaled<const N: usize, const S: usize, const N2: usize>;
+
+impl<const N: usize, const S: usize, const N2: usize> AssertScaled<N, S, N2> {
+    const OK: () = assert!(
+        N * S == N2,
+        "upsampled size must be the original size times S"
+    );
+}
+
+/// For index `dst` of a dimension upsampled from `n` to `n * scale`, returns the two source indices
+/// it is interpolated between, and the weight of the second one. Uses the same coordinates as
+/// pytorch's `align_corners=False`.
+fn bilinear_source(dst: usize, scale: usize, n: usize) -> (usize, usize, f32) {
+    let src = ((dst as f32 + 0.5) / scale as f32 - 0.5).max(0.0);
+    let i0 = (src as usize).min(n - 1);
+    let i1 = (i0 + 1).min(n - 1);
+    (i0, i1, src - i0 as f32)
+}
+
+fn nearest_forward<const H: usize, const W: usize, const H2: usize, const W2: usize>(
+    out: &mut [[f32; W2]; H2],
+    inp: &[[f32; W]; H],
+    scale: usize,
+) {
+    for (i, out_i) in out.iter_mut().enumerate() {
+        for (j, o) in out_i.iter_mut().enumerate() {
+            *o = inp[i / scale][j / scale];
+        }
+    }
+}
+
+fn nearest_backward<const H: usize, const W: usize, const H2: usize, const W2: usize>(
+    inp_grad: &mut [[f32; W]; H],
+    out_grad: &[[f32; W2]; H2],
+    scale: usize,
+) {
+    for (i, g_i) in out_grad.iter().enumerate() {
+        for (j, g) in g_i.iter().enumerate() {
+            inp_grad[i / scale][j / scale] += g;
+        }
+    }
+}
+
+fn bilinear_forward<const H: usize, const W: usize, const H2: usize, const W2: usize>(
+    out: &mut [[f32; W2]; H2],
+    inp: &[[f32; W]; H],
+    scale: usize,
+) {
+    for (i, out_i) in out.iter_mut().enumerate() {
+        let (i0, i1, di) = bilinear_source(i, scale, H);
+        for (j, o) in out_i.iter_mut().enumerate() {
+            let (j0, j1, dj) = bilinear_source(j, scale, W);
+            let top = inp[i0][j0] * (1.0 - dj) + inp[i0][j1] * dj;
+            let bottom = inp[i1][j0] * (1.0 - dj) + inp[i1][j1] * dj;
+            *o = top * (1.0 - di) + bottom * di;
+        }
+    }
+}
+
+fn bilinear_backward<const H: usize, const W: usize, const H2: usize, const W2: usize>(
+    inp_grad: &mut [[f32; W]; H],
+    out_grad: &[[f32; W2]; H2],
+    scale: usize,
+) {
+    for (i, g_i) in out_grad.iter().enumerate() {
+        let (i0, i1, di) = bilinear_source(i, scale, H);
+        for (j, g) in g_i.iter().enumerate() {
+            let (j0, j1, dj) = bilinear_source(j, scale, W);
+            inp_grad[i0][j0] += g * (1.0 - di) * (1.0 - dj);
+            inp_grad[i0][j1] += g * (1.0 - di) * dj;
+            inp_grad[i1][j0] += g * di * (1.0 - dj);
+            inp_grad[i1][j1] += g * di * dj;
+        }
+    }
+}
+
+impl<const B: usize, const C: usize, const H: usize, const W: usize, TAPE: Tape>
+    Tensor4D<B, C, H, W, TAPE>
+{
+    /// Upsamples the last two (spatial) dimensions of `self` by a factor of `S`, by repeating
+    /// every value in an `S x S` block. This is the same as pytorch's `interpolate(mode="nearest")`.
+    ///
+    /// The output size must be `H * S` by `W * S`, which is checked at compile time.
+    /// It can be inferred by passing `_` for `H2` and `W2`.
+    ///
+    /// The gradient of each value is the sum of the gradients of its `S x S` block.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+    /// let r = t.upsample_nearest::<2, _, _>();
+    /// assert_eq!(
+    ///     r.data(),
+    ///     &[[[
+    ///         [1.0, 1.0, 2.0, 2.0],
+    ///         [1.0, 1.0, 2.0, 2.0],
+    ///         [3.0, 3.0, 4.0, 4.0],
+    ///         [3.0, 3.0, 4.0, 4.0],
+    ///     ]]]
+    /// );
+    /// ```
+    ///
+    /// The wrong output size is a compile time error:
+    /// ```compile_fail
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 1, 2, 2> = Tensor4D::zeros();
+    /// let r: Tensor4D<1, 1, 4, 5> = t.upsample_nearest::<2, _, _>();
+    /// ```
+    pub fn upsample_nearest<const S: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor4D<B, C, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (AssertScaled::<H, S, H2>::OK, AssertScaled::<W, S, W2>::OK);
+
+        let mut result: Tensor4D<B, C, H2, W2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            for (r_c, t_c) in r_b.iter_mut().zip(t_b.iter()) {
+                nearest_forward(r_c, t_c, S);
+            }
+        }
+        move_tape_and_add_backward_op("upsample_nearest", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[[f32; W2]; H2]; C]; B]) =
+                grads.mut_and_ref(&t, &result);
+            for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                for (t_c, r_c) in t_b.iter_mut().zip(r_b.iter()) {
+                    nearest_backward(t_c, r_c, S);
+                }
+            }
+        })
+    }
+
+    /// Upsamples the last two (spatial) dimensions of `self` by a factor of `S`, by bilinearly
+    /// interpolating between the nearest 4 values. This is the same as pytorch's
+    /// `interpolate(mode="bilinear", align_corners=False)`.
+    ///
+    /// The output size must be `H * S` by `W * S`, which is checked at compile time.
+    /// It can be inferred by passing `_` for `H2` and `W2`.
+    ///
+    /// The gradient of each output value flows back into the 4 values it was interpolated from,
+    /// weighted by the same weights used to interpolate.
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+    /// let r = t.upsample_bilinear::<2, _, _>();
+    /// assert_eq!(
+    ///     r.data(),
+    ///     &[[[
+    ///         [1.0, 1.25, 1.75, 2.0],
+    ///         [1.5, 1.75, 2.25, 2.5],
+    ///         [2.5, 2.75, 3.25, 3.5],
+    ///         [3.0, 3.25, 3.75, 4.0],
+    ///     ]]]
+    /// );
+    /// ```
+    pub fn upsample_bilinear<const S: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor4D<B, C, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (AssertScaled::<H, S, H2>::OK, AssertScaled::<W, S, W2>::OK);
+
+        let mut result: Tensor4D<B, C, H2, W2> = Tensor4D::zeros();
+        for (r_b, t_b) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            for (r_c, t_c) in r_b.iter_mut().zip(t_b.iter()) {
+                bilinear_forward(r_c, t_c, S);
+            }
+        }
+        move_tape_and_add_backward_op(
+            "upsample_bilinear",
+            self,
+            result,
+            move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[[[[f32; W2]; H2]; C]; B]) =
+                    grads.mut_and_ref(&t, &result);
+                for (t_b, r_b) in t_grad.iter_mut().zip(result_grad.iter()) {
+                    for (t_c, r_c) in t_b.iter_mut().zip(r_b.iter()) {
+                        bilinear_backward(t_c, r_c, S);
+                    }
+                }
+            },
+        )
+    }
+}
+
+impl<const C: usize, const H: usize, const W: usize, TAPE: Tape> Tensor3D<C, H, W, TAPE> {
+    /// Upsamples the last two (spatial) dimensions of a single image by a factor of `S`, by
+    /// repeating every value in an `S x S` block. See [Tensor4D::upsample_nearest()].
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor3D<1, 1, 2> = Tensor3D::new([[[1.0, 2.0]]]);
+    /// let r: Tensor3D<1, 2, 4> = t.upsample_nearest::<2, _, _>();
+    /// assert_eq!(r.data(), &[[[1.0, 1.0, 2.0, 2.0]; 2]]);
+    /// ```
+    pub fn upsample_nearest<const S: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor3D<C, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (AssertScaled::<H, S, H2>::OK, AssertScaled::<W, S, W2>::OK);
+
+        let mut result: Tensor3D<C, H2, W2> = Tensor3D::zeros();
+        for (r_c, t_c) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            nearest_forward(r_c, t_c, S);
+        }
+        move_tape_and_add_backward_op("upsample_nearest", self, result, move |t, result, grads| {
+            let (t_grad, result_grad): (_, &[[[f32; W2]; H2]; C]) = grads.mut_and_ref(&t, &result);
+            for (t_c, r_c) in t_grad.iter_mut().zip(result_grad.iter()) {
+                nearest_backward(t_c, r_c, S);
+            }
+        })
+    }
+
+    /// Upsamples the last two (spatial) dimensions of a single image by a factor of `S`, by
+    /// bilinearly interpolating between the nearest 4 values. See [Tensor4D::upsample_bilinear()].
+    ///
+    /// Examples:
+    /// ```rust
+    /// # use dfdx::prelude::*;
+    /// let t: Tensor3D<1, 1, 2> = Tensor3D::new([[[1.0, 2.0]]]);
+    /// let r: Tensor3D<1, 2, 4> = t.upsample_bilinear::<2, _, _>();
+    /// assert_eq!(r.data(), &[[[1.0, 1.25, 1.75, 2.0]; 2]]);
+    /// ```
+    pub fn upsample_bilinear<const S: usize, const H2: usize, const W2: usize>(
+        self,
+    ) -> Tensor3D<C, H2, W2, TAPE> {
+        #[allow(clippy::let_unit_value)]
+        let _ = (AssertScaled::<H, S, H2>::OK, AssertScaled::<W, S, W2>::OK);
+
+        let mut result: Tensor3D<C, H2, W2> = Tensor3D::zeros();
+        for (r_c, t_c) in result.mut_data().iter_mut().zip(self.data().iter()) {
+            bilinear_forward(r_c, t_c, S);
+        }
+        move_tape_and_add_backward_op(
+            "upsample_bilinear",
+            self,
+            result,
+            move |t, result, grads| {
+                let (t_grad, result_grad): (_, &[[[f32; W2]; H2]; C]) =
+                    grads.mut_and_ref(&t, &result);
+                for (t_c, r_c) in t_grad.iter_mut().zip(result_grad.iter()) {
+                    bilinear_backward(t_c, r_c, S);
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradcheck::gradcheck;
+    use crate::tests::assert_close;
+    use rand::{prelude::StdRng, SeedableRng};
+
+    #[test]
+    fn test_upsample_nearest_2x() {
+        let t: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let r = t.trace().upsample_nearest::<2, _, _>();
+        assert_eq!(
+            r.data(),
+            &[[[
+                [1.0, 1.0, 2.0, 2.0],
+                [1.0, 1.0, 2.0, 2.0],
+                [3.0, 3.0, 4.0, 4.0],
+                [3.0, 3.0, 4.0, 4.0],
+            ]]]
+        );
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[[4.0; 2]; 2]]]);
+    }
+
+    #[test]
+    fn test_upsample_nearest_backward_sums_blocks() {
+        let t: Tensor4D<2, 1, 1, 2> = Tensor4D::new([[[[1.0, 2.0]]], [[[-1.0, 0.5]]]]);
+        let r: Tensor4D<2, 1, 3, 6, OwnedTape> = t.trace().upsample_nearest::<3, _, _>();
+        let w: Tensor4D<2, 1, 3, 6> = Tensor4D::new([
+            [[[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]; 3]],
+            [[[0.0, 0.0, 1.0, 1.0, 1.0, 1.0]; 3]],
+        ]);
+        let gradients = mul(r, &w).sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&t),
+            &[[[[18.0, 45.0]]], [[[3.0, 9.0]]]]
+        );
+    }
+
+    #[test]
+    fn test_upsample_bilinear_2x() {
+        let t: Tensor4D<1, 1, 2, 2> = Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]]]]);
+        let r = t.trace().upsample_bilinear::<2, _, _>();
+        assert_eq!(
+            r.data(),
+            &[[[
+                [1.0, 1.25, 1.75, 2.0],
+                [1.5, 1.75, 2.25, 2.5],
+                [2.5, 2.75, 3.25, 3.5],
+                [3.0, 3.25, 3.75, 4.0],
+            ]]]
+        );
+        // every input is interpolated into a total weight of 4
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[[4.0; 2]; 2]]]);
+    }
+
+    #[test]
+    fn test_upsample_bilinear_backward_weights() {
+        let t: Tensor4D<1, 1, 1, 2> = Tensor4D::new([[[[1.0, 2.0]]]]);
+        let r: Tensor4D<1, 1, 2, 4, OwnedTape> = t.trace().upsample_bilinear::<2, _, _>();
+        assert_eq!(r.data(), &[[[[1.0, 1.25, 1.75, 2.0]; 2]]]);
+        let w: Tensor4D<1, 1, 2, 4> = Tensor4D::new([[[[1.0, 2.0, 3.0, 4.0], [0.0; 4]]]]);
+        let gradients = mul(r, &w).sum().backward();
+        // weights into the first input are [1, 0.75, 0.25, 0], and into the second [0, 0.25, 0.75, 1]
+        assert_close(gradients.ref_gradient(&t), &[[[[3.25, 6.75]]]]);
+    }
+
+    #[test]
+    fn test_upsample_scale_1_is_identity() {
+        let t: Tensor4D<1, 2, 2, 2> =
+            Tensor4D::new([[[[1.0, 2.0], [3.0, 4.0]], [[5.0, 6.0], [7.0, 8.0]]]]);
+        let r: Tensor4D<1, 2, 2, 2> = t.duplicate().upsample_nearest::<1, _, _>();
+        assert_eq!(r.data(), t.data());
+        let r: Tensor4D<1, 2, 2, 2> = t.duplicate().upsample_bilinear::<1, _, _>();
+        assert_eq!(r.data(), t.data());
+    }
+
+    #[test]
+    fn test_upsample_3d_matches_4d() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let t: Tensor3D<2, 3, 2> = Tensor3D::randn(&mut rng);
+        let batched: Tensor4D<1, 2, 3, 2> = Tensor4D::new([*t.data()]);
+
+        let r: Tensor3D<2, 6, 4> = t.duplicate().upsample_nearest::<2, _, _>();
+        let e = batched.duplicate().upsample_nearest::<2, 6, 4>();
+        assert_eq!(r.data(), &e.data()[0]);
+
+        let r: Tensor3D<2, 9, 6> = t.duplicate().upsample_bilinear::<3, _, _>();
+        let e = batched.upsample_bilinear::<3, 9, 6>();
+        assert_eq!(r.data(), &e.data()[0]);
+    }
+
+    #[test]
+    fn test_upsample_gradcheck() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let t: Tensor3D<2, 3, 2> = Tensor3D::randn(&mut rng);
+        let w: Tensor3D<2, 6, 4> = Tensor3D::randn(&mut rng);
+        let f = |t: Tensor3D<2, 3, 2, OwnedTape>| mul(t.upsample_nearest::<2, 6, 4>(), &w).sum();
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+        let f = |t: Tensor3D<2, 3, 2, OwnedTape>| mul(t.upsample_bilinear::<2, 6, 4>(), &w).sum();
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+
+        let t: Tensor4D<2, 1, 2, 3> = Tensor4D::randn(&mut rng);
+        let w: Tensor4D<2, 1, 6, 9> = Tensor4D::randn(&mut rng);
+        let f = |t: Tensor4D<2, 1, 2, 3, OwnedTape>| mul(t.upsample_nearest::<3, 6, 9>(), &w).sum();
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+        let f =
+            |t: Tensor4D<2, 1, 2, 3, OwnedTape>| mul(t.upsample_bilinear::<3, 6, 9>(), &w).sum();
+        assert!(gradcheck(f, &t, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    fn test_upsample_3d_scale_1_is_identity() {
+        let t: Tensor3D<1, 2, 2> = Tensor3D::new([[[1.0, 2.0], [3.0, 4.0]]]);
+        let r = t.trace().upsample_nearest::<1, 2, 2>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[1.0; 2]; 2]]);
+
+        let r = t.trace().upsample_bilinear::<1, 2, 2>();
+        assert_eq!(r.data(), t.data());
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[[[1.0; 2]; 2]]);
+    }
+}
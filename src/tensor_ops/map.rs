@@ -1,5 +1,8 @@
-use super::utils::move_tape_and_add_backward_op;
+use super::utils::{move_tape_and_add_backward_op, move_tape_and_add_backward_op_taped};
+use crate::devices::par_foreach_mrr;
 use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use std::ops::Neg;
 
 /// `-t`. Computes the negation of `t`.
@@ -13,7 +16,7 @@ use std::ops::Neg;
 /// assert_eq!(r.data(), &[2.0, 0.0, -5.0]);
 /// ```
 pub fn negate<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| -x, |_| -1.0)
+    map_named("negate", t, |x| -x, |_| -1.0)
 }
 
 /// `max(0, t)`. Computes [Rectified Linear Unit (ReLU)](https://en.wikipedia.org/wiki/Rectifier_(neural_networks)).
@@ -32,7 +35,12 @@ pub fn negate<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.relu();
 /// ```
 pub fn relu<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.max(0.0), |x| if x > &0.0 { 1.0 } else { 0.0 })
+    map_named_piecewise_linear(
+        "relu",
+        t,
+        |x| x.max(0.0),
+        |x| if x > &0.0 { 1.0 } else { 0.0 },
+    )
 }
 
 /// `t^2`
@@ -51,13 +59,16 @@ pub fn relu<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.square();
 /// ```
 pub fn square<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.powi(2), |x| 2.0 * x)
+    map_named("square", t, |x| x.powi(2), |x| 2.0 * x)
 }
 
 /// `√t` or `t^0.5`. Computes the square root.
 ///
 /// The derivative is `0.5 / (t ^ 0.5)`.
 ///
+/// Negative values of `t` produce `NaN` (for both the value and the gradient),
+/// and `t == 0` has a gradient of `inf`.
+///
 /// Examples:
 /// ```rust
 /// # use dfdx::prelude::*;
@@ -70,7 +81,7 @@ pub fn square<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.sqrt();
 /// ```
 pub fn sqrt<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.sqrt(), |x| 0.5 * x.sqrt().recip())
+    map_named("sqrt", t, |x| x.sqrt(), |x| 0.5 * x.sqrt().recip())
 }
 
 /// `tanh(t)`. Computes the [Hyperbolic Tangent (Tanh)](https://en.wikipedia.org/wiki/Hyperbolic_functions).
@@ -89,7 +100,7 @@ pub fn sqrt<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.tanh();
 /// ```
 pub fn tanh<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.tanh(), |x| 1.0 - x.tanh().powi(2))
+    map_named("tanh", t, |x| x.tanh(), |x| 1.0 - x.tanh().powi(2))
 }
 
 /// `1 / (1 + exp(-t))`. Computes [sigmoid](https://en.wikipedia.org/wiki/Sigmoid_function).
@@ -117,7 +128,7 @@ pub fn sigmoid<T: Tensor<Dtype = f32>>(t: T) -> T {
         s * (1.0 - s)
     }
 
-    map(t, f, df)
+    map_named("sigmoid", t, f, df)
 }
 
 /// `sin(t)`. Computes the [sine function](https://en.wikipedia.org/wiki/Sine_and_cosine).
@@ -136,7 +147,7 @@ pub fn sigmoid<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.sin();
 /// ```
 pub fn sin<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.sin(), |x| x.cos())
+    map_named("sin", t, |x| x.sin(), |x| x.cos())
 }
 
 /// `cos(t)`. Computes the [cosine function](https://en.wikipedia.org/wiki/Sine_and_cosine).
@@ -155,13 +166,15 @@ pub fn sin<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.cos();
 /// ```
 pub fn cos<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.cos(), |x| x.sin().neg())
+    map_named("cos", t, |x| x.cos(), |x| x.sin().neg())
 }
 
 /// `ln(t)` or `log_e(t)`. Computes the [Natural Logarithm (ln)](https://en.wikipedia.org/wiki/Natural_logarithm).
 ///
 /// It's derivative is `1 / t`.
 ///
+/// Negative values of `t` produce `NaN`, and `ln(0)` is `-inf` (with a gradient of `inf`).
+///
 /// Examples:
 /// ```rust
 /// # use dfdx::prelude::*;
@@ -174,7 +187,7 @@ pub fn cos<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.ln();
 /// ```
 pub fn ln<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.ln(), |x| x.recip())
+    map_named("ln", t, |x| x.ln(), |x| x.recip())
 }
 
 /// `e^t`. Computes the [exponential function (exp)](https://en.wikipedia.org/wiki/Natural_logarithm).
@@ -193,12 +206,13 @@ pub fn ln<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.exp();
 /// ```
 pub fn exp<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.exp(), |x| x.exp())
+    map_named("exp", t, |x| x.exp(), |x| x.exp())
 }
 
 /// `|t|`. Computes the [absolute value (abs)](https://en.wikipedia.org/wiki/Absolute_value).
 ///
 /// The derivative is -1.0 for t < 0, 0 for t == 0, and 1.0 for t > 0.
+/// Note that the gradient at `t == 0` is defined to be `0.0`, even though `|t|` is not differentiable there.
 ///
 /// Examples:
 /// ```rust
@@ -212,7 +226,12 @@ pub fn exp<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r2 = t.abs();
 /// ```
 pub fn abs<T: Tensor<Dtype = f32>>(t: T) -> T {
-    map(t, |x| x.abs(), |x| if x == &0.0 { 0.0 } else { x.signum() })
+    map_named(
+        "abs",
+        t,
+        |x| x.abs(),
+        |x| if x == &0.0 { 0.0 } else { x.signum() },
+    )
 }
 
 /// `f(t)`. Applies a function `f` to every element of the [Tensor]. The derivative
@@ -228,13 +247,28 @@ pub fn abs<T: Tensor<Dtype = f32>>(t: T) -> T {
 /// let r = map(t, |x| 2.0 * x, |x| 2.0);
 /// assert_eq!(r.data(), &[-4.0, -2.0, 0.0, 2.0, 4.0]);
 /// ```
-pub fn map<T: Tensor<Dtype = f32>, F, Df>(t: T, f: F, mut df: Df) -> T
+///
+/// In the backward pass, the gradient of `t` is `df(t) * gradient(result)`. Since both `f` and `df`
+/// are stored on the tape, they must be `'static` (i.e. they can't borrow anything). Function pointers
+/// and closures that only capture by `move` both work:
+/// ```rust
+/// # use dfdx::prelude::*;
+/// fn cube(x: &f32) -> f32 {
+///     x.powi(3)
+/// }
+/// let scale = 3.0;
+/// let t = Tensor1D::new([-1.0, 0.0, 2.0]);
+/// let r = t.trace().map(cube, move |x| scale * x.powi(2));
+/// assert_eq!(r.data(), &[-1.0, 0.0, 8.0]);
+/// ```
+pub fn map<T: Tensor<Dtype = f32>, F, Df>(t: T, f: F, df: Df) -> T
 where
     F: 'static + FnMut(&f32) -> f32,
-    Df: 'static + FnMut(&f32) -> f32,
+    Df: 'static + Fn(&f32) -> f32,
 {
+    // `df` may not be `Sync`, so unlike `map_named` this doesn't use `par_foreach_mrr`
     let result = T::NoTape::new_boxed(T::Device::map(t.data(), f));
-    move_tape_and_add_backward_op(t, result, move |t, result, grads| {
+    move_tape_and_add_backward_op("map", t, result, move |t, result, grads| {
         let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
         T::Device::foreach_mrr(t_grad, t.data(), result_grad, &mut |g, t, r| {
             *g += df(t) * r;
@@ -242,6 +276,54 @@ where
     })
 }
 
+/// [map()], but the operation is recorded on the tape as `name`. See [GradientTape::op_names()].
+///
+/// `df` must be [Sync], since the backward pass may call it from multiple threads with the
+/// `rayon` feature.
+pub(crate) fn map_named<T: Tensor<Dtype = f32>, F, Df>(name: &'static str, t: T, f: F, df: Df) -> T
+where
+    F: 'static + FnMut(&f32) -> f32,
+    Df: 'static + Fn(&f32) -> f32 + Sync,
+{
+    let result = T::NoTape::new_boxed(T::Device::map(t.data(), f));
+    move_tape_and_add_backward_op(name, t, result, move |t, result, grads| {
+        let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+        par_foreach_mrr(t_grad, t.data(), result_grad, |g, t, r| *g += df(t) * r);
+    })
+}
+
+/// [map_named()] for functions whose derivative `df` is piecewise constant (e.g. [relu()]).
+/// The second derivative is zero, so the backward operation also has a taped version that
+/// treats `df(t)` as a constant. See [GradientTape::execute_with_tape()].
+pub(crate) fn map_named_piecewise_linear<T: Tensor<Dtype = f32>, F, Df>(
+    name: &'static str,
+    t: T,
+    f: F,
+    df: Df,
+) -> T
+where
+    F: 'static + FnMut(&f32) -> f32,
+    Df: 'static + Fn(&f32) -> f32 + Sync + Copy,
+{
+    let result = T::NoTape::new_boxed(T::Device::map(t.data(), f));
+    move_tape_and_add_backward_op_taped(
+        name,
+        t,
+        result,
+        move |t, result, grads| {
+            let (t_grad, result_grad) = grads.mut_and_ref(&t, &result);
+            par_foreach_mrr(t_grad, t.data(), result_grad, |g, t, r| *g += df(t) * r);
+        },
+        move |t, result, grads| {
+            if let Some(result_grad) = grads.traced::<T::OwnedTape>(&result) {
+                let deriv: <T::OwnedTape as Tensor>::NoTape =
+                    TensorCreator::new_boxed(T::Device::map(t.data(), df));
+                grads.accumulate(&t, mul(result_grad, &deriv));
+            }
+        },
+    )
+}
+
 macro_rules! activation_impl {
     ($func_name:ident, #[$docstring:meta]) => {
         #[$docstring]
@@ -265,6 +347,15 @@ impl<$(const $Vs: usize, )* H: Tape> $typename<$($Vs, )* H> {
     activation_impl!(square, #[doc="Calls [square()] on `self`."]);
     activation_impl!(sqrt, #[doc="Calls [sqrt()] on `self`."]);
     activation_impl!(abs, #[doc="Calls [abs()] on `self`."]);
+
+    /// Calls [map()] on `self`.
+    pub fn map<F, Df>(self, f: F, df: Df) -> Self
+    where
+        F: 'static + FnMut(&f32) -> f32,
+        Df: 'static + Fn(&f32) -> f32,
+    {
+        map(self, f, df)
+    }
 }
 
 impl<$(const $Vs: usize, )* H: Tape> std::ops::Neg for $typename<$($Vs, )* H>
@@ -287,6 +378,17 @@ tensor_impl!(Tensor4D, [M, N, O, P]);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_map_custom() {
+        let x = Tensor1D::new([-1.0, 0.0, 1.0, 2.0]);
+        let r = x.trace().map(|x| x.powi(3), |x| 3.0 * x.powi(2));
+        assert_eq!(r.data(), &[-1.0, 0.0, 1.0, 8.0]);
+        // NOTE: * 2.0 so we make sure its using result grad properly
+        let gradients = (r * 2.0).sum().backward();
+        assert_eq!(gradients.ref_gradient(&x), &[6.0, 0.0, 6.0, 24.0]);
+    }
 
     #[test]
     fn test_relu() {
@@ -331,7 +433,12 @@ mod tests {
         );
     }
 
+    // the gradients are non-finite, which `nan-checks` panics on
     #[test]
+    #[cfg_attr(
+        feature = "nan-checks",
+        should_panic = "produced a non-finite gradient"
+    )]
     fn test_ln() {
         let x = Tensor1D::new([-2.0, -1.0, 0.0, 1.0, 2.0]);
         let r = x.trace().ln();
@@ -399,7 +506,12 @@ mod tests {
         assert_eq!(gradients.ref_gradient(&x), &[-0.8, -0.4, 0.0, 0.4, 0.8]);
     }
 
+    // the gradients are non-finite, which `nan-checks` panics on
     #[test]
+    #[cfg_attr(
+        feature = "nan-checks",
+        should_panic = "produced a non-finite gradient"
+    )]
     fn test_sqrt() {
         let x = Tensor1D::new([-1.0, 0.0, 1.0, 4.0]);
         let r = x.trace().sqrt();
@@ -422,6 +534,60 @@ mod tests {
         assert_eq!(gradients.ref_gradient(&x), &[-0.2, -0.2, 0.0, 0.2, 0.2]);
     }
 
+    /// Compares the gradient of `op` against central finite differences of `f` at `x`.
+    fn check_finite_difference<const M: usize>(
+        x: [f32; M],
+        op: fn(Tensor1D<M, OwnedTape>) -> Tensor1D<M, OwnedTape>,
+        f: fn(f32) -> f32,
+    ) {
+        const H: f32 = 1e-3;
+        let t = Tensor1D::new(x);
+        let gradients = op(t.trace()).sum().backward();
+        let grad = gradients.ref_gradient(&t);
+        for i in 0..M {
+            let approx = (f(x[i] + H) - f(x[i] - H)) / (2.0 * H);
+            let tolerance = 1e-2 * approx.abs().max(1.0);
+            assert!(
+                (grad[i] - approx).abs() <= tolerance,
+                "x={} grad={} approx={}",
+                x[i],
+                grad[i],
+                approx
+            );
+        }
+    }
+
+    #[test]
+    fn test_sqrt_finite_difference() {
+        check_finite_difference([0.1, 0.5, 1.0, 4.0, 10.0], sqrt, f32::sqrt);
+    }
+
+    #[test]
+    fn test_exp_finite_difference() {
+        check_finite_difference([-3.0, -1.0, 0.0, 1.0, 3.0], exp, f32::exp);
+    }
+
+    #[test]
+    fn test_ln_finite_difference() {
+        check_finite_difference([0.1, 0.5, 1.0, 4.0, 10.0], ln, f32::ln);
+    }
+
+    #[test]
+    fn test_abs_finite_difference() {
+        check_finite_difference([-3.0, -1.0, -0.5, 0.5, 3.0], abs, f32::abs);
+    }
+
+    #[test]
+    fn test_map_df_captures_rc() {
+        // `df` doesn't need to be `Sync`
+        let scale = std::rc::Rc::new(2.0);
+        let t = Tensor1D::new([1.0, -2.0, 3.0]);
+        let r = t.trace().map(|x| 2.0 * x, move |_| *scale);
+        assert_eq!(r.data(), &[2.0, -4.0, 6.0]);
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&t), &[2.0, 2.0, 2.0]);
+    }
+
     #[test]
     fn test_0d_neg() {
         let a = Tensor0D::new(10.0);
@@ -461,4 +627,53 @@ mod tests {
         let gradients = r.mean().backward();
         assert_eq!(gradients.ref_gradient(&a), &[[[-1.0 / 24.0; 3]; 2]; 4]);
     }
+
+    #[test]
+    fn test_4d_neg() {
+        let a: Tensor4D<2, 4, 2, 3> = Tensor4D::ones();
+        let r = -(a.trace());
+        assert_eq!(r.data(), &[[[[-1.0; 3]; 2]; 4]; 2]);
+        let gradients = r.mean().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[[[[-1.0 / 48.0; 3]; 2]; 4]; 2]);
+    }
+
+    #[test]
+    fn test_4d_relu_matches_3d() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a: Tensor4D<3, 2, 4, 5> = Tensor4D::randn(&mut rng);
+        let w: Tensor4D<3, 2, 4, 5> = Tensor4D::randn(&mut rng);
+        let r = a.trace().relu();
+        let r_data = *r.data();
+        let gradients = mul(r, &w).sum().backward();
+        let a_grad = gradients.ref_gradient(&a);
+
+        for b in 0..3 {
+            let a_b = Tensor3D::new(a.data()[b]);
+            let w_b = Tensor3D::new(w.data()[b]);
+            let r_b = a_b.trace().relu();
+            assert_eq!(r_b.data(), &r_data[b]);
+            let expected = mul(r_b, &w_b).sum().backward();
+            assert_eq!(expected.ref_gradient(&a_b), &a_grad[b]);
+        }
+    }
+
+    #[test]
+    fn test_4d_sigmoid_tanh() {
+        let a: Tensor4D<1, 2, 1, 2> = Tensor4D::new([[[[0.0, 1.0]], [[-2.0, 100.0]]]]);
+        let r = a.trace().sigmoid();
+        assert_eq!(r.data(), &[[[[0.5, 0.7310586]], [[0.11920292, 1.0]]]]);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[[[0.25, 0.19661193]], [[0.10499358, 0.0]]]]
+        );
+
+        let r = a.trace().tanh();
+        assert_eq!(r.data(), &[[[[0.0, 0.7615942]], [[-0.9640276, 1.0]]]]);
+        let gradients = r.sum().backward();
+        assert_eq!(
+            gradients.ref_gradient(&a),
+            &[[[[1.0, 0.41997433]], [[0.070650816, 0.0]]]]
+        );
+    }
 }
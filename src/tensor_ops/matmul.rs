@@ -1,4 +1,4 @@
-use super::utils::move_tape_and_add_backward_binop;
+use super::utils::{move_tape_and_add_backward_binop, move_tape_and_add_backward_binop_taped};
 use crate::prelude::*;
 
 /// Matrix multiplication.
@@ -31,14 +31,105 @@ pub fn matmul<const M: usize, const K: usize, const N: usize, TAPE: Tape>(
 
     // copy rhs data for use later when computing gradients
     let rhs_data = rhs.data.clone();
+    let taped_rhs = rhs.duplicate();
 
-    move_tape_and_add_backward_binop(lhs, rhs, result, move |lhs, rhs, result, grads| {
-        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        mm_bt(result_grad, rhs_data.as_ref(), lhs_grad);
+    move_tape_and_add_backward_binop_taped(
+        "matmul",
+        lhs,
+        rhs,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            mm_bt(result_grad, rhs_data.as_ref(), lhs_grad);
 
-        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
-        mm_at(lhs.data(), result_grad, rhs_grad);
-    })
+            let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            mm_at(lhs.data(), result_grad, rhs_grad);
+        },
+        move |lhs, _, result, grads| taped_matmul_backward(&lhs, &taped_rhs, &result, grads),
+    )
+}
+
+/// Matrix multiplication, where both `lhs` and `rhs` may own a tape. Like [matmul()], but
+/// the tapes are merged together into the result, so gradients flow into both. See [MergeTape].
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor2D::new([[1.0, 2.0]]);
+/// let y = Tensor2D::new([[1.0, -1.0], [0.5, 2.0]]);
+/// let result: Tensor2D<1, 2, OwnedTape> = matmul_merged(x.trace(), y.trace());
+/// assert_eq!(result.data(), &[[2.0, 3.0]]);
+/// let gradients = result.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&x), &[[0.0, 2.5]]);
+/// assert_eq!(gradients.ref_gradient(&y), &[[1.0, 1.0], [2.0, 2.0]]);
+/// ```
+pub fn matmul_merged<const M: usize, const K: usize, const N: usize, HA, HB>(
+    lhs: Tensor2D<M, K, HA>,
+    rhs: Tensor2D<K, N, HB>,
+) -> Tensor2D<M, N, HA::Output>
+where
+    HA: MergeTape<HB>,
+    HB: Tape,
+{
+    let (lhs, lhs_tape) = lhs.split_tape();
+    let (rhs, rhs_tape) = rhs.split_tape();
+
+    let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+    mm(lhs.data(), rhs.data(), result.mut_data());
+
+    let mut tape = lhs_tape.merge_tape(rhs_tape);
+    let phantom_result = result.phantom();
+    let (taped_lhs, taped_rhs) = (lhs.duplicate(), rhs.duplicate());
+    tape.add_backward_op_taped(
+        "matmul",
+        move |grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+            mm_bt(result_grad, rhs.data(), lhs_grad);
+
+            let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+            mm_at(lhs.data(), result_grad, rhs_grad);
+        },
+        move |grads| taped_matmul_backward(&taped_lhs, &taped_rhs, &phantom_result, grads),
+    );
+    result.put_tape(tape)
+}
+
+/// The backward operation of [matmul()] with tensor operations, so that it can be differentiated
+/// again. See [GradientTape::execute_with_tape()].
+fn taped_matmul_backward<const M: usize, const K: usize, const N: usize>(
+    lhs: &Tensor2D<M, K>,
+    rhs: &Tensor2D<K, N>,
+    result: &PhantomTensor<Tensor2D<M, N>>,
+    grads: &mut TapedGradients,
+) {
+    if let Some(result_grad) = grads.traced::<Tensor2D<M, N, OwnedTape>>(result) {
+        grads.accumulate(lhs, matmul_transpose(result_grad, rhs));
+    }
+    if let Some(result_grad) = grads.traced::<Tensor2D<M, N, OwnedTape>>(result) {
+        grads.accumulate(rhs, transpose_matmul(lhs, result_grad));
+    }
+}
+
+/// `transpose(lhs) * rhs`, where `rhs` owns the tape. Used by [taped_matmul_backward()].
+fn transpose_matmul<const M: usize, const K: usize, const N: usize>(
+    lhs: &Tensor2D<M, K>,
+    rhs: Tensor2D<M, N, OwnedTape>,
+) -> Tensor2D<K, N, OwnedTape> {
+    let mut result: Tensor2D<K, N> = Tensor2D::zeros();
+    mm_at(lhs.data(), rhs.data(), result.mut_data());
+
+    let lhs = lhs.duplicate();
+    let (rhs, mut tape) = rhs.split_tape();
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("transpose_matmul", move |grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+        mm_bt(rhs.data(), result_grad, lhs_grad);
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+        mm(lhs.data(), result_grad, rhs_grad);
+    });
+    result.put_tape(tape)
 }
 
 /// Matrix multiplication with the transpose of `rhs`. Equivalent to `matmul(lhs, transpose(rhs))`.
@@ -71,14 +162,114 @@ pub fn matmul_transpose<const M: usize, const K: usize, const N: usize, TAPE: Ta
 
     // copy rhs data for use later when computing gradients
     let rhs_data = rhs_t.data.clone();
+    let taped_rhs_t = rhs_t.duplicate();
 
-    move_tape_and_add_backward_binop(lhs, rhs_t, result, move |lhs, rhs, result, grads| {
-        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        mm(result_grad, rhs_data.as_ref(), lhs_grad);
+    move_tape_and_add_backward_binop_taped(
+        "matmul_transpose",
+        lhs,
+        rhs_t,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            mm(result_grad, rhs_data.as_ref(), lhs_grad);
 
-        let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
-        mm_atct(lhs.data(), result_grad, rhs_t_grad);
-    })
+            let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            mm_atct(lhs.data(), result_grad, rhs_t_grad);
+        },
+        move |lhs, _, result, grads| {
+            if let Some(result_grad) = grads.traced::<Tensor2D<M, N, OwnedTape>>(&result) {
+                grads.accumulate(&lhs, matmul(result_grad, &taped_rhs_t));
+            }
+            if let Some(result_grad) = grads.traced::<Tensor2D<M, N, OwnedTape>>(&result) {
+                grads.accumulate(&taped_rhs_t, transpose_matmul_transpose(&lhs, result_grad));
+            }
+        },
+    )
+}
+
+/// `transpose(transpose(lhs) * rhs)`, i.e. `transpose(rhs) * lhs`, where `rhs` owns the tape.
+/// Used by the taped backward operation of [matmul_transpose()].
+fn transpose_matmul_transpose<const M: usize, const K: usize, const N: usize>(
+    lhs: &Tensor2D<M, K>,
+    rhs: Tensor2D<M, N, OwnedTape>,
+) -> Tensor2D<N, K, OwnedTape> {
+    let mut result: Tensor2D<N, K> = Tensor2D::zeros();
+    mm_atct(lhs.data(), rhs.data(), result.mut_data());
+
+    let lhs = lhs.duplicate();
+    let (rhs, mut tape) = rhs.split_tape();
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("transpose_matmul_transpose", move |grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+        mm(rhs.data(), result_grad, lhs_grad);
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+        mm_bt(lhs.data(), result_grad, rhs_grad);
+    });
+    result.put_tape(tape)
+}
+
+/// Batched matrix multiplication with the transpose of `rhs`. Every `MxK` matrix in `lhs` is
+/// multiplied by `transpose(rhs_t)`, which is equivalent to calling [matmul_transpose()] on each of them.
+///
+/// # Arguments
+/// * `lhs` - a 3d tensor representing a batch of B MxK matrices
+/// * `rhs_t` - a 2d tensor representing a NxK matrix.
+///
+/// # Generics
+/// - `B`: the batch size of `lhs`.
+/// - `M`: number of rows of each matrix in `lhs`.
+/// - `K`: number of columns of `lhs` and number of rows of `rhs`.
+/// - `N`: Number of columns of `rhs`.
+///
+/// Returns a 3d tensor representing a batch of B MxN matrices. The gradient of `rhs_t`
+/// is summed over the batch.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x: Tensor3D<5, 3, 2> = Tensor3D::zeros();
+/// let y: Tensor2D<4, 2> = Tensor2D::zeros();
+/// let result: Tensor3D<5, 3, 4> = batch_matmul_transpose(x, &y);
+/// ```
+pub fn batch_matmul_transpose<
+    const B: usize,
+    const M: usize,
+    const K: usize,
+    const N: usize,
+    TAPE: Tape,
+>(
+    lhs: Tensor3D<B, M, K, TAPE>,
+    rhs_t: &Tensor2D<N, K, NoneTape>,
+) -> Tensor3D<B, M, N, TAPE> {
+    let mut result: Tensor3D<B, M, N> = Tensor3D::zeros();
+    for (r, l) in result.mut_data().iter_mut().zip(lhs.data().iter()) {
+        mm_bt(l, rhs_t.data(), r);
+    }
+
+    // copy rhs data for use later when computing gradients
+    let rhs_data = rhs_t.data.clone();
+
+    move_tape_and_add_backward_binop(
+        "batch_matmul_transpose",
+        lhs,
+        rhs_t,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad): (_, &[[[f32; N]; M]; B]) =
+                grads.mut_and_ref(&lhs, &result);
+            for (l, r) in lhs_grad.iter_mut().zip(result_grad.iter()) {
+                mm(r, rhs_data.as_ref(), l);
+            }
+
+            let (rhs_t_grad, result_grad): (_, &[[[f32; N]; M]; B]) =
+                grads.mut_and_ref(&rhs, &result);
+            for (l, r) in lhs.data().iter().zip(result_grad.iter()) {
+                mm_atct(l, r, rhs_t_grad);
+            }
+        },
+    )
 }
 
 /// vector * matrix multiplication.
@@ -112,13 +303,19 @@ pub fn vecmat_mul<const K: usize, const N: usize, TAPE: Tape>(
 
     let rhs_data = rhs.data.clone();
 
-    move_tape_and_add_backward_binop(lhs, rhs, result, move |lhs, rhs, result, grads| {
-        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        vm_bt(result_grad, rhs_data.as_ref(), lhs_grad);
+    move_tape_and_add_backward_binop(
+        "vecmat_mul",
+        lhs,
+        rhs,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            vm_bt(result_grad, rhs_data.as_ref(), lhs_grad);
 
-        let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
-        vv(lhs.data(), result_grad, rhs_t_grad);
-    })
+            let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            vv(lhs.data(), result_grad, rhs_t_grad);
+        },
+    )
 }
 
 /// vector * matrix multiplication where `rhs` is transposed. `y * transpose(rhs)`
@@ -149,18 +346,214 @@ pub fn vecmat_mul_transpose<const K: usize, const N: usize, TAPE: Tape>(
     vm_bt(lhs.data(), rhs_t.data(), result.mut_data());
 
     let rhs_t_data = rhs_t.data.clone();
+    let taped_rhs_t = rhs_t.duplicate();
 
-    move_tape_and_add_backward_binop(lhs, rhs_t, result, move |lhs, rhs, result, grads| {
+    move_tape_and_add_backward_binop_taped(
+        "vecmat_mul_transpose",
+        lhs,
+        rhs_t,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            vm(result_grad, rhs_t_data.as_ref(), lhs_grad);
+
+            let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            vv(result_grad, lhs.data(), rhs_t_grad);
+        },
+        move |lhs, _, result, grads| {
+            if let Some(result_grad) = grads.traced::<Tensor1D<N, OwnedTape>>(&result) {
+                grads.accumulate(&lhs, vecmat_mul(result_grad, &taped_rhs_t));
+            }
+            if let Some(result_grad) = grads.traced::<Tensor1D<N, OwnedTape>>(&result) {
+                grads.accumulate(&taped_rhs_t, outer(result_grad, &lhs));
+            }
+        },
+    )
+}
+
+/// matrix * vector multiplication.
+///
+/// This is equivalent to matrix multiplication with N == 1.
+///
+/// # Generics
+/// - `M`: number of rows of `lhs`.
+/// - `K`: number of columns of `lhs` and number of rows of `rhs`.
+///
+/// # Arguments
+/// * `lhs` - a 2d tensor representing a MxK matrix
+/// * `rhs` - a 1d tensor representing a Kx1 matrix
+///
+/// Returns a 1d tensor representing an Mx1 matrix.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x: Tensor2D<4, 2> = Tensor2D::zeros();
+/// let y: Tensor1D<2> = Tensor1D::zeros();
+/// let result: Tensor1D<4> = matvec_mul(x, &y);
+/// ```
+pub fn matvec_mul<const M: usize, const K: usize, TAPE: Tape>(
+    lhs: Tensor2D<M, K, TAPE>,
+    rhs: &Tensor1D<K, NoneTape>,
+) -> Tensor1D<M, TAPE> {
+    let mut result = Tensor1D::zeros();
+    vm_bt(rhs.data(), lhs.data(), result.mut_data());
+
+    let rhs_data = rhs.data.clone();
+
+    move_tape_and_add_backward_binop(
+        "matvec_mul",
+        lhs,
+        rhs,
+        result,
+        move |lhs, rhs, result, grads| {
+            let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+            vv(result_grad, rhs_data.as_ref(), lhs_grad);
+
+            let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+            vm(result_grad, lhs.data(), rhs_grad);
+        },
+    )
+}
+
+/// Outer product of two vectors. `result[i][j] = lhs[i] * rhs[j]`.
+///
+/// This is the vector * vector member of the [matmul()] family, and is equivalent to matrix
+/// multiplication with K == 1.
+///
+/// # Generics
+/// - `M`: number of elements of `lhs`.
+/// - `N`: number of elements of `rhs`.
+///
+/// # Arguments
+/// * `lhs` - a 1d tensor representing a Mx1 matrix
+/// * `rhs` - a 1d tensor representing a 1xN matrix
+///
+/// Returns a 2d tensor representing an MxN matrix.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0]);
+/// let y = Tensor1D::new([1.0, -1.0, 0.5]);
+/// let result: Tensor2D<2, 3> = outer(x, &y);
+/// assert_eq!(result.data(), &[[1.0, -1.0, 0.5], [2.0, -2.0, 1.0]]);
+/// ```
+pub fn outer<const M: usize, const N: usize, TAPE: Tape>(
+    lhs: Tensor1D<M, TAPE>,
+    rhs: &Tensor1D<N, NoneTape>,
+) -> Tensor2D<M, N, TAPE> {
+    let mut result = Tensor2D::zeros();
+    vv(lhs.data(), rhs.data(), result.mut_data());
+
+    let rhs_data = rhs.data.clone();
+
+    move_tape_and_add_backward_binop("outer", lhs, rhs, result, move |lhs, rhs, result, grads| {
         let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
-        vm(result_grad, rhs_t_data.as_ref(), lhs_grad);
+        vm_bt(rhs_data.as_ref(), result_grad, lhs_grad);
 
-        let (rhs_t_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
-        vv(result_grad, lhs.data(), rhs_t_grad);
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+        vm(lhs.data(), result_grad, rhs_grad);
+    })
+}
+
+/// Outer product of two vectors, where both `lhs` and `rhs` may own a tape. Like [outer()], but
+/// the tapes are merged together into the result, so gradients flow into both.
+///
+/// The gradient of `lhs` is `g * rhs`, and the gradient of `rhs` is `lhs * g`, where `g` is
+/// the result's gradient.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0]);
+/// let y = Tensor1D::new([1.0, -1.0, 0.5]);
+/// let result: Tensor2D<2, 3, OwnedTape> = outer_merged(x.trace(), y.trace()); // or x.trace().outer(y.trace())
+/// assert_eq!(result.data(), &[[1.0, -1.0, 0.5], [2.0, -2.0, 1.0]]);
+/// let gradients = result.sum().backward();
+/// assert_eq!(gradients.ref_gradient(&x), &[0.5, 0.5]);
+/// assert_eq!(gradients.ref_gradient(&y), &[3.0, 3.0, 3.0]);
+/// ```
+pub fn outer_merged<const M: usize, const N: usize, HA, HB>(
+    lhs: Tensor1D<M, HA>,
+    rhs: Tensor1D<N, HB>,
+) -> Tensor2D<M, N, HA::Output>
+where
+    HA: MergeTape<HB>,
+    HB: Tape,
+{
+    let (lhs, lhs_tape) = lhs.split_tape();
+    let (rhs, rhs_tape) = rhs.split_tape();
+
+    let mut result: Tensor2D<M, N> = Tensor2D::zeros();
+    vv(lhs.data(), rhs.data(), result.mut_data());
+
+    let mut tape = lhs_tape.merge_tape(rhs_tape);
+    let phantom_result = result.phantom();
+    tape.add_backward_op_named("outer", move |grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &phantom_result);
+        vm_bt(rhs.data(), result_grad, lhs_grad);
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &phantom_result);
+        vm(lhs.data(), result_grad, rhs_grad);
+    });
+    result.put_tape(tape)
+}
+
+impl<const M: usize, H: Tape> Tensor1D<M, H> {
+    /// Calls [outer_merged()] on `self`.
+    pub fn outer<const N: usize, HB: Tape>(self, rhs: Tensor1D<N, HB>) -> Tensor2D<M, N, H::Output>
+    where
+        H: MergeTape<HB>,
+    {
+        outer_merged(self, rhs)
+    }
+}
+
+/// Dot product of two vectors. `sum(lhs * rhs)`.
+///
+/// # Arguments
+/// * `lhs` - a 1d tensor with N elements
+/// * `rhs` - a 1d tensor with N elements
+///
+/// Returns a 0d tensor.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dfdx::prelude::*;
+/// let x = Tensor1D::new([1.0, 2.0, 3.0]);
+/// let y = Tensor1D::new([1.0, -1.0, 0.5]);
+/// let result: Tensor0D = dot(x, &y);
+/// assert_eq!(result.data(), &0.5);
+/// ```
+pub fn dot<const N: usize, TAPE: Tape>(
+    lhs: Tensor1D<N, TAPE>,
+    rhs: &Tensor1D<N, NoneTape>,
+) -> Tensor0D<TAPE> {
+    let mut result = Tensor0D::zeros();
+    *result.mut_data() = lhs.data().iter().zip(rhs.data()).map(|(l, r)| l * r).sum();
+
+    let rhs_data = rhs.data.clone();
+
+    move_tape_and_add_backward_binop("dot", lhs, rhs, result, move |lhs, rhs, result, grads| {
+        let (lhs_grad, result_grad) = grads.mut_and_ref(&lhs, &result);
+        for (l, r) in lhs_grad.iter_mut().zip(rhs_data.iter()) {
+            *l += result_grad * r;
+        }
+
+        let (rhs_grad, result_grad) = grads.mut_and_ref(&rhs, &result);
+        for (r, l) in rhs_grad.iter_mut().zip(lhs.data().iter()) {
+            *r += result_grad * l;
+        }
     })
 }
 
 /// matrix multiply `c += a * b`
-fn mm<const M: usize, const K: usize, const N: usize>(
+pub(super) fn mm<const M: usize, const K: usize, const N: usize>(
     a: &[[f32; K]; M],
     b: &[[f32; N]; K],
     c: &mut [[f32; N]; M],
@@ -198,7 +591,7 @@ fn mm<const M: usize, const K: usize, const N: usize>(
 }
 
 /// matrix multiply `c += trans(a) * b`
-fn mm_at<const M: usize, const K: usize, const N: usize>(
+pub(super) fn mm_at<const M: usize, const K: usize, const N: usize>(
     a_t: &[[f32; M]; K],
     b: &[[f32; N]; K],
     c: &mut [[f32; N]; M],
@@ -236,7 +629,7 @@ fn mm_at<const M: usize, const K: usize, const N: usize>(
 }
 
 /// matrix multiply `c += a * trans(b)`
-fn mm_bt<const M: usize, const K: usize, const N: usize>(
+pub(super) fn mm_bt<const M: usize, const K: usize, const N: usize>(
     a: &[[f32; K]; M],
     b_t: &[[f32; K]; N],
     c: &mut [[f32; N]; M],
@@ -415,7 +808,113 @@ fn vv<const M: usize, const N: usize>(a: &[f32; M], b: &[f32; N], c: &mut [[f32;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::assert_close;
+    use crate::tests::{assert_close, AssertClose};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// BLAS sums in a different order than the naive loops, so results differ slightly.
+    const TOLERANCE: f32 = 1e-5;
+
+    /// `c += a * b` with the naive triple loop, to check the BLAS backend against.
+    fn naive_mm<const M: usize, const K: usize, const N: usize>(
+        a: &[[f32; K]; M],
+        b: &[[f32; N]; K],
+        c: &mut [[f32; N]; M],
+    ) {
+        for m in 0..M {
+            for n in 0..N {
+                for k in 0..K {
+                    c[m][n] += a[m][k] * b[k][n];
+                }
+            }
+        }
+    }
+
+    fn transpose<const M: usize, const N: usize>(a: &[[f32; N]; M]) -> [[f32; M]; N] {
+        let mut t = [[0.0; M]; N];
+        for (m, a_m) in a.iter().enumerate() {
+            for (n, v) in a_m.iter().enumerate() {
+                t[n][m] = *v;
+            }
+        }
+        t
+    }
+
+    fn randn_array<const M: usize, const N: usize>(rng: &mut StdRng) -> [[f32; N]; M] {
+        [[0.0; N]; M].map(|row| row.map(|_| rng.gen_range(-1.0..1.0)))
+    }
+
+    #[test]
+    fn test_blas_matches_naive() {
+        // sizes that aren't multiples of the kernel sizes of the BLAS backend
+        const M: usize = 13;
+        const K: usize = 37;
+        const N: usize = 19;
+        let mut rng = StdRng::seed_from_u64(0);
+        let a: [[f32; K]; M] = randn_array(&mut rng);
+        let b: [[f32; N]; K] = randn_array(&mut rng);
+        let c0: [[f32; N]; M] = randn_array(&mut rng);
+        let (a_t, b_t) = (transpose(&a), transpose(&b));
+
+        // all variants accumulate into `c`
+        let mut expected = c0;
+        naive_mm(&a, &b, &mut expected);
+
+        let mut c = c0;
+        mm(&a, &b, &mut c);
+        c.assert_close(&expected, TOLERANCE);
+
+        let mut c = c0;
+        mm_at(&a_t, &b, &mut c);
+        c.assert_close(&expected, TOLERANCE);
+
+        let mut c = c0;
+        mm_bt(&a, &b_t, &mut c);
+        c.assert_close(&expected, TOLERANCE);
+
+        let mut c_t = transpose(&c0);
+        mm_atct(&a_t, &b, &mut c_t);
+        transpose(&c_t).assert_close(&expected, TOLERANCE);
+
+        let mut c = c0[0];
+        vm(&a[0], &b, &mut c);
+        c.assert_close(&expected[0], TOLERANCE);
+
+        let mut c = c0[0];
+        vm_bt(&a[0], &b_t, &mut c);
+        c.assert_close(&expected[0], TOLERANCE);
+
+        let mut c = c0;
+        let mut expected = c0;
+        let a_col: [[f32; 1]; M] = a.map(|a_m| [a_m[0]]);
+        naive_mm(&a_col, &[b[0]], &mut expected);
+        vv(&a_col.map(|a_m| a_m[0]), &b[0], &mut c);
+        c.assert_close(&expected, TOLERANCE);
+    }
+
+    #[test]
+    fn test_matmul_gradients_match_naive() {
+        const M: usize = 13;
+        const K: usize = 37;
+        const N: usize = 19;
+        let mut rng = StdRng::seed_from_u64(1);
+        let a: Tensor2D<M, K> = Tensor2D::new(randn_array(&mut rng));
+        let b: Tensor2D<K, N> = Tensor2D::new(randn_array(&mut rng));
+        let g: Tensor2D<M, N> = Tensor2D::new(randn_array(&mut rng));
+        let r = matmul(a.trace(), &b);
+
+        let mut expected = [[0.0; N]; M];
+        naive_mm(a.data(), b.data(), &mut expected);
+        r.data().assert_close(&expected, TOLERANCE);
+
+        // d(sum(r * g))/da = g * b^T, and /db = a^T * g
+        let gradients = (r * &g).sum().backward();
+        let mut a_grad = [[0.0; K]; M];
+        naive_mm(g.data(), &transpose(b.data()), &mut a_grad);
+        gradients.ref_gradient(&a).assert_close(&a_grad, TOLERANCE);
+        let mut b_grad = [[0.0; N]; K];
+        naive_mm(&transpose(a.data()), g.data(), &mut b_grad);
+        gradients.ref_gradient(&b).assert_close(&b_grad, TOLERANCE);
+    }
 
     #[test]
     fn test_vecmul() {
@@ -569,6 +1068,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_matmul_transpose() {
+        // the same values as test_matmul_transpose, split into a batch of 2
+        let a = Tensor3D::new([
+            [[0.5086, 0.5234, 0.2684], [0.8075, 0.8437, 0.9951]],
+            [[0.0774, 0.7539, 0.8894], [0.8119, 0.2693, 0.7249]],
+        ]);
+        let b = Tensor2D::new([[0.4651, 0.3360, 0.8092], [0.9106, 0.5534, 0.3827]]);
+        let r: Tensor3D<2, 2, 2, OwnedTape> = batch_matmul_transpose(a.trace(), &b);
+        assert_close(
+            r.data(),
+            &[
+                [[0.62960154, 0.8554974], [1.4642863, 1.5830379]],
+                [[1.0090116, 0.82806206], [1.0546886, 1.165766]],
+            ],
+        );
+        let gradients = r.exp().mean().backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[
+                [
+                    [0.37689444, 0.24156547, 0.30238447],
+                    [0.80570966, 0.5184905, 0.6703743],
+                ],
+                [
+                    [0.4199963, 0.2735345, 0.38693744],
+                    [0.5321113, 0.34252504, 0.4438907],
+                ],
+            ],
+        );
+        assert_close(
+            gradients.ref_gradient(&b),
+            &[
+                [0.8737376, 0.9339924, 1.1659734],
+                [0.9888564, 0.991189, 1.2298465],
+            ],
+        );
+    }
+
     #[test]
     fn test_vecmat_mul() {
         let a = Tensor1D::new([0.7296, 0.3974, 0.9487]);
@@ -609,4 +1147,126 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_matvec_mul() {
+        let a = Tensor2D::new([[0.7804, 0.5378, 0.5042], [0.5540, 0.8401, 0.8604]]);
+        let b = Tensor1D::new([0.7296, 0.3974, 0.9487]);
+        let r: Tensor1D<2, OwnedTape> = matvec_mul(a.trace(), &b);
+        assert_close(r.data(), &[1.261436, 1.5543157]);
+        let gradients = r.exp().mean().backward();
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[
+                [1.2879219, 0.70150787, 1.6746868],
+                [1.7261779, 0.94021803, 2.244552],
+            ],
+        );
+        assert_close(
+            gradients.ref_gradient(&b),
+            &[2.6883178, 2.9369607, 2.9256766],
+        );
+    }
+
+    #[test]
+    fn test_outer() {
+        let a = Tensor1D::new([0.7296, -0.3974, 0.9487]);
+        let b = Tensor1D::new([0.5540, 0.8401]);
+        let r: Tensor2D<3, 2, OwnedTape> = outer(a.trace(), &b);
+        let r_data = *r.data();
+        let gradients = r.exp().mean().backward();
+
+        let a2: Tensor2D<3, 1> = Tensor2D::new([[0.7296], [-0.3974], [0.9487]]);
+        let b2: Tensor2D<1, 2> = Tensor2D::new([[0.5540, 0.8401]]);
+        let r2: Tensor2D<3, 2, OwnedTape> = matmul(a2.trace(), &b2);
+        assert_eq!(&r_data, r2.data());
+        let gradients2 = r2.exp().mean().backward();
+
+        let a2_grad = gradients2.ref_gradient(&a2);
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[a2_grad[0][0], a2_grad[1][0], a2_grad[2][0]],
+        );
+        assert_close(gradients.ref_gradient(&b), &gradients2.ref_gradient(&b2)[0]);
+    }
+
+    #[test]
+    fn test_matmul_merged() {
+        let a = Tensor2D::new([[0.5, -1.0, 2.0], [1.5, 0.25, -0.5]]);
+        let b = Tensor2D::new([[-0.3, 1.2], [0.8, 0.1], [-0.7, 0.4]]);
+        let r = matmul_merged(a.trace().exp(), b.trace().square());
+        let gradients = r.sum().backward();
+
+        // the gradients of each side when the other has no tape
+        let a_grad = *matmul(a.trace().exp(), &b.duplicate().square())
+            .sum()
+            .backward()
+            .ref_gradient(&a);
+        let b_sq = b.trace().square();
+        let (b_sq, tape) = b_sq.split_tape();
+        let b_grad = matmul_merged(a.duplicate().exp(), b_sq.put_tape(tape))
+            .sum()
+            .backward();
+        assert_close(gradients.ref_gradient(&a), &a_grad);
+        assert_close(gradients.ref_gradient(&b), b_grad.ref_gradient(&b));
+
+        let f = |a: Tensor2D<2, 3, OwnedTape>, b: Tensor2D<3, 2, OwnedTape>| {
+            matmul_merged(a.tanh(), b.sin()).square().sum()
+        };
+        assert!(crate::gradcheck::gradcheck2(f, &a, &b, 1e-3) < 1e-2);
+    }
+
+    #[test]
+    fn test_outer_merged() {
+        let a = Tensor1D::new([0.7296, -0.3974, 0.9487]);
+        let b = Tensor1D::new([0.5540, 0.8401]);
+        let r = a.trace().outer(b.trace());
+        assert_eq!(r.data(), outer(a.clone(), &b).data());
+        let r_data = *r.data();
+        let gradients = r.square().mean().backward();
+
+        // the same as the matmul of a 3x1 and a 1x2 matrix
+        let a2: Tensor2D<3, 1> = Tensor2D::new([[0.7296], [-0.3974], [0.9487]]);
+        let b2: Tensor2D<1, 2> = Tensor2D::new([[0.5540, 0.8401]]);
+        let a2_grad = *matmul(a2.trace(), &b2)
+            .square()
+            .mean()
+            .backward()
+            .ref_gradient(&a2);
+        // and the transpose of the matmul of a 2x1 and a 1x3 matrix
+        let b2_t: Tensor2D<2, 1> = Tensor2D::new([[0.5540], [0.8401]]);
+        let a2_t: Tensor2D<1, 3> = Tensor2D::new([[0.7296, -0.3974, 0.9487]]);
+        let b2_grad = *matmul(b2_t.trace(), &a2_t)
+            .square()
+            .mean()
+            .backward()
+            .ref_gradient(&b2_t);
+        assert_eq!(&r_data, matmul(a2.clone(), &b2).data());
+        assert_close(
+            gradients.ref_gradient(&a),
+            &[a2_grad[0][0], a2_grad[1][0], a2_grad[2][0]],
+        );
+        assert_close(gradients.ref_gradient(&b), &[b2_grad[0][0], b2_grad[1][0]]);
+    }
+
+    #[test]
+    fn test_outer_merged_same_tensor() {
+        let a = Tensor1D::new([1.0, -2.0]);
+        let r = outer_merged(a.trace(), a.trace());
+        assert_eq!(r.data(), &[[1.0, -2.0], [-2.0, 4.0]]);
+        // d/da_k sum_ij a_i * a_j = 2 * sum(a)
+        let gradients = r.sum().backward();
+        assert_eq!(gradients.ref_gradient(&a), &[-2.0, -2.0]);
+    }
+
+    #[test]
+    fn test_dot() {
+        let a = Tensor1D::new([1.0, 2.0, 3.0]);
+        let b = Tensor1D::new([-1.0, 0.5, 2.0]);
+        let r: Tensor0D<OwnedTape> = dot(a.trace(), &b);
+        assert_eq!(r.data(), &6.0);
+        let gradients = (r * 2.0).backward();
+        assert_eq!(gradients.ref_gradient(&a), &[-2.0, 1.0, 4.0]);
+        assert_eq!(gradients.ref_gradient(&b), &[2.0, 4.0, 6.0]);
+    }
 }
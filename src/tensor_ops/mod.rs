@@ -27,49 +27,120 @@
 //!
 //! 1. [add_broadcast_rhs_last()] (and others) broadcasts the last dimension
 //! 2. [add_broadcast_rhs_first()] (and others) broadcast the entire array according to the first dimension in `lhs`.
+//! 3. [add_broadcast_rhs_channel()] (and others) broadcast a `Tensor1D<C>` across the batch & spatial dimensions
+//!    of a `Tensor4D<B, C, H, W>`.
+//!
+//! For a `Tensor2D<M, N>`, this means a `Tensor1D<N>` is added to every row with [add_broadcast_rhs_first()],
+//! and a `Tensor1D<M>` is added to every column with [add_broadcast_rhs_last()].
 //!
 //! See relevant functions for more examples.
+//!
+//! # Matrix multiplication
+//!
+//! There is a function for each combination of matrix (2d) and vector (1d) arguments, all of which
+//! compute gradients for both `lhs` and `rhs`:
+//! - [matmul()] & [matmul_transpose()]: matrix * matrix
+//! - [matvec_mul()]: matrix * vector
+//! - [vecmat_mul()] & [vecmat_mul_transpose()]: vector * matrix
+//! - [outer()] & [dot()]: vector * vector
+//!
+//! So there's no need to turn a vector into a `Tensor2D<1, N>` to multiply it with a matrix.
+//!
+//! [batch_matmul_transpose()] multiplies every matrix in a batch (3d) with the same matrix.
+//!
+//! # In place operations
+//!
+//! Tensors implement [std::ops::AddAssign], [std::ops::SubAssign], [std::ops::MulAssign], and
+//! [std::ops::DivAssign] with both tensors and scalars (where the tensor on the right has no tape).
+//! These modify `self` in place, and do not allocate unless the underlying data is shared with another tensor.
+//!
+//! With an [crate::gradients::OwnedTape], the backward op is still recorded, and `self` gets a new
+//! [crate::unique_id::UniqueId]. `*= &rhs` still allocates, since its backward op needs the original values.
 
 mod arith;
+mod arith_assign;
+mod arith_broadcast_channel;
 mod arith_broadcast_inner;
 mod arith_broadcast_outer;
 mod arith_scalar;
 pub(super) mod binary_map;
+mod impl_attention;
 mod impl_backward;
+mod impl_choose;
 mod impl_clamp;
+mod impl_cmp;
+mod impl_conv_transpose;
+mod impl_cumsum;
+mod impl_diag;
+mod impl_distance;
 mod impl_dropout;
+mod impl_flip;
 mod impl_gather_last;
+mod impl_gather_scatter;
+mod impl_linalg;
 mod impl_mask;
 mod impl_max_last;
 mod impl_mean;
 mod impl_mean_last;
+mod impl_min_last;
 mod impl_nans;
+mod impl_norm;
 mod impl_normalize;
+mod impl_pad;
+mod impl_pixel_shuffle;
+mod impl_prelu;
+mod impl_repeat;
+mod impl_reshape;
+mod impl_slice;
 mod impl_softmax;
+mod impl_sort;
 mod impl_std_last;
 mod impl_sum;
 mod impl_sum_last;
+mod impl_tensordot;
+mod impl_topk;
+mod impl_transfer_tape;
+mod impl_tri;
+mod impl_upsample;
 mod map;
 mod matmul;
 mod utils;
 
 pub use arith::*;
+pub use arith_broadcast_channel::*;
 pub use arith_broadcast_inner::*;
 pub use arith_broadcast_outer::*;
 pub use arith_scalar::*;
+pub use impl_attention::*;
 pub use impl_backward::*;
+pub use impl_choose::*;
 pub use impl_clamp::*;
+pub use impl_cmp::*;
+pub use impl_cumsum::*;
+pub use impl_diag::*;
+pub use impl_distance::*;
 pub use impl_dropout::*;
+pub use impl_flip::*;
 pub use impl_gather_last::*;
+pub use impl_gather_scatter::*;
+pub use impl_linalg::*;
 pub use impl_mask::*;
 pub use impl_max_last::*;
 pub use impl_mean::*;
 pub use impl_mean_last::*;
+pub use impl_min_last::*;
 pub use impl_nans::*;
+pub use impl_norm::*;
 pub use impl_normalize::*;
+pub use impl_pad::*;
+pub use impl_prelu::*;
+pub use impl_reshape::*;
 pub use impl_softmax::*;
 pub use impl_std_last::*;
 pub use impl_sum::*;
 pub use impl_sum_last::*;
+pub use impl_tensordot::*;
+pub use impl_transfer_tape::*;
+pub use impl_tri::*;
 pub use map::*;
 pub use matmul::*;
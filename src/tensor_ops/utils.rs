@@ -9,39 +9,99 @@
 
 use crate::prelude::*;
 
-/// Moves tape from `inp` to `out`, and does `tape.add_backward_op()` with `f`
+/// Moves tape from `inp` to `out`, and does `tape.add_backward_op_named()` with `name` and `f`
 pub(super) fn move_tape_and_add_backward_op<Inp, Out, F>(
+    name: &'static str,
     inp: Inp,
     out: Out::NoTape,
-    mut f: F,
+    f: F,
 ) -> Out
 where
     Inp: Tensor,
     Out: Tensor<Tape = Inp::Tape>,
-    F: 'static + FnMut(Inp::NoTape, PhantomTensor<Out::NoTape>, &mut Gradients),
+    F: 'static + Fn(Inp::NoTape, PhantomTensor<Out::NoTape>, &mut Gradients),
 {
     let phantom_out = out.phantom();
     let (t, mut tape) = inp.split_tape();
-    tape.add_backward_op(move |grads| f(t, phantom_out, grads));
+    tape.add_backward_op_named(name, move |grads| f(t.duplicate(), phantom_out, grads));
     out.put_tape(tape)
 }
 
-/// Moves tape from `lhs` to `out`, and does `tape.add_backward_op()` with `f`
+/// Moves tape from `lhs` to `out`, and does `tape.add_backward_op_named()` with `name` and `f`
 pub(super) fn move_tape_and_add_backward_binop<Lhs, Rhs, Out, F>(
+    name: &'static str,
     lhs: Lhs,
     rhs: &Rhs,
     out: Out::NoTape,
-    mut f: F,
+    f: F,
 ) -> Out
 where
     Lhs: Tensor,
     Rhs: 'static + Tensor,
     Out: Tensor<Tape = Lhs::Tape>,
-    F: 'static + FnMut(Lhs::NoTape, PhantomTensor<Rhs>, PhantomTensor<Out::NoTape>, &mut Gradients),
+    F: 'static + Fn(Lhs::NoTape, PhantomTensor<Rhs>, PhantomTensor<Out::NoTape>, &mut Gradients),
 {
     let phantom_rhs = rhs.phantom();
     let phantom_out = out.phantom();
     let (lhs, mut tape) = lhs.split_tape();
-    tape.add_backward_op(move |grads| f(lhs, phantom_rhs, phantom_out, grads));
+    tape.add_backward_op_named(name, move |grads| {
+        f(lhs.duplicate(), phantom_rhs, phantom_out, grads)
+    });
+    out.put_tape(tape)
+}
+
+/// Like [move_tape_and_add_backward_op()], but also adds `taped`, which does the same as `f`
+/// with tensor operations. See [GradientTape::execute_with_tape()].
+pub(super) fn move_tape_and_add_backward_op_taped<Inp, Out, F, G>(
+    name: &'static str,
+    inp: Inp,
+    out: Out::NoTape,
+    f: F,
+    taped: G,
+) -> Out
+where
+    Inp: Tensor,
+    Out: Tensor<Tape = Inp::Tape>,
+    F: 'static + Fn(Inp::NoTape, PhantomTensor<Out::NoTape>, &mut Gradients),
+    G: 'static + Fn(Inp::NoTape, PhantomTensor<Out::NoTape>, &mut TapedGradients),
+{
+    let phantom_out = out.phantom();
+    let (t, mut tape) = inp.split_tape();
+    let taped_t = t.duplicate();
+    tape.add_backward_op_taped(
+        name,
+        move |grads| f(t.duplicate(), phantom_out, grads),
+        move |grads| taped(taped_t.duplicate(), phantom_out, grads),
+    );
+    out.put_tape(tape)
+}
+
+/// Like [move_tape_and_add_backward_binop()], but also adds `taped`, which does the same as `f`
+/// with tensor operations. See [GradientTape::execute_with_tape()].
+pub(super) fn move_tape_and_add_backward_binop_taped<Lhs, Rhs, Out, F, G>(
+    name: &'static str,
+    lhs: Lhs,
+    rhs: &Rhs,
+    out: Out::NoTape,
+    f: F,
+    taped: G,
+) -> Out
+where
+    Lhs: Tensor,
+    Rhs: 'static + Tensor,
+    Out: Tensor<Tape = Lhs::Tape>,
+    F: 'static + Fn(Lhs::NoTape, PhantomTensor<Rhs>, PhantomTensor<Out::NoTape>, &mut Gradients),
+    G: 'static
+        + Fn(Lhs::NoTape, PhantomTensor<Rhs>, PhantomTensor<Out::NoTape>, &mut TapedGradients),
+{
+    let phantom_rhs = rhs.phantom();
+    let phantom_out = out.phantom();
+    let (lhs, mut tape) = lhs.split_tape();
+    let taped_lhs = lhs.duplicate();
+    tape.add_backward_op_taped(
+        name,
+        move |grads| f(lhs.duplicate(), phantom_rhs, phantom_out, grads),
+        move |grads| taped(taped_lhs.duplicate(), phantom_rhs, phantom_out, grads),
+    );
     out.put_tape(tape)
 }
@@ -2,16 +2,27 @@
 
 /// An id used in to associate gradients with Tensors.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
-pub struct UniqueId(usize);
+pub struct UniqueId(u64);
 
-/// Generate a [UniqueId].
+/// Generate a [UniqueId]. This is thread safe, so tensors can be created from multiple threads
+/// (e.g. in a data loading pipeline) without their ids colliding.
 pub(crate) fn unique_id() -> UniqueId {
-    static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
-    UniqueId(COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    #[cfg(target_has_atomic = "64")]
+    {
+        static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        UniqueId(COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    }
+
+    // targets without 64 bit atomics (e.g. 32 bit microcontrollers) count with a usize instead
+    #[cfg(not(target_has_atomic = "64"))]
+    {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        UniqueId(COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed) as u64)
+    }
 }
 
 impl std::ops::Deref for UniqueId {
-    type Target = usize;
+    type Target = u64;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -19,7 +30,7 @@ impl std::ops::Deref for UniqueId {
 
 impl UniqueId {
     pub(crate) fn as_u64(&self) -> u64 {
-        self.0 as u64
+        self.0
     }
 }
 
@@ -27,3 +38,39 @@ impl UniqueId {
 pub trait HasUniqueId {
     fn id(&self) -> &UniqueId;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_unique_id_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<UniqueId>();
+    }
+
+    #[test]
+    fn test_unique_ids_from_many_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    (0..1000)
+                        .map(|_| {
+                            let t: Tensor1D<2> = Tensor1D::zeros();
+                            *t.id()
+                        })
+                        .collect::<Vec<UniqueId>>()
+                })
+            })
+            .collect();
+        let mut ids = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(ids.insert(id), "{id:?} was generated twice");
+            }
+        }
+        assert_eq!(ids.len(), 8000);
+    }
+}